@@ -0,0 +1,27 @@
+//! Byte-wise accesses to a large buffer. The data race detector and the borrow tracker keep
+//! per-range metadata for every allocation, which fragments into one entry per byte under such
+//! access patterns unless adjacent equal entries are merged again. This benchmark is mostly
+//! interesting for its peak memory usage.
+//!
+//! The size of the buffer in MiB can be passed as an argument. It defaults to 1 MiB, which already
+//! shows the effect but keeps the time it takes to interpret the benchmark reasonable.
+
+use std::hint::black_box;
+
+fn main() {
+    let mib: usize = std::env::args().nth(1).map_or(1, |arg| arg.parse().unwrap());
+    let mut buf = vec![0u8; mib * 1024 * 1024];
+    // Write every byte, front to back and back to front.
+    for (i, b) in buf.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    for b in buf.iter_mut().rev() {
+        *b = b.wrapping_add(1);
+    }
+    // Read every byte, and also access the buffer from another thread so that the data race
+    // detector has a reason to track it.
+    let sum = std::thread::scope(|s| {
+        s.spawn(|| buf.iter().map(|&b| u64::from(b)).sum::<u64>()).join().unwrap()
+    });
+    black_box(sum);
+}
@@ -400,6 +400,22 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         }
     }
 
+    /// Returns the base address of every live allocation of the given kind, sorted by address.
+    fn live_alloc_addresses(&self, kind: MemoryKind) -> InterpResult<'tcx, Vec<(u64, AllocId)>> {
+        let this = self.eval_context_ref();
+        let ids: Vec<AllocId> = this.memory.alloc_map().iter(|it| {
+            it.filter(|(_id, (alloc_kind, _alloc))| *alloc_kind == kind)
+                .map(|(id, _alloc)| *id)
+                .collect()
+        });
+        let mut addrs = Vec::with_capacity(ids.len());
+        for id in ids {
+            addrs.push((this.addr_from_alloc_id(id, kind)?, id));
+        }
+        addrs.sort();
+        interp_ok(addrs)
+    }
+
     /// When a pointer is used for a memory access, this computes where in which allocation the
     /// access is going.
     fn ptr_get_alloc(
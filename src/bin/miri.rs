@@ -29,7 +29,10 @@ use std::num::NonZero;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use miri::{BacktraceStyle, BorrowTrackerMethod, ProvenanceMode, RetagFields, ValidationMode};
+use miri::{
+    BacktraceStyle, BorrowTrackerMethod, CheckScope, ProvenanceMode, RLIMIT_RESOURCES,
+    ResourceLimit, RetagFields, ValidationMode,
+};
 use rustc_abi::ExternAbi;
 use rustc_data_structures::sync::Lrc;
 use rustc_driver::Compilation;
@@ -310,6 +313,42 @@ fn parse_rate(input: &str) -> Result<f64, &'static str> {
     }
 }
 
+/// Parses a resource limit of the form `<resource>=<soft>[:<hard>]`, where `<resource>` is the
+/// lowercase name of an `RLIMIT_*` constant without the prefix, and the limits are either
+/// integers or `unlimited`. If the hard limit is omitted, it equals the soft limit.
+fn parse_rlimit(input: &str) -> Result<(&'static str, ResourceLimit), String> {
+    fn parse_limit(input: &str) -> Result<Option<u64>, String> {
+        match input {
+            "unlimited" => Ok(None),
+            _ =>
+                input
+                    .parse::<u64>()
+                    .map(Some)
+                    .map_err(|err| format!("invalid limit `{input}`: {err}")),
+        }
+    }
+
+    let Some((resource, limits)) = input.split_once('=') else {
+        return Err("requires an argument of the form `<resource>=<soft>[:<hard>]`".to_owned());
+    };
+    let name = format!("RLIMIT_{}", resource.to_ascii_uppercase());
+    let Some(&resource) = RLIMIT_RESOURCES.iter().find(|&&r| r == name) else {
+        return Err(format!("unknown resource `{resource}`"));
+    };
+    let (soft, hard) = match limits.split_once(':') {
+        Some((soft, hard)) => (parse_limit(soft)?, parse_limit(hard)?),
+        None => {
+            let limit = parse_limit(limits)?;
+            (limit, limit)
+        }
+    };
+    let limit = ResourceLimit { soft, hard };
+    if limit.soft_exceeds_hard() {
+        return Err(format!("the soft limit of `{resource}` exceeds its hard limit"));
+    }
+    Ok((resource, limit))
+}
+
 #[cfg(any(target_os = "linux", target_os = "macos"))]
 fn jemalloc_magic() {
     // These magic runes are copied from
@@ -471,10 +510,32 @@ fn main() {
             miri_config.validation = ValidationMode::No;
         } else if arg == "-Zmiri-recursive-validation" {
             miri_config.validation = ValidationMode::Deep;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-check-scope=") {
+            miri_config.check_scope = match param {
+                "all" => CheckScope::All,
+                "local" => CheckScope::Local,
+                _ => show_error!("`-Zmiri-check-scope` can only be `all` or `local`"),
+            };
         } else if arg == "-Zmiri-disable-stacked-borrows" {
             miri_config.borrow_tracker = None;
         } else if arg == "-Zmiri-tree-borrows" {
             miri_config.borrow_tracker = Some(BorrowTrackerMethod::TreeBorrows);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-borrow-tracker=") {
+            miri_config.borrow_tracker = Some(match param {
+                "stacked" => BorrowTrackerMethod::StackedBorrows,
+                "tree" => BorrowTrackerMethod::TreeBorrows,
+                "both" => BorrowTrackerMethod::Both,
+                _ =>
+                    show_error!("`-Zmiri-borrow-tracker` can only be `stacked`, `tree`, or `both`"),
+            });
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-borrow-trace=") {
+            let (id, dir) = param.split_once(',').unwrap_or((param, "."));
+            let id = id.parse::<NonZero<u64>>().unwrap_or_else(|err| {
+                show_error!(
+                    "-Zmiri-borrow-trace requires a valid non-zero `u64` allocation id: {err}"
+                )
+            });
+            miri_config.borrow_trace = Some((miri::AllocId(id), PathBuf::from(dir)));
         } else if arg == "-Zmiri-unique-is-unique" {
             miri_config.unique_is_unique = true;
         } else if arg == "-Zmiri-disable-data-race-detector" {
@@ -500,6 +561,17 @@ fn main() {
             miri_config.isolated_op = miri::IsolatedOp::Allow;
         } else if arg == "-Zmiri-disable-leak-backtraces" {
             miri_config.collect_leak_backtraces = false;
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-leak-suppressions=") {
+            let suppressions = std::fs::read_to_string(param).unwrap_or_else(|err| {
+                show_error!("-Zmiri-leak-suppressions: failed to read `{param}`: {err}")
+            });
+            miri_config.leak_suppressions.extend(
+                suppressions
+                    .lines()
+                    .map(str::trim)
+                    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                    .map(str::to_owned),
+            );
         } else if arg == "-Zmiri-disable-weak-memory-emulation" {
             miri_config.weak_memory_emulation = false;
         } else if arg == "-Zmiri-track-weak-memory-loads" {
@@ -531,6 +603,8 @@ fn main() {
             miri_config.provenance_mode = ProvenanceMode::Strict;
         } else if arg == "-Zmiri-permissive-provenance" {
             miri_config.provenance_mode = ProvenanceMode::Permissive;
+        } else if arg == "-Zmiri-float-nondet" {
+            miri_config.float_nondet = true;
         } else if arg == "-Zmiri-mute-stdout-stderr" {
             miri_config.mute_stdout_stderr = true;
         } else if arg == "-Zmiri-retag-fields" {
@@ -579,6 +653,8 @@ fn main() {
             miri_config.tracked_alloc_ids.extend(ids.into_iter().map(miri::AllocId));
         } else if arg == "-Zmiri-track-alloc-accesses" {
             miri_config.track_alloc_accesses = true;
+        } else if arg == "-Zmiri-auto-track" {
+            miri_config.auto_track = true;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-address-reuse-rate=") {
             miri_config.address_reuse_rate = parse_rate(param)
                 .unwrap_or_else(|err| show_error!("-Zmiri-address-reuse-rate {err}"));
@@ -607,6 +683,12 @@ fn main() {
             miri_config.gc_interval = interval;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-measureme=") {
             miri_config.measureme_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-self-profile=") {
+            miri_config.self_profile_out = Some(param.to_string());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-heap-profile=") {
+            miri_config.heap_profile_out = Some(param.to_string());
+        } else if arg == "-Zmiri-track-uninit-origins" {
+            miri_config.track_uninit_origins = true;
         } else if let Some(param) = arg.strip_prefix("-Zmiri-backtrace=") {
             miri_config.backtrace_style = match param {
                 "0" => BacktraceStyle::Off,
@@ -643,6 +725,15 @@ fn main() {
                 show_error!("-Zmiri-force-page-size requires a power of 2: {page_size}");
             };
             miri_config.page_size = Some(page_size);
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-hostname=") {
+            if param.is_empty() || param.contains('\0') {
+                show_error!("-Zmiri-hostname requires a non-empty name without null bytes");
+            }
+            miri_config.hostname = Some(param.to_owned());
+        } else if let Some(param) = arg.strip_prefix("-Zmiri-rlimit=") {
+            let (resource, limit) =
+                parse_rlimit(param).unwrap_or_else(|err| show_error!("-Zmiri-rlimit {err}"));
+            miri_config.rlimits.insert(resource, limit);
         } else {
             // Forward to rustc.
             rustc_args.push(arg);
@@ -656,15 +747,34 @@ fn main() {
             "-Zmiri-unique-is-unique only has an effect when -Zmiri-tree-borrows is also used"
         );
     }
+    if miri_config.borrow_trace.is_some() && miri_config.borrow_tracker.is_none() {
+        show_error!("-Zmiri-borrow-trace cannot be used along with -Zmiri-disable-stacked-borrows");
+    }
+    if miri_config.auto_track && miri_config.borrow_tracker.is_none() {
+        show_error!("-Zmiri-auto-track cannot be used along with -Zmiri-disable-stacked-borrows");
+    }
     // Tree Borrows + permissive provenance does not work.
     if miri_config.provenance_mode == ProvenanceMode::Permissive
-        && matches!(miri_config.borrow_tracker, Some(BorrowTrackerMethod::TreeBorrows))
+        && matches!(
+            miri_config.borrow_tracker,
+            Some(BorrowTrackerMethod::TreeBorrows | BorrowTrackerMethod::Both)
+        )
     {
         show_error!(
             "Tree Borrows does not support integer-to-pointer casts, and is hence not compatible with permissive provenance"
         );
     }
 
+    // Suppressions are matched against the backtrace of the leaked allocation.
+    if !miri_config.leak_suppressions.is_empty()
+        && !miri_config.collect_leak_backtraces
+        && !miri_config.ignore_leaks
+    {
+        show_error!(
+            "-Zmiri-leak-suppressions cannot be used along with -Zmiri-disable-leak-backtraces"
+        );
+    }
+
     debug!("rustc arguments: {:?}", rustc_args);
     debug!("crate arguments: {:?}", miri_config.args);
     run_compiler(
@@ -0,0 +1,300 @@
+//! Implements `-Zmiri-borrow-tracker=both`, which checks a single execution against both
+//! Stacked Borrows and Tree Borrows.
+//!
+//! Each retag runs the retag logic of both models, one after the other. Stacked Borrows goes
+//! first and picks the new tags. Tree Borrows then reuses the tag that Stacked Borrows picked for
+//! every pointer that it also retags. For the pointers that only Stacked Borrows retags (e.g. raw
+//! pointers, or `!Unpin` mutable references), the new tag is recorded as an alias of the parent
+//! tag, so that Tree Borrows keeps seeing the pointer that the retagged one was derived from.
+//! `-Zmiri-unique-is-unique` is only accepted with `-Zmiri-tree-borrows`, so Tree Borrows never
+//! retags a pointer that Stacked Borrows left alone.
+//!
+//! The first aliasing violation found by one model does not halt the program: it is reported,
+//! that model is switched off, and the execution continues with the other model only. A violation
+//! found by the remaining model halts the program as usual. Either way, the execution ends with a
+//! combined report of which model flagged which access.
+
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::mir::RetagKind;
+use rustc_span::SpanData;
+
+use crate::borrow_tracker::GlobalStateInner;
+use crate::borrow_tracker::stacked_borrows::diagnostics::TagHistory;
+use crate::*;
+
+/// An aliasing violation found by one of the two models.
+#[derive(Debug)]
+pub struct DifferentialReport {
+    /// The model that flagged the access.
+    pub model: BorrowTrackerMethod,
+    /// The error message of that model.
+    pub msg: String,
+    /// Where the access happened.
+    pub span: SpanData,
+}
+
+/// Global state of the differential mode.
+#[derive(Debug, Default)]
+pub struct DifferentialState {
+    /// Tags that Stacked Borrows created for a retag that Tree Borrows did not perform, mapped
+    /// to the tag under which Tree Borrows knows the same pointer.
+    tb_aliases: FxHashMap<BorTag, BorTag>,
+    /// Tags that Stacked Borrows created in the retag that is currently in progress, and that
+    /// Tree Borrows has not reused yet.
+    pending: FxHashSet<BorTag>,
+    /// All violations found so far, in the order in which they were found.
+    /// A model stops checking accesses once it has a report here.
+    reports: Vec<DifferentialReport>,
+}
+
+impl DifferentialState {
+    fn is_active(&self, model: BorrowTrackerMethod) -> bool {
+        !self.reports.iter().any(|report| report.model == model)
+    }
+}
+
+impl VisitProvenance for DifferentialState {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
+        // Pointers carrying an alias still refer to its target in Tree Borrows, so the target
+        // must stay alive. The aliases themselves are removed once no pointer carries them any
+        // more, see `GlobalStateInner::remove_unreachable_aliases`.
+        for tag in self.tb_aliases.values() {
+            visit(None, Some(*tag));
+        }
+    }
+}
+
+impl GlobalStateInner {
+    /// Returns whether Stacked Borrows and Tree Borrows, respectively, are still checking
+    /// accesses.
+    pub(super) fn active_models(&self) -> (bool, bool) {
+        let state = self.differential.as_ref().unwrap();
+        (
+            state.is_active(BorrowTrackerMethod::StackedBorrows),
+            state.is_active(BorrowTrackerMethod::TreeBorrows),
+        )
+    }
+
+    /// The tag under which Tree Borrows knows the pointer with the given tag. Outside of the
+    /// differential mode, this is always `tag` itself.
+    pub fn tb_tag(&self, tag: BorTag) -> BorTag {
+        self.differential
+            .as_ref()
+            .and_then(|state| state.tb_aliases.get(&tag).copied())
+            .unwrap_or(tag)
+    }
+
+    /// Records that Stacked Borrows retagged the pointer with the tag `parent` to `tag`. Until
+    /// Tree Borrows retags the same pointer, it keeps using `parent` for pointers with the tag
+    /// `tag`. Outside of the differential mode, this does nothing.
+    pub fn record_sb_retag(&mut self, tag: BorTag, parent: BorTag) {
+        if self.differential.is_none() || !self.active_models().1 {
+            return;
+        }
+        let parent = self.tb_tag(parent);
+        let state = self.differential.as_mut().unwrap();
+        state.tb_aliases.insert(tag, parent);
+        state.pending.insert(tag);
+    }
+
+    /// The tag that Tree Borrows should use to retag a pointer with the provenance `prov`: the
+    /// tag that Stacked Borrows picked for the same pointer in the current retag, or a fresh one.
+    /// Returns `None` if Tree Borrows must not retag this pointer, because Stacked Borrows did
+    /// not retag it and is still checking accesses.
+    pub fn tb_new_tag(&mut self, prov: Option<Provenance>) -> Option<BorTag> {
+        if let Some(state) = &self.differential {
+            if let Some(Provenance::Concrete { tag, .. }) = prov
+                && state.pending.contains(&tag)
+            {
+                return Some(tag);
+            }
+            if self.active_models().0 {
+                return None;
+            }
+        }
+        Some(self.new_ptr())
+    }
+
+    /// Records that Tree Borrows created the tag `tag` itself, so it no longer needs an alias.
+    pub fn record_tb_retag(&mut self, tag: BorTag) {
+        if let Some(state) = &mut self.differential
+            && state.pending.remove(&tag)
+        {
+            state.tb_aliases.remove(&tag);
+        }
+    }
+
+    /// Forgets the aliases of tags that no pointer carries any more. `tags` are all live tags.
+    pub fn remove_unreachable_aliases(&mut self, tags: &FxHashSet<BorTag>) {
+        if let Some(state) = &mut self.differential {
+            state.tb_aliases.retain(|tag, _| tags.contains(tag));
+        }
+    }
+
+    /// All violations found in differential mode, or `None` outside of it.
+    pub fn differential_reports(&self) -> Option<&[DifferentialReport]> {
+        self.differential.as_ref().map(|state| &state.reports[..])
+    }
+}
+
+/// Extracts the message and the model-specific notes of an aliasing violation, or returns `None`
+/// if `err` is any other kind of error.
+fn describe_violation(
+    err: &InterpErrorInfo<'_>,
+) -> Option<(String, Vec<(Option<SpanData>, String)>)> {
+    let InterpErrorKind::MachineStop(info) = err.kind() else {
+        return None;
+    };
+    let info = info.downcast_ref::<TerminationInfo>()?;
+    let helps = match info {
+        TerminationInfo::StackedBorrowsUb { help, history, .. } => {
+            let mut helps: Vec<_> = help.iter().map(|help| (None, help.clone())).collect();
+            if let Some(TagHistory { created, invalidated, protected }) = history {
+                helps.push((Some(created.1), created.0.clone()));
+                for (msg, span) in invalidated.iter().chain(protected) {
+                    helps.push((Some(*span), msg.clone()));
+                }
+            }
+            helps
+        }
+        TerminationInfo::TreeBorrowsUb { details, history, .. } =>
+            details
+                .iter()
+                .map(|detail| (None, detail.clone()))
+                .chain(history.events.iter().cloned())
+                .collect(),
+        _ => return None,
+    };
+    Some((info.to_string(), helps))
+}
+
+/// Filters the result of an operation of `model` in differential mode.
+///
+/// An aliasing violation is recorded. If the other model is still active, the violation is
+/// reported without halting the program, `model` is switched off, and `None` is returned.
+/// Otherwise, and for all other errors, the error is passed on.
+pub(super) fn check_model<'tcx, T>(
+    machine: &MiriMachine<'tcx>,
+    model: BorrowTrackerMethod,
+    res: InterpResult<'tcx, T>,
+) -> InterpResult<'tcx, Option<T>> {
+    let err = match res.report_err() {
+        Ok(val) => return interp_ok(Some(val)),
+        Err(err) => err,
+    };
+    let Some((msg, helps)) = describe_violation(&err) else {
+        return err.into();
+    };
+
+    let mut global = machine.borrow_tracker.as_ref().unwrap().borrow_mut();
+    let state = global.differential.as_mut().unwrap();
+    state.reports.push(DifferentialReport {
+        model,
+        msg: msg.clone(),
+        span: machine.current_span().data(),
+    });
+    let other = match model {
+        BorrowTrackerMethod::StackedBorrows => BorrowTrackerMethod::TreeBorrows,
+        BorrowTrackerMethod::TreeBorrows => BorrowTrackerMethod::StackedBorrows,
+        BorrowTrackerMethod::Both => unreachable!(),
+    };
+    if !state.is_active(other) {
+        // Both models have flagged the program now, there is nothing left to compare.
+        return err.into();
+    }
+    drop(global);
+
+    machine.emit_diagnostic(NonHaltingDiagnostic::AliasingViolation { model, other, msg, helps });
+    interp_ok(None)
+}
+
+/// Runs an operation in each model that is still active, filtering the results through
+/// `check_model`.
+pub(super) fn both_models<'tcx>(
+    machine: &MiriMachine<'tcx>,
+    sb: impl FnOnce() -> InterpResult<'tcx>,
+    tb: impl FnOnce() -> InterpResult<'tcx>,
+) -> InterpResult<'tcx> {
+    let (sb_active, tb_active) = machine.borrow_tracker.as_ref().unwrap().borrow().active_models();
+    if sb_active {
+        check_model(machine, BorrowTrackerMethod::StackedBorrows, sb())?;
+    }
+    if tb_active {
+        check_model(machine, BorrowTrackerMethod::TreeBorrows, tb())?;
+    }
+    interp_ok(())
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Performs a retag of `val` in each model that is still active, filtering the results
+    /// through `check_model`. `sb_retag` runs first and picks the new tags, then `tb_retag`
+    /// runs on its result. Each model uses its own retag logic to decide which pointers to
+    /// retag.
+    fn both_retag<T: Clone>(
+        &mut self,
+        val: &T,
+        sb_retag: impl FnOnce(&mut MiriInterpCx<'tcx>, &T) -> InterpResult<'tcx, T>,
+        tb_retag: impl FnOnce(&mut MiriInterpCx<'tcx>, &T) -> InterpResult<'tcx, T>,
+    ) -> InterpResult<'tcx, T> {
+        let this = self.eval_context_mut();
+        let (sb_active, tb_active) =
+            this.machine.borrow_tracker.as_ref().unwrap().borrow().active_models();
+        let mut val = val.clone();
+
+        if sb_active {
+            let res = sb_retag(this, &val);
+            if let Some(new_val) =
+                check_model(&this.machine, BorrowTrackerMethod::StackedBorrows, res)?
+            {
+                val = new_val;
+            }
+        }
+        if tb_active {
+            let res = tb_retag(this, &val);
+            if let Some(new_val) =
+                check_model(&this.machine, BorrowTrackerMethod::TreeBorrows, res)?
+            {
+                val = new_val;
+            }
+        }
+
+        // Tags that Tree Borrows did not reuse keep their alias.
+        let global = this.machine.borrow_tracker.as_mut().unwrap().get_mut();
+        global.differential.as_mut().unwrap().pending.clear();
+        interp_ok(val)
+    }
+
+    fn both_retag_ptr_value(
+        &mut self,
+        kind: RetagKind,
+        val: &ImmTy<'tcx>,
+    ) -> InterpResult<'tcx, ImmTy<'tcx>> {
+        self.both_retag(
+            val,
+            |this, val| this.sb_retag_ptr_value(kind, val),
+            |this, val| this.tb_retag_ptr_value(kind, val),
+        )
+    }
+
+    fn both_retag_place_contents(
+        &mut self,
+        kind: RetagKind,
+        place: &PlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        // The visitors retag the place in-place, so there is no value to pass on.
+        self.both_retag(
+            &(),
+            |this, ()| this.sb_retag_place_contents(kind, place),
+            |this, ()| this.tb_retag_place_contents(kind, place),
+        )
+    }
+
+    fn both_protect_place(&mut self, place: &MPlaceTy<'tcx>) -> InterpResult<'tcx, MPlaceTy<'tcx>> {
+        self.both_retag(
+            place,
+            |this, place| this.sb_protect_place(place),
+            |this, place| this.tb_protect_place(place),
+        )
+    }
+}
@@ -7,8 +7,12 @@ use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_middle::mir::RetagKind;
 use smallvec::SmallVec;
 
+use self::differential::{DifferentialState, EvalContextExt as _, both_models, check_model};
+use self::trace::{BorrowTrace, is_traced, record_event};
 use crate::*;
+pub mod differential;
 pub mod stacked_borrows;
+pub mod trace;
 pub mod tree_borrows;
 
 /// Tracking pointer provenance
@@ -104,13 +108,20 @@ pub struct GlobalStateInner {
     retag_fields: RetagFields,
     /// Whether `core::ptr::Unique` gets special (`Box`-like) handling.
     unique_is_unique: bool,
+    /// State of `-Zmiri-borrow-tracker=both`; `None` for the other methods.
+    differential: Option<DifferentialState>,
+    /// The trace recorded for `-Zmiri-borrow-trace`, if enabled.
+    borrow_trace: Option<BorrowTrace>,
 }
 
 impl VisitProvenance for GlobalStateInner {
-    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+    fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         // All the provenance in protected_tags is also stored in FrameState, and visited there.
         // The only other candidate is base_ptr_tags, and that does not need visiting since we don't ever
         // GC the bottommost/root tag.
+        if let Some(differential) = &self.differential {
+            differential.visit_provenance(visit);
+        }
     }
 }
 
@@ -165,6 +176,7 @@ impl GlobalStateInner {
         tracked_pointer_tags: FxHashSet<BorTag>,
         retag_fields: RetagFields,
         unique_is_unique: bool,
+        borrow_trace: Option<BorrowTrace>,
     ) -> Self {
         GlobalStateInner {
             borrow_tracker_method,
@@ -174,6 +186,9 @@ impl GlobalStateInner {
             tracked_pointer_tags,
             retag_fields,
             unique_is_unique,
+            differential: (borrow_tracker_method == BorrowTrackerMethod::Both)
+                .then(DifferentialState::default),
+            borrow_trace,
         }
     }
 
@@ -231,6 +246,18 @@ pub enum BorrowTrackerMethod {
     StackedBorrows,
     /// Tree borrows, as implemented in borrow_tracker/tree_borrows
     TreeBorrows,
+    /// Both of the above side by side, as implemented in borrow_tracker/differential
+    Both,
+}
+
+impl fmt::Display for BorrowTrackerMethod {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BorrowTrackerMethod::StackedBorrows => write!(f, "Stacked Borrows"),
+            BorrowTrackerMethod::TreeBorrows => write!(f, "Tree Borrows"),
+            BorrowTrackerMethod::Both => write!(f, "Stacked Borrows and Tree Borrows"),
+        }
+    }
 }
 
 impl BorrowTrackerMethod {
@@ -240,6 +267,10 @@ impl BorrowTrackerMethod {
             config.tracked_pointer_tags.clone(),
             config.retag_fields,
             config.unique_is_unique,
+            config
+                .borrow_trace
+                .as_ref()
+                .map(|(alloc_id, dir)| BorrowTrace::new(*alloc_id, dir.clone())),
         ))
     }
 }
@@ -252,7 +283,7 @@ impl GlobalStateInner {
         kind: MemoryKind,
         machine: &MiriMachine<'_>,
     ) -> AllocState {
-        match self.borrow_tracker_method {
+        let state = match self.borrow_tracker_method {
             BorrowTrackerMethod::StackedBorrows =>
                 AllocState::StackedBorrows(Box::new(RefCell::new(Stacks::new_allocation(
                     id, alloc_size, self, kind, machine,
@@ -261,7 +292,21 @@ impl GlobalStateInner {
                 AllocState::TreeBorrows(Box::new(RefCell::new(Tree::new_allocation(
                     id, alloc_size, self, kind, machine,
                 )))),
+            BorrowTrackerMethod::Both =>
+                AllocState::Both(
+                    Box::new(RefCell::new(Stacks::new_allocation(
+                        id, alloc_size, self, kind, machine,
+                    ))),
+                    Box::new(RefCell::new(Tree::new_allocation(
+                        id, alloc_size, self, kind, machine,
+                    ))),
+                ),
+        };
+        if self.is_traced(id) {
+            let event = format!("allocation of {} bytes", alloc_size.bytes());
+            self.push_trace_step(machine, &state, event);
         }
+        state
     }
 }
 
@@ -277,6 +322,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         match method {
             BorrowTrackerMethod::StackedBorrows => this.sb_retag_ptr_value(kind, val),
             BorrowTrackerMethod::TreeBorrows => this.tb_retag_ptr_value(kind, val),
+            BorrowTrackerMethod::Both => this.both_retag_ptr_value(kind, val),
         }
     }
 
@@ -290,6 +336,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         match method {
             BorrowTrackerMethod::StackedBorrows => this.sb_retag_place_contents(kind, place),
             BorrowTrackerMethod::TreeBorrows => this.tb_retag_place_contents(kind, place),
+            BorrowTrackerMethod::Both => this.both_retag_place_contents(kind, place),
         }
     }
 
@@ -299,6 +346,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         match method {
             BorrowTrackerMethod::StackedBorrows => this.sb_protect_place(place),
             BorrowTrackerMethod::TreeBorrows => this.tb_protect_place(place),
+            BorrowTrackerMethod::Both => this.both_protect_place(place),
         }
     }
 
@@ -308,6 +356,10 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         match method {
             BorrowTrackerMethod::StackedBorrows => this.sb_expose_tag(alloc_id, tag),
             BorrowTrackerMethod::TreeBorrows => this.tb_expose_tag(alloc_id, tag),
+            BorrowTrackerMethod::Both => {
+                this.sb_expose_tag(alloc_id, tag)?;
+                this.tb_expose_tag(alloc_id, tag)
+            }
         }
     }
 
@@ -324,7 +376,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.tcx.tcx.dcx().warn("Stacked Borrows does not support named pointers; `miri_pointer_name` is a no-op");
                 interp_ok(())
             }
-            BorrowTrackerMethod::TreeBorrows =>
+            BorrowTrackerMethod::TreeBorrows | BorrowTrackerMethod::Both =>
                 this.tb_give_pointer_debug_name(ptr, nth_parent, name),
         }
     }
@@ -339,6 +391,10 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         match method {
             BorrowTrackerMethod::StackedBorrows => this.print_stacks(alloc_id),
             BorrowTrackerMethod::TreeBorrows => this.print_tree(alloc_id, show_unnamed),
+            BorrowTrackerMethod::Both => {
+                this.print_stacks(alloc_id)?;
+                this.print_tree(alloc_id, show_unnamed)
+            }
         }
     }
 
@@ -376,6 +432,17 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             }
         }
         borrow_tracker.borrow_mut().end_call(&frame.extra);
+        for (alloc_id, tag) in &frame.extra.borrow_tracker.as_ref().unwrap().protected_tags {
+            if is_traced(&this.machine, *alloc_id)
+                && matches!(this.get_alloc_info(*alloc_id).kind, AllocKind::LiveData)
+            {
+                let alloc_borrow_tracker =
+                    this.get_alloc_extra(*alloc_id)?.borrow_tracker.as_ref().unwrap();
+                record_event(&this.machine, *alloc_id, alloc_borrow_tracker, || {
+                    format!("protector of {tag:?} ended")
+                });
+            }
+        }
         interp_ok(())
     }
 }
@@ -387,13 +454,15 @@ pub enum AllocState {
     StackedBorrows(Box<RefCell<stacked_borrows::AllocState>>),
     /// Data corresponding to Tree Borrows
     TreeBorrows(Box<RefCell<tree_borrows::AllocState>>),
+    /// Data corresponding to both of the above, for the differential mode
+    Both(Box<RefCell<stacked_borrows::AllocState>>, Box<RefCell<tree_borrows::AllocState>>),
 }
 
 impl machine::AllocExtra<'_> {
     #[track_caller]
     pub fn borrow_tracker_sb(&self) -> &RefCell<stacked_borrows::AllocState> {
         match self.borrow_tracker {
-            Some(AllocState::StackedBorrows(ref sb)) => sb,
+            Some(AllocState::StackedBorrows(ref sb) | AllocState::Both(ref sb, _)) => sb,
             _ => panic!("expected Stacked Borrows borrow tracking, got something else"),
         }
     }
@@ -401,7 +470,7 @@ impl machine::AllocExtra<'_> {
     #[track_caller]
     pub fn borrow_tracker_sb_mut(&mut self) -> &mut RefCell<stacked_borrows::AllocState> {
         match self.borrow_tracker {
-            Some(AllocState::StackedBorrows(ref mut sb)) => sb,
+            Some(AllocState::StackedBorrows(ref mut sb) | AllocState::Both(ref mut sb, _)) => sb,
             _ => panic!("expected Stacked Borrows borrow tracking, got something else"),
         }
     }
@@ -409,7 +478,7 @@ impl machine::AllocExtra<'_> {
     #[track_caller]
     pub fn borrow_tracker_tb(&self) -> &RefCell<tree_borrows::AllocState> {
         match self.borrow_tracker {
-            Some(AllocState::TreeBorrows(ref tb)) => tb,
+            Some(AllocState::TreeBorrows(ref tb) | AllocState::Both(_, ref tb)) => tb,
             _ => panic!("expected Tree Borrows borrow tracking, got something else"),
         }
     }
@@ -423,7 +492,7 @@ impl AllocState {
         range: AllocRange,
         machine: &MiriMachine<'tcx>,
     ) -> InterpResult<'tcx> {
-        match self {
+        let res = match self {
             AllocState::StackedBorrows(sb) =>
                 sb.borrow_mut().before_memory_read(alloc_id, prov_extra, range, machine),
             AllocState::TreeBorrows(tb) =>
@@ -434,7 +503,24 @@ impl AllocState {
                     range,
                     machine,
                 ),
-        }
+            AllocState::Both(sb, tb) =>
+                both_models(
+                    machine,
+                    || sb.borrow_mut().before_memory_read(alloc_id, prov_extra, range, machine),
+                    || {
+                        tb.borrow_mut().before_memory_access(
+                            AccessKind::Read,
+                            alloc_id,
+                            prov_extra,
+                            range,
+                            machine,
+                        )
+                    },
+                ),
+        };
+        self.trace_access(alloc_id, res, machine, || {
+            format!("read access with tag {prov_extra:?} at {range:?}")
+        })
     }
 
     pub fn before_memory_write<'tcx>(
@@ -444,7 +530,7 @@ impl AllocState {
         range: AllocRange,
         machine: &MiriMachine<'tcx>,
     ) -> InterpResult<'tcx> {
-        match self {
+        let res = match self {
             AllocState::StackedBorrows(sb) =>
                 sb.get_mut().before_memory_write(alloc_id, prov_extra, range, machine),
             AllocState::TreeBorrows(tb) =>
@@ -455,7 +541,24 @@ impl AllocState {
                     range,
                     machine,
                 ),
-        }
+            AllocState::Both(sb, tb) =>
+                both_models(
+                    machine,
+                    || sb.get_mut().before_memory_write(alloc_id, prov_extra, range, machine),
+                    || {
+                        tb.get_mut().before_memory_access(
+                            AccessKind::Write,
+                            alloc_id,
+                            prov_extra,
+                            range,
+                            machine,
+                        )
+                    },
+                ),
+        };
+        self.trace_access(alloc_id, res, machine, || {
+            format!("write access with tag {prov_extra:?} at {range:?}")
+        })
     }
 
     pub fn before_memory_deallocation<'tcx>(
@@ -465,11 +568,46 @@ impl AllocState {
         size: Size,
         machine: &MiriMachine<'tcx>,
     ) -> InterpResult<'tcx> {
-        match self {
+        let res = match self {
             AllocState::StackedBorrows(sb) =>
                 sb.get_mut().before_memory_deallocation(alloc_id, prov_extra, size, machine),
             AllocState::TreeBorrows(tb) =>
                 tb.get_mut().before_memory_deallocation(alloc_id, prov_extra, size, machine),
+            AllocState::Both(sb, tb) =>
+                both_models(
+                    machine,
+                    || sb.get_mut().before_memory_deallocation(alloc_id, prov_extra, size, machine),
+                    || tb.get_mut().before_memory_deallocation(alloc_id, prov_extra, size, machine),
+                ),
+        };
+        self.trace_access(alloc_id, res, machine, || {
+            format!("deallocation with tag {prov_extra:?}")
+        })
+    }
+
+    /// Records an access on the trace of `-Zmiri-borrow-trace`, including accesses that are
+    /// Undefined Behavior, and passes on its result.
+    fn trace_access<'tcx>(
+        &self,
+        alloc_id: AllocId,
+        res: InterpResult<'tcx>,
+        machine: &MiriMachine<'tcx>,
+        event: impl FnOnce() -> String,
+    ) -> InterpResult<'tcx> {
+        if !is_traced(machine, alloc_id) {
+            return res;
+        }
+        match res.report_err() {
+            Ok(()) => {
+                record_event(machine, alloc_id, self, event);
+                interp_ok(())
+            }
+            Err(err) => {
+                record_event(machine, alloc_id, self, || {
+                    format!("{} (Undefined Behavior)", event())
+                });
+                err.into()
+            }
         }
     }
 
@@ -477,6 +615,10 @@ impl AllocState {
         match self {
             AllocState::StackedBorrows(sb) => sb.borrow_mut().remove_unreachable_tags(tags),
             AllocState::TreeBorrows(tb) => tb.borrow_mut().remove_unreachable_tags(tags),
+            AllocState::Both(sb, tb) => {
+                sb.borrow_mut().remove_unreachable_tags(tags);
+                tb.borrow_mut().remove_unreachable_tags(tags);
+            }
         }
     }
 
@@ -492,6 +634,16 @@ impl AllocState {
             AllocState::StackedBorrows(_sb) => interp_ok(()),
             AllocState::TreeBorrows(tb) =>
                 tb.borrow_mut().release_protector(machine, global, tag, alloc_id),
+            AllocState::Both(_sb, tb) => {
+                // Stacked Borrows also protects pointers that Tree Borrows does not retag,
+                // so Tree Borrows may not know this tag.
+                if !global.borrow().active_models().1 || !tb.borrow().has_tag(tag) {
+                    return interp_ok(());
+                }
+                let res = tb.borrow_mut().release_protector(machine, global, tag, alloc_id);
+                check_model(machine, BorrowTrackerMethod::TreeBorrows, res)?;
+                interp_ok(())
+            }
         }
     }
 }
@@ -501,6 +653,10 @@ impl VisitProvenance for AllocState {
         match self {
             AllocState::StackedBorrows(sb) => sb.visit_provenance(visit),
             AllocState::TreeBorrows(tb) => tb.visit_provenance(visit),
+            AllocState::Both(sb, tb) => {
+                sb.visit_provenance(visit);
+                tb.visit_provenance(visit);
+            }
         }
     }
 }
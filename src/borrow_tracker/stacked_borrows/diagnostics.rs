@@ -13,8 +13,9 @@ fn err_sb_ub<'tcx>(
     msg: String,
     help: Vec<String>,
     history: Option<TagHistory>,
+    involved: InvolvedTags,
 ) -> InterpErrorKind<'tcx> {
-    err_machine_stop!(TerminationInfo::StackedBorrowsUb { msg, help, history })
+    err_machine_stop!(TerminationInfo::StackedBorrowsUb { msg, help, history, involved })
 }
 
 #[derive(Clone, Debug)]
@@ -397,6 +398,7 @@ impl<'history, 'ecx, 'tcx> DiagnosticCx<'history, 'ecx, 'tcx> {
             format!("{action}{}", error_cause(stack, op.orig_tag)),
             helps,
             op.orig_tag.and_then(|orig_tag| self.get_logs_relevant_to(orig_tag, None)),
+            self.involved_tags(&[op.orig_tag, ProvenanceExtra::Concrete(op.new_tag)]),
         )
     }
 
@@ -420,6 +422,7 @@ impl<'history, 'ecx, 'tcx> DiagnosticCx<'history, 'ecx, 'tcx> {
             format!("{action}{}", error_cause(stack, op.tag)),
             vec![operation_summary("an access", self.history.id, op.range)],
             op.tag.and_then(|tag| self.get_logs_relevant_to(tag, None)),
+            self.involved_tags(&[op.tag]),
         )
     }
 
@@ -433,9 +436,15 @@ impl<'history, 'ecx, 'tcx> DiagnosticCx<'history, 'ecx, 'tcx> {
             ProtectorKind::WeakProtector => "weakly protected",
             ProtectorKind::StrongProtector => "strongly protected",
         };
+        let protected_tag = ProvenanceExtra::Concrete(item.tag());
         match self.operation {
-            Operation::Dealloc(_) =>
-                err_sb_ub(format!("deallocating while item {item:?} is {protected}",), vec![], None),
+            Operation::Dealloc(DeallocOp { tag }) =>
+                err_sb_ub(
+                    format!("deallocating while item {item:?} is {protected}",),
+                    vec![],
+                    None,
+                    self.involved_tags(&[tag, protected_tag]),
+                ),
             Operation::Retag(RetagOp { orig_tag: tag, .. })
             | Operation::Access(AccessOp { tag, .. }) =>
                 err_sb_ub(
@@ -444,6 +453,7 @@ impl<'history, 'ecx, 'tcx> DiagnosticCx<'history, 'ecx, 'tcx> {
                     ),
                     vec![],
                     tag.and_then(|tag| self.get_logs_relevant_to(tag, Some(item.tag()))),
+                    self.involved_tags(&[tag, protected_tag]),
                 ),
         }
     }
@@ -462,9 +472,18 @@ impl<'history, 'ecx, 'tcx> DiagnosticCx<'history, 'ecx, 'tcx> {
             ),
             vec![],
             op.tag.and_then(|tag| self.get_logs_relevant_to(tag, None)),
+            self.involved_tags(&[op.tag]),
         )
     }
 
+    /// The allocation of this operation and the given tags, for `-Zmiri-auto-track`.
+    fn involved_tags(&self, tags: &[ProvenanceExtra]) -> InvolvedTags {
+        InvolvedTags {
+            alloc_id: self.history.id,
+            tags: tags.iter().filter_map(|tag| tag.and_then(Some)).collect(),
+        }
+    }
+
     #[inline(never)]
     pub fn check_tracked_tag_popped(&self, item: &Item, global: &GlobalStateInner) {
         if !global.tracked_pointer_tags.contains(&item.tag()) {
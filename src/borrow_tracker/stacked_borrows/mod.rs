@@ -21,6 +21,7 @@ pub use self::stack::Stack;
 use crate::borrow_tracker::stacked_borrows::diagnostics::{
     AllocHistory, DiagnosticCx, DiagnosticCxBuilder,
 };
+use crate::borrow_tracker::trace::record_event;
 use crate::borrow_tracker::{GlobalStateInner, ProtectorKind};
 use crate::concurrency::data_race::{NaReadType, NaWriteType};
 use crate::*;
@@ -446,6 +447,27 @@ impl Stacks {
     }
 }
 
+impl Stacks {
+    /// Render the contents of all stacks, one line per location range.
+    pub fn display_stacks(&self) -> Vec<String> {
+        self.stacks
+            .iter_all()
+            .map(|(range, stack)| {
+                let mut line = format!("{range:?}: [");
+                if let Some(bottom) = stack.unknown_bottom() {
+                    line.push_str(&format!(" unknown-bottom(..{bottom:?})"));
+                }
+                for i in 0..stack.len() {
+                    let item = stack.get(i).unwrap();
+                    line.push_str(&format!(" {:?}{:?}", item.perm(), item.tag()));
+                }
+                line.push_str(" ]");
+                line
+            })
+            .collect()
+    }
+}
+
 impl VisitProvenance for Stacks {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
         for tag in self.exposed_tags.iter().copied() {
@@ -805,6 +827,20 @@ trait EvalContextPrivExt<'tcx, 'ecx>: crate::MiriInterpCxExt<'tcx> {
             }
         }
 
+        let alloc_state = this.get_alloc_extra(alloc_id)?.borrow_tracker.as_ref().unwrap();
+        record_event(&this.machine, alloc_id, alloc_state, || {
+            let perm = match new_perm {
+                NewPermission::Uniform { perm, .. } => format!("{perm:?}"),
+                NewPermission::FreezeSensitive { freeze_perm, nonfreeze_perm, .. } =>
+                    format!("{freeze_perm:?}/{nonfreeze_perm:?}"),
+            };
+            let protected = if new_perm.protector().is_some() { ", protected" } else { "" };
+            format!(
+                "retag {orig_tag:?} -> {new_tag:?} at {:?} ({perm}{protected})",
+                alloc_range(base_offset, size)
+            )
+        });
+
         interp_ok(Some(Provenance::Concrete { alloc_id, tag: new_tag }))
     }
 
@@ -841,6 +877,14 @@ trait EvalContextPrivExt<'tcx, 'ecx>: crate::MiriInterpCxExt<'tcx> {
 
         // Reborrow.
         let new_prov = this.sb_reborrow(place, size, new_perm, new_tag, info)?;
+        // In differential mode, Tree Borrows reuses this tag if it retags the same pointer.
+        if let Some(Provenance::Concrete { tag: orig_tag, .. }) = place.ptr().provenance
+            && let Some(Provenance::Concrete { tag, .. }) = new_prov
+            && tag == new_tag
+        {
+            let global = this.machine.borrow_tracker.as_mut().unwrap().get_mut();
+            global.record_sb_retag(new_tag, orig_tag);
+        }
 
         // Adjust place.
         // (If the closure gets called, that means the old provenance was `Some`, and hence the new
@@ -1038,16 +1082,8 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let this = self.eval_context_mut();
         let alloc_extra = this.get_alloc_extra(alloc_id)?;
         let stacks = alloc_extra.borrow_tracker_sb().borrow();
-        for (range, stack) in stacks.stacks.iter_all() {
-            print!("{range:?}: [");
-            if let Some(bottom) = stack.unknown_bottom() {
-                print!(" unknown-bottom(..{bottom:?})");
-            }
-            for i in 0..stack.len() {
-                let item = stack.get(i).unwrap();
-                print!(" {:?}{:?}", item.perm(), item.tag());
-            }
-            println!(" ]");
+        for line in stacks.display_stacks() {
+            println!("{line}");
         }
         interp_ok(())
     }
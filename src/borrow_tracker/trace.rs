@@ -0,0 +1,338 @@
+//! Implements `-Zmiri-borrow-trace`, which records how the borrow state of a single allocation
+//! evolves over the execution.
+//!
+//! Every retag, access, deallocation and protector release on the traced allocation adds a step
+//! holding a description of the event, its source location, and a rendering of the stacks (for
+//! Stacked Borrows) or the tree (for Tree Borrows) right after the event. When the program ends,
+//! the steps are written to an HTML page that can be stepped through in a browser, and to a
+//! Graphviz file that shows the same steps as a timeline. To bound the memory used by the trace,
+//! at most `MAX_STEPS` steps with renderings of at most `MAX_STATE_LINES` lines are recorded.
+
+use std::fmt::Write as _;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use rustc_span::{FileName, Span};
+
+use crate::borrow_tracker::{AllocState, GlobalStateInner};
+use crate::*;
+
+/// The maximal number of steps recorded. Later events are counted, but not recorded.
+const MAX_STEPS: usize = 10_000;
+
+/// The maximal number of lines of the rendering of the borrow state of a step.
+const MAX_STATE_LINES: usize = 1_000;
+
+/// A single event on the traced allocation.
+#[derive(Debug)]
+struct TraceStep {
+    /// What happened, e.g. "write access with tag <12> at 0..4".
+    event: String,
+    /// Where it happened, for display.
+    location: String,
+    /// The source file containing `location`, if it is a real file on disk.
+    path: Option<(PathBuf, usize)>,
+    /// The borrow state of the allocation after the event, one line per stack or tree node.
+    state: Vec<String>,
+}
+
+/// The trace of a single allocation.
+#[derive(Debug)]
+pub struct BorrowTrace {
+    alloc_id: AllocId,
+    /// The directory to write the trace to.
+    dir: PathBuf,
+    steps: Vec<TraceStep>,
+    /// The number of events that were not recorded because the trace had reached `MAX_STEPS`.
+    dropped_steps: u64,
+}
+
+impl BorrowTrace {
+    pub fn new(alloc_id: AllocId, dir: PathBuf) -> Self {
+        BorrowTrace { alloc_id, dir, steps: Vec::new(), dropped_steps: 0 }
+    }
+
+    /// The number of recorded steps.
+    pub fn recorded_steps(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// The number of events that happened after the trace reached its maximal length.
+    pub fn dropped_steps(&self) -> u64 {
+        self.dropped_steps
+    }
+
+    /// Writes the trace as HTML and Graphviz files, and returns the path of the HTML file.
+    pub fn write(&self) -> io::Result<PathBuf> {
+        fs::create_dir_all(&self.dir)?;
+        let base = format!("borrow-trace-alloc{}", self.alloc_id.0);
+        let html = self.dir.join(format!("{base}.html"));
+        self.write_html(&html)?;
+        self.write_dot(&self.dir.join(format!("{base}.dot")))?;
+        Ok(html)
+    }
+
+    fn write_html(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        let title = format!("Borrow trace of {:?}", self.alloc_id);
+        writeln!(file, "<!DOCTYPE html>")?;
+        writeln!(file, "<html><head><meta charset=\"utf-8\"><title>{title}</title>")?;
+        writeln!(file, "<style>")?;
+        writeln!(file, "body {{ font-family: sans-serif; display: flex; margin: 0; }}")?;
+        writeln!(
+            file,
+            "#steps {{ width: 40%; height: 100vh; overflow-y: auto; border-right: 1px solid #ccc; }}"
+        )?;
+        writeln!(file, "#steps li {{ cursor: pointer; padding: 2px 4px; }}")?;
+        writeln!(file, "#steps li.current {{ background: #ddeeff; }}")?;
+        writeln!(file, "#steps li.ub {{ color: #b00; }}")?;
+        writeln!(file, "#panel {{ flex: 1; padding: 0 1em; height: 100vh; overflow: auto; }}")?;
+        writeln!(file, "#state .changed {{ background: #fff3b0; }}")?;
+        writeln!(file, "</style></head><body>")?;
+        writeln!(file, "<ol id=\"steps\" start=\"0\">")?;
+        for step in &self.steps {
+            let class =
+                if step.event.ends_with("(Undefined Behavior)") { " class=\"ub\"" } else { "" };
+            writeln!(
+                file,
+                "<li{class}>{}<br><small>{}</small></li>",
+                escape_html(&step.event),
+                self.link(step),
+            )?;
+        }
+        writeln!(file, "</ol>")?;
+        writeln!(file, "<div id=\"panel\"><h2>{title}</h2>")?;
+        if self.dropped_steps > 0 {
+            writeln!(
+                file,
+                "<p>The trace was truncated: {} later events were not recorded.</p>",
+                self.dropped_steps
+            )?;
+        }
+        writeln!(
+            file,
+            "<p><button id=\"prev\">&larr;</button> <span id=\"pos\"></span> \
+             <button id=\"next\">&rarr;</button></p>"
+        )?;
+        writeln!(file, "<p id=\"event\"></p><pre id=\"state\"></pre></div>")?;
+        writeln!(file, "<script>")?;
+        writeln!(file, "const steps = [")?;
+        for step in &self.steps {
+            let state = step
+                .state
+                .iter()
+                .map(|line| format!("\"{}\"", escape_js(line)))
+                .collect::<Vec<_>>();
+            writeln!(
+                file,
+                "  {{ event: \"{}\", location: \"{}\", state: [{}] }},",
+                escape_js(&step.event),
+                escape_js(&step.location),
+                state.join(", "),
+            )?;
+        }
+        writeln!(file, "];")?;
+        file.write_all(HTML_SCRIPT.as_bytes())?;
+        writeln!(file, "</script></body></html>")?;
+        file.flush()
+    }
+
+    fn write_dot(&self, path: &Path) -> io::Result<()> {
+        let mut file = BufWriter::new(File::create(path)?);
+        writeln!(file, "digraph borrow_trace {{")?;
+        writeln!(file, "  node [shape=box, fontname=monospace];")?;
+        for (i, step) in self.steps.iter().enumerate() {
+            let mut label =
+                format!("{i}: {}\\l{}\\l\\l", escape_dot(&step.event), escape_dot(&step.location));
+            for line in &step.state {
+                write!(label, "{}\\l", escape_dot(line)).unwrap();
+            }
+            write!(file, "  step{i} [label=\"{label}\"")?;
+            if let Some(url) = file_url(step) {
+                write!(file, ", URL=\"{}\"", escape_dot(&url))?;
+            }
+            writeln!(file, "];")?;
+            if i > 0 {
+                writeln!(file, "  step{} -> step{i};", i - 1)?;
+            }
+        }
+        if self.dropped_steps > 0 {
+            writeln!(
+                file,
+                "  truncated [label=\"{} later events were not recorded\", shape=plaintext];",
+                self.dropped_steps
+            )?;
+            if let Some(last) = self.steps.len().checked_sub(1) {
+                writeln!(file, "  step{last} -> truncated;")?;
+            }
+        }
+        writeln!(file, "}}")?;
+        file.flush()
+    }
+
+    /// The location of a step, as a link to the source file if it exists on disk.
+    fn link(&self, step: &TraceStep) -> String {
+        match file_url(step) {
+            Some(url) =>
+                format!("<a href=\"{}\">{}</a>", escape_html(&url), escape_html(&step.location)),
+            None => escape_html(&step.location),
+        }
+    }
+}
+
+/// Steps through the trace and highlights the lines of the state that changed since the previous
+/// step. Expects `steps` to be defined.
+const HTML_SCRIPT: &str = r##"
+const items = document.querySelectorAll("#steps li");
+let current = 0;
+function show(i) {
+  if (i < 0 || i >= steps.length) return;
+  items[current].classList.remove("current");
+  current = i;
+  items[current].classList.add("current");
+  items[current].scrollIntoView({ block: "nearest" });
+  document.getElementById("pos").textContent = `step ${i} of ${steps.length - 1}`;
+  document.getElementById("event").textContent = `${steps[i].event} (${steps[i].location})`;
+  const prev = i > 0 ? new Set(steps[i - 1].state) : new Set();
+  const state = document.getElementById("state");
+  state.replaceChildren(...steps[i].state.map(line => {
+    const div = document.createElement("div");
+    div.textContent = line;
+    if (i > 0 && !prev.has(line)) div.className = "changed";
+    return div;
+  }));
+}
+items.forEach((item, i) => item.addEventListener("click", () => show(i)));
+document.getElementById("prev").onclick = () => show(current - 1);
+document.getElementById("next").onclick = () => show(current + 1);
+document.addEventListener("keydown", e => {
+  if (e.key === "ArrowUp" || e.key === "ArrowLeft") show(current - 1);
+  if (e.key === "ArrowDown" || e.key === "ArrowRight") show(current + 1);
+});
+if (steps.length > 0) show(0);
+"##;
+
+fn file_url(step: &TraceStep) -> Option<String> {
+    let (path, line) = step.path.as_ref()?;
+    let path = path.canonicalize().ok()?;
+    Some(format!("file://{}#L{line}", path.display()))
+}
+
+fn escape_html(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '&' => escaped.push_str("&amp;"),
+            '"' => escaped.push_str("&quot;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_js(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            // Keep `</script>` in the data from ending the script.
+            '<' => escaped.push_str("\\u003c"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn escape_dot(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl AllocState {
+    /// Renders the borrow state of this allocation, one line per stack or tree node.
+    fn render(&self, global: &GlobalStateInner) -> Vec<String> {
+        match self {
+            AllocState::StackedBorrows(sb) => sb.borrow().display_stacks(),
+            AllocState::TreeBorrows(tb) => tb.borrow().display_tree(&global.protected_tags, true),
+            AllocState::Both(sb, tb) => {
+                let mut lines = vec!["Stacked Borrows:".to_string()];
+                lines.extend(sb.borrow().display_stacks());
+                lines.push("Tree Borrows:".to_string());
+                lines.extend(tb.borrow().display_tree(&global.protected_tags, true));
+                lines
+            }
+        }
+    }
+}
+
+impl GlobalStateInner {
+    /// Whether `-Zmiri-borrow-trace` is tracing `alloc_id`.
+    pub(super) fn is_traced(&self, alloc_id: AllocId) -> bool {
+        self.borrow_trace.as_ref().is_some_and(|trace| trace.alloc_id == alloc_id)
+    }
+
+    /// Adds a step to the trace. The caller must have checked `is_traced`.
+    pub(super) fn push_trace_step(
+        &mut self,
+        machine: &MiriMachine<'_>,
+        alloc: &AllocState,
+        event: String,
+    ) {
+        let trace = self.borrow_trace.as_mut().unwrap();
+        if trace.steps.len() >= MAX_STEPS {
+            trace.dropped_steps += 1;
+            return;
+        }
+        let mut state = alloc.render(self);
+        if state.len() > MAX_STATE_LINES {
+            let omitted = state.len() - MAX_STATE_LINES;
+            state.truncate(MAX_STATE_LINES);
+            state.push(format!("... {omitted} more lines"));
+        }
+        let span = machine.current_span();
+        let (location, path) = describe_span(machine, span);
+        let trace = self.borrow_trace.as_mut().unwrap();
+        trace.steps.push(TraceStep { event, location, path, state });
+    }
+
+    /// The trace of `-Zmiri-borrow-trace`, if enabled.
+    pub fn borrow_trace(&self) -> Option<&BorrowTrace> {
+        self.borrow_trace.as_ref()
+    }
+}
+
+/// Whether `-Zmiri-borrow-trace` is tracing `alloc_id`. Unlike `GlobalStateInner::is_traced`,
+/// this does not need to borrow the global state of the borrow tracker.
+pub(super) fn is_traced(machine: &MiriMachine<'_>, alloc_id: AllocId) -> bool {
+    machine.borrow_trace_alloc == Some(alloc_id)
+}
+
+/// Records an event on `alloc_id` if that allocation is traced. `event` is only called if it is.
+pub(super) fn record_event(
+    machine: &MiriMachine<'_>,
+    alloc_id: AllocId,
+    alloc: &AllocState,
+    event: impl FnOnce() -> String,
+) {
+    if !is_traced(machine, alloc_id) {
+        return;
+    }
+    machine.borrow_tracker.as_ref().unwrap().borrow_mut().push_trace_step(machine, alloc, event());
+}
+
+fn describe_span(machine: &MiriMachine<'_>, span: Span) -> (String, Option<(PathBuf, usize)>) {
+    if span.is_dummy() {
+        return ("<no location>".to_string(), None);
+    }
+    let source_map = machine.tcx.sess.source_map();
+    let loc = source_map.lookup_char_pos(span.lo());
+    let path = match &loc.file.name {
+        FileName::Real(name) => name.local_path().map(|path| (path.to_path_buf(), loc.line)),
+        _ => None,
+    };
+    (source_map.span_to_embeddable_string(span), path)
+}
@@ -359,7 +359,12 @@ impl TbError<'_> {
             conflicting_tag_name,
             true,
         );
-        err_machine_stop!(TerminationInfo::TreeBorrowsUb { title, details, history })
+        let mut tags = vec![accessed.tag];
+        if !accessed_is_conflicting {
+            tags.push(conflicting.tag);
+        }
+        let involved = InvolvedTags { alloc_id: self.alloc_id, tags };
+        err_machine_stop!(TerminationInfo::TreeBorrowsUb { title, details, history, involved })
     }
 }
 
@@ -645,7 +650,8 @@ impl DisplayRepr {
         protected_tags: &FxHashMap<BorTag, ProtectorKind>,
         ranges: Vec<Range<u64>>,
         print_warning: bool,
-    ) {
+    ) -> Vec<String> {
+        let mut out = Vec::new();
         let mut block = Vec::new();
         // Push the header and compute the required paddings for the body.
         // Header looks like this: `0.. 1.. 2.. 3.. 4.. 5.. 6.. 7.. 8`,
@@ -696,15 +702,14 @@ impl DisplayRepr {
                     block_width
                 }
             };
-            eprintln!("{}", char_repeat(wr.top, max_width));
+            out.push(char_repeat(wr.top, max_width));
             if print_warning {
-                eprintln!("{}", wr.warning_text,);
+                out.push(wr.warning_text.to_string());
             }
-            for line in block {
-                eprintln!("{line}");
-            }
-            eprintln!("{}", char_repeat(wr.bot, max_width));
+            out.extend(block);
+            out.push(char_repeat(wr.bot, max_width));
         }
+        return out;
 
         // Here is the function that does the heavy lifting
         fn print_aux(
@@ -787,17 +792,29 @@ impl<'tcx> Tree {
         protected_tags: &FxHashMap<BorTag, ProtectorKind>,
         show_unnamed: bool,
     ) -> InterpResult<'tcx> {
-        let mut indenter = DisplayIndent::new();
-        let ranges = self.rperms.iter_all().map(|(range, _perms)| range).collect::<Vec<_>>();
-        if let Some(repr) = DisplayRepr::from(self, show_unnamed) {
-            repr.print(
-                &DEFAULT_FORMATTER,
-                &mut indenter,
-                protected_tags,
-                ranges,
-                /* print warning message about tags not shown */ !show_unnamed,
-            );
+        for line in self.display_tree(protected_tags, show_unnamed) {
+            eprintln!("{line}");
         }
         interp_ok(())
     }
+
+    /// Render the contents of the tree, one line per tag.
+    pub fn display_tree(
+        &self,
+        protected_tags: &FxHashMap<BorTag, ProtectorKind>,
+        show_unnamed: bool,
+    ) -> Vec<String> {
+        let mut indenter = DisplayIndent::new();
+        let ranges = self.rperms.iter_all().map(|(range, _perms)| range).collect::<Vec<_>>();
+        let Some(repr) = DisplayRepr::from(self, show_unnamed) else {
+            return Vec::new();
+        };
+        repr.print(
+            &DEFAULT_FORMATTER,
+            &mut indenter,
+            protected_tags,
+            ranges,
+            /* print warning message about tags not shown */ !show_unnamed,
+        )
+    }
 }
@@ -4,6 +4,7 @@ use rustc_middle::ty::layout::HasTypingEnv;
 use rustc_middle::ty::{self, Ty};
 use rustc_span::def_id::DefId;
 
+use crate::borrow_tracker::trace::record_event;
 use crate::borrow_tracker::{GlobalState, GlobalStateInner, ProtectorKind};
 use crate::concurrency::data_race::NaReadType;
 use crate::*;
@@ -59,6 +60,7 @@ impl<'tcx> Tree {
             ProvenanceExtra::Wildcard => return interp_ok(()),
         };
         let global = machine.borrow_tracker.as_ref().unwrap();
+        let tag = global.borrow().tb_tag(tag);
         let span = machine.current_span();
         self.perform_access(
             tag,
@@ -84,6 +86,7 @@ impl<'tcx> Tree {
             ProvenanceExtra::Wildcard => return interp_ok(()),
         };
         let global = machine.borrow_tracker.as_ref().unwrap();
+        let tag = global.borrow().tb_tag(tag);
         let span = machine.current_span();
         self.dealloc(tag, alloc_range(Size::ZERO, size), global, alloc_id, span)
     }
@@ -244,6 +247,8 @@ trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             ProvenanceExtra::Wildcard => return interp_ok(place.ptr().provenance), // TODO: handle wildcard pointers
             ProvenanceExtra::Concrete(tag) => tag,
         };
+        // In differential mode, the parent may carry a tag that only Stacked Borrows knows.
+        let orig_tag = this.machine.borrow_tracker.as_ref().unwrap().borrow().tb_tag(orig_tag);
 
         trace!(
             "reborrow: reference {:?} derived from {:?} (pointee {}): {:?}, size {}",
@@ -254,7 +259,18 @@ trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             ptr_size.bytes()
         );
 
-        if let Some(protect) = new_perm.protector {
+        // In differential mode, Stacked Borrows has already registered the protector of this tag.
+        let already_protected = this
+            .machine
+            .borrow_tracker
+            .as_ref()
+            .unwrap()
+            .borrow()
+            .protected_tags
+            .contains_key(&new_tag);
+        if let Some(protect) = new_perm.protector
+            && !already_protected
+        {
             // We register the protection in two different places.
             // This makes creating a protector slower, but checking whether a tag
             // is protected faster.
@@ -312,6 +328,14 @@ trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             }
         }
 
+        record_event(&this.machine, alloc_id, alloc_extra.borrow_tracker.as_ref().unwrap(), || {
+            let protected = if new_perm.protector.is_some() { ", protected" } else { "" };
+            format!(
+                "retag {orig_tag:?} -> {new_tag:?} at {range:?} ({}{protected})",
+                new_perm.initial_state
+            )
+        });
+
         interp_ok(Some(Provenance::Concrete { alloc_id, tag: new_tag }))
     }
 
@@ -342,10 +366,20 @@ trait EvalContextPrivExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         // so that rather than taking as input a fresh tag and deciding whether it uses this
         // one or the parent it instead just returns whether a new tag should be created.
         // This will avoid creating tags than end up never being used.
-        let new_tag = this.machine.borrow_tracker.as_mut().unwrap().get_mut().new_ptr();
+        // In differential mode, this is the tag that Stacked Borrows picked for the same retag.
+        let global = this.machine.borrow_tracker.as_mut().unwrap().get_mut();
+        let Some(new_tag) = global.tb_new_tag(place.ptr().provenance) else {
+            // Stacked Borrows did not retag this pointer, so we cannot either.
+            return interp_ok(place.clone());
+        };
 
         // Compute the actual reborrow.
         let new_prov = this.tb_reborrow(place, reborrow_size, new_perm, new_tag)?;
+        if let Some(Provenance::Concrete { tag, .. }) = new_prov
+            && tag == new_tag
+        {
+            this.machine.borrow_tracker.as_mut().unwrap().get_mut().record_tb_retag(new_tag);
+        }
 
         // Adjust place.
         // (If the closure gets called, that means the old provenance was `Some`, and hence the new
@@ -545,6 +579,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 // if converting this alloc_id from a global to a local one
                 // uncovers a non-supported `extern static`.
                 let alloc_extra = this.get_alloc_extra(alloc_id)?;
+                let tag = this.machine.borrow_tracker.as_ref().unwrap().borrow().tb_tag(tag);
                 trace!("Tree Borrows tag {tag:?} exposed in {alloc_id:?}");
                 alloc_extra.borrow_tracker_tb().borrow_mut().expose_tag(tag);
             }
@@ -581,6 +616,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 return interp_ok(());
             }
         };
+        let tag = this.machine.borrow_tracker.as_ref().unwrap().borrow().tb_tag(tag);
         let alloc_extra = this.get_alloc_extra(alloc_id)?;
         let mut tree_borrows = alloc_extra.borrow_tracker_tb().borrow_mut();
         tree_borrows.give_pointer_debug_name(tag, nth_parent, name)
@@ -606,6 +606,11 @@ impl Tree {
         };
         Self { root: root_idx, nodes, rperms, tag_mapping }
     }
+
+    /// Whether this tree has a node for the given tag.
+    pub fn has_tag(&self, tag: BorTag) -> bool {
+        self.tag_mapping.contains_key(&tag)
+    }
 }
 
 impl<'tcx> Tree {
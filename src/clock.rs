@@ -1,5 +1,5 @@
 use std::cell::Cell;
-use std::time::{Duration, Instant as StdInstant};
+use std::time::{Duration, Instant as StdInstant, SystemTime};
 
 /// When using a virtual clock, this defines how many nanoseconds we pretend are passing for each
 /// basic block.
@@ -72,6 +72,8 @@ enum ClockKind {
         /// The "epoch" for this machine's monotone clock:
         /// the moment we consider to be time = 0.
         epoch: StdInstant,
+        /// The wall-clock time at `epoch`.
+        system_epoch: SystemTime,
     },
     Virtual {
         /// The "current virtual time".
@@ -83,7 +85,7 @@ impl Clock {
     /// Create a new clock based on the availability of communication with the host.
     pub fn new(communicate: bool) -> Self {
         let kind = if communicate {
-            ClockKind::Host { epoch: StdInstant::now() }
+            ClockKind::Host { epoch: StdInstant::now(), system_epoch: SystemTime::now() }
         } else {
             ClockKind::Virtual { nanoseconds: 0.into() }
         };
@@ -121,7 +123,7 @@ impl Clock {
     /// Return the `epoch` instant (time = 0), to convert between monotone instants and absolute durations.
     pub fn epoch(&self) -> Instant {
         match &self.kind {
-            ClockKind::Host { epoch } => Instant { kind: InstantKind::Host(*epoch) },
+            ClockKind::Host { epoch, .. } => Instant { kind: InstantKind::Host(*epoch) },
             ClockKind::Virtual { .. } => Instant { kind: InstantKind::Virtual { nanoseconds: 0 } },
         }
     }
@@ -133,4 +135,20 @@ impl Clock {
                 Instant { kind: InstantKind::Virtual { nanoseconds: nanoseconds.get() } },
         }
     }
+
+    /// The wall-clock time that corresponds to `now`. The virtual clock starts at the Unix epoch,
+    /// so that it does not depend on the host.
+    pub fn system_time_now(&self) -> SystemTime {
+        match &self.kind {
+            ClockKind::Host { epoch, system_epoch } => *system_epoch + epoch.elapsed(),
+            ClockKind::Virtual { nanoseconds } => {
+                let nanoseconds = nanoseconds.get();
+                SystemTime::UNIX_EPOCH
+                    + Duration::new(
+                        (nanoseconds / 1_000_000_000).try_into().unwrap(),
+                        (nanoseconds % 1_000_000_000).try_into().unwrap(),
+                    )
+            }
+        }
+    }
 }
@@ -71,6 +71,35 @@ impl CpuAffinityMask {
         };
     }
 
+    /// Whether the given CPU is in this mask.
+    pub fn contains<'tcx>(&self, cx: &impl LayoutOf<'tcx>, cpu: usize) -> bool {
+        if cpu >= MAX_CPUS {
+            return false;
+        }
+
+        // See `set` for the representation.
+        let target = &cx.tcx().sess.target;
+        match Self::chunk_size(cx) {
+            4 => {
+                let chunk = self.0[cpu / 32 * 4..].first_chunk::<4>().unwrap();
+                let chunk = match target.options.endian {
+                    Endian::Little => u32::from_le_bytes(*chunk),
+                    Endian::Big => u32::from_be_bytes(*chunk),
+                };
+                chunk & 1 << (cpu % 32) != 0
+            }
+            8 => {
+                let chunk = self.0[cpu / 64 * 8..].first_chunk::<8>().unwrap();
+                let chunk = match target.options.endian {
+                    Endian::Little => u64::from_le_bytes(*chunk),
+                    Endian::Big => u64::from_be_bytes(*chunk),
+                };
+                chunk & 1 << (cpu % 64) != 0
+            }
+            other => bug!("chunk size not supported: {other}"),
+        }
+    }
+
     pub fn as_slice(&self) -> &[u8] {
         self.0.as_slice()
     }
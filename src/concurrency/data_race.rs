@@ -55,6 +55,7 @@ use rustc_span::Span;
 use super::vector_clock::{VClock, VTimestamp, VectorIdx};
 use super::weak_memory::EvalContextExt as _;
 use crate::diagnostics::RacingOp;
+use crate::self_profile::Subsystem;
 use crate::*;
 
 pub type AllocState = VClockAlloc;
@@ -835,7 +836,11 @@ pub trait EvalContextExt<'tcx>: MiriInterpCxExt<'tcx> {
     /// The closure will only be invoked if data race handling is on.
     fn release_clock<R>(&self, callback: impl FnOnce(&VClock) -> R) -> Option<R> {
         let this = self.eval_context_ref();
-        Some(this.machine.data_race.as_ref()?.release_clock(&this.machine.threads, callback))
+        let data_race = this.machine.data_race.as_ref()?;
+        let start = this.machine.self_profile_start();
+        let r = data_race.release_clock(&this.machine.threads, callback);
+        this.machine.self_profile_end(start, Subsystem::DataRace);
+        Some(r)
     }
 
     /// Acquire the given clock into the current thread, establishing synchronization with
@@ -843,7 +848,9 @@ pub trait EvalContextExt<'tcx>: MiriInterpCxExt<'tcx> {
     fn acquire_clock(&self, clock: &VClock) {
         let this = self.eval_context_ref();
         if let Some(data_race) = &this.machine.data_race {
+            let start = this.machine.self_profile_start();
             data_race.acquire_clock(clock, &this.machine.threads);
+            this.machine.self_profile_end(start, Subsystem::DataRace);
         }
     }
 }
@@ -2,6 +2,7 @@ use std::cell::RefCell;
 use std::collections::VecDeque;
 use std::collections::hash_map::Entry;
 use std::default::Default;
+use std::mem;
 use std::ops::Not;
 use std::rc::Rc;
 use std::time::Duration;
@@ -155,6 +156,49 @@ struct FutexWaiter {
     bitset: u32,
 }
 
+/// The semaphore state.
+#[derive(Default, Debug)]
+struct Semaphore {
+    /// The current value of the semaphore.
+    value: u32,
+    /// The queue of threads waiting for the value to become positive.
+    waiters: VecDeque<ThreadId>,
+    /// Tracks the happens-before relationship between a post and a wait that decrements the
+    /// semaphore. Since we do not know which post a wait "consumes", this contains the joined
+    /// clock of all threads that performed a post.
+    clock: VClock,
+}
+
+#[derive(Default, Clone, Debug)]
+pub struct SemaphoreRef(Rc<RefCell<Semaphore>>);
+
+impl VisitProvenance for SemaphoreRef {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // No provenance in `Semaphore`.
+    }
+}
+
+/// The barrier state.
+#[derive(Debug)]
+struct Barrier {
+    /// The number of threads that must call wait before all of them are released.
+    count: u32,
+    /// The threads that are currently waiting at the barrier.
+    waiters: Vec<ThreadId>,
+    /// Tracks the happens-before relationship between all the threads of one round: contains
+    /// the joined clock of the threads that arrived at the barrier in the current round.
+    clock: VClock,
+}
+
+#[derive(Clone, Debug)]
+pub struct BarrierRef(Rc<RefCell<Barrier>>);
+
+impl VisitProvenance for BarrierRef {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // No provenance in `Barrier`.
+    }
+}
+
 /// The state of all synchronization objects.
 #[derive(Default, Debug)]
 pub struct SynchronizationObjects {
@@ -201,6 +245,18 @@ impl SynchronizationObjects {
     pub fn init_once_create(&mut self) -> InitOnceId {
         self.init_onces.push(Default::default())
     }
+
+    pub fn semaphore_create(&mut self, value: u32) -> SemaphoreRef {
+        SemaphoreRef(Rc::new(RefCell::new(Semaphore { value, ..Default::default() })))
+    }
+
+    pub fn barrier_create(&mut self, count: u32) -> BarrierRef {
+        BarrierRef(Rc::new(RefCell::new(Barrier {
+            count,
+            waiters: Vec::new(),
+            clock: VClock::default(),
+        })))
+    }
 }
 
 impl<'tcx> AllocExtra<'tcx> {
@@ -298,6 +354,28 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         }
     }
 
+    /// Helper for lazily initialized `alloc_extra.sync` data:
+    /// Returns whether data of type `T` is stored for the primitive, i.e., whether the primitive
+    /// was initialized at this location and not destroyed since.
+    fn lazy_sync_has_data<T: 'static>(
+        &self,
+        primitive: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_ref();
+        let (alloc, offset, _) = this.ptr_get_alloc_id(primitive.ptr(), 0)?;
+        interp_ok(this.get_alloc_extra(alloc)?.get_sync::<T>(offset).is_some())
+    }
+
+    /// Helper for lazily initialized `alloc_extra.sync` data:
+    /// Removes the data stored for the primitive. This is done when the primitive is destroyed.
+    fn lazy_sync_remove(&mut self, primitive: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let (alloc, offset, _) = this.ptr_get_alloc_id(primitive.ptr(), 0)?;
+        let (alloc_extra, _machine) = this.get_alloc_extra_mut(alloc)?;
+        alloc_extra.sync.remove(&offset);
+        interp_ok(())
+    }
+
     /// Get the synchronization primitive associated with the given pointer,
     /// or initialize a new one.
     ///
@@ -355,9 +433,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             mutex.owner = Some(thread);
         }
         mutex.lock_count = mutex.lock_count.strict_add(1);
-        if let Some(data_race) = &this.machine.data_race {
-            data_race.acquire_clock(&mutex.clock, &this.machine.threads);
-        }
+        this.acquire_clock(&mutex.clock);
     }
 
     /// Try unlocking by decreasing the lock count and returning the old lock
@@ -379,11 +455,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 mutex.owner = None;
                 // The mutex is completely unlocked. Try transferring ownership
                 // to another thread.
-                if let Some(data_race) = &this.machine.data_race {
-                    data_race.release_clock(&this.machine.threads, |clock| {
-                        mutex.clock.clone_from(clock)
-                    });
-                }
+                this.release_clock(|clock| mutex.clock.clone_from(clock));
                 let thread_id = mutex.queue.pop_front();
                 // We need to drop our mutex borrow before unblock_thread
                 // because it will be borrowed again in the unblock callback.
@@ -517,13 +589,13 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     }
 
     /// Put the reader in the queue waiting for the lock and block it.
-    /// Once the lock becomes available, `retval` will be written to `dest`.
+    /// Once the lock becomes available and if it exists, `retval_dest.0` will
+    /// be written to `retval_dest.1`.
     #[inline]
     fn rwlock_enqueue_and_block_reader(
         &mut self,
         id: RwLockId,
-        retval: Scalar,
-        dest: MPlaceTy<'tcx>,
+        retval_dest: Option<(Scalar, MPlaceTy<'tcx>)>,
     ) {
         let this = self.eval_context_mut();
         let thread = this.active_thread();
@@ -535,12 +607,13 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             callback!(
                 @capture<'tcx> {
                     id: RwLockId,
-                    retval: Scalar,
-                    dest: MPlaceTy<'tcx>,
+                    retval_dest: Option<(Scalar, MPlaceTy<'tcx>)>,
                 }
                 @unblock = |this| {
                     this.rwlock_reader_lock(id);
-                    this.write_scalar(retval, &dest)?;
+                    if let Some((retval, dest)) = retval_dest {
+                        this.write_scalar(retval, &dest)?;
+                    }
                     interp_ok(())
                 }
             ),
@@ -602,13 +675,13 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     }
 
     /// Put the writer in the queue waiting for the lock.
-    /// Once the lock becomes available, `retval` will be written to `dest`.
+    /// Once the lock becomes available and if it exists, `retval_dest.0` will
+    /// be written to `retval_dest.1`.
     #[inline]
     fn rwlock_enqueue_and_block_writer(
         &mut self,
         id: RwLockId,
-        retval: Scalar,
-        dest: MPlaceTy<'tcx>,
+        retval_dest: Option<(Scalar, MPlaceTy<'tcx>)>,
     ) {
         let this = self.eval_context_mut();
         assert!(this.rwlock_is_locked(id), "write-queueing on unlocked rwlock");
@@ -620,12 +693,13 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             callback!(
                 @capture<'tcx> {
                     id: RwLockId,
-                    retval: Scalar,
-                    dest: MPlaceTy<'tcx>,
+                    retval_dest: Option<(Scalar, MPlaceTy<'tcx>)>,
                 }
                 @unblock = |this| {
                     this.rwlock_writer_lock(id);
-                    this.write_scalar(retval, &dest)?;
+                    if let Some((retval, dest)) = retval_dest {
+                        this.write_scalar(retval, &dest)?;
+                    }
                     interp_ok(())
                 }
             ),
@@ -755,9 +829,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 @unblock = |this| {
                     let futex = futex_ref.0.borrow();
                     // Acquire the clock of the futex.
-                    if let Some(data_race) = &this.machine.data_race {
-                        data_race.acquire_clock(&futex.clock, &this.machine.threads);
-                    }
+                    this.acquire_clock(&futex.clock);
                     // Write the return value.
                     this.write_scalar(retval_succ, &dest)?;
                     interp_ok(())
@@ -781,12 +853,9 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn futex_wake(&mut self, futex_ref: &FutexRef, bitset: u32) -> InterpResult<'tcx, bool> {
         let this = self.eval_context_mut();
         let mut futex = futex_ref.0.borrow_mut();
-        let data_race = &this.machine.data_race;
 
         // Each futex-wake happens-before the end of the futex wait
-        if let Some(data_race) = data_race {
-            data_race.release_clock(&this.machine.threads, |clock| futex.clock.clone_from(clock));
-        }
+        this.release_clock(|clock| futex.clock.clone_from(clock));
 
         // Wake up the first thread in the queue that matches any of the bits in the bitset.
         let Some(i) = futex.waiters.iter().position(|w| w.bitset & bitset != 0) else {
@@ -797,4 +866,158 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         this.unblock_thread(waiter.thread, BlockReason::Futex)?;
         interp_ok(true)
     }
+
+    /// Get the current value of the semaphore.
+    #[inline]
+    fn semaphore_get_value(&self, sem_ref: &SemaphoreRef) -> u32 {
+        sem_ref.0.borrow().value
+    }
+
+    /// Is any thread waiting on the semaphore?
+    #[inline]
+    fn semaphore_is_awaited(&self, sem_ref: &SemaphoreRef) -> bool {
+        !sem_ref.0.borrow().waiters.is_empty()
+    }
+
+    /// Try to decrement the semaphore without blocking. Returns `true` on success.
+    fn semaphore_try_wait(&mut self, sem_ref: &SemaphoreRef) -> bool {
+        let this = self.eval_context_mut();
+        let mut sem = sem_ref.0.borrow_mut();
+        if sem.value == 0 {
+            return false;
+        }
+        sem.value = sem.value.strict_sub(1);
+        // Synchronize with all previous posts.
+        this.acquire_clock(&sem.clock);
+        true
+    }
+
+    /// Decrement the semaphore, blocking until its value is positive or the timeout happens.
+    /// On success, `retval_succ` is written to `dest`.
+    /// On a timeout, `retval_timeout` is written to `dest` and `errno_timeout` is set as the last error.
+    fn semaphore_wait(
+        &mut self,
+        sem_ref: SemaphoreRef,
+        timeout: Option<(TimeoutClock, TimeoutAnchor, Duration)>,
+        retval_succ: Scalar,
+        retval_timeout: Scalar,
+        dest: MPlaceTy<'tcx>,
+        errno_timeout: IoError,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        if this.semaphore_try_wait(&sem_ref) {
+            return this.write_scalar(retval_succ, &dest);
+        }
+
+        let thread = this.active_thread();
+        sem_ref.0.borrow_mut().waiters.push_back(thread);
+        this.block_thread(
+            BlockReason::Semaphore,
+            timeout,
+            callback!(
+                @capture<'tcx> {
+                    sem_ref: SemaphoreRef,
+                    retval_succ: Scalar,
+                    retval_timeout: Scalar,
+                    dest: MPlaceTy<'tcx>,
+                    errno_timeout: IoError,
+                }
+                @unblock = |this| {
+                    // The post that woke us up already handed the value over to us, so we only
+                    // need to acquire the clock.
+                    this.acquire_clock(&sem_ref.0.borrow().clock);
+                    this.write_scalar(retval_succ, &dest)?;
+                    interp_ok(())
+                }
+                @timeout = |this| {
+                    // Remove the waiter from the semaphore.
+                    let thread = this.active_thread();
+                    sem_ref.0.borrow_mut().waiters.retain(|waiter| *waiter != thread);
+                    // Set errno and write return value.
+                    this.set_last_error(errno_timeout)?;
+                    this.write_scalar(retval_timeout, &dest)?;
+                    interp_ok(())
+                }
+            ),
+        );
+        interp_ok(())
+    }
+
+    /// Increment the semaphore, or directly hand the increment over to the first waiting thread.
+    /// Returns `false` if the value would exceed `max`.
+    fn semaphore_post(&mut self, sem_ref: &SemaphoreRef, max: u32) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+        let mut sem = sem_ref.0.borrow_mut();
+        if sem.value >= max {
+            return interp_ok(false);
+        }
+
+        // Each post happens-before the end of every later wait.
+        this.release_clock(|clock| sem.clock.join(clock));
+
+        let Some(waiter) = sem.waiters.pop_front() else {
+            sem.value = sem.value.strict_add(1);
+            return interp_ok(true);
+        };
+        drop(sem);
+        this.unblock_thread(waiter, BlockReason::Semaphore)?;
+        interp_ok(true)
+    }
+
+    /// Is any thread waiting at the barrier?
+    #[inline]
+    fn barrier_is_awaited(&self, barrier_ref: &BarrierRef) -> bool {
+        !barrier_ref.0.borrow().waiters.is_empty()
+    }
+
+    /// Wait at the barrier until `count` threads have arrived. The thread that completes the
+    /// round gets `retval_last` written to `dest`, and all the others get `retval`.
+    fn barrier_wait(
+        &mut self,
+        barrier_ref: BarrierRef,
+        retval: Scalar,
+        retval_last: Scalar,
+        dest: MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let mut barrier = barrier_ref.0.borrow_mut();
+
+        // Arriving at the barrier happens-before leaving it, for all threads of this round.
+        this.release_clock(|clock| barrier.clock.join(clock));
+
+        let arrived = u32::try_from(barrier.waiters.len()).unwrap().strict_add(1);
+        if arrived < barrier.count {
+            barrier.waiters.push(this.active_thread());
+            drop(barrier);
+            this.block_thread(
+                BlockReason::Barrier,
+                None,
+                callback!(
+                    @capture<'tcx> {
+                        barrier_ref: BarrierRef,
+                        retval: Scalar,
+                        dest: MPlaceTy<'tcx>,
+                    }
+                    @unblock = |this| {
+                        this.acquire_clock(&barrier_ref.0.borrow().clock);
+                        this.write_scalar(retval, &dest)?;
+                        interp_ok(())
+                    }
+                ),
+            );
+            return interp_ok(());
+        }
+
+        // This thread completes the round: wake up everyone else and start a new round.
+        let waiters = mem::take(&mut barrier.waiters);
+        drop(barrier);
+        for waiter in waiters {
+            this.unblock_thread(waiter, BlockReason::Barrier)?;
+        }
+        let mut barrier = barrier_ref.0.borrow_mut();
+        this.acquire_clock(&barrier.clock);
+        barrier.clock = VClock::default();
+        drop(barrier);
+        this.write_scalar(retval_last, &dest)
+    }
 }
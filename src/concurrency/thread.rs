@@ -104,6 +104,17 @@ macro_rules! callback {
     }}
 }
 
+/// The stack size of threads that were created without asking for a particular size. This is also
+/// the default of the Rust standard library. 16bit targets use `MiriMachine::stack_size` instead.
+pub const DEFAULT_THREAD_STACK_SIZE: u64 = 2 * 1024 * 1024;
+
+/// The stack size of the main thread on Unix targets, i.e., the usual `ulimit -s`. This is also the
+/// default soft limit of `RLIMIT_STACK`, which determines the actual size.
+pub const MAIN_THREAD_STACK_SIZE: u64 = 8 * 1024 * 1024;
+
+/// The stack size of the main thread on Windows targets, i.e., the default of the MSVC linker.
+const WINDOWS_MAIN_THREAD_STACK_SIZE: u64 = 1024 * 1024;
+
 /// A thread identifier.
 #[derive(Clone, Copy, Debug, PartialOrd, Ord, PartialEq, Eq, Hash)]
 pub struct ThreadId(u32);
@@ -153,8 +164,14 @@ pub enum BlockReason {
     RwLock(RwLockId),
     /// Blocked on a Futex variable.
     Futex,
+    /// Blocked on a semaphore.
+    Semaphore,
+    /// Blocked on a barrier.
+    Barrier,
     /// Blocked on an InitOnce.
     InitOnce(InitOnceId),
+    /// Blocked waiting for Windows kernel objects (events, mutexes, semaphores) to be signaled.
+    WindowsObject,
     /// Blocked on epoll.
     Epoll,
     /// Blocked on eventfd.
@@ -247,6 +264,13 @@ pub struct Thread<'tcx> {
 
     /// Last OS error location in memory. It is a 32-bit integer.
     pub(crate) last_error: Option<MPlaceTy<'tcx>>,
+
+    /// The size of the stack of this thread, in bytes. The frames on `stack` may use at most this
+    /// much memory for their locals.
+    stack_size: u64,
+
+    /// The memory used by the locals of all frames on `stack`, in bytes.
+    stack_usage: u64,
 }
 
 pub type StackEmptyCallback<'tcx> =
@@ -306,6 +330,28 @@ impl<'tcx> Thread<'tcx> {
             .map(|frame_idx| self.stack[frame_idx].current_span())
             .unwrap_or(rustc_span::DUMMY_SP)
     }
+
+    /// Whether the thread is blocked, e.g. because a shim it called blocked it.
+    pub fn is_blocked(&self) -> bool {
+        matches!(self.state, ThreadState::Blocked { .. })
+    }
+
+    /// The size of the stack of this thread, in bytes.
+    pub fn stack_size(&self) -> u64 {
+        self.stack_size
+    }
+
+    /// Account for a frame of the given size being pushed onto the stack.
+    /// Returns `false` if this exceeds the stack size.
+    pub fn push_stack_usage(&mut self, frame_size: u64) -> bool {
+        self.stack_usage = self.stack_usage.saturating_add(frame_size);
+        self.stack_usage <= self.stack_size
+    }
+
+    /// Account for a frame of the given size being popped off the stack.
+    pub fn pop_stack_usage(&mut self, frame_size: u64) {
+        self.stack_usage = self.stack_usage.saturating_sub(frame_size);
+    }
 }
 
 impl<'tcx> std::fmt::Debug for Thread<'tcx> {
@@ -321,7 +367,11 @@ impl<'tcx> std::fmt::Debug for Thread<'tcx> {
 }
 
 impl<'tcx> Thread<'tcx> {
-    fn new(name: Option<&str>, on_stack_empty: Option<StackEmptyCallback<'tcx>>) -> Self {
+    fn new(
+        name: Option<&str>,
+        on_stack_empty: Option<StackEmptyCallback<'tcx>>,
+        stack_size: u64,
+    ) -> Self {
         Self {
             state: ThreadState::Enabled,
             thread_name: name.map(|name| Vec::from(name.as_bytes())),
@@ -331,6 +381,8 @@ impl<'tcx> Thread<'tcx> {
             panic_payloads: Vec::new(),
             last_error: None,
             on_stack_empty,
+            stack_size,
+            stack_usage: 0,
         }
     }
 }
@@ -346,6 +398,8 @@ impl VisitProvenance for Thread<'_> {
             thread_name: _,
             join_status: _,
             on_stack_empty: _, // we assume the closure captures no GC-relevant state
+            stack_size: _,
+            stack_usage: _,
         } = self;
 
         for payload in panic_payload {
@@ -475,7 +529,8 @@ impl<'tcx> Default for ThreadManager<'tcx> {
     fn default() -> Self {
         let mut threads = IndexVec::new();
         // Create the main thread and add it to the list of threads.
-        threads.push(Thread::new(Some("main"), None));
+        // The stack size gets adjusted for the target in `ThreadManager::init`.
+        threads.push(Thread::new(Some("main"), None, MAIN_THREAD_STACK_SIZE));
         Self {
             active_thread: ThreadId::MAIN_THREAD,
             threads,
@@ -496,6 +551,18 @@ impl<'tcx> ThreadManager<'tcx> {
             // The main thread can *not* be joined on except on windows.
             ecx.machine.threads.threads[ThreadId::MAIN_THREAD].join_status =
                 ThreadJoinStatus::Detached;
+            // Like the kernel, size the main thread's stack after the soft `RLIMIT_STACK`. It can
+            // grow without bound if that is unlimited, but we need some size to detect overflows.
+            ecx.machine.threads.threads[ThreadId::MAIN_THREAD].stack_size =
+                ecx.machine.rlimits["RLIMIT_STACK"].soft.unwrap_or(MAIN_THREAD_STACK_SIZE);
+        } else {
+            // The main thread on Windows gets the default stack size of executables.
+            ecx.machine.threads.threads[ThreadId::MAIN_THREAD].stack_size =
+                WINDOWS_MAIN_THREAD_STACK_SIZE;
+        }
+        if ecx.tcx.pointer_size().bits() < 32 {
+            // The usual stack sizes are more than the entire address space of 16bit targets.
+            ecx.machine.threads.threads[ThreadId::MAIN_THREAD].stack_size = ecx.machine.stack_size;
         }
     }
 
@@ -541,10 +608,14 @@ impl<'tcx> ThreadManager<'tcx> {
         self.threads.iter_enumerated().map(|(id, t)| (id, &t.stack[..]))
     }
 
-    /// Create a new thread and returns its id.
-    fn create_thread(&mut self, on_stack_empty: StackEmptyCallback<'tcx>) -> ThreadId {
+    /// Create a new thread with a stack of the given size and returns its id.
+    fn create_thread(
+        &mut self,
+        on_stack_empty: StackEmptyCallback<'tcx>,
+        stack_size: u64,
+    ) -> ThreadId {
         let new_thread_id = ThreadId::new(self.threads.len());
-        self.threads.push(Thread::new(None, Some(on_stack_empty)));
+        self.threads.push(Thread::new(None, Some(on_stack_empty), stack_size));
         new_thread_id
     }
 
@@ -575,11 +646,21 @@ impl<'tcx> ThreadManager<'tcx> {
         self.threads.iter().filter(|t| !t.state.is_terminated()).count()
     }
 
+    /// Get the ids of the threads that are currently live, i.e., not yet terminated.
+    pub fn get_live_threads(&self) -> impl Iterator<Item = ThreadId> + '_ {
+        self.threads.iter_enumerated().filter(|(_, t)| !t.state.is_terminated()).map(|(id, _)| id)
+    }
+
     /// Has the given thread terminated?
-    fn has_terminated(&self, thread_id: ThreadId) -> bool {
+    pub fn has_terminated(&self, thread_id: ThreadId) -> bool {
         self.threads[thread_id].state.is_terminated()
     }
 
+    /// Has the given thread been detached, i.e., can it no longer be joined?
+    pub fn is_detached(&self, thread_id: ThreadId) -> bool {
+        self.threads[thread_id].join_status == ThreadJoinStatus::Detached
+    }
+
     /// Have all threads terminated?
     fn have_all_terminated(&self) -> bool {
         self.threads.iter().all(|thread| thread.state.is_terminated())
@@ -707,6 +788,11 @@ impl<'tcx> ThreadManager<'tcx> {
         self.threads[thread].thread_name()
     }
 
+    /// Get the stack size of the given thread.
+    pub fn get_thread_stack_size(&self, thread: ThreadId) -> u64 {
+        self.threads[thread].stack_size()
+    }
+
     pub fn get_thread_display_name(&self, thread: ThreadId) -> String {
         self.threads[thread].thread_display_name(thread)
     }
@@ -911,7 +997,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         }
     }
 
-    /// Start a regular (non-main) thread.
+    /// Start a regular (non-main) thread with a stack of the given size.
     #[inline]
     fn start_regular_thread(
         &mut self,
@@ -920,14 +1006,18 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         start_abi: ExternAbi,
         func_arg: ImmTy<'tcx>,
         ret_layout: TyAndLayout<'tcx>,
+        stack_size: u64,
     ) -> InterpResult<'tcx, ThreadId> {
         let this = self.eval_context_mut();
 
         // Create the new thread
-        let new_thread_id = this.machine.threads.create_thread({
-            let mut state = tls::TlsDtorsState::default();
-            Box::new(move |m| state.on_stack_empty(m))
-        });
+        let new_thread_id = this.machine.threads.create_thread(
+            {
+                let mut state = tls::TlsDtorsState::default();
+                Box::new(move |m| state.on_stack_empty(m))
+            },
+            stack_size,
+        );
         let current_span = this.machine.current_span();
         if let Some(data_race) = &mut this.machine.data_race {
             data_race.thread_created(&this.machine.threads, new_thread_id, current_span);
@@ -1003,8 +1093,20 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             // Now free the TLS statics.
             for ptr in free_tls_statics {
                 match tls_alloc_action {
-                    TlsAllocAction::Deallocate =>
-                        this.deallocate_ptr(ptr.into(), None, MiriMemoryKind::Tls.into())?,
+                    TlsAllocAction::Deallocate => {
+                        // Memory that is only referenced from thread-local storage is not lost,
+                        // so remember what this static points to for the leak check.
+                        if let Some(alloc_id) = ptr.provenance.get_alloc_id() {
+                            let pointees: Vec<AllocId> = this
+                                .get_alloc_raw(alloc_id)?
+                                .provenance()
+                                .provenances()
+                                .filter_map(|prov| prov.get_alloc_id())
+                                .collect();
+                            this.machine.tls_roots.extend(pointees);
+                        }
+                        this.deallocate_ptr(ptr.into(), None, MiriMemoryKind::Tls.into())?
+                    }
                     TlsAllocAction::Leak =>
                         if let Some(alloc) = ptr.provenance.get_alloc_id() {
                             trace!(
@@ -1027,6 +1129,9 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         for thread in joining_threads {
             this.unblock_thread(thread, unblock_reason)?;
         }
+        // On Windows, the handle of the thread is a kernel object that just got signaled, and the
+        // kernel mutexes that the thread still owns are abandoned.
+        shims::windows_thread_terminated(this)?;
 
         interp_ok(())
     }
@@ -1177,6 +1282,11 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         self.eval_context_ref().machine.threads.get_thread_name(thread)
     }
 
+    #[inline]
+    fn get_thread_stack_size(&self, thread: ThreadId) -> u64 {
+        self.eval_context_ref().machine.threads.get_thread_stack_size(thread)
+    }
+
     #[inline]
     fn yield_active_thread(&mut self) {
         self.eval_context_mut().machine.threads.yield_active_thread();
@@ -1,9 +1,13 @@
+use std::cell::RefCell;
+use std::collections::hash_map::Entry;
 use std::fmt::{self, Write};
 use std::num::NonZero;
 
 use rustc_abi::{Align, Size};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
 use rustc_errors::{Diag, DiagMessage, Level};
-use rustc_span::{DUMMY_SP, SpanData, Symbol};
+use rustc_middle::ty::{Instance, TyCtxt};
+use rustc_span::{DUMMY_SP, Span, SpanData, Symbol, sym};
 
 use crate::borrow_tracker::stacked_borrows::diagnostics::TagHistory;
 use crate::borrow_tracker::tree_borrows::diagnostics as tree_diagnostics;
@@ -21,11 +25,13 @@ pub enum TerminationInfo {
         msg: String,
         help: Vec<String>,
         history: Option<TagHistory>,
+        involved: InvolvedTags,
     },
     TreeBorrowsUb {
         title: String,
         details: Vec<String>,
         history: tree_diagnostics::HistoryData,
+        involved: InvolvedTags,
     },
     Int2PtrWithStrictProvenance,
     Deadlock,
@@ -49,6 +55,16 @@ pub enum TerminationInfo {
         retag_explain: bool,
     },
     UnsupportedForeignItem(String),
+    SegmentationFault {
+        ptr: interpret::Pointer<AllocId>,
+        access: AccessKind,
+        /// The protection of the page, e.g. `PROT_READ`.
+        prot: String,
+    },
+    StackOverflow {
+        /// The stack size of the thread, in bytes.
+        stack_size: u64,
+    },
 }
 
 pub struct RacingOp {
@@ -87,6 +103,17 @@ impl fmt::Display for TerminationInfo {
                     op2.thread_info
                 ),
             UnsupportedForeignItem(msg) => write!(f, "{msg}"),
+            SegmentationFault { ptr, access, prot } =>
+                write!(
+                    f,
+                    "{} access to {ptr:?}, which is in a page with protection {prot}",
+                    match access {
+                        AccessKind::Read => "read",
+                        AccessKind::Write => "write",
+                    },
+                ),
+            StackOverflow { stack_size } =>
+                write!(f, "the evaluated program overflowed its stack of {stack_size} bytes"),
         }
     }
 }
@@ -108,6 +135,38 @@ impl MachineStopType for TerminationInfo {
     }
 }
 
+/// The allocation and the tags involved in an aliasing violation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvolvedTags {
+    pub alloc_id: AllocId,
+    pub tags: Vec<BorTag>,
+}
+
+impl TerminationInfo {
+    /// The allocation and tags involved, if this is an aliasing violation.
+    pub fn involved_tags(&self) -> Option<&InvolvedTags> {
+        match self {
+            TerminationInfo::StackedBorrowsUb { involved, .. }
+            | TerminationInfo::TreeBorrowsUb { involved, .. } => Some(involved),
+            _ => None,
+        }
+    }
+}
+
+/// State of `-Zmiri-auto-track` in the current run.
+pub enum AutoTrack {
+    /// `-Zmiri-auto-track` is not enabled.
+    Off,
+    /// The first run. If it ends with an aliasing violation, the violation is not reported yet;
+    /// instead, the tags involved are stored here so that the program can be run again with
+    /// these tags being tracked.
+    Probe(Option<InvolvedTags>),
+    /// The second run. Tracking events are collected in `events` instead of being emitted, and
+    /// attached to the final report of the violation. The violation is only reported by this run
+    /// if it involves the same allocation and tags as the one found by the first run.
+    Collect { expected: InvolvedTags, events: RefCell<Vec<(Option<SpanData>, String)>> },
+}
+
 /// Miri specific diagnostics
 pub enum NonHaltingDiagnostic {
     /// (new_tag, new_perm, (alloc_id, base_offset, orig_tag))
@@ -130,6 +189,17 @@ pub enum NonHaltingDiagnostic {
         ptr: Pointer,
     },
     ExternTypeReborrow,
+    /// The standard library called `mprotect` on memory that was not allocated with `mmap`, which
+    /// has no effect.
+    IgnoredMprotect,
+    /// In `-Zmiri-borrow-tracker=both`, `model` found an aliasing violation, but `other` did not
+    /// (yet), so the execution continues with `other` only.
+    AliasingViolation {
+        model: BorrowTrackerMethod,
+        other: BorrowTrackerMethod,
+        msg: String,
+        helps: Vec<(Option<SpanData>, String)>,
+    },
 }
 
 /// Level of Miri specific diagnostics
@@ -207,6 +277,71 @@ pub fn prune_stacktrace<'tcx>(
     }
 }
 
+/// Collapses repeated cycles of frames in a stacktrace, which are usually caused by unbounded
+/// recursion. Returns the collapsed stacktrace, and a description of each part that was omitted.
+fn summarize_recursion<'tcx>(
+    stacktrace: Vec<FrameInfo<'tcx>>,
+) -> (Vec<FrameInfo<'tcx>>, Vec<String>) {
+    /// The longest cycle of mutually recursive calls that we detect.
+    const MAX_CYCLE_LEN: usize = 8;
+    /// How many repetitions of a cycle we keep in the stacktrace.
+    const KEPT_REPETITIONS: usize = 2;
+
+    let same_frame =
+        |a: &FrameInfo<'tcx>, b: &FrameInfo<'tcx>| a.instance == b.instance && a.span == b.span;
+    // How often the cycle of length `len` starting at `start` repeats back-to-back.
+    let repetitions = |start: usize, len: usize| {
+        let mut reps = 1;
+        while start + (reps + 1) * len <= stacktrace.len()
+            && (0..len)
+                .all(|k| same_frame(&stacktrace[start + k], &stacktrace[start + reps * len + k]))
+        {
+            reps += 1;
+        }
+        reps
+    };
+
+    let mut summary = Vec::new();
+    let mut omitted = Vec::new();
+    let mut idx = 0;
+    while idx < stacktrace.len() {
+        // Pick the cycle that covers the most frames, preferring shorter cycles.
+        let cycle = (1..=MAX_CYCLE_LEN)
+            .map(|len| (len, repetitions(idx, len)))
+            .filter(|&(_, reps)| reps > KEPT_REPETITIONS)
+            .max_by_key(|&(len, reps)| (len * reps, std::cmp::Reverse(len)));
+        if let Some((len, reps)) = cycle {
+            summary.extend_from_slice(&stacktrace[idx..idx + KEPT_REPETITIONS * len]);
+            omitted.push(format!(
+                "{} frames of recursion through `{}` are omitted from the backtrace",
+                (reps - KEPT_REPETITIONS) * len,
+                stacktrace[idx].instance,
+            ));
+            idx += reps * len;
+        } else {
+            summary.push(stacktrace[idx].clone());
+            idx += 1;
+        }
+    }
+    (summary, omitted)
+}
+
+/// Adds the tracking events collected by the second run of `-Zmiri-auto-track` to the report of
+/// an aliasing violation.
+fn add_auto_track_events(machine: &MiriMachine<'_>, helps: &mut Vec<(Option<SpanData>, String)>) {
+    let AutoTrack::Collect { events, .. } = &machine.auto_track else {
+        return;
+    };
+    let events = events.borrow();
+    if events.is_empty() {
+        return;
+    }
+    helps.push(note!(
+        "`-Zmiri-auto-track` re-ran the program while tracking the tags and allocation involved, which recorded these events:"
+    ));
+    helps.extend(events.iter().cloned());
+}
+
 /// Emit a custom diagnostic without going through the miri-engine machinery.
 ///
 /// Returns `Some` if this was regular program termination with a given exit code and a `bool` indicating whether a leak check should happen; `None` otherwise.
@@ -219,7 +354,7 @@ pub fn report_error<'tcx>(
 
     let mut msg = vec![];
 
-    let (title, helps) = if let MachineStop(info) = e.kind() {
+    let (title, mut helps) = if let MachineStop(info) = e.kind() {
         let info = info.downcast_ref::<TerminationInfo>().expect("invalid MachineStop payload");
         use TerminationInfo::*;
         let title = match info {
@@ -230,6 +365,8 @@ pub fn report_error<'tcx>(
             StackedBorrowsUb { .. } | TreeBorrowsUb { .. } | DataRace { .. } =>
                 Some("Undefined Behavior"),
             Deadlock => Some("deadlock"),
+            SegmentationFault { .. } => Some("segmentation fault"),
+            StackOverflow { .. } => Some("stack overflow"),
             MultipleSymbolDefinitions { .. } | SymbolShimClashing { .. } => None,
         };
         #[rustfmt::skip]
@@ -260,9 +397,10 @@ pub fn report_error<'tcx>(
                         helps.push(note_span!(protector_span, "{protector_msg}"));
                     }
                 }
+                add_auto_track_events(&ecx.machine, &mut helps);
                 helps
             },
-            TreeBorrowsUb { title: _, details, history } => {
+            TreeBorrowsUb { title: _, details, history, involved: _ } => {
                 let mut helps = vec![
                     note!("this indicates a potential bug in the program: it performed an invalid operation, but the Tree Borrows rules it violated are still experimental")
                 ];
@@ -272,6 +410,7 @@ pub fn report_error<'tcx>(
                 for event in history.events.clone() {
                     helps.push(event);
                 }
+                add_auto_track_events(&ecx.machine, &mut helps);
                 helps
             }
             MultipleSymbolDefinitions { first, first_crate, second, second_crate, .. } =>
@@ -281,6 +420,13 @@ pub fn report_error<'tcx>(
                 ],
             SymbolShimClashing { link_name, span } =>
                 vec![note_span!(*span, "the `{link_name}` symbol is defined here")],
+            SegmentationFault { .. } =>
+                vec![note!("the page protections were set by `mmap` or `mprotect`")],
+            StackOverflow { .. } =>
+                vec![
+                    note!("Miri accounts the size of the arguments and variables of each stack frame against the stack size of the thread"),
+                    note!("the stack size of new threads can be set with `std::thread::Builder::stack_size`"),
+                ],
             Int2PtrWithStrictProvenance =>
                 vec![note!("use Strict Provenance APIs (https://doc.rust-lang.org/nightly/std/ptr/index.html#strict-provenance, https://crates.io/crates/sptr) instead")],
             DataRace { op1, extra, retag_explain, .. } => {
@@ -363,6 +509,10 @@ pub fn report_error<'tcx>(
                             helps.push(note_span!(span, "{:?} was deallocated here:", alloc_id));
                         }
                     }
+                    InvalidUninitBytes(bad) => {
+                        let bad = bad.as_ref().map(|(alloc_id, access)| (*alloc_id, access.bad.start));
+                        helps.extend(uninit_origin::origin_notes(ecx, bad));
+                    }
                     AbiMismatchArgument { .. } | AbiMismatchReturn { .. } => {
                         helps.push(note!("this means these two types are not *guaranteed* to be ABI-compatible across all targets"));
                         helps.push(note!("if you think this code should be accepted anyway, please report an issue with Miri"));
@@ -384,7 +534,7 @@ pub fn report_error<'tcx>(
     };
 
     let stacktrace = ecx.generate_stacktrace();
-    let (stacktrace, mut any_pruned) = prune_stacktrace(stacktrace, &ecx.machine);
+    let (mut stacktrace, mut any_pruned) = prune_stacktrace(stacktrace, &ecx.machine);
 
     let mut show_all_threads = false;
 
@@ -407,6 +557,13 @@ pub fn report_error<'tcx>(
                 TerminationInfo::Deadlock => {
                     show_all_threads = true;
                 }
+                TerminationInfo::StackOverflow { .. }
+                    if ecx.machine.backtrace_style != BacktraceStyle::Full =>
+                {
+                    let omitted;
+                    (stacktrace, omitted) = summarize_recursion(stacktrace);
+                    helps.extend(omitted.into_iter().map(|msg| note!("{msg}")));
+                }
                 _ => {}
             }
         }
@@ -468,19 +625,181 @@ pub fn report_error<'tcx>(
     None
 }
 
+/// How a leaked allocation relates to the state of the program when it ends.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+enum LeakKind {
+    /// Nothing refers to the allocation any more.
+    DefinitelyLost,
+    /// The allocation is still referenced from memory that is allowed to leak (such as statics and
+    /// thread-local storage) or from state managed by the machine, but it will never be freed.
+    StillReachable,
+}
+
+impl fmt::Display for LeakKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LeakKind::DefinitelyLost => write!(f, "definitely lost"),
+            LeakKind::StillReachable => write!(f, "still reachable"),
+        }
+    }
+}
+
+/// Where a group of leaks was allocated: the kind of the leaks and the frames of their backtrace.
+type LeakSite<'tcx> = (LeakKind, MemoryKind, Option<Vec<(Instance<'tcx>, Span)>>);
+
+/// Leaked allocations of the same kind that were allocated by the same backtrace.
+struct LeakGroup<'tcx> {
+    leak_kind: LeakKind,
+    kind: MemoryKind,
+    /// The first leaked allocation of the group, with its size and alignment.
+    first: (AllocId, Size, Align),
+    count: u64,
+    total_size: Size,
+    backtrace: Option<Vec<FrameInfo<'tcx>>>,
+}
+
+/// Determines which of the leaked allocations can still be reached, following pointers through
+/// the leaked allocations themselves, from memory that is exempt from the leak check (such as
+/// extern statics or memory mapped by the program) or from other state managed by the machine.
+/// The machine state is traversed the same way as by the provenance GC.
+fn still_reachable_leaks<'tcx>(
+    ecx: &InterpCx<'tcx, MiriMachine<'tcx>>,
+    leaks: &[(AllocId, MemoryKind, Allocation<Provenance, AllocExtra<'tcx>, MiriAllocBytes>)],
+) -> FxHashSet<AllocId> {
+    let leaked: FxHashMap<AllocId, &Allocation<Provenance, AllocExtra<'tcx>, MiriAllocBytes>> =
+        leaks.iter().map(|(id, _kind, alloc)| (*id, alloc)).collect();
+    let mut todo = Vec::new();
+    ecx.machine.visit_provenance(&mut |id, _tag| todo.extend(id));
+    // Memory referenced from the thread-local storage of terminated threads is not lost either.
+    todo.extend(ecx.machine.tls_roots.iter().copied());
+    // The leaked allocations have already been removed from memory, so everything that remains
+    // is either reachable from a global, or allowed to leak.
+    ecx.memory.alloc_map().iter(|it| {
+        for (_id, (_kind, alloc)) in it {
+            todo.extend(alloc.provenance().provenances().filter_map(|prov| prov.get_alloc_id()));
+        }
+    });
+    let mut reachable = FxHashSet::default();
+    while let Some(id) = todo.pop() {
+        if let Some(alloc) = leaked.get(&id)
+            && reachable.insert(id)
+        {
+            todo.extend(alloc.provenance().provenances().filter_map(|prov| prov.get_alloc_id()));
+        }
+    }
+    reachable
+}
+
+/// Whether `path` matches `pattern`, in which `*` matches any sequence of characters.
+fn matches_suppression(pattern: &str, path: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(mut rest) = path.strip_prefix(parts.next().unwrap()) else {
+        return false;
+    };
+    let parts: Vec<&str> = parts.collect();
+    let Some((last, middle)) = parts.split_last() else {
+        // There is no `*` in the pattern.
+        return rest.is_empty();
+    };
+    for part in middle {
+        let Some(pos) = rest.find(part) else {
+            return false;
+        };
+        rest = &rest[pos + part.len()..];
+    }
+    rest.ends_with(last)
+}
+
+/// The function that allocated a leak: the innermost frame of its allocation backtrace that is not
+/// part of the allocator API (the `alloc` crate, and the `GlobalAlloc` machinery in `core::alloc`
+/// and `std::alloc`). Like the top frame of a valgrind suppression, this is what the
+/// `-Zmiri-leak-suppressions` patterns are matched against, so that a pattern for a function
+/// further up the stack (such as `main`) does not hide every leak below it.
+fn allocation_site<'tcx>(tcx: TyCtxt<'tcx>, backtrace: &[FrameInfo<'tcx>]) -> Option<String> {
+    backtrace.iter().find_map(|frame| {
+        let def_id = frame.instance.def_id();
+        if tcx.crate_name(def_id.krate) == sym::alloc {
+            return None;
+        }
+        let path = tcx.def_path_str(def_id);
+        let allocator_api = path.contains("core::alloc::") || path.contains("std::alloc::");
+        (!allocator_api).then_some(path)
+    })
+}
+
+/// Reports the given leaks, grouped by their allocation backtrace. Leaks whose allocation site
+/// matches one of the `-Zmiri-leak-suppressions` patterns are not reported.
+/// Returns whether any leak was reported.
 pub fn report_leaks<'tcx>(
     ecx: &InterpCx<'tcx, MiriMachine<'tcx>>,
-    leaks: Vec<(AllocId, MemoryKind, Allocation<Provenance, AllocExtra<'tcx>, MiriAllocBytes>)>,
-) {
-    let mut any_pruned = false;
+    mut leaks: Vec<(AllocId, MemoryKind, Allocation<Provenance, AllocExtra<'tcx>, MiriAllocBytes>)>,
+) -> bool {
+    let still_reachable = still_reachable_leaks(ecx, &leaks);
+    // Report the leaks in the order they were allocated.
+    leaks.sort_by_key(|(id, _kind, _alloc)| *id);
+
+    let mut groups: Vec<LeakGroup<'tcx>> = Vec::new();
+    // The index in `groups` of the group of each allocation site.
+    let mut group_of_site: FxHashMap<LeakSite<'tcx>, usize> = FxHashMap::default();
     for (id, kind, alloc) in leaks {
-        let mut title = format!(
-            "memory leaked: {id:?} ({}, size: {:?}, align: {:?})",
-            kind,
-            alloc.size().bytes(),
-            alloc.align.bytes()
-        );
-        let Some(backtrace) = alloc.extra.backtrace else {
+        let leak_kind = if still_reachable.contains(&id) {
+            LeakKind::StillReachable
+        } else {
+            LeakKind::DefinitelyLost
+        };
+        let backtrace = alloc.extra.backtrace;
+        let frames = backtrace
+            .as_ref()
+            .map(|backtrace| backtrace.iter().map(|frame| (frame.instance, frame.span)).collect());
+        match group_of_site.entry((leak_kind, kind, frames)) {
+            Entry::Occupied(entry) => {
+                let group = &mut groups[*entry.get()];
+                group.count += 1;
+                group.total_size += alloc.size();
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(groups.len());
+                groups.push(LeakGroup {
+                    leak_kind,
+                    kind,
+                    first: (id, alloc.size(), alloc.align),
+                    count: 1,
+                    total_size: alloc.size(),
+                    backtrace,
+                });
+            }
+        }
+    }
+
+    let mut any_reported = false;
+    let mut any_pruned = false;
+    for group in groups {
+        if let Some(backtrace) = &group.backtrace
+            && let Some(site) = allocation_site(*ecx.tcx, backtrace)
+            && ecx
+                .machine
+                .leak_suppressions
+                .iter()
+                .any(|pattern| matches_suppression(pattern, &site))
+        {
+            continue;
+        }
+        any_reported = true;
+
+        let LeakGroup { leak_kind, kind, first: (id, size, align), count, total_size, .. } = group;
+        let mut title = if count == 1 {
+            format!(
+                "memory leaked ({leak_kind}): {id:?} ({kind}, size: {:?}, align: {:?})",
+                size.bytes(),
+                align.bytes()
+            )
+        } else {
+            format!(
+                "memory leaked ({leak_kind}): {count} allocations ({kind}, {} bytes in total)",
+                total_size.bytes()
+            )
+        };
+        let Some(backtrace) = group.backtrace else {
             ecx.tcx.dcx().err(title);
             continue;
         };
@@ -503,6 +822,43 @@ pub fn report_leaks<'tcx>(
             "some details are omitted, run with `MIRIFLAGS=-Zmiri-backtrace=full` for a verbose backtrace",
         );
     }
+    any_reported
+}
+
+/// Summarizes `-Zmiri-borrow-tracker=both`: which of the two models flagged which access.
+/// Returns whether any model flagged anything.
+pub fn report_differential_borrows<'tcx>(ecx: &InterpCx<'tcx, MiriMachine<'tcx>>) -> bool {
+    let Some(borrow_tracker) = &ecx.machine.borrow_tracker else {
+        return false;
+    };
+    let borrow_tracker = borrow_tracker.borrow();
+    let Some(reports) = borrow_tracker.differential_reports() else {
+        return false;
+    };
+    if reports.is_empty() {
+        return false;
+    }
+
+    let models = [BorrowTrackerMethod::StackedBorrows, BorrowTrackerMethod::TreeBorrows];
+    let flagged_by = models.map(|model| reports.iter().any(|report| report.model == model));
+    let verdict = match flagged_by {
+        [true, true] => "both Stacked Borrows and Tree Borrows".to_string(),
+        [true, false] => format!("{} only", models[0]),
+        [false, true] => format!("{} only", models[1]),
+        [false, false] => unreachable!(),
+    };
+    let mut diag =
+        ecx.tcx.dcx().struct_err(format!("this program has Undefined Behavior under {verdict}"));
+    for report in reports {
+        diag.span_note(report.span.span(), format!("{} flagged: {}", report.model, report.msg));
+    }
+    for (model, flagged) in models.into_iter().zip(flagged_by) {
+        if !flagged {
+            diag.note(format!("{model} did not flag any access"));
+        }
+    }
+    diag.emit();
+    true
 }
 
 /// Report an error or note (depending on the `error` argument) with the given stacktrace.
@@ -604,6 +960,9 @@ impl<'tcx> MiriMachine<'tcx> {
             Int2Ptr { .. } => ("integer-to-pointer cast".to_string(), DiagLevel::Warning),
             ExternTypeReborrow =>
                 ("reborrow of reference to `extern type`".to_string(), DiagLevel::Warning),
+            IgnoredMprotect => ("ignored `mprotect`".to_string(), DiagLevel::Warning),
+            AliasingViolation { model, .. } =>
+                (format!("Undefined Behavior under {model}"), DiagLevel::Error),
             CreatedPointerTag(..)
             | PoppedPointerTag(..)
             | CreatedAlloc(..)
@@ -641,8 +1000,28 @@ impl<'tcx> MiriMachine<'tcx> {
                 format!("weak memory emulation: outdated value returned from load at {ptr}"),
             ExternTypeReborrow =>
                 format!("reborrow of a reference to `extern type` is not properly supported"),
+            IgnoredMprotect =>
+                format!("`mprotect` on memory not allocated with `mmap` does not protect anything"),
+            AliasingViolation { msg, .. } => msg.clone(),
         };
 
+        if let AutoTrack::Collect { events, .. } = &self.auto_track {
+            // This is the second run of `-Zmiri-auto-track`; the first one already emitted all
+            // other diagnostics.
+            if matches!(
+                e,
+                CreatedPointerTag(..)
+                    | PoppedPointerTag(..)
+                    | CreatedAlloc(..)
+                    | AccessedAlloc(..)
+                    | FreedAlloc(..)
+            ) {
+                let span = stacktrace.first().map(|frame| frame.span.data());
+                events.borrow_mut().push((span, msg));
+            }
+            return;
+        }
+
         let notes = match &e {
             ProgressReport { block_count } => {
                 vec![note!("so far, {block_count} basic blocks have been executed")]
@@ -667,7 +1046,10 @@ impl<'tcx> MiriMachine<'tcx> {
                     ),
                 ];
                 if self.borrow_tracker.as_ref().is_some_and(|b| {
-                    matches!(b.borrow().borrow_tracker_method(), BorrowTrackerMethod::TreeBorrows)
+                    matches!(
+                        b.borrow().borrow_tracker_method(),
+                        BorrowTrackerMethod::TreeBorrows | BorrowTrackerMethod::Both
+                    )
                 }) {
                     v.push(
                         note!("Tree Borrows does not support integer-to-pointer casts, so the program is likely to go wrong when this pointer gets used")
@@ -689,6 +1071,18 @@ impl<'tcx> MiriMachine<'tcx> {
                     ),
                 ]
             }
+            IgnoredMprotect => {
+                vec![note!(
+                    "Miri only enforces page protections for memory allocated with `mmap`, so accesses to these pages are not affected"
+                )]
+            }
+            AliasingViolation { other, helps, .. } => {
+                let mut v = helps.clone();
+                v.push(note!(
+                    "{other} has not flagged this access, so the execution continues with {other} only"
+                ));
+                v
+            }
             _ => vec![],
         };
 
@@ -732,3 +1126,22 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         );
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::matches_suppression;
+
+    #[test]
+    fn suppression_patterns() {
+        assert!(matches_suppression("main", "main"));
+        assert!(!matches_suppression("main", "main::helper"));
+        assert!(matches_suppression("std::*", "std::rc::Rc::<T>::new"));
+        assert!(matches_suppression("*::new", "std::rc::Rc::<T>::new"));
+        assert!(matches_suppression("*Rc*new", "std::rc::Rc::<T>::new"));
+        assert!(!matches_suppression("*Rc*new", "std::rc::Rc::<T>::clone"));
+        assert!(matches_suppression("*", "anything"));
+        // A `*` may match the empty string, but parts must not overlap.
+        assert!(matches_suppression("a*a", "aa"));
+        assert!(!matches_suppression("aa*aa", "aaa"));
+    }
+}
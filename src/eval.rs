@@ -15,7 +15,7 @@ use rustc_middle::ty::{self, Ty, TyCtxt};
 use rustc_session::config::EntryFnType;
 
 use crate::concurrency::thread::TlsAllocAction;
-use crate::diagnostics::report_leaks;
+use crate::diagnostics::{report_differential_borrows, report_leaks};
 use crate::shims::tls;
 use crate::*;
 
@@ -83,6 +83,15 @@ pub enum ValidationMode {
     Deep,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum CheckScope {
+    /// Perform all checks in all code.
+    All,
+    /// Only retag and validate in frames of local crates (the crate being run and the crates listed
+    /// in `MIRI_LOCAL_CRATES`). Memory accesses are still checked everywhere.
+    Local,
+}
+
 /// Configuration needed to spawn a Miri instance.
 #[derive(Clone)]
 pub struct MiriConfig {
@@ -91,8 +100,13 @@ pub struct MiriConfig {
     pub env: Vec<(OsString, OsString)>,
     /// Determine if validity checking is enabled.
     pub validation: ValidationMode,
+    /// Determines in which frames retagging and validation are performed.
+    pub check_scope: CheckScope,
     /// Determines if Stacked Borrows or Tree Borrows is enabled.
     pub borrow_tracker: Option<BorrowTrackerMethod>,
+    /// If `Some`, record the borrow state of the given allocation after every event that changes
+    /// it, writing the trace to the specified directory.
+    pub borrow_trace: Option<(AllocId, PathBuf)>,
     /// Whether `core::ptr::Unique` receives special treatment.
     /// If `true` then `Unique` is reborrowed with its own new tag and permission,
     /// otherwise `Unique` is just another raw pointer.
@@ -117,6 +131,9 @@ pub struct MiriConfig {
     pub tracked_alloc_ids: FxHashSet<AllocId>,
     /// For the tracked alloc ids, also report read/write accesses.
     pub track_alloc_accesses: bool,
+    /// If an aliasing violation is found, run the program again while tracking the tags and the
+    /// allocation involved, and add the tracking events to the report.
+    pub auto_track: bool,
     /// Determine if data race detection should be enabled
     pub data_race_detector: bool,
     /// Determine if weak memory emulation should be enabled. Requires data race detection to be enabled
@@ -126,9 +143,21 @@ pub struct MiriConfig {
     /// Rate of spurious failures for compare_exchange_weak atomic operations,
     /// between 0.0 and 1.0, defaulting to 0.8 (80% chance of failure).
     pub cmpxchg_weak_failure_rate: f64,
+    /// Whether to apply a random error to the results of float operations whose precision Rust
+    /// leaves unspecified.
+    pub float_nondet: bool,
     /// If `Some`, enable the `measureme` profiler, writing results to a file
     /// with the specified prefix.
     pub measureme_out: Option<String>,
+    /// If `Some`, measure the overhead of Miri's own subsystems, writing results to the
+    /// specified file.
+    pub self_profile_out: Option<String>,
+    /// If `Some`, record a heap profile of the interpreted program, writing it to the specified
+    /// file.
+    pub heap_profile_out: Option<String>,
+    /// Whether to record where uninitialized bytes came from, to explain errors about
+    /// uninitialized memory.
+    pub track_uninit_origins: bool,
     /// Which style to use for printing backtraces.
     pub backtrace_style: BacktraceStyle,
     /// Which provenance to use for int2ptr casts
@@ -151,8 +180,15 @@ pub struct MiriConfig {
     pub num_cpus: u32,
     /// Requires Miri to emulate pages of a certain size
     pub page_size: Option<u64>,
+    /// The host name to be reported by miri. If `None`, we use the host's name when isolation is
+    /// disabled and a fixed name otherwise.
+    pub hostname: Option<String>,
+    /// Resource limits to be reported by miri, overriding the defaults.
+    pub rlimits: FxHashMap<&'static str, ResourceLimit>,
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub collect_leak_backtraces: bool,
+    /// Patterns of function paths; leaks allocated by a matching function are not reported.
+    pub leak_suppressions: Vec<String>,
     /// Probability for address reuse.
     pub address_reuse_rate: f64,
     /// Probability for address reuse across threads.
@@ -164,7 +200,9 @@ impl Default for MiriConfig {
         MiriConfig {
             env: vec![],
             validation: ValidationMode::Shallow,
+            check_scope: CheckScope::All,
             borrow_tracker: Some(BorrowTrackerMethod::StackedBorrows),
+            borrow_trace: None,
             unique_is_unique: false,
             check_alignment: AlignmentCheck::Int,
             isolated_op: IsolatedOp::Reject(RejectOpWith::Abort),
@@ -176,11 +214,16 @@ impl Default for MiriConfig {
             tracked_pointer_tags: FxHashSet::default(),
             tracked_alloc_ids: FxHashSet::default(),
             track_alloc_accesses: false,
+            auto_track: false,
             data_race_detector: true,
             weak_memory_emulation: true,
             track_outdated_loads: false,
             cmpxchg_weak_failure_rate: 0.8, // 80%
+            float_nondet: false,
             measureme_out: None,
+            self_profile_out: None,
+            heap_profile_out: None,
+            track_uninit_origins: false,
             backtrace_style: BacktraceStyle::Short,
             provenance_mode: ProvenanceMode::Default,
             mute_stdout_stderr: false,
@@ -191,7 +234,10 @@ impl Default for MiriConfig {
             gc_interval: 10_000,
             num_cpus: 1,
             page_size: None,
+            hostname: None,
+            rlimits: FxHashMap::default(),
             collect_leak_backtraces: true,
+            leak_suppressions: vec![],
             address_reuse_rate: 0.5,
             address_reuse_cross_thread_rate: 0.1,
         }
@@ -433,15 +479,91 @@ pub fn eval_entry<'tcx>(
     // Copy setting before we move `config`.
     let ignore_leaks = config.ignore_leaks;
 
-    let mut ecx = match create_ecx(tcx, entry_id, entry_type, &config).report_err() {
-        Ok(v) => v,
-        Err(err) => {
-            let (kind, backtrace) = err.into_parts();
-            backtrace.print_backtrace();
-            panic!("Miri initialization error: {kind:?}")
+    let init = |config: &MiriConfig| {
+        match create_ecx(tcx, entry_id, entry_type, config).report_err() {
+            Ok(v) => v,
+            Err(err) => {
+                let (kind, backtrace) = err.into_parts();
+                backtrace.print_backtrace();
+                panic!("Miri initialization error: {kind:?}")
+            }
+        }
+    };
+    let mut ecx = init(&config);
+
+    let res = match run_and_check_leaks(tcx, &mut ecx, ignore_leaks) {
+        Ok(res) => res,
+        Err(probe_err) => {
+            // The first run of `-Zmiri-auto-track` ended in an aliasing violation. Tracking
+            // does not change the execution, so running the program again usually reaches the
+            // same violation, this time knowing how the tags involved came to be. The program
+            // output and all other diagnostics were already shown by the first run.
+            let AutoTrack::Probe(Some(involved)) = &ecx.machine.auto_track else {
+                unreachable!("the first run of `-Zmiri-auto-track` did not record the violation")
+            };
+            let mut tracked_config = config.clone();
+            tracked_config.tracked_pointer_tags.extend(involved.tags.iter().copied());
+            tracked_config.tracked_alloc_ids.insert(involved.alloc_id);
+            tracked_config.mute_stdout_stderr = true;
+            tracked_config.auto_track = false;
+            let mut tracked_ecx = init(&tracked_config);
+            tracked_ecx.machine.auto_track =
+                AutoTrack::Collect { expected: involved.clone(), events: Default::default() };
+            match run_and_check_leaks(tcx, &mut tracked_ecx, ignore_leaks) {
+                Ok(res) => res,
+                Err(_) => {
+                    // The re-run took a different path, e.g. because the program depends on
+                    // the host or on scheduling. Report the violation of the first run instead.
+                    report_error(&ecx, probe_err);
+                    tcx.dcx().note(
+                        "`-Zmiri-auto-track` re-ran the program to track the tags and allocation involved, but the re-run did not reach this violation again",
+                    );
+                    None
+                }
+            }
         }
     };
+    if let Some(self_profile) = &ecx.machine.self_profile {
+        self_profile.report();
+    }
+    if let Some(heap_profile) = &ecx.machine.heap_profile {
+        heap_profile.borrow_mut().report(tcx, ecx.machine.basic_block_count);
+    }
+    if let Some(borrow_tracker) = &ecx.machine.borrow_tracker
+        && let Some(trace) = borrow_tracker.borrow().borrow_trace()
+    {
+        match trace.write() {
+            Ok(path) => {
+                tcx.dcx().note(format!(
+                    "borrow trace with {} steps written to {}",
+                    trace.recorded_steps(),
+                    path.display()
+                ));
+                if trace.dropped_steps() > 0 {
+                    tcx.dcx().note(format!(
+                        "the borrow trace is truncated, {} later events were not recorded",
+                        trace.dropped_steps()
+                    ));
+                }
+            }
+            Err(err) => {
+                tcx.dcx().err(format!("failed to write the borrow trace: {err}"));
+                return None;
+            }
+        }
+    }
+    res
+}
 
+/// Runs the program to completion, then reports errors and leaks.
+/// The return value is the same as for `eval_entry`, wrapped in `Ok`. With `-Zmiri-auto-track`,
+/// returns the error without reporting it if the first run ended in an aliasing violation, or if
+/// the second run did not end in the same violation.
+fn run_and_check_leaks<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ecx: &mut MiriInterpCx<'tcx>,
+    ignore_leaks: bool,
+) -> Result<Option<i64>, InterpErrorInfo<'tcx>> {
     // Perform the main execution.
     let res: thread::Result<InterpResult<'_, !>> =
         panic::catch_unwind(AssertUnwindSafe(|| ecx.run_threads()));
@@ -452,6 +574,36 @@ pub fn eval_entry<'tcx>(
     // `Ok` can never happen.
     let Err(err) = res.report_err();
 
+    // The first run of `-Zmiri-auto-track` does not report aliasing violations; the program gets
+    // run again instead. The second run only reports the violation it was looking for.
+    let involved = match err.kind() {
+        InterpErrorKind::MachineStop(info) =>
+            info.downcast_ref::<TerminationInfo>().and_then(TerminationInfo::involved_tags),
+        _ => None,
+    };
+    match &mut ecx.machine.auto_track {
+        AutoTrack::Off => {}
+        AutoTrack::Probe(probe) =>
+            if let Some(involved) = involved {
+                *probe = Some(involved.clone());
+                return Err(err);
+            },
+        AutoTrack::Collect { expected, .. } =>
+            if involved != Some(&*expected) {
+                return Err(err);
+            },
+    }
+    Ok(report_and_check_leaks(tcx, ecx, err, ignore_leaks))
+}
+
+/// Reports the error that ended the program, then checks for leaks.
+/// The return value is the same as for `eval_entry`.
+fn report_and_check_leaks<'tcx>(
+    tcx: TyCtxt<'tcx>,
+    ecx: &mut MiriInterpCx<'tcx>,
+    err: InterpErrorInfo<'tcx>,
+    ignore_leaks: bool,
+) -> Option<i64> {
     // Machine cleanup. Only do this if all threads have terminated; threads that are still running
     // might cause Stacked Borrows errors (https://github.com/rust-lang/miri/issues/2396).
     if ecx.have_all_terminated() {
@@ -459,11 +611,16 @@ pub fn eval_entry<'tcx>(
         // might not have joined the main thread (https://github.com/rust-lang/miri/issues/2020,
         // https://github.com/rust-lang/miri/issues/2508).
         ecx.allow_data_races_all_threads_done();
-        EnvVars::cleanup(&mut ecx).expect("error during env var cleanup");
+        EnvVars::cleanup(ecx).expect("error during env var cleanup");
     }
 
     // Process the result.
-    let (return_code, leak_check) = report_error(&ecx, err)?;
+    let res = report_error(ecx, err);
+    // With `-Zmiri-borrow-tracker=both`, a model may have flagged the program without halting it.
+    if report_differential_borrows(ecx) {
+        return None;
+    }
+    let (return_code, leak_check) = res?;
     if leak_check && !ignore_leaks {
         // Check for thread leaks.
         if !ecx.have_all_terminated() {
@@ -474,8 +631,7 @@ pub fn eval_entry<'tcx>(
         // Check for memory leaks.
         info!("Additional static roots: {:?}", ecx.machine.static_roots);
         let leaks = ecx.take_leaked_allocations(|ecx| &ecx.machine.static_roots);
-        if !leaks.is_empty() {
-            report_leaks(&ecx, leaks);
+        if !leaks.is_empty() && report_leaks(ecx, leaks) {
             tcx.dcx().note("set `MIRIFLAGS=-Zmiri-ignore-leaks` to disable this check");
             // Ignore the provided return code - let the reported error
             // determine the return code.
@@ -0,0 +1,245 @@
+//! Heap profiling of the interpreted program (`-Zmiri-heap-profile`).
+//!
+//! Every heap allocation is attributed to the call stack that created it. For each such allocation
+//! site, we record how many bytes and blocks it allocated in total, how long they lived, and how
+//! many of them were live at the same time. The result is written in the format used by DHAT, so
+//! it can be inspected with DHAT's viewer (`dh_view.html`).
+//!
+//! Time is measured in basic blocks executed by the interpreter rather than in wall-clock time, so
+//! the profile of a deterministic program is the same on every run. The profile is written to
+//! stderr if the output file is `-`.
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+
+use rustc_abi::Size;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::{Instance, TyCtxt};
+use rustc_span::Span;
+
+use crate::*;
+
+/// The statistics of one allocation site, i.e., of one call stack that allocates memory.
+#[derive(Debug)]
+struct AllocSite {
+    /// Indices into `HeapProfile::frames`, innermost frame first.
+    frames: Vec<usize>,
+    total_bytes: u64,
+    total_blocks: u64,
+    /// The sum of the lifetimes of all blocks. Blocks that are never freed live until the end of
+    /// the program; that part is only added when the profile is written.
+    total_lifetimes: u64,
+    curr_bytes: u64,
+    curr_blocks: u64,
+    max_bytes: u64,
+    max_blocks: u64,
+    /// The live bytes and blocks at the moment the program had the most live heap memory.
+    at_peak_bytes: u64,
+    at_peak_blocks: u64,
+}
+
+/// A live heap allocation.
+#[derive(Debug)]
+struct LiveBlock {
+    site: usize,
+    size: u64,
+    /// When the allocation was created.
+    birth: u64,
+}
+
+#[derive(Debug)]
+pub struct HeapProfile<'tcx> {
+    /// Where the profile is written to, or `None` for stderr.
+    out: Option<PathBuf>,
+    /// The command line of the interpreted program.
+    cmd: String,
+    /// All frames that occur in some allocation site, and their index in that list.
+    frames: Vec<(Instance<'tcx>, Span)>,
+    frame_ids: FxHashMap<(Instance<'tcx>, Span), usize>,
+    /// All allocation sites, in the order they first allocated, and their index in that list.
+    sites: Vec<AllocSite>,
+    site_ids: FxHashMap<Vec<usize>, usize>,
+    live: FxHashMap<AllocId, LiveBlock>,
+    curr_bytes: u64,
+    curr_blocks: u64,
+    max_bytes: u64,
+    max_blocks: u64,
+    /// When the most live heap memory was reached.
+    peak_time: u64,
+    /// Whether the current state is the peak. The per-site statistics at the peak are only
+    /// recorded once the peak is over, so that we do not have to do that on every allocation.
+    at_peak: bool,
+}
+
+impl<'tcx> HeapProfile<'tcx> {
+    pub fn new(out: &str, cmd: String) -> Self {
+        let out = (out != "-").then(|| PathBuf::from(out));
+        if let Some(out) = &out {
+            // Fail early rather than after the entire program has been interpreted.
+            File::create(out).expect("Couldn't create heap profile output file");
+        }
+        HeapProfile {
+            out,
+            cmd,
+            frames: Vec::new(),
+            frame_ids: FxHashMap::default(),
+            sites: Vec::new(),
+            site_ids: FxHashMap::default(),
+            live: FxHashMap::default(),
+            curr_bytes: 0,
+            curr_blocks: 0,
+            max_bytes: 0,
+            max_blocks: 0,
+            peak_time: 0,
+            at_peak: false,
+        }
+    }
+
+    /// Records a new heap allocation, created by the given stack (innermost frame first).
+    pub fn allocated(&mut self, id: AllocId, size: Size, stacktrace: &[FrameInfo<'tcx>], now: u64) {
+        let frames: Vec<usize> = stacktrace
+            .iter()
+            .map(|frame| {
+                let frame = (frame.instance, frame.span);
+                *self.frame_ids.entry(frame).or_insert_with(|| {
+                    self.frames.push(frame);
+                    // Index 0 is the root of the frame table.
+                    self.frames.len()
+                })
+            })
+            .collect();
+        let site = *self.site_ids.entry(frames).or_insert_with_key(|frames| {
+            self.sites.push(AllocSite {
+                frames: frames.clone(),
+                total_bytes: 0,
+                total_blocks: 0,
+                total_lifetimes: 0,
+                curr_bytes: 0,
+                curr_blocks: 0,
+                max_bytes: 0,
+                max_blocks: 0,
+                at_peak_bytes: 0,
+                at_peak_blocks: 0,
+            });
+            self.sites.len() - 1
+        });
+
+        let size = size.bytes();
+        let alloc_site = &mut self.sites[site];
+        alloc_site.total_bytes += size;
+        alloc_site.total_blocks += 1;
+        alloc_site.curr_bytes += size;
+        alloc_site.curr_blocks += 1;
+        if alloc_site.curr_bytes > alloc_site.max_bytes {
+            alloc_site.max_bytes = alloc_site.curr_bytes;
+            alloc_site.max_blocks = alloc_site.curr_blocks;
+        }
+        self.live.insert(id, LiveBlock { site, size, birth: now });
+
+        self.curr_bytes += size;
+        self.curr_blocks += 1;
+        if self.curr_bytes > self.max_bytes {
+            self.max_bytes = self.curr_bytes;
+            self.max_blocks = self.curr_blocks;
+            self.peak_time = now;
+            self.at_peak = true;
+        }
+    }
+
+    /// Records the deallocation of a heap allocation. Allocations we do not know are ignored.
+    pub fn deallocated(&mut self, id: AllocId, now: u64) {
+        let Some(block) = self.live.remove(&id) else { return };
+        if self.at_peak {
+            self.record_peak();
+        }
+        let site = &mut self.sites[block.site];
+        site.total_lifetimes += now - block.birth;
+        site.curr_bytes -= block.size;
+        site.curr_blocks -= 1;
+        self.curr_bytes -= block.size;
+        self.curr_blocks -= 1;
+    }
+
+    fn record_peak(&mut self) {
+        for site in &mut self.sites {
+            site.at_peak_bytes = site.curr_bytes;
+            site.at_peak_blocks = site.curr_blocks;
+        }
+        self.at_peak = false;
+    }
+
+    /// Writes the profile to the output file. `now` is the end of the program.
+    pub fn report(&mut self, tcx: TyCtxt<'tcx>, now: u64) {
+        if self.at_peak {
+            self.record_peak();
+        }
+        // Blocks that were never freed live until the end of the program.
+        for block in self.live.values() {
+            self.sites[block.site].total_lifetimes += now - block.birth;
+        }
+        let res = match &self.out {
+            Some(out) => File::create(out).and_then(|file| self.write(file, tcx, now)),
+            None => self.write(io::stderr().lock(), tcx, now),
+        };
+        res.expect("Couldn't write heap profile output file");
+    }
+
+    fn write(&self, out: impl Write, tcx: TyCtxt<'tcx>, now: u64) -> io::Result<()> {
+        let mut file = BufWriter::new(out);
+        writeln!(file, "{{")?;
+        writeln!(file, "\"dhatFileVersion\": 2,")?;
+        writeln!(file, "\"mode\": \"rust-heap\",")?;
+        writeln!(file, "\"verb\": \"Allocated\",")?;
+        writeln!(file, "\"bklt\": true,")?;
+        writeln!(file, "\"bkacc\": false,")?;
+        writeln!(file, "\"tu\": \"blocks\",")?;
+        writeln!(file, "\"Mtu\": \"blocks\",")?;
+        writeln!(file, "\"tuth\": 500,")?;
+        writeln!(file, "\"cmd\": \"{}\",", json_escape(&self.cmd))?;
+        // The viewer requires a PID, but using the real one would make the output differ between
+        // runs.
+        writeln!(file, "\"pid\": 0,")?;
+        writeln!(file, "\"tg\": {},", self.peak_time)?;
+        writeln!(file, "\"te\": {now},")?;
+        writeln!(file, "\"pps\": [")?;
+        for (i, site) in self.sites.iter().enumerate() {
+            let frames =
+                site.frames.iter().map(|frame| frame.to_string()).collect::<Vec<_>>().join(",");
+            write!(
+                file,
+                "{{\"tb\":{},\"tbk\":{},\"tl\":{},",
+                site.total_bytes, site.total_blocks, site.total_lifetimes,
+            )?;
+            write!(file, "\"mb\":{},\"mbk\":{},", site.max_bytes, site.max_blocks)?;
+            write!(file, "\"gb\":{},\"gbk\":{},", site.at_peak_bytes, site.at_peak_blocks)?;
+            write!(file, "\"eb\":{},\"ebk\":{},", site.curr_bytes, site.curr_blocks)?;
+            write!(file, "\"fs\":[{frames}]}}")?;
+            writeln!(file, "{}", if i + 1 < self.sites.len() { "," } else { "" })?;
+        }
+        writeln!(file, "],")?;
+        writeln!(file, "\"ftbl\": [")?;
+        write!(file, "\"[root]\"")?;
+        let source_map = tcx.sess.source_map();
+        for (instance, span) in &self.frames {
+            let span = source_map.span_to_embeddable_string(*span);
+            write!(file, ",\n\"{}\"", json_escape(&format!("{instance} ({span})")))?;
+        }
+        writeln!(file, "\n]")?;
+        writeln!(file, "}}")?;
+        file.flush()
+    }
+}
+
+fn json_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", u32::from(c))),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
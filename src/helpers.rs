@@ -15,6 +15,7 @@ use rustc_index::IndexVec;
 use rustc_middle::middle::codegen_fn_attrs::CodegenFnAttrFlags;
 use rustc_middle::middle::dependency_format::Linkage;
 use rustc_middle::middle::exported_symbols::ExportedSymbol;
+use rustc_middle::mir;
 use rustc_middle::ty::layout::{FnAbiOf, LayoutOf, MaybeResult, TyAndLayout};
 use rustc_middle::ty::{self, FloatTy, IntTy, Ty, TyCtxt, UintTy};
 use rustc_session::config::CrateType;
@@ -949,6 +950,39 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         crate_name == "std" || crate_name == "std_miri_test"
     }
 
+    /// Computes the stack space we account for the given frame against the stack size of the
+    /// thread. This is a conservative approximation: natively, temporaries mostly live in
+    /// registers or share stack slots, and the return place is owned by the caller. So we only
+    /// count the arguments and the named variables of the function, each of them once.
+    fn frame_size(&mut self, frame: &Frame<'tcx, Provenance>) -> InterpResult<'tcx, u64> {
+        let this = self.eval_context_mut();
+        let instance = frame.instance();
+        if let Some(&size) = this.machine.frame_sizes.get(&instance) {
+            return interp_ok(size);
+        }
+        let body = frame.body();
+        let mut locals: BTreeSet<mir::Local> = body.args_iter().collect();
+        for var in &body.var_debug_info {
+            if let mir::VarDebugInfoContents::Place(place) = var.value
+                && let Some(local) = place.as_local()
+                && local != mir::RETURN_PLACE
+            {
+                locals.insert(local);
+            }
+        }
+        let mut size = Size::ZERO;
+        for local in locals {
+            let ty = instance.instantiate_mir_and_normalize_erasing_regions(
+                *this.tcx,
+                this.typing_env(),
+                ty::EarlyBinder::bind(body.local_decls[local].ty),
+            );
+            size += this.layout_of(ty)?.size;
+        }
+        this.machine.frame_sizes.insert(instance, size.bytes());
+        interp_ok(size.bytes())
+    }
+
     fn check_abi_and_shim_symbol_clash(
         &mut self,
         abi: ExternAbi,
@@ -1166,6 +1200,17 @@ impl<'tcx> MiriMachine<'tcx> {
         (def_id.is_local() || self.local_crates.contains(&def_id.krate))
             && !frame.instance().def.requires_caller_location(self.tcx)
     }
+
+    /// This is the source of truth for the `in_check_scope` flag in our `FrameExtra`.
+    pub fn is_in_check_scope(&self, frame: &Frame<'tcx, Provenance>) -> bool {
+        match self.check_scope {
+            CheckScope::All => true,
+            CheckScope::Local => {
+                let def_id = frame.instance().def_id();
+                def_id.is_local() || self.local_crates.contains(&def_id.krate)
+            }
+        }
+    }
 }
 
 /// Check that the number of args is what we expect.
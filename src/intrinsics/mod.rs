@@ -5,6 +5,7 @@ mod simd;
 
 use rand::Rng;
 use rustc_abi::Size;
+use rustc_apfloat::ieee::{Double, Half, Quad, Single};
 use rustc_apfloat::{Float, Round};
 use rustc_middle::mir;
 use rustc_middle::ty::{self, FloatTy};
@@ -206,6 +207,34 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.write_scalar(res, dest)?;
             }
 
+            #[rustfmt::skip]
+            | "sinf16"
+            | "cosf16"
+            | "sqrtf16"
+            | "expf16"
+            | "exp2f16"
+            | "logf16"
+            | "log10f16"
+            | "log2f16"
+            => {
+                let [f] = check_arg_count(args)?;
+                let f = this.read_scalar(f)?.to_f16()?;
+                let res = match intrinsic_name {
+                    "sinf16" => math::sin(f),
+                    "cosf16" => math::cos(f),
+                    "sqrtf16" => math::sqrt(f),
+                    "expf16" => math::exp(f),
+                    "exp2f16" => math::exp2(f),
+                    "logf16" => math::ln(f),
+                    "log10f16" => math::log10(f),
+                    "log2f16" => math::log2(f),
+                    _ => bug!(),
+                };
+                let op = intrinsic_name.strip_suffix("f16").unwrap();
+                let res = math::apply_float_nondet(this, op, &[f], res);
+                let res = this.adjust_nan(res, &[f]);
+                this.write_scalar(res, dest)?;
+            }
             #[rustfmt::skip]
             | "sinf32"
             | "cosf32"
@@ -218,19 +247,19 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             => {
                 let [f] = check_arg_count(args)?;
                 let f = this.read_scalar(f)?.to_f32()?;
-                // Using host floats except for sqrt (but it's fine, these operations do not have
-                // guaranteed precision).
                 let res = match intrinsic_name {
-                    "sinf32" => f.to_host().sin().to_soft(),
-                    "cosf32" => f.to_host().cos().to_soft(),
+                    "sinf32" => math::sin(f),
+                    "cosf32" => math::cos(f),
                     "sqrtf32" => math::sqrt(f),
-                    "expf32" => f.to_host().exp().to_soft(),
-                    "exp2f32" => f.to_host().exp2().to_soft(),
-                    "logf32" => f.to_host().ln().to_soft(),
-                    "log10f32" => f.to_host().log10().to_soft(),
-                    "log2f32" => f.to_host().log2().to_soft(),
+                    "expf32" => math::exp(f),
+                    "exp2f32" => math::exp2(f),
+                    "logf32" => math::ln(f),
+                    "log10f32" => math::log10(f),
+                    "log2f32" => math::log2(f),
                     _ => bug!(),
                 };
+                let op = intrinsic_name.strip_suffix("f32").unwrap();
+                let res = math::apply_float_nondet(this, op, &[f], res);
                 let res = this.adjust_nan(res, &[f]);
                 this.write_scalar(res, dest)?;
             }
@@ -246,19 +275,47 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             => {
                 let [f] = check_arg_count(args)?;
                 let f = this.read_scalar(f)?.to_f64()?;
-                // Using host floats except for sqrt (but it's fine, these operations do not have
-                // guaranteed precision).
                 let res = match intrinsic_name {
-                    "sinf64" => f.to_host().sin().to_soft(),
-                    "cosf64" => f.to_host().cos().to_soft(),
+                    "sinf64" => math::sin(f),
+                    "cosf64" => math::cos(f),
                     "sqrtf64" => math::sqrt(f),
-                    "expf64" => f.to_host().exp().to_soft(),
-                    "exp2f64" => f.to_host().exp2().to_soft(),
-                    "logf64" => f.to_host().ln().to_soft(),
-                    "log10f64" => f.to_host().log10().to_soft(),
-                    "log2f64" => f.to_host().log2().to_soft(),
+                    "expf64" => math::exp(f),
+                    "exp2f64" => math::exp2(f),
+                    "logf64" => math::ln(f),
+                    "log10f64" => math::log10(f),
+                    "log2f64" => math::log2(f),
+                    _ => bug!(),
+                };
+                let op = intrinsic_name.strip_suffix("f64").unwrap();
+                let res = math::apply_float_nondet(this, op, &[f], res);
+                let res = this.adjust_nan(res, &[f]);
+                this.write_scalar(res, dest)?;
+            }
+            #[rustfmt::skip]
+            | "sinf128"
+            | "cosf128"
+            | "sqrtf128"
+            | "expf128"
+            | "exp2f128"
+            | "logf128"
+            | "log10f128"
+            | "log2f128"
+            => {
+                let [f] = check_arg_count(args)?;
+                let f = this.read_scalar(f)?.to_f128()?;
+                let res = match intrinsic_name {
+                    "sinf128" => math::sin(f),
+                    "cosf128" => math::cos(f),
+                    "sqrtf128" => math::sqrt(f),
+                    "expf128" => math::exp(f),
+                    "exp2f128" => math::exp2(f),
+                    "logf128" => math::ln(f),
+                    "log10f128" => math::log10(f),
+                    "log2f128" => math::log2(f),
                     _ => bug!(),
                 };
+                let op = intrinsic_name.strip_suffix("f128").unwrap();
+                let res = math::apply_float_nondet(this, op, &[f], res);
                 let res = this.adjust_nan(res, &[f]);
                 this.write_scalar(res, dest)?;
             }
@@ -315,12 +372,21 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.write_scalar(res, dest)?;
             }
 
+            "powf16" => {
+                let [f1, f2] = check_arg_count(args)?;
+                let f1 = this.read_scalar(f1)?.to_f16()?;
+                let f2 = this.read_scalar(f2)?.to_f16()?;
+                let res = math::powf(f1, f2);
+                let res = math::apply_float_nondet(this, "pow", &[f1, f2], res);
+                let res = this.adjust_nan(res, &[f1, f2]);
+                this.write_scalar(res, dest)?;
+            }
             "powf32" => {
                 let [f1, f2] = check_arg_count(args)?;
                 let f1 = this.read_scalar(f1)?.to_f32()?;
                 let f2 = this.read_scalar(f2)?.to_f32()?;
-                // Using host floats (but it's fine, this operation does not have guaranteed precision).
-                let res = f1.to_host().powf(f2.to_host()).to_soft();
+                let res = math::powf(f1, f2);
+                let res = math::apply_float_nondet(this, "pow", &[f1, f2], res);
                 let res = this.adjust_nan(res, &[f1, f2]);
                 this.write_scalar(res, dest)?;
             }
@@ -328,18 +394,38 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let [f1, f2] = check_arg_count(args)?;
                 let f1 = this.read_scalar(f1)?.to_f64()?;
                 let f2 = this.read_scalar(f2)?.to_f64()?;
-                // Using host floats (but it's fine, this operation does not have guaranteed precision).
-                let res = f1.to_host().powf(f2.to_host()).to_soft();
+                let res = math::powf(f1, f2);
+                let res = math::apply_float_nondet(this, "pow", &[f1, f2], res);
+                let res = this.adjust_nan(res, &[f1, f2]);
+                this.write_scalar(res, dest)?;
+            }
+            "powf128" => {
+                let [f1, f2] = check_arg_count(args)?;
+                let f1 = this.read_scalar(f1)?.to_f128()?;
+                let f2 = this.read_scalar(f2)?.to_f128()?;
+                let res = math::powf(f1, f2);
+                let res = math::apply_float_nondet(this, "pow", &[f1, f2], res);
                 let res = this.adjust_nan(res, &[f1, f2]);
                 this.write_scalar(res, dest)?;
             }
 
+            "powif16" => {
+                let [f, i] = check_arg_count(args)?;
+                let f = this.read_scalar(f)?.to_f16()?;
+                let i = this.read_scalar(i)?.to_i32()?;
+                let res = math::powi(f, i);
+                let n = Half::from_i128(i.into()).value;
+                let res = math::apply_float_nondet(this, "powi", &[f, n], res);
+                let res = this.adjust_nan(res, &[f]);
+                this.write_scalar(res, dest)?;
+            }
             "powif32" => {
                 let [f, i] = check_arg_count(args)?;
                 let f = this.read_scalar(f)?.to_f32()?;
                 let i = this.read_scalar(i)?.to_i32()?;
-                // Using host floats (but it's fine, this operation does not have guaranteed precision).
-                let res = f.to_host().powi(i).to_soft();
+                let res = math::powi(f, i);
+                let n = Single::from_i128(i.into()).value;
+                let res = math::apply_float_nondet(this, "powi", &[f, n], res);
                 let res = this.adjust_nan(res, &[f]);
                 this.write_scalar(res, dest)?;
             }
@@ -347,8 +433,19 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let [f, i] = check_arg_count(args)?;
                 let f = this.read_scalar(f)?.to_f64()?;
                 let i = this.read_scalar(i)?.to_i32()?;
-                // Using host floats (but it's fine, this operation does not have guaranteed precision).
-                let res = f.to_host().powi(i).to_soft();
+                let res = math::powi(f, i);
+                let n = Double::from_i128(i.into()).value;
+                let res = math::apply_float_nondet(this, "powi", &[f, n], res);
+                let res = this.adjust_nan(res, &[f]);
+                this.write_scalar(res, dest)?;
+            }
+            "powif128" => {
+                let [f, i] = check_arg_count(args)?;
+                let f = this.read_scalar(f)?.to_f128()?;
+                let i = this.read_scalar(i)?.to_i32()?;
+                let res = math::powi(f, i);
+                let n = Quad::from_i128(i.into()).value;
+                let res = math::apply_float_nondet(this, "powi", &[f, n], res);
                 let res = this.adjust_nan(res, &[f]);
                 this.write_scalar(res, dest)?;
             }
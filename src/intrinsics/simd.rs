@@ -1,4 +1,5 @@
 use either::Either;
+use rand::Rng as _;
 use rustc_abi::{Endian, HasDataLayout};
 use rustc_apfloat::{Float, Round};
 use rustc_middle::ty::FloatTy;
@@ -104,23 +105,23 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                             let ty::Float(float_ty) = op.layout.ty.kind() else {
                                 span_bug!(this.cur_span(), "{} operand is not a float", intrinsic_name)
                             };
-                            // Using host floats except for sqrt (but it's fine, these operations do not
-                            // have guaranteed precision).
                             match float_ty {
                                 FloatTy::F16 => unimplemented!("f16_f128"),
                                 FloatTy::F32 => {
                                     let f = op.to_scalar().to_f32()?;
                                     let res = match host_op {
                                         "fsqrt" => math::sqrt(f),
-                                        "fsin" => f.to_host().sin().to_soft(),
-                                        "fcos" => f.to_host().cos().to_soft(),
-                                        "fexp" => f.to_host().exp().to_soft(),
-                                        "fexp2" => f.to_host().exp2().to_soft(),
-                                        "flog" => f.to_host().ln().to_soft(),
-                                        "flog2" => f.to_host().log2().to_soft(),
-                                        "flog10" => f.to_host().log10().to_soft(),
+                                        "fsin" => math::sin(f),
+                                        "fcos" => math::cos(f),
+                                        "fexp" => math::exp(f),
+                                        "fexp2" => math::exp2(f),
+                                        "flog" => math::ln(f),
+                                        "flog2" => math::log2(f),
+                                        "flog10" => math::log10(f),
                                         _ => bug!(),
                                     };
+                                    let op = host_op.strip_prefix('f').unwrap();
+                                    let res = math::apply_float_nondet(this, op, &[f], res);
                                     let res = this.adjust_nan(res, &[f]);
                                     Scalar::from(res)
                                 }
@@ -128,15 +129,17 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                                     let f = op.to_scalar().to_f64()?;
                                     let res = match host_op {
                                         "fsqrt" => math::sqrt(f),
-                                        "fsin" => f.to_host().sin().to_soft(),
-                                        "fcos" => f.to_host().cos().to_soft(),
-                                        "fexp" => f.to_host().exp().to_soft(),
-                                        "fexp2" => f.to_host().exp2().to_soft(),
-                                        "flog" => f.to_host().ln().to_soft(),
-                                        "flog2" => f.to_host().log2().to_soft(),
-                                        "flog10" => f.to_host().log10().to_soft(),
+                                        "fsin" => math::sin(f),
+                                        "fcos" => math::cos(f),
+                                        "fexp" => math::exp(f),
+                                        "fexp2" => math::exp2(f),
+                                        "flog" => math::ln(f),
+                                        "flog2" => math::log2(f),
+                                        "flog10" => math::log10(f),
                                         _ => bug!(),
                                     };
+                                    let op = host_op.strip_prefix('f').unwrap();
+                                    let res = math::apply_float_nondet(this, op, &[f], res);
                                     let res = this.adjust_nan(res, &[f]);
                                     Scalar::from(res)
                                 }
@@ -421,6 +424,47 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 }
                 this.write_immediate(*res, dest)?;
             }
+            #[rustfmt::skip]
+            | "reduce_add_unordered"
+            | "reduce_mul_unordered" => {
+                use mir::BinOp;
+
+                let [op] = check_arg_count(args)?;
+                let (op, op_len) = this.project_to_simd(op)?;
+
+                let mir_op = match intrinsic_name {
+                    "reduce_add_unordered" => BinOp::Add,
+                    "reduce_mul_unordered" => BinOp::Mul,
+                    _ => unreachable!(),
+                };
+
+                let mut lanes = (0..op_len)
+                    .map(|i| this.read_immediate(&this.project_index(&op, i)?))
+                    .collect::<InterpResult<'tcx, Vec<_>>>()?;
+                // The order in which the lanes are combined is unspecified. For floats, this can
+                // change the result, so with `-Zmiri-float-nondet` we combine random pairs of
+                // lanes until only one is left, which can produce any order and association.
+                // Otherwise, we just go from left to right.
+                let random_order =
+                    this.machine.float_nondet && matches!(lanes[0].layout.ty.kind(), ty::Float(_));
+                let res = if random_order {
+                    while lanes.len() > 1 {
+                        let rng = this.machine.rng.get_mut();
+                        let left = lanes.swap_remove(rng.gen_range(0..lanes.len()));
+                        let right = lanes.swap_remove(rng.gen_range(0..lanes.len()));
+                        lanes.push(this.binary_op(mir_op, &left, &right)?);
+                    }
+                    lanes.pop().unwrap()
+                } else {
+                    let mut lanes = lanes.into_iter();
+                    let mut res = lanes.next().unwrap();
+                    for op in lanes {
+                        res = this.binary_op(mir_op, &res, &op)?;
+                    }
+                    res
+                };
+                this.write_immediate(*res, dest)?;
+            }
             "select" => {
                 let [mask, yes, no] = check_arg_count(args)?;
                 let (mask, mask_len) = this.project_to_simd(mask)?;
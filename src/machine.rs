@@ -28,6 +28,8 @@ use rustc_span::{Span, SpanData, Symbol};
 use crate::concurrency::cpu_affinity::{self, CpuAffinityMask};
 use crate::concurrency::data_race::{self, NaReadType, NaWriteType};
 use crate::concurrency::weak_memory;
+use crate::heap_profile::HeapProfile;
+use crate::self_profile::{SelfProfile, Subsystem};
 use crate::*;
 
 /// First real-time signal.
@@ -66,6 +68,10 @@ pub struct FrameExtra<'tcx> {
     /// This is used by `MiriMachine::current_span` and `MiriMachine::caller_span`
     pub is_user_relevant: bool,
 
+    /// Indicates whether retagging and validation are performed in this `Frame`, as determined by
+    /// `-Zmiri-check-scope`. We compute this once on creation and store the result.
+    pub in_check_scope: bool,
+
     /// We have a cache for the mapping from [`mir::Const`] to resulting [`AllocId`].
     /// However, we don't want all frames to always get the same result, so we insert
     /// an additional bit of "salt" into the cache key. This salt is fixed per-frame
@@ -74,6 +80,10 @@ pub struct FrameExtra<'tcx> {
 
     /// Data race detector per-frame data.
     pub data_race: Option<data_race::FrameState>,
+
+    /// The stack space of this frame, see `frame_size`. This is accounted against the stack size of
+    /// the thread while the frame is on the stack.
+    frame_size: u64,
 }
 
 impl<'tcx> std::fmt::Debug for FrameExtra<'tcx> {
@@ -84,15 +94,19 @@ impl<'tcx> std::fmt::Debug for FrameExtra<'tcx> {
             catch_unwind,
             timing: _,
             is_user_relevant,
+            in_check_scope,
             salt,
             data_race,
+            frame_size,
         } = self;
         f.debug_struct("FrameData")
             .field("borrow_tracker", borrow_tracker)
             .field("catch_unwind", catch_unwind)
             .field("is_user_relevant", is_user_relevant)
+            .field("in_check_scope", in_check_scope)
             .field("salt", salt)
             .field("data_race", data_race)
+            .field("frame_size", frame_size)
             .finish()
     }
 }
@@ -104,8 +118,10 @@ impl VisitProvenance for FrameExtra<'_> {
             borrow_tracker,
             timing: _,
             is_user_relevant: _,
+            in_check_scope: _,
             salt: _,
             data_race: _,
+            frame_size: _,
         } = self;
 
         catch_unwind.visit_provenance(visit);
@@ -114,7 +130,7 @@ impl VisitProvenance for FrameExtra<'_> {
 }
 
 /// Extra memory kinds
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum MiriMemoryKind {
     /// `__rust_alloc` memory.
     Rust,
@@ -174,6 +190,16 @@ impl MiriMemoryKind {
             Machine | Global | ExternStatic | Tls | Runtime => false,
         }
     }
+
+    /// Whether this is memory the program allocated from a heap, and that should hence show up in
+    /// a heap profile.
+    fn is_heap(self) -> bool {
+        use self::MiriMemoryKind::*;
+        match self {
+            Rust | Miri | C | WinHeap | WinLocal => true,
+            Machine | Global | ExternStatic | Tls | Runtime | Mmap => false,
+        }
+    }
 }
 
 impl fmt::Display for MiriMemoryKind {
@@ -333,6 +359,12 @@ pub struct AllocExtra<'tcx> {
     /// Weak memory emulation via the use of store buffers.
     /// This is only added if it is enabled.
     pub weak_memory: Option<weak_memory::AllocState>,
+    /// Where the uninitialized bytes of this allocation came from.
+    /// This is only added if `-Zmiri-track-uninit-origins` is set.
+    pub uninit_origins: Option<uninit_origin::AllocState>,
+    /// The page protections of memory mapped by `mmap`, as set by `mmap` and `mprotect`.
+    /// This is only added for such memory.
+    pub page_protections: Option<shims::PageProtections>,
     /// A backtrace to where this allocation was allocated.
     /// As this is recorded for leak reports, it only exists
     /// if this allocation is leakable. The backtrace is not
@@ -355,7 +387,15 @@ impl<'tcx> Clone for AllocExtra<'tcx> {
 
 impl VisitProvenance for AllocExtra<'_> {
     fn visit_provenance(&self, visit: &mut VisitWith<'_>) {
-        let AllocExtra { borrow_tracker, data_race, weak_memory, backtrace: _, sync: _ } = self;
+        let AllocExtra {
+            borrow_tracker,
+            data_race,
+            weak_memory,
+            uninit_origins: _,
+            page_protections: _,
+            backtrace: _,
+            sync: _,
+        } = self;
 
         borrow_tracker.visit_provenance(visit);
         data_race.visit_provenance(visit);
@@ -441,6 +481,9 @@ pub struct MiriMachine<'tcx> {
 
     /// Global data for borrow tracking.
     pub borrow_tracker: Option<borrow_tracker::GlobalState>,
+    /// The allocation traced by `-Zmiri-borrow-trace`, if any. This is kept outside of the borrow
+    /// tracker so that checking whether an event needs to be recorded is cheap.
+    pub(crate) borrow_trace_alloc: Option<AllocId>,
 
     /// Data race detector global data.
     pub data_race: Option<data_race::GlobalState>,
@@ -472,13 +515,23 @@ pub struct MiriMachine<'tcx> {
     /// Whether to enforce the validity invariant.
     pub(crate) validation: ValidationMode,
 
+    /// In which frames to perform retagging and validation.
+    pub(crate) check_scope: CheckScope,
+
     /// The table of file descriptors.
     pub(crate) fds: shims::FdTable,
     /// The table of directory descriptors.
     pub(crate) dirs: shims::DirTable,
+    /// The file mode creation mask set by `umask`. This is only tracked in the interpreter;
+    /// the host's umask is never changed.
+    pub(crate) umask: u32,
+    /// The metadata changed with isolation enabled. This is only tracked in the interpreter.
+    pub(crate) isolated_metadata: shims::IsolatedMetadataTable,
 
     /// The list of all EpollEventInterest.
     pub(crate) epoll_interests: shims::EpollInterestTable,
+    /// The shared file mappings created by `mmap`.
+    pub(crate) mmaps: shims::MmapTable,
 
     /// This machine's monotone clock.
     pub(crate) clock: Clock,
@@ -494,11 +547,18 @@ pub struct MiriMachine<'tcx> {
     /// The state of the primitive synchronization objects.
     pub(crate) sync: SynchronizationObjects,
 
+    /// The Windows kernel objects (events, mutexes and semaphores) that `HANDLE`s can refer to.
+    pub(crate) windows_objects: shims::WindowsObjectTable,
+
     /// Precomputed `TyLayout`s for primitive data types that are commonly used inside Miri.
     pub(crate) layouts: PrimitiveLayouts<'tcx>,
 
     /// Allocations that are considered roots of static memory (that may leak).
     pub(crate) static_roots: Vec<AllocId>,
+    /// Allocations that the thread-local statics of terminated threads pointed to when those
+    /// statics were deallocated. The leak check considers them to be still reachable. Entries are
+    /// removed when the allocation is deallocated.
+    pub(crate) tls_roots: FxHashSet<AllocId>,
 
     /// The `measureme` profiler used to record timing information about
     /// the emulated program.
@@ -507,6 +567,13 @@ pub struct MiriMachine<'tcx> {
     /// used with `measureme`.
     string_cache: FxHashMap<String, measureme::StringId>,
 
+    /// The profile of Miri's own overhead, if `-Zmiri-self-profile` is set.
+    pub(crate) self_profile: Option<SelfProfile<'tcx>>,
+    /// The heap profile of the interpreted program, if `-Zmiri-heap-profile` is set.
+    pub(crate) heap_profile: Option<RefCell<HeapProfile<'tcx>>>,
+    /// The origins of uninitialized bytes, if `-Zmiri-track-uninit-origins` is set.
+    pub(crate) uninit_origins: Option<uninit_origin::GlobalState>,
+
     /// Cache of `Instance` exported under the given `Symbol` name.
     /// `None` means no `Instance` exported under the given name is found.
     pub(crate) exported_symbols_cache: FxHashMap<Symbol, Option<Instance<'tcx>>>,
@@ -536,9 +603,15 @@ pub struct MiriMachine<'tcx> {
     /// Failure rate of compare_exchange_weak, between 0.0 and 1.0
     pub(crate) cmpxchg_weak_failure_rate: f64,
 
+    /// Corresponds to -Zmiri-float-nondet: whether imprecise float operations get a random error.
+    pub(crate) float_nondet: bool,
+
     /// Corresponds to -Zmiri-mute-stdout-stderr and doesn't write the output but acts as if it succeeded.
     pub(crate) mute_stdout_stderr: bool,
 
+    /// The state of `-Zmiri-auto-track` in this run.
+    pub(crate) auto_track: AutoTrack,
+
     /// Whether weak memory emulation is enabled
     pub(crate) weak_memory: bool,
 
@@ -567,10 +640,19 @@ pub struct MiriMachine<'tcx> {
     /// Determines Miri's page size and associated values
     pub(crate) page_size: u64,
     pub(crate) stack_addr: u64,
+    /// The stack size of threads that were created without asking for a particular size.
     pub(crate) stack_size: u64,
 
+    /// The host name to be reported by miri, if set on the command line.
+    pub(crate) hostname: Option<String>,
+    /// The current resource limits of the emulated process, indexed by the name of the
+    /// `RLIMIT_*` constant.
+    pub(crate) rlimits: FxHashMap<&'static str, shims::ResourceLimit>,
+
     /// Whether to collect a backtrace when each allocation is created, just in case it leaks.
     pub(crate) collect_leak_backtraces: bool,
+    /// Function path patterns of allocation sites whose leaks are not reported.
+    pub(crate) leak_suppressions: Vec<String>,
 
     /// The spans we will use to report where an allocation was created and deallocated in
     /// diagnostics.
@@ -591,6 +673,9 @@ pub struct MiriMachine<'tcx> {
 
     /// A cache of "data range" computations for unions (i.e., the offsets of non-padding bytes).
     union_data_ranges: FxHashMap<Ty<'tcx>, RangeSet>,
+
+    /// A cache of the stack space of the frames of each function, see `frame_size`.
+    pub(crate) frame_sizes: FxHashMap<Instance<'tcx>, u64>,
 }
 
 impl<'tcx> MiriMachine<'tcx> {
@@ -638,8 +723,11 @@ impl<'tcx> MiriMachine<'tcx> {
         };
         // On 16bit targets, 32 pages is more than the entire address space!
         let stack_addr = if tcx.pointer_size().bits() < 32 { page_size } else { page_size * 32 };
-        let stack_size =
-            if tcx.pointer_size().bits() < 32 { page_size * 4 } else { page_size * 16 };
+        let stack_size = if tcx.pointer_size().bits() < 32 {
+            page_size * 4
+        } else {
+            DEFAULT_THREAD_STACK_SIZE
+        };
         assert!(
             usize::try_from(config.num_cpus).unwrap() <= cpu_affinity::MAX_CPUS,
             "miri only supports up to {} CPUs, but {} were configured",
@@ -655,6 +743,7 @@ impl<'tcx> MiriMachine<'tcx> {
         MiriMachine {
             tcx,
             borrow_tracker,
+            borrow_trace_alloc: config.borrow_trace.as_ref().map(|(alloc_id, _dir)| *alloc_id),
             data_race,
             alloc_addresses: RefCell::new(alloc_addresses::GlobalStateInner::new(config, stack_addr)),
             // `env_vars` depends on a full interpreter so we cannot properly initialize it yet.
@@ -666,16 +755,34 @@ impl<'tcx> MiriMachine<'tcx> {
             tls: TlsData::default(),
             isolated_op: config.isolated_op,
             validation: config.validation,
+            check_scope: config.check_scope,
             fds: shims::FdTable::init(config.mute_stdout_stderr),
             epoll_interests: shims::EpollInterestTable::new(),
+            mmaps: Default::default(),
             dirs: Default::default(),
+            // The usual default on Unix systems.
+            umask: 0o022,
+            isolated_metadata: Default::default(),
             layouts,
             threads,
             thread_cpu_affinity,
             sync: SynchronizationObjects::default(),
+            windows_objects: Default::default(),
             static_roots: Vec::new(),
+            tls_roots: FxHashSet::default(),
             profiler,
             string_cache: Default::default(),
+            self_profile: config
+                .self_profile_out
+                .as_deref()
+                .map(|out| SelfProfile::new(out, Subsystem::active(config))),
+            heap_profile: config
+                .heap_profile_out
+                .as_deref()
+                .map(|out| RefCell::new(HeapProfile::new(out, config.args.join(" ")))),
+            uninit_origins: config
+                .track_uninit_origins
+                .then(uninit_origin::GlobalState::default),
             exported_symbols_cache: FxHashMap::default(),
             backtrace_style: config.backtrace_style,
             local_crates,
@@ -685,7 +792,9 @@ impl<'tcx> MiriMachine<'tcx> {
             track_alloc_accesses: config.track_alloc_accesses,
             check_alignment: config.check_alignment,
             cmpxchg_weak_failure_rate: config.cmpxchg_weak_failure_rate,
+            float_nondet: config.float_nondet,
             mute_stdout_stderr: config.mute_stdout_stderr,
+            auto_track: if config.auto_track { AutoTrack::Probe(None) } else { AutoTrack::Off },
             weak_memory: config.weak_memory_emulation,
             preemption_rate: config.preemption_rate,
             report_progress: config.report_progress,
@@ -723,11 +832,15 @@ impl<'tcx> MiriMachine<'tcx> {
             page_size,
             stack_addr,
             stack_size,
+            hostname: config.hostname.clone(),
+            rlimits: shims::init_rlimits(config),
             collect_leak_backtraces: config.collect_leak_backtraces,
+            leak_suppressions: config.leak_suppressions.clone(),
             allocation_spans: RefCell::new(FxHashMap::default()),
             const_cache: RefCell::new(FxHashMap::default()),
             symbolic_alignment: RefCell::new(FxHashMap::default()),
             union_data_ranges: FxHashMap::default(),
+            frame_sizes: FxHashMap::default(),
         }
     }
 
@@ -767,6 +880,12 @@ impl<'tcx> MiriMachine<'tcx> {
         drop(self.profiler.take());
     }
 
+    /// Whether retagging and validation should be performed for the code that is currently
+    /// executing. This is always true when the active thread has no frames.
+    pub(crate) fn in_check_scope(&self) -> bool {
+        self.threads.active_thread_stack().last().is_none_or(|frame| frame.extra.in_check_scope)
+    }
+
     pub(crate) fn page_align(&self) -> Align {
         Align::from_bytes(self.page_size).unwrap()
     }
@@ -794,6 +913,7 @@ impl VisitProvenance for MiriMachine<'_> {
             threads,
             thread_cpu_affinity: _,
             sync: _,
+            windows_objects: _,
             tls,
             env_vars,
             main_fn_ret_place,
@@ -802,19 +922,28 @@ impl VisitProvenance for MiriMachine<'_> {
             cmd_line,
             extern_statics,
             dirs,
+            umask: _,
+            isolated_metadata: _,
             borrow_tracker,
+            borrow_trace_alloc: _,
             data_race,
             alloc_addresses,
             fds,
             epoll_interests:_,
+            mmaps: _,
             tcx: _,
             isolated_op: _,
             validation: _,
+            check_scope: _,
             clock: _,
             layouts: _,
             static_roots: _,
+            tls_roots: _,
             profiler: _,
             string_cache: _,
+            self_profile: _,
+            heap_profile: _,
+            uninit_origins: _,
             exported_symbols_cache: _,
             backtrace_style: _,
             local_crates: _,
@@ -823,7 +952,9 @@ impl VisitProvenance for MiriMachine<'_> {
             track_alloc_accesses: _,
             check_alignment: _,
             cmpxchg_weak_failure_rate: _,
+            float_nondet: _,
             mute_stdout_stderr: _,
+            auto_track: _,
             weak_memory: _,
             preemption_rate: _,
             report_progress: _,
@@ -835,11 +966,15 @@ impl VisitProvenance for MiriMachine<'_> {
             page_size: _,
             stack_addr: _,
             stack_size: _,
+            hostname: _,
+            rlimits: _,
             collect_leak_backtraces: _,
+            leak_suppressions: _,
             allocation_spans: _,
             const_cache: _,
             symbolic_alignment: _,
             union_data_ranges: _,
+            frame_sizes: _,
         } = self;
 
         threads.visit_provenance(visit);
@@ -951,7 +1086,7 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
 
     #[inline(always)]
     fn enforce_validity(ecx: &MiriInterpCx<'tcx>, _layout: TyAndLayout<'tcx>) -> bool {
-        ecx.machine.validation != ValidationMode::No
+        ecx.machine.validation != ValidationMode::No && ecx.machine.in_check_scope()
     }
     #[inline(always)]
     fn enforce_validity_recursively(
@@ -1162,12 +1297,19 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             ecx.emit_diagnostic(NonHaltingDiagnostic::CreatedAlloc(id, size, align, kind));
         }
 
+        ecx.machine.self_profile_allocation();
+
+        let start = ecx.machine.self_profile_start();
         let borrow_tracker = ecx
             .machine
             .borrow_tracker
             .as_ref()
             .map(|bt| bt.borrow_mut().new_allocation(id, size, kind, &ecx.machine));
+        if borrow_tracker.is_some() {
+            ecx.machine.self_profile_end(start, ecx.machine.borrow_tracker_subsystem());
+        }
 
+        let start = ecx.machine.self_profile_start();
         let data_race = ecx.machine.data_race.as_ref().map(|data_race| {
             data_race::AllocState::new_allocation(
                 data_race,
@@ -1177,7 +1319,19 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
                 ecx.machine.current_span(),
             )
         });
+        if data_race.is_some() {
+            ecx.machine.self_profile_end(start, Subsystem::DataRace);
+        }
         let weak_memory = ecx.machine.weak_memory.then(weak_memory::AllocState::new_allocation);
+        let uninit_origins = ecx.machine.uninit_origins.as_ref().map(|global| {
+            uninit_origin::AllocState::new_allocation(
+                global,
+                id,
+                size,
+                kind,
+                ecx.machine.current_span(),
+            )
+        });
 
         // If an allocation is leaked, we want to report a backtrace to indicate where it was
         // allocated. We don't need to record a backtrace for allocations which are allowed to
@@ -1188,6 +1342,18 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             Some(ecx.generate_stacktrace())
         };
 
+        if let Some(heap_profile) = &ecx.machine.heap_profile
+            && matches!(kind, MemoryKind::Machine(kind) if kind.is_heap())
+        {
+            let stacktrace = ecx.generate_stacktrace();
+            heap_profile.borrow_mut().allocated(
+                id,
+                size,
+                &stacktrace,
+                ecx.machine.basic_block_count,
+            );
+        }
+
         if matches!(kind, MemoryKind::Machine(kind) if kind.should_save_allocation_span()) {
             ecx.machine
                 .allocation_spans
@@ -1199,6 +1365,8 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             borrow_tracker,
             data_race,
             weak_memory,
+            uninit_origins,
+            page_protections: None,
             backtrace,
             sync: FxHashMap::default(),
         })
@@ -1316,15 +1484,26 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             machine
                 .emit_diagnostic(NonHaltingDiagnostic::AccessedAlloc(alloc_id, AccessKind::Read));
         }
+        if let Some(page_protections) = &alloc_extra.page_protections {
+            page_protections.check_access(alloc_id, range, AccessKind::Read)?;
+        }
         if let Some(data_race) = &alloc_extra.data_race {
+            let start = machine.self_profile_start();
             data_race.read(alloc_id, range, NaReadType::Read, None, machine)?;
+            machine.self_profile_end(start, Subsystem::DataRace);
         }
         if let Some(borrow_tracker) = &alloc_extra.borrow_tracker {
+            let start = machine.self_profile_start();
             borrow_tracker.before_memory_read(alloc_id, prov_extra, range, machine)?;
+            machine.self_profile_end(start, machine.borrow_tracker_subsystem());
         }
         if let Some(weak_memory) = &alloc_extra.weak_memory {
             weak_memory.memory_accessed(range, machine.data_race.as_ref().unwrap());
         }
+        if let Some(uninit_origins) = &alloc_extra.uninit_origins {
+            let global = machine.uninit_origins.as_ref().unwrap();
+            uninit_origins.read(global, alloc_id, range, machine.current_span());
+        }
         interp_ok(())
     }
 
@@ -1340,15 +1519,29 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             machine
                 .emit_diagnostic(NonHaltingDiagnostic::AccessedAlloc(alloc_id, AccessKind::Write));
         }
+        if let Some(page_protections) = &alloc_extra.page_protections {
+            page_protections.check_access(alloc_id, range, AccessKind::Write)?;
+        }
         if let Some(data_race) = &mut alloc_extra.data_race {
+            let start = machine.self_profile_start();
             data_race.write(alloc_id, range, NaWriteType::Write, None, machine)?;
+            machine.self_profile_end(start, Subsystem::DataRace);
         }
         if let Some(borrow_tracker) = &mut alloc_extra.borrow_tracker {
+            let start = machine.self_profile_start();
             borrow_tracker.before_memory_write(alloc_id, prov_extra, range, machine)?;
+            machine.self_profile_end(start, machine.borrow_tracker_subsystem());
         }
         if let Some(weak_memory) = &alloc_extra.weak_memory {
             weak_memory.memory_accessed(range, machine.data_race.as_ref().unwrap());
         }
+        if let Some(uninit_origins) = &mut alloc_extra.uninit_origins {
+            uninit_origins.write(
+                machine.uninit_origins.as_ref().unwrap(),
+                range,
+                machine.current_span(),
+            );
+        }
         interp_ok(())
     }
 
@@ -1366,6 +1559,7 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             machine.emit_diagnostic(NonHaltingDiagnostic::FreedAlloc(alloc_id));
         }
         if let Some(data_race) = &mut alloc_extra.data_race {
+            let start = machine.self_profile_start();
             data_race.write(
                 alloc_id,
                 alloc_range(Size::ZERO, size),
@@ -1373,14 +1567,21 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
                 None,
                 machine,
             )?;
+            machine.self_profile_end(start, Subsystem::DataRace);
         }
         if let Some(borrow_tracker) = &mut alloc_extra.borrow_tracker {
+            let start = machine.self_profile_start();
             borrow_tracker.before_memory_deallocation(alloc_id, prove_extra, size, machine)?;
+            machine.self_profile_end(start, machine.borrow_tracker_subsystem());
+        }
+        if let Some(heap_profile) = &machine.heap_profile {
+            heap_profile.borrow_mut().deallocated(alloc_id, machine.basic_block_count);
         }
         if let Some((_, deallocated_at)) = machine.allocation_spans.borrow_mut().get_mut(&alloc_id)
         {
             *deallocated_at = Some(machine.current_span());
         }
+        machine.tls_roots.remove(&alloc_id);
         machine.free_alloc_id(alloc_id, size, align, kind);
         interp_ok(())
     }
@@ -1391,8 +1592,11 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         kind: mir::RetagKind,
         val: &ImmTy<'tcx>,
     ) -> InterpResult<'tcx, ImmTy<'tcx>> {
-        if ecx.machine.borrow_tracker.is_some() {
-            ecx.retag_ptr_value(kind, val)
+        if ecx.machine.borrow_tracker.is_some() && ecx.machine.in_check_scope() {
+            let start = ecx.machine.self_profile_start();
+            let val = ecx.retag_ptr_value(kind, val)?;
+            ecx.machine.self_profile_end(start, ecx.machine.borrow_tracker_subsystem());
+            interp_ok(val)
         } else {
             interp_ok(val.clone())
         }
@@ -1404,8 +1608,10 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         kind: mir::RetagKind,
         place: &PlaceTy<'tcx>,
     ) -> InterpResult<'tcx> {
-        if ecx.machine.borrow_tracker.is_some() {
+        if ecx.machine.borrow_tracker.is_some() && ecx.machine.in_check_scope() {
+            let start = ecx.machine.self_profile_start();
             ecx.retag_place_contents(kind, place)?;
+            ecx.machine.self_profile_end(start, ecx.machine.borrow_tracker_subsystem());
         }
         interp_ok(())
     }
@@ -1417,7 +1623,10 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         // If we have a borrow tracker, we also have it set up protection so that all reads *and
         // writes* during this call are insta-UB.
         let protected_place = if ecx.machine.borrow_tracker.is_some() {
-            ecx.protect_place(place)?
+            let start = ecx.machine.self_profile_start();
+            let protected_place = ecx.protect_place(place)?;
+            ecx.machine.self_profile_end(start, ecx.machine.borrow_tracker_subsystem());
+            protected_place
         } else {
             // No borrow tracker.
             place.clone()
@@ -1451,15 +1660,23 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
             None
         };
 
+        let frame_size = ecx.frame_size(&frame)?;
         let borrow_tracker = ecx.machine.borrow_tracker.as_ref();
+        let in_check_scope = ecx.machine.is_in_check_scope(&frame);
 
         let extra = FrameExtra {
             borrow_tracker: borrow_tracker.map(|bt| bt.borrow_mut().new_frame()),
             catch_unwind: None,
             timing,
             is_user_relevant: ecx.machine.is_user_relevant(&frame),
+            in_check_scope,
             salt: ecx.machine.rng.borrow_mut().gen::<usize>() % ADDRS_PER_ANON_GLOBAL,
-            data_race: ecx.machine.data_race.as_ref().map(|_| data_race::FrameState::default()),
+            // Frames outside the check scope do not track their not-yet-allocated locals. Those
+            // locals can only be accessed by this thread anyway; if they are moved to memory, their
+            // allocation is treated as freshly initialized at that point.
+            data_race: (ecx.machine.data_race.is_some() && in_check_scope)
+                .then(data_race::FrameState::default),
+            frame_size,
         };
 
         interp_ok(frame.with_extra(extra))
@@ -1495,7 +1712,9 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         // where it mistakenly removes an important tag become visible.
         if ecx.machine.gc_interval > 0 && ecx.machine.since_gc >= ecx.machine.gc_interval {
             ecx.machine.since_gc = 0;
+            let start = ecx.machine.self_profile_start();
             ecx.run_provenance_gc();
+            ecx.machine.self_profile_end(start, Subsystem::ProvenanceGc);
         }
 
         // These are our preemption points.
@@ -1509,6 +1728,11 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
 
     #[inline(always)]
     fn after_stack_push(ecx: &mut InterpCx<'tcx, Self>) -> InterpResult<'tcx> {
+        let frame_size = ecx.frame().extra.frame_size;
+        if !ecx.active_thread_mut().push_stack_usage(frame_size) {
+            let stack_size = ecx.active_thread_ref().stack_size();
+            throw_machine_stop!(TerminationInfo::StackOverflow { stack_size });
+        }
         if ecx.frame().extra.is_user_relevant {
             // We just pushed a local frame, so we know that the topmost local frame is the topmost
             // frame. If we push a non-local frame, there's no need to do anything.
@@ -1525,7 +1749,9 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         // We want this *before* the return value copy, because the return place itself is protected
         // until we do `end_call` here.
         if ecx.machine.borrow_tracker.is_some() {
+            let start = ecx.machine.self_profile_start();
             ecx.on_stack_pop(frame)?;
+            ecx.machine.self_profile_end(start, ecx.machine.borrow_tracker_subsystem());
         }
         // tracing-tree can autoamtically annotate scope changes, but it gets very confused by our
         // concurrency and what it prints is just plain wrong. So we print our own information
@@ -1540,6 +1766,7 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         frame: Frame<'tcx, Provenance, FrameExtra<'tcx>>,
         unwinding: bool,
     ) -> InterpResult<'tcx, ReturnAction> {
+        ecx.active_thread_mut().pop_stack_usage(frame.extra.frame_size);
         if frame.extra.is_user_relevant {
             // All that we store is whether or not the frame we just removed is local, so now we
             // have no idea where the next topmost local frame is. So we recompute it.
@@ -1567,7 +1794,9 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
 
     fn after_local_read(ecx: &InterpCx<'tcx, Self>, local: mir::Local) -> InterpResult<'tcx> {
         if let Some(data_race) = &ecx.frame().extra.data_race {
+            let start = ecx.machine.self_profile_start();
             data_race.local_read(local, &ecx.machine);
+            ecx.machine.self_profile_end(start, Subsystem::DataRace);
         }
         interp_ok(())
     }
@@ -1578,7 +1807,9 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         storage_live: bool,
     ) -> InterpResult<'tcx> {
         if let Some(data_race) = &ecx.frame().extra.data_race {
+            let start = ecx.machine.self_profile_start();
             data_race.local_write(local, storage_live, &ecx.machine);
+            ecx.machine.self_profile_end(start, Subsystem::DataRace);
         }
         interp_ok(())
     }
@@ -1600,7 +1831,12 @@ impl<'tcx> Machine<'tcx> for MiriMachine<'tcx> {
         if let Some(data_race) =
             &machine.threads.active_thread_stack().last().unwrap().extra.data_race
         {
+            let start = machine.self_profile_start();
             data_race.local_moved_to_memory(local, alloc_info.data_race.as_mut().unwrap(), machine);
+            machine.self_profile_end(start, Subsystem::DataRace);
+        }
+        if let Some(uninit_origins) = &mut alloc_info.uninit_origins {
+            uninit_origins.local_moved_to_memory(machine.uninit_origins.as_ref().unwrap(), span);
         }
         interp_ok(())
     }
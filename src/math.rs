@@ -1,7 +1,23 @@
+//! Floating-point operations that are not provided by `rustc_apfloat`.
+//!
+//! The transcendental functions are implemented in software, so that their results do not depend on
+//! the host. They are all computed in quad precision with a relative error below 2^-100, and that
+//! quad result is then rounded to the precision of the argument. Since this rounds twice, the
+//! result for `f16`, `f32`, and `f64` is not always the correctly rounded one: the error is at most
+//! 0.5 units in the last place plus 2^-47 (`f64`), 2^-76 (`f32`), or 2^-89 (`f16`) units in the
+//! last place. The result can only differ from the correctly rounded one when the exact value is
+//! that close to the midpoint between two neighboring floats. The exception is `ln_gamma` very
+//! close to its zeros at 1 and 2, where the error is tiny in absolute terms, but not relative to
+//! the result.
+//!
+//! For `f128`, the results of most functions are within a few units in the last place. `powf`,
+//! `gamma`, and `ln_gamma` are less precise for results far from 1, but still have a relative error
+//! below 2^-96.
+
 use rand::Rng as _;
 use rand::distributions::Distribution as _;
-use rustc_apfloat::Float as _;
-use rustc_apfloat::ieee::IeeeFloat;
+use rustc_apfloat::ieee::{IeeeFloat, Quad, Semantics};
+use rustc_apfloat::{Float as _, FloatConvert as _};
 
 /// Disturbes a floating-point result by a relative error on the order of (-2^scale, 2^scale).
 pub(crate) fn apply_random_float_error<F: rustc_apfloat::Float>(
@@ -21,6 +37,54 @@ pub(crate) fn apply_random_float_error<F: rustc_apfloat::Float>(
     (val * (F::from_u128(1).value + err).value).value
 }
 
+/// Applies the imprecision that Rust permits for the result `res` of the operation `op` on `args`,
+/// if `-Zmiri-float-nondet` is set. `op` is the name of the corresponding C function for `f64`
+/// (e.g. `"log"` or `"atan2"`); for `powi`, the exponent is passed as the second argument.
+///
+/// The result is disturbed by at most 4 units in the last place. Results that the function takes
+/// exactly at some points, like `exp(0) = 1` or `pow(x, 0) = 1`, are kept exact, and the result
+/// never leaves the range of the function, e.g. `sin(x)` stays within `[-1, 1]`.
+pub(crate) fn apply_float_nondet<S: Semantics>(
+    ecx: &mut crate::MiriInterpCx<'_>,
+    op: &str,
+    args: &[IeeeFloat<S>],
+    res: IeeeFloat<S>,
+) -> IeeeFloat<S> {
+    if !ecx.machine.float_nondet || !res.is_finite() || res.is_zero() {
+        return res;
+    }
+    let one = IeeeFloat::<S>::from_u128(1).value;
+    let exact = match op {
+        // These are specified to be correctly rounded.
+        "sqrt" | "fdim" => true,
+        "exp" | "exp2" | "cos" | "cosh" => args[0].is_zero(),
+        "pow" =>
+            args[1].is_zero() || args[0] == one || (args[0] == -one && args[1].is_infinite()),
+        "powi" => args[1].is_zero(),
+        _ => false,
+    };
+    if exact {
+        return res;
+    }
+
+    // A relative error below 2^(2 - PRECISION) is at most 4 units in the last place.
+    let err_scale = 2 - i32::try_from(S::PRECISION).unwrap();
+    let res = apply_random_float_error(ecx, res, err_scale);
+
+    let pi = quad::pi().convert(&mut false).value;
+    let frac_pi_2 = quad::frac_pi_2().convert(&mut false).value;
+    let (min, max) = match op {
+        "sin" | "cos" | "tanh" => (-one, one),
+        "cosh" => (one, IeeeFloat::INFINITY),
+        "expm1" => (-one, IeeeFloat::INFINITY),
+        "asin" | "atan" => (-frac_pi_2, frac_pi_2),
+        "acos" => (IeeeFloat::ZERO, pi),
+        "atan2" => (-pi, pi),
+        _ => return res,
+    };
+    res.max(min).min(max)
+}
+
 pub(crate) fn sqrt<S: rustc_apfloat::ieee::Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
     match x.category() {
         // preserve zero sign
@@ -103,11 +167,938 @@ pub(crate) fn sqrt<S: rustc_apfloat::ieee::Semantics>(x: IeeeFloat<S>) -> IeeeFl
     }
 }
 
+/// Computes `f` in quad precision and rounds the result to the precision of `x`.
+fn via_quad<S: Semantics>(x: IeeeFloat<S>, f: impl FnOnce(Quad) -> Quad) -> IeeeFloat<S> {
+    f(x.convert(&mut false).value).convert(&mut false).value
+}
+
+/// Like `via_quad`, but for functions with two arguments.
+fn via_quad2<S: Semantics>(
+    x: IeeeFloat<S>,
+    y: IeeeFloat<S>,
+    f: impl FnOnce(Quad, Quad) -> Quad,
+) -> IeeeFloat<S> {
+    f(x.convert(&mut false).value, y.convert(&mut false).value).convert(&mut false).value
+}
+
+pub(crate) fn sin<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::sin)
+}
+
+pub(crate) fn cos<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::cos)
+}
+
+pub(crate) fn tan<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::tan)
+}
+
+pub(crate) fn asin<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::asin)
+}
+
+pub(crate) fn acos<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::acos)
+}
+
+pub(crate) fn atan<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::atan)
+}
+
+/// The angle of the point `(x, y)`, i.e. `atan(y / x)` in the right quadrant.
+pub(crate) fn atan2<S: Semantics>(y: IeeeFloat<S>, x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad2(y, x, quad::atan2)
+}
+
+pub(crate) fn sinh<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::sinh)
+}
+
+pub(crate) fn cosh<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::cosh)
+}
+
+pub(crate) fn tanh<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::tanh)
+}
+
+pub(crate) fn exp<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::exp)
+}
+
+pub(crate) fn exp2<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::exp2)
+}
+
+/// `e^x - 1`, accurate even when `x` is close to zero.
+pub(crate) fn exp_m1<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::exp_m1)
+}
+
+pub(crate) fn ln<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::ln)
+}
+
+pub(crate) fn log2<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::log2)
+}
+
+pub(crate) fn log10<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::log10)
+}
+
+/// `ln(1 + x)`, accurate even when `x` is close to zero.
+pub(crate) fn ln_1p<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::ln_1p)
+}
+
+/// `x^y`, with the special cases of C's `pow`.
+pub(crate) fn powf<S: Semantics>(x: IeeeFloat<S>, y: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad2(x, y, quad::powf)
+}
+
+/// `x^n`. Like LLVM's `powi`, `x^0` is 1 even if `x` is NaN.
+pub(crate) fn powi<S: Semantics>(x: IeeeFloat<S>, n: i32) -> IeeeFloat<S> {
+    via_quad(x, |x| quad::powi(x, n))
+}
+
+pub(crate) fn cbrt<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::cbrt)
+}
+
+/// `sqrt(x^2 + y^2)`, without intermediate overflow.
+pub(crate) fn hypot<S: Semantics>(x: IeeeFloat<S>, y: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad2(x, y, quad::hypot)
+}
+
+/// The positive difference `max(x - y, 0)`.
+pub(crate) fn fdim<S: Semantics>(x: IeeeFloat<S>, y: IeeeFloat<S>) -> IeeeFloat<S> {
+    // This is computed in the original precision, so that the subtraction is rounded only once.
+    if x.is_nan() || y.is_nan() {
+        IeeeFloat::NAN
+    } else if x > y {
+        (x - y).value
+    } else {
+        IeeeFloat::ZERO
+    }
+}
+
+/// The gamma function.
+pub(crate) fn gamma<S: Semantics>(x: IeeeFloat<S>) -> IeeeFloat<S> {
+    via_quad(x, quad::gamma)
+}
+
+/// The natural logarithm of the absolute value of the gamma function, and the sign of the gamma
+/// function (1 or -1), like C's `lgamma_r`.
+pub(crate) fn ln_gamma<S: Semantics>(x: IeeeFloat<S>) -> (IeeeFloat<S>, i32) {
+    let (res, sign) = quad::ln_gamma(x.convert(&mut false).value);
+    (res.convert(&mut false).value, sign)
+}
+
+/// The implementations of the functions above, in quad precision.
+mod quad {
+    use rustc_apfloat::ieee::Quad;
+    use rustc_apfloat::{Float as _, Round};
+
+    // Mathematical constants, rounded to quad precision.
+    fn ln_2() -> Quad {
+        Quad::from_bits(0x3ffe62e42fefa39ef35793c7673007e6)
+    }
+
+    fn log2_e() -> Quad {
+        Quad::from_bits(0x3fff71547652b82fe1777d0ffda0d23a)
+    }
+
+    fn log10_e() -> Quad {
+        Quad::from_bits(0x3ffdbcb7b1526e50e32a6ab7555f5a68)
+    }
+
+    fn log10_2() -> Quad {
+        Quad::from_bits(0x3ffd34413509f79fef311f12b35816f9)
+    }
+
+    pub(super) fn pi() -> Quad {
+        Quad::from_bits(0x4000921fb54442d18469898cc51701b8)
+    }
+
+    pub(super) fn frac_pi_2() -> Quad {
+        Quad::from_bits(0x3fff921fb54442d18469898cc51701b8)
+    }
+
+    fn frac_pi_4() -> Quad {
+        Quad::from_bits(0x3ffe921fb54442d18469898cc51701b8)
+    }
+
+    fn sqrt_2() -> Quad {
+        Quad::from_bits(0x3fff6a09e667f3bcc908b2fb1366ea95)
+    }
+
+    /// `ln(2π) / 2`
+    fn ln_2pi_half() -> Quad {
+        Quad::from_bits(0x3ffed67f1c864beb4a69297920028832)
+    }
+
+    /// `ln(2)` split into a part with 98 significant bits, so that multiplying it with an integer
+    /// of up to 15 bits is exact, and the (rounded) rest.
+    fn ln_2_hi() -> Quad {
+        Quad::from_bits(0x3ffe62e42fefa39ef35793c767300000)
+    }
+
+    fn ln_2_lo() -> Quad {
+        Quad::from_bits(0x3f98f97b57a079a193394c5b16c5068c)
+    }
+
+    /// Beyond this, `exp` overflows or underflows even with the scaling done by `sinh` and `cosh`.
+    /// This also keeps the multiples of `ln_2_hi` in `exp` exact.
+    const EXP_LIMIT: i128 = 20000;
+    /// The number of terms of the Taylor series of `exp(r) - 1` for `|r| < 1/2`.
+    const EXP_TERMS: i128 = 28;
+    /// The number of terms of the series of `atanh(s)` for `|s| <= 3 - 2*sqrt(2)`.
+    const ATANH_TERMS: i128 = 24;
+    /// The number of terms of the Taylor series of `sin` and `cos` for `|r| <= π/4`.
+    const TRIG_TERMS: i128 = 16;
+    /// The number of terms of the Taylor series of `atan(a)` for `|a| <= tan(π/32)`.
+    const ATAN_TERMS: i128 = 18;
+    /// Beyond this, `sinh(x)` and `cosh(x)` are `exp(|x|) / 2` and `tanh(x)` is ±1 in quad
+    /// precision.
+    const HYPERBOLIC_LIMIT: i128 = 40;
+    /// The Stirling series of `ln_gamma` is used for arguments of at least this size; smaller
+    /// arguments are shifted up with the recurrence `Γ(x + 1) = x Γ(x)`.
+    const GAMMA_SHIFT: i128 = 40;
+    /// Beyond this, `gamma` overflows.
+    const GAMMA_LIMIT: i128 = 1800;
+    /// The coefficients `B_2k / (2k (2k - 1))` of the Stirling series, as fractions.
+    const STIRLING: [(i128, i128); 15] = [
+        (1, 12),
+        (-1, 360),
+        (1, 1260),
+        (-1, 1680),
+        (1, 1188),
+        (-691, 360360),
+        (1, 156),
+        (-3617, 122400),
+        (43867, 244188),
+        (-174611, 125400),
+        (77683, 5796),
+        (-236364091, 1506960),
+        (657931, 300),
+        (-3392780147, 93960),
+        (1723168255201, 2492028),
+    ];
+
+    fn int(n: i128) -> Quad {
+        Quad::from_i128(n).value
+    }
+
+    /// Converts an integer-valued float to an `i32`, saturating.
+    fn to_i32(x: Quad) -> i32 {
+        x.to_i128(32).value.try_into().unwrap()
+    }
+
+    fn is_odd_integer(x: Quad) -> bool {
+        x.is_integer() && !x.scalbn(-1).is_integer()
+    }
+
+    /// `exp(r) - 1` for `|r| < 1/2`.
+    fn exp_m1_taylor(r: Quad) -> Quad {
+        let mut acc = int(1);
+        for i in (2..=EXP_TERMS).rev() {
+            acc = (int(1) + ((acc * r).value / int(i)).value).value;
+        }
+        (r * acc).value
+    }
+
+    /// `exp(x) * 2^scale` for finite `x`.
+    fn exp_scaled(x: Quad, scale: i32) -> Quad {
+        if x > int(EXP_LIMIT) {
+            return Quad::INFINITY;
+        }
+        if x < int(-EXP_LIMIT) {
+            return Quad::ZERO;
+        }
+        // Write x = k * ln(2) + r with an integer k and |r| <= ln(2) / 2. Both products with k are
+        // exact and so is the first subtraction, so r is only rounded once.
+        let k = (x * log2_e()).value.round_to_integral(Round::NearestTiesToEven).value;
+        let r = ((x - (k * ln_2_hi()).value).value - (k * ln_2_lo()).value).value;
+        (int(1) + exp_m1_taylor(r)).value.scalbn(to_i32(k) + scale)
+    }
+
+    pub(super) fn exp(x: Quad) -> Quad {
+        if x.is_nan() {
+            x
+        } else if x.is_infinite() {
+            if x.is_negative() { Quad::ZERO } else { x }
+        } else {
+            exp_scaled(x, 0)
+        }
+    }
+
+    pub(super) fn exp2(x: Quad) -> Quad {
+        if x.is_nan() {
+            x
+        } else if x.is_infinite() {
+            if x.is_negative() { Quad::ZERO } else { x }
+        } else if x > int(EXP_LIMIT) {
+            Quad::INFINITY
+        } else if x < int(-EXP_LIMIT) {
+            Quad::ZERO
+        } else {
+            // Write x = k + f with an integer k and |f| <= 1/2; the subtraction is exact.
+            let k = x.round_to_integral(Round::NearestTiesToEven).value;
+            let r = ((x - k).value * ln_2()).value;
+            (int(1) + exp_m1_taylor(r)).value.scalbn(to_i32(k))
+        }
+    }
+
+    pub(super) fn exp_m1(x: Quad) -> Quad {
+        if x.is_nan() {
+            x
+        } else if x.is_infinite() {
+            if x.is_negative() { int(-1) } else { x }
+        } else if x.abs() < int(1).scalbn(-1) {
+            exp_m1_taylor(x)
+        } else {
+            (exp(x) - int(1)).value
+        }
+    }
+
+    /// `atanh(s)` for `|s| <= 3 - 2*sqrt(2)`.
+    fn atanh_series(s: Quad) -> Quad {
+        // atanh(s) = s + s^3/3 + s^5/5 + ...
+        let s2 = (s * s).value;
+        let mut acc = (int(1) / int(2 * ATANH_TERMS + 1)).value;
+        for k in (0..ATANH_TERMS).rev() {
+            acc = ((int(1) / int(2 * k + 1)).value + (s2 * acc).value).value;
+        }
+        (s * acc).value
+    }
+
+    /// Splits `ln(x)` for finite positive `x` into `e * ln(2) + ln(m)` with `m` close to 1, and
+    /// returns `e` and `ln(m)`.
+    fn ln_parts(x: Quad) -> (i32, Quad) {
+        let mut e = x.ilogb();
+        let mut m = x.scalbn(-e);
+        if m > sqrt_2() {
+            m = m.scalbn(-1);
+            e += 1;
+        }
+        // ln(m) = 2 atanh((m - 1) / (m + 1)), and |(m - 1) / (m + 1)| <= 3 - 2*sqrt(2).
+        let s = ((m - int(1)).value / (m + int(1)).value).value;
+        (e, atanh_series(s).scalbn(1))
+    }
+
+    /// The result of the logarithms for arguments that are not finite and positive.
+    fn ln_special(x: Quad) -> Option<Quad> {
+        if x.is_nan() {
+            Some(x)
+        } else if x.is_zero() {
+            Some(-Quad::INFINITY)
+        } else if x.is_negative() {
+            Some(Quad::NAN)
+        } else if x.is_infinite() {
+            Some(x)
+        } else {
+            None
+        }
+    }
+
+    pub(super) fn ln(x: Quad) -> Quad {
+        if let Some(res) = ln_special(x) {
+            return res;
+        }
+        let (e, ln_m) = ln_parts(x);
+        let e = int(e.into());
+        // The product with `ln_2_hi()` is exact.
+        ((e * ln_2_hi()).value + ((e * ln_2_lo()).value + ln_m).value).value
+    }
+
+    pub(super) fn log2(x: Quad) -> Quad {
+        if let Some(res) = ln_special(x) {
+            return res;
+        }
+        // This is exact for powers of 2.
+        let (e, ln_m) = ln_parts(x);
+        (int(e.into()) + (ln_m * log2_e()).value).value
+    }
+
+    pub(super) fn log10(x: Quad) -> Quad {
+        if let Some(res) = ln_special(x) {
+            return res;
+        }
+        let (e, ln_m) = ln_parts(x);
+        ((int(e.into()) * log10_2()).value + (ln_m * log10_e()).value).value
+    }
+
+    pub(super) fn ln_1p(x: Quad) -> Quad {
+        if x.is_nan() {
+            x
+        } else if x < int(-1) {
+            Quad::NAN
+        } else if x == int(-1) {
+            -Quad::INFINITY
+        } else if x.is_infinite() {
+            x
+        } else if int(-1).scalbn(-2) < x && x < int(3).scalbn(-3) {
+            // ln(1 + x) = 2 atanh(x / (2 + x)), and |x / (2 + x)| <= 3 - 2*sqrt(2) in this range.
+            atanh_series((x / (int(2) + x).value).value).scalbn(1)
+        } else {
+            ln((int(1) + x).value)
+        }
+    }
+
+    /// The number of fractional bits of `x * 2/π` computed by `rem_frac_pi_2`. This is enough to
+    /// get a full-precision result even if `x` is very close to a multiple of π/2.
+    const FRAC_BITS: usize = 353;
+    /// The number of limbs needed for `FRAC_BITS + 2` bits of 2/π.
+    const WINDOW_LIMBS: usize = (FRAC_BITS + 2).div_ceil(64);
+
+    /// Bit `i` of the binary expansion of 2/π, where bit 1 is the one with value 1/2.
+    fn two_over_pi_bit(i: i32) -> bool {
+        let Ok(i) = usize::try_from(i - 1) else { return false };
+        (TWO_OVER_PI[i / 64] >> (63 - i % 64)) & 1 == 1
+    }
+
+    /// Reduces finite `x` modulo π/2: returns `n` and `r` such that `x = n * π/2 + r` (with `n`
+    /// taken modulo 4) and `|r| <= π/4`.
+    fn rem_frac_pi_2(x: Quad) -> (u32, Quad) {
+        if x.abs() <= frac_pi_4() {
+            return (0, x);
+        }
+        // This is the Payne-Hanek reduction. Write |x| = m * 2^e with an integer m. We need the
+        // fractional part of x * 2/π, and its integer part modulo 4. The bits of 2/π with a value
+        // of at most 2^(-e - 2) are irrelevant for the latter, so we can multiply m with a window
+        // of the bits of 2/π starting at that position, in integer arithmetic.
+        let ilogb = x.ilogb();
+        let m = x.abs().scalbn(112 - ilogb).to_u128(113).value;
+        let e = ilogb - 112;
+        let first = e - 1;
+        let last = e + i32::try_from(FRAC_BITS).unwrap();
+        // The window, as a little-endian integer.
+        let mut window = [0u64; WINDOW_LIMBS];
+        for i in first..=last {
+            if two_over_pi_bit(i) {
+                let pos = usize::try_from(last - i).unwrap();
+                window[pos / 64] |= 1 << (pos % 64);
+            }
+        }
+        // Multiply the window with m. The product is x * 2/π with `FRAC_BITS` fractional bits.
+        let m = [m as u64, (m >> 64) as u64];
+        let mut prod = [0u64; WINDOW_LIMBS + 2];
+        for (i, &a) in window.iter().enumerate() {
+            let mut carry = 0u128;
+            for (j, &b) in m.iter().enumerate() {
+                let t = u128::from(a) * u128::from(b) + u128::from(prod[i + j]) + carry;
+                prod[i + j] = t as u64;
+                carry = t >> 64;
+            }
+            prod[i + 2] = carry as u64;
+        }
+        let bit = |prod: &[u64], pos: usize| prod[pos / 64] >> (pos % 64) & 1;
+        let mut n = u32::try_from(bit(&prod, FRAC_BITS) | bit(&prod, FRAC_BITS + 1) << 1).unwrap();
+        // Keep only the fractional part.
+        prod[FRAC_BITS / 64] &= (1 << (FRAC_BITS % 64)) - 1;
+        prod[FRAC_BITS / 64 + 1..].fill(0);
+        // Round to the nearest integer instead of truncating, i.e. subtract 1 from the fractional
+        // part if it is at least 1/2.
+        let negative = bit(&prod, FRAC_BITS - 1) == 1;
+        if negative {
+            n = (n + 1) % 4;
+            // Compute 2^FRAC_BITS - frac by negating in two's complement.
+            let mut carry = true;
+            for limb in &mut prod {
+                (*limb, carry) = (!*limb).overflowing_add(u64::from(carry));
+            }
+            prod[FRAC_BITS / 64] &= (1 << (FRAC_BITS % 64)) - 1;
+            prod[FRAC_BITS / 64 + 1..].fill(0);
+        }
+        // Convert the top 128 bits of the fractional part to a float, and multiply with π/2.
+        let len = prod
+            .iter()
+            .rposition(|&limb| limb != 0)
+            .map_or(0, |i| i * 64 + 64 - usize::try_from(prod[i].leading_zeros()).unwrap());
+        let shift = len.saturating_sub(128);
+        let mut top = 0u128;
+        for k in 0..len.min(128) {
+            top |= u128::from(bit(&prod, shift + k)) << k;
+        }
+        let exp = i32::try_from(shift).unwrap() - i32::try_from(FRAC_BITS).unwrap();
+        let r = (Quad::from_u128(top).value.scalbn(exp) * frac_pi_2()).value;
+        let r = if negative { -r } else { r };
+        if x.is_negative() { ((4 - n) % 4, -r) } else { (n, r) }
+    }
+
+    /// `sin(r)` for `|r| <= π/4`.
+    fn sin_taylor(r: Quad) -> Quad {
+        // sin(r) = r (1 - r^2/(2*3) (1 - r^2/(4*5) (1 - ...)))
+        let r2 = (r * r).value;
+        let mut acc = int(1);
+        for k in (1..=TRIG_TERMS).rev() {
+            acc = (int(1) - ((acc * r2).value / int((2 * k) * (2 * k + 1))).value).value;
+        }
+        (r * acc).value
+    }
+
+    /// `cos(r)` for `|r| <= π/4`.
+    fn cos_taylor(r: Quad) -> Quad {
+        // cos(r) = 1 - r^2/(1*2) (1 - r^2/(3*4) (1 - ...))
+        let r2 = (r * r).value;
+        let mut acc = int(1);
+        for k in (1..=TRIG_TERMS).rev() {
+            acc = (int(1) - ((acc * r2).value / int((2 * k - 1) * (2 * k))).value).value;
+        }
+        acc
+    }
+
+    pub(super) fn sin(x: Quad) -> Quad {
+        if !x.is_finite() {
+            return Quad::NAN;
+        }
+        match rem_frac_pi_2(x) {
+            (0, r) => sin_taylor(r),
+            (1, r) => cos_taylor(r),
+            (2, r) => -sin_taylor(r),
+            (_, r) => -cos_taylor(r),
+        }
+    }
+
+    pub(super) fn cos(x: Quad) -> Quad {
+        if !x.is_finite() {
+            return Quad::NAN;
+        }
+        match rem_frac_pi_2(x) {
+            (0, r) => cos_taylor(r),
+            (1, r) => -sin_taylor(r),
+            (2, r) => -cos_taylor(r),
+            (_, r) => sin_taylor(r),
+        }
+    }
+
+    pub(super) fn tan(x: Quad) -> Quad {
+        if !x.is_finite() {
+            return Quad::NAN;
+        }
+        let (n, r) = rem_frac_pi_2(x);
+        if n % 2 == 0 {
+            (sin_taylor(r) / cos_taylor(r)).value
+        } else {
+            (-cos_taylor(r) / sin_taylor(r)).value
+        }
+    }
+
+    pub(super) fn atan(x: Quad) -> Quad {
+        if x.is_nan() {
+            return x;
+        }
+        // Reduce to 0 <= a <= 1 with atan(a) = π/2 - atan(1/a).
+        let invert = x.abs() > int(1);
+        let mut a = if invert { (int(1) / x.abs()).value } else { x.abs() };
+        // Reduce further to a <= tan(π/32) by halving the angle three times, using
+        // atan(a) = 2 atan(a / (1 + sqrt(1 + a^2))).
+        for _ in 0..3 {
+            let hyp = super::sqrt((int(1) + (a * a).value).value);
+            a = (a / (int(1) + hyp).value).value;
+        }
+        // atan(a) = a (1 - a^2/3 + a^4/5 - ...)
+        let a2 = (a * a).value;
+        let mut acc = (int(1) / int(2 * ATAN_TERMS + 1)).value;
+        for k in (0..ATAN_TERMS).rev() {
+            acc = ((int(1) / int(2 * k + 1)).value - (a2 * acc).value).value;
+        }
+        let res = (a * acc).value.scalbn(3);
+        let res = if invert { (frac_pi_2() - res).value } else { res };
+        if x.is_negative() { -res } else { res }
+    }
+
+    pub(super) fn atan2(y: Quad, x: Quad) -> Quad {
+        if x.is_nan() || y.is_nan() {
+            return Quad::NAN;
+        }
+        // The angle in the upper half-plane, i.e. for |y|.
+        let res = if y.is_zero() {
+            if x.is_negative() { pi() } else { Quad::ZERO }
+        } else if x.is_infinite() {
+            match (y.is_infinite(), x.is_negative()) {
+                (false, false) => Quad::ZERO,
+                (false, true) => pi(),
+                (true, false) => frac_pi_4(),
+                (true, true) => (pi() - frac_pi_4()).value,
+            }
+        } else if x.is_zero() || y.is_infinite() {
+            frac_pi_2()
+        } else {
+            let a = atan((y.abs() / x.abs()).value);
+            if x.is_negative() { (pi() - a).value } else { a }
+        };
+        if y.is_negative() { -res } else { res }
+    }
+
+    pub(super) fn asin(x: Quad) -> Quad {
+        if x.is_nan() || x.abs() > int(1) {
+            return Quad::NAN;
+        }
+        // asin(x) = atan(x / sqrt(1 - x^2)), where (1 - x)(1 + x) avoids cancellation.
+        let cos = super::sqrt(((int(1) - x).value * (int(1) + x).value).value);
+        atan((x / cos).value)
+    }
+
+    pub(super) fn acos(x: Quad) -> Quad {
+        if x.is_nan() || x.abs() > int(1) {
+            return Quad::NAN;
+        }
+        // acos(x) = 2 atan(sqrt((1 - x) / (1 + x)))
+        atan(super::sqrt(((int(1) - x).value / (int(1) + x).value).value)).scalbn(1)
+    }
+
+    pub(super) fn sinh(x: Quad) -> Quad {
+        if !x.is_finite() {
+            return x;
+        }
+        let a = x.abs();
+        let res = if a > int(HYPERBOLIC_LIMIT) {
+            exp_scaled(a, -1)
+        } else {
+            // sinh(a) = (t + t / (t + 1)) / 2 with t = exp(a) - 1.
+            let t = exp_m1(a);
+            (t + (t / (t + int(1)).value).value).value.scalbn(-1)
+        };
+        if x.is_negative() { -res } else { res }
+    }
+
+    pub(super) fn cosh(x: Quad) -> Quad {
+        if x.is_nan() {
+            return x;
+        }
+        let a = x.abs();
+        if a > int(HYPERBOLIC_LIMIT) {
+            // This also covers infinity.
+            exp_scaled(a, -1)
+        } else {
+            let t = exp(a);
+            (t + (int(1) / t).value).value.scalbn(-1)
+        }
+    }
+
+    pub(super) fn tanh(x: Quad) -> Quad {
+        if x.is_nan() {
+            return x;
+        }
+        let a = x.abs();
+        let res = if a > int(HYPERBOLIC_LIMIT) {
+            int(1)
+        } else {
+            // tanh(a) = t / (t + 2) with t = exp(2a) - 1.
+            let t = exp_m1(a.scalbn(1));
+            (t / (t + int(2)).value).value
+        };
+        if x.is_negative() { -res } else { res }
+    }
+
+    /// Normalizes `x` to `1 <= |x| < 2`, moving its exponent into `e`.
+    fn normalize(x: &mut Quad, e: &mut i128) {
+        let ilogb = x.ilogb();
+        *x = x.scalbn(-ilogb);
+        *e += i128::from(ilogb);
+    }
+
+    /// `x^n` for finite non-zero `x`, by repeated squaring. The exponents are tracked separately,
+    /// so that intermediate results cannot overflow.
+    fn powi_finite(x: Quad, n: i128) -> Quad {
+        let mut base = x;
+        let mut base_e = 0;
+        normalize(&mut base, &mut base_e);
+        let mut acc = int(1);
+        let mut acc_e = 0;
+        let mut k = n.unsigned_abs();
+        while k > 0 {
+            if k & 1 == 1 {
+                acc = (acc * base).value;
+                acc_e += base_e;
+                normalize(&mut acc, &mut acc_e);
+            }
+            k >>= 1;
+            if k > 0 {
+                base = (base * base).value;
+                base_e *= 2;
+                normalize(&mut base, &mut base_e);
+            }
+        }
+        if n < 0 {
+            acc = (int(1) / acc).value;
+            acc_e = -acc_e;
+        }
+        // Exponents beyond this range overflow or underflow anyway.
+        acc.scalbn(acc_e.clamp(-40000, 40000).try_into().unwrap())
+    }
+
+    pub(super) fn powi(x: Quad, n: i32) -> Quad {
+        if n == 0 {
+            int(1)
+        } else if x.is_nan() {
+            x
+        } else if x.is_zero() || x.is_infinite() {
+            // The result is 0 or infinity, negative if x is negative and n is odd.
+            let res = if x.is_zero() == (n > 0) { Quad::ZERO } else { Quad::INFINITY };
+            if x.is_negative() && n % 2 != 0 { -res } else { res }
+        } else {
+            powi_finite(x, n.into())
+        }
+    }
+
+    pub(super) fn powf(x: Quad, y: Quad) -> Quad {
+        if y.is_zero() || x == int(1) {
+            return int(1);
+        }
+        if x.is_nan() || y.is_nan() {
+            return Quad::NAN;
+        }
+        let odd = is_odd_integer(y);
+        if x.is_zero() || x.is_infinite() {
+            // The result is 0 or infinity, negative if x is negative and y an odd integer.
+            let res = if x.is_zero() == y.is_negative() { Quad::INFINITY } else { Quad::ZERO };
+            return if x.is_negative() && odd { -res } else { res };
+        }
+        if y.is_infinite() {
+            return if x.abs() == int(1) {
+                int(1)
+            } else if (x.abs() < int(1)) == y.is_negative() {
+                Quad::INFINITY
+            } else {
+                Quad::ZERO
+            };
+        }
+        if x.is_negative() && !y.is_integer() {
+            return Quad::NAN;
+        }
+        // Use repeated squaring if possible, as it is exact more often.
+        let res = if y.is_integer() && y.abs() < int(1).scalbn(64) {
+            powi_finite(x.abs(), y.to_i128(128).value)
+        } else {
+            exp((y * ln(x.abs())).value)
+        };
+        if x.is_negative() && odd { -res } else { res }
+    }
+
+    pub(super) fn cbrt(x: Quad) -> Quad {
+        if !x.is_finite() || x.is_zero() {
+            return x;
+        }
+        // Write |x| = m * 2^(3k) with 1 <= m < 8.
+        let k = x.ilogb().div_euclid(3);
+        let m = x.abs().scalbn(-3 * k);
+        // Refine the initial approximation with a Newton step.
+        let y = exp((ln(m) / int(3)).value);
+        let y3 = ((y * y).value * y).value;
+        let y = (y - ((y3 - m).value / ((y * y).value * int(3)).value).value).value;
+        let res = y.scalbn(k);
+        if x.is_negative() { -res } else { res }
+    }
+
+    pub(super) fn hypot(x: Quad, y: Quad) -> Quad {
+        if x.is_infinite() || y.is_infinite() {
+            return Quad::INFINITY;
+        }
+        if x.is_nan() || y.is_nan() {
+            return Quad::NAN;
+        }
+        let (x, y) = (x.abs(), y.abs());
+        if x.is_zero() {
+            return y;
+        }
+        if y.is_zero() {
+            return x;
+        }
+        // Scale the arguments to avoid overflow and underflow in the squares.
+        let e = x.ilogb().max(y.ilogb());
+        let (x, y) = (x.scalbn(-e), y.scalbn(-e));
+        super::sqrt(((x * x).value + (y * y).value).value).scalbn(e)
+    }
+
+    /// `ln(Γ(z))` for `z >= GAMMA_SHIFT`, using the Stirling series.
+    fn stirling(z: Quad) -> Quad {
+        let w = (int(1) / (z * z).value).value;
+        let (num, den) = STIRLING[STIRLING.len() - 1];
+        let mut acc = (int(num) / int(den)).value;
+        for &(num, den) in STIRLING[..STIRLING.len() - 1].iter().rev() {
+            acc = ((int(num) / int(den)).value + (w * acc).value).value;
+        }
+        let main = ((z - int(1).scalbn(-1)).value * ln(z)).value;
+        (((main - z).value + ln_2pi_half()).value + (acc / z).value).value
+    }
+
+    /// `ln(Γ(x))` for finite positive `x`.
+    fn ln_gamma_positive(x: Quad) -> Quad {
+        if x == int(1) || x == int(2) {
+            return Quad::ZERO;
+        }
+        // ln(Γ(x)) = ln(Γ(x + n)) - ln(x (x + 1) ... (x + n - 1))
+        let mut z = x;
+        let mut prod = int(1);
+        while z < int(GAMMA_SHIFT) {
+            prod = (prod * z).value;
+            z = (z + int(1)).value;
+        }
+        (stirling(z) - ln(prod)).value
+    }
+
+    /// `sin(πx)` for finite `x`, which is exact at the integers.
+    fn sin_pi(x: Quad) -> Quad {
+        // Write x = n + r with an integer n and |r| <= 1/2; the subtraction is exact.
+        let n = x.round_to_integral(Round::NearestTiesToEven).value;
+        let r = (x - n).value;
+        let res = if r.abs() > int(1).scalbn(-2) {
+            cos_taylor(((int(1).scalbn(-1) - r.abs()).value * pi()).value)
+        } else {
+            sin_taylor((r.abs() * pi()).value)
+        };
+        // sin(π(n + r)) = (-1)^n sin(πr)
+        let res = if r.is_negative() { -res } else { res };
+        if is_odd_integer(n) { -res } else { res }
+    }
+
+    pub(super) fn gamma(x: Quad) -> Quad {
+        if x.is_nan() {
+            x
+        } else if x.is_zero() {
+            // The sign of the pole depends on the sign of the zero.
+            if x.is_negative() { -Quad::INFINITY } else { Quad::INFINITY }
+        } else if x.is_infinite() {
+            if x.is_negative() { Quad::NAN } else { x }
+        } else if x.is_negative() {
+            if x.is_integer() {
+                return Quad::NAN;
+            }
+            // The reflection formula: Γ(x) = π / (sin(πx) Γ(1 - x))
+            let s = sin_pi(x);
+            (pi() / (s * gamma((int(1) - x).value)).value).value
+        } else if x.is_integer() && x <= int(GAMMA_SHIFT) {
+            // Compute the factorial directly, which is exact for small arguments.
+            let mut res = int(1);
+            let mut i = int(2);
+            while i < x {
+                res = (res * i).value;
+                i = (i + int(1)).value;
+            }
+            res
+        } else if x > int(GAMMA_LIMIT) {
+            Quad::INFINITY
+        } else {
+            exp(ln_gamma_positive(x))
+        }
+    }
+
+    pub(super) fn ln_gamma(x: Quad) -> (Quad, i32) {
+        if x.is_nan() {
+            (x, 1)
+        } else if x.is_zero() {
+            (Quad::INFINITY, if x.is_negative() { -1 } else { 1 })
+        } else if x.is_infinite() || (x.is_negative() && x.is_integer()) {
+            (Quad::INFINITY, 1)
+        } else if x.is_negative() {
+            // The reflection formula: |Γ(x)| = π / (|sin(πx)| Γ(1 - x))
+            let s = sin_pi(x);
+            let res = (ln((pi() / s.abs()).value) - ln_gamma_positive((int(1) - x).value)).value;
+            (res, if s.is_negative() { -1 } else { 1 })
+        } else {
+            (ln_gamma_positive(x), 1)
+        }
+    }
+
+    /// The binary expansion of 2/π, enough for the largest quad exponent.
+    #[rustfmt::skip]
+    const TWO_OVER_PI: [u64; 260] = [
+    0xa2f9836e4e441529, 0xfc2757d1f534ddc0, 0xdb6295993c439041, 0xfe5163abdebbc561,
+    0xb7246e3a424dd2e0, 0x06492eea09d1921c, 0xfe1deb1cb129a73e, 0xe88235f52ebb4484,
+    0xe99c7026b45f7e41, 0x3991d639835339f4, 0x9c845f8bbdf9283b, 0x1ff897ffde05980f,
+    0xef2f118b5a0a6d1f, 0x6d367ecf27cb09b7, 0x4f463f669e5fea2d, 0x7527bac7ebe5f17b,
+    0x3d0739f78a5292ea, 0x6bfb5fb11f8d5d08, 0x56033046fc7b6bab, 0xf0cfbc209af4361d,
+    0xa9e391615ee61b08, 0x6599855f14a06840, 0x8dffd8804d732731, 0x06061556ca73a8c9,
+    0x60e27bc08c6b47c4, 0x19c367cddce8092a, 0x8359c4768b961ca6, 0xddaf44d15719053e,
+    0xa5ff07053f7e33e8, 0x32c2de4f98327dbb, 0xc33d26ef6b1e5ef8, 0x9f3a1f35caf27f1d,
+    0x87f121907c7c246a, 0xfa6ed5772d30433b, 0x15c614b59d19c3c2, 0xc4ad414d2c5d000c,
+    0x467d862d71e39ac6, 0x9b0062337cd2b497, 0xa7b4d55537f63ed7, 0x1810a3fc764d2a9d,
+    0x64abd770f87c6357, 0xb07ae715175649c0, 0xd9d63b3884a7cb23, 0x24778ad623545ab9,
+    0x1f001b0af1dfce19, 0xff319f6a1e666157, 0x9947fbacd87f7eb7, 0x652289e83260bfe6,
+    0xcdc4ef09366cd43f, 0x5dd7de16de3b5892, 0x9bde2822d2e88628, 0x4d58e232cac616e3,
+    0x08cb7de050c017a7, 0x1df35be01834132e, 0x6212830148835b8e, 0xf57fb0adf2e91e43,
+    0x4a48d36710d8ddaa, 0x425faece616aa428, 0x0ab499d3f2a6067f, 0x775c83c2a3883c61,
+    0x78738a5a8cafbdd7, 0x6f63a62dcbbff4ef, 0x818d67c12645ca55, 0x36d9cad2a8288d61,
+    0xc277c9121426049b, 0x4612c459c444c5c8, 0x91b24df31700ad43, 0xd4e5492910d5fdfc,
+    0xbe00cc941eeece70, 0xf53e1380f1ecc3e7, 0xb328f8c79405933e, 0x71c1b3092ef3450b,
+    0x9c12887b20ab9fb5, 0x2ec292472f327b6d, 0x550c90a7721fe76b, 0x96cb314a1679e279,
+    0x4189dff49794e884, 0xe6e29731996bed88, 0x365f5f0efdbbb49a, 0x486ca46742727132,
+    0x5d8db8159f09e5bc, 0x25318d3974f71c05, 0x30010c0d68084b58, 0xee2c90aa4702e774,
+    0x24d6bda67df77248, 0x6eef169fa6948ef6, 0x91b45153d1f20acf, 0x3398207e4bf56863,
+    0xb25f3edd035d407f, 0x8985295255c06437, 0x10d86d324832754c, 0x5bd4714e6e5445c1,
+    0x090b69f52ad56614, 0x9d072750045ddb3b, 0xb4c576ea17f9877d, 0x6b49ba271d296996,
+    0xacccc65414ad6ae2, 0x9089d98850722cbe, 0xa4049407777030f3, 0x27fc00a871ea49c2,
+    0x663de06483dd9797, 0x3fa3fd94438c860d, 0xde41319d39928c70, 0xdde7b7173bdf082b,
+    0x3715a0805c93805a, 0x921110d8e80faf80, 0x6c4bffdb0f903876, 0x185915a562bbcb61,
+    0xb989c7bd401004f2, 0xd2277549f6b6ebbb, 0x22dbaa140a2f2689, 0x768364333b091a94,
+    0x0eaa3a51c2a31dae, 0xedaf12265c4dc26d, 0x9c7a2d9756c0833f, 0x03f6f0098c402b99,
+    0x316d07b43915200c, 0x5bc3d8c492f54bad, 0xc6a5ca4ecd37a736, 0xa9e69492ab6842dd,
+    0xde6319ef8c76528b, 0x6837dbfcaba1ae31, 0x15dfa1ae00dafb0c, 0x664d64b705ed3065,
+    0x29bf56573aff47b9, 0xf96af3be75df9328, 0x3080abf68c6615cb, 0x040622fa1de4d9a4,
+    0xb33d8f1b5709cd36, 0xe9424ea4be13b523, 0x331aaaf0a8654fa5, 0xc1d20f3f0bcd785b,
+    0x76f923048b7b7217, 0x8953a6c6e26e6f00, 0xebef584a9bb7dac4, 0xba66aacfcf761d02,
+    0xd12df1b1c1998c77, 0xadc3da4886a05df7, 0xf480c62ff0ac9aec, 0xddbc5c3f6dded01f,
+    0xc790b6db2a3a25a3, 0x9aaf009353ad0457, 0xb6b42d297e804ba7, 0x07da0eaa76a1597b,
+    0x2a12162db7dcfde5, 0xfafedb89fdbe896c, 0x76e4fca90670803e, 0x156e85ff87fd073e,
+    0x2833676186182aea, 0xbd4dafe7b36e6d8f, 0x3967955bbf3148d7, 0x8416df30432dc735,
+    0x6125ce70c9b8cb30, 0xfd6cbfa200a4e46c, 0x05a0dd5a476f21d2, 0x1262845cb9496170,
+    0xe0566b0152993755, 0x50b7d51ec4f1335f, 0x6e13e4305da92e85, 0xc3b21d3632a1a4b7,
+    0x08d4b1ea21f716e4, 0x698f77ff2780030c, 0x2d408da0cd4f99a5, 0x20d3a2b30a5d2f42,
+    0xf9b4cbda11d0be7d, 0xc1db9bbd17ab81a2, 0xca5c6a0817552e55, 0x0027f0147f8607e1,
+    0x640b148d4196debe, 0x872afddab6256b34, 0x897bfef3059ebfb9, 0x4f6a68a82a4a5ac4,
+    0x4fbcf82d985ad795, 0xc7f48d4d0da63a20, 0x5f57a4b13f149538, 0x800120cc86dd71b6,
+    0xdec9f560bf11654d, 0x6b0701acb08cd0c0, 0xb24855510efb1ec3, 0x72953b06a33540c0,
+    0x7bdc06cc45e0fa29, 0x4ec8cad641f3e8de, 0x647cd8649b31bed9, 0xc397a4d45877c5e3,
+    0x6913daf03c3aba46, 0x18465f7555f5bdd2, 0xc6926e5d2eaced44, 0x0e423e1c87c461e9,
+    0xfd29f3d6e7ca7c22, 0x35916fc5e0088dd7, 0xffe26a6ec6fdb0c1, 0x0893745d7cb2ad6b,
+    0x9d6ecd7b723e6a11, 0xc6a9cff7df7329ba, 0xc9b55100b70db2e2, 0x24ba74607de58ad8,
+    0x742c150d0c188194, 0x667e162901767a9f, 0xbefdfdef4556367e, 0xd913d9ecb9ba8bfc,
+    0x97c427a831c36ef1, 0x36c59456a8d8b5a8, 0xb40ecccf2d891234, 0x576f89562ce3ce99,
+    0xb920d6aa5e6b9c2a, 0x3ecc5f114a0bfdfb, 0xf4e16d3b8e2c86e2, 0x84d4e9a9b4fcd1ee,
+    0xefc9352e61392f44, 0x2138c8d91b0afc81, 0x6a4afbd81c2f84b4, 0x538c994ecc2254dc,
+    0x552ad6c6c096190b, 0xb8701a649569605a, 0x26ee523f0f117f11, 0xb5f4f5cbfc2dbc34,
+    0xeebc34cc5de8605e, 0xdd9b8e67ef3392b8, 0x17c99b5861bc57e1, 0xc68351103ed84871,
+    0xdddd1c2da118af46, 0x2c21d7f359987ad9, 0xc0549efa864ffc06, 0x56ae79e536228922,
+    0xad38dc9367aae855, 0x3826829be7caa40d, 0x51b133990ed7a948, 0x0569f0b265a7887f,
+    0x974c8836d1f9b392, 0x214a827b21cf98dc, 0x9f405547dc3a74e1, 0x42eb67df9dfe5fd4,
+    0x5ea4677b7aacbaa2, 0xf65523882b55ba41, 0x086e59862a218347, 0x39e6e389d49ee540,
+    0xfb49e956ffca0f1c, 0x8a59c52bfa94c5c1, 0xd3cfc50fae5adb86, 0xc5476243853b8621,
+    0x94792c8761107b4c, 0x2a1a2c8012bf4390, 0x2688893c78e4c4a8, 0x7bdbe5c23ac4eaf4,
+    0x268a67f7bf920d2b, 0xa365b1933d0b7cbd, 0xdc51a463dd27dde1, 0x6919949a9529a828,
+    0xce68b4ed09209f44, 0xca984e638270237c, 0x7e32b90f8ef5a7e7, 0x561408f1212a9db5,
+    0x4d7e6f5119a5abf9, 0xb5d6df8261dd9602, 0x36169f3ac4a1a283, 0x6ded727a8d39a9b8,
+    0x825c326b5b2746ed, 0x34007700d255f4fc, 0x4d59018071e0e13f, 0x89b295f364a8f1ae,
+    ];
+}
+
 #[cfg(test)]
 mod tests {
-    use rustc_apfloat::ieee::{DoubleS, HalfS, IeeeFloat, QuadS, SingleS};
+    use rustc_apfloat::Float as _;
+    use rustc_apfloat::ieee::{DoubleS, HalfS, IeeeFloat, QuadS, Semantics, SingleS};
+
+    use super::*;
+
+    fn f<S: Semantics>(x: &str) -> IeeeFloat<S> {
+        x.parse().unwrap()
+    }
+
+    /// Checks that `result` is at most `ulps` units in the last place away from `expected`.
+    #[track_caller]
+    fn check<S: Semantics>(result: IeeeFloat<S>, expected: &str, ulps: u128) {
+        let expected = f::<S>(expected);
+        let diff = result.to_bits().abs_diff(expected.to_bits());
+        assert!(diff <= ulps, "got {result}, expected {expected}");
+    }
 
-    use super::sqrt;
+    #[track_caller]
+    fn check_ln_gamma<S: Semantics>(x: IeeeFloat<S>, expected: &str, sign: i32, ulps: u128) {
+        let (result, result_sign) = ln_gamma(x);
+        assert_eq!(result_sign, sign);
+        check(result, expected, ulps);
+    }
 
     #[test]
     fn test_sqrt() {
@@ -161,4 +1152,293 @@ mod tests {
         test::<SingleS>("3.4028235e38", "1.8446743e19");
         test::<DoubleS>("1.7976931348623157e308", "1.3407807929942596e154");
     }
+
+    #[test]
+    fn test_exp_log() {
+        const F128_ULPS: u128 = 4;
+        check(exp(f::<HalfS>("0.5")), "1.6484", 0);
+        check(exp(f::<SingleS>("0.5")), "1.64872122", 0);
+        check(exp(f::<DoubleS>("0.5")), "1.6487212707001282", 0);
+        check(exp(f::<QuadS>("0.5")), "1.64872127070012814684865078781416358", F128_ULPS);
+        check(exp(f::<HalfS>("-3.25")), "0.038788", 0);
+        check(exp(f::<SingleS>("-3.25")), "0.0387742072", 0);
+        check(exp(f::<DoubleS>("-3.25")), "0.038774207831722009", 0);
+        check(exp(f::<QuadS>("-3.25")), "0.0387742078317220098868998352675961411", F128_ULPS);
+        check(exp(f::<HalfS>("10")), "22032.0", 0);
+        check(exp(f::<SingleS>("10")), "22026.4648", 0);
+        check(exp(f::<DoubleS>("10")), "22026.465794806718", 0);
+        check(exp(f::<QuadS>("10")), "22026.465794806716516957900645284245", F128_ULPS);
+        check(exp(f::<DoubleS>("700.5")), "1.6721859620674984e304", 0);
+        check(exp(f::<QuadS>("700.5")), "1.67218596206749855724103607930212025e304", F128_ULPS);
+        check(exp2(f::<HalfS>("0.3")), "1.2314", 0);
+        check(exp2(f::<SingleS>("0.3")), "1.23114443", 0);
+        check(exp2(f::<DoubleS>("0.3")), "1.2311444133449163", 0);
+        check(exp2(f::<QuadS>("0.3")), "1.23114441334491628449939306916774302", F128_ULPS);
+        check(exp2(f::<HalfS>("-7.5")), "0.0055237", 0);
+        check(exp2(f::<SingleS>("-7.5")), "0.00552427163", 0);
+        check(exp2(f::<DoubleS>("-7.5")), "0.0055242717280199029", 0);
+        check(exp2(f::<QuadS>("-7.5")), "0.00552427172801990253438159657894413275", F128_ULPS);
+        check(exp_m1(f::<HalfS>("0.001")), "0.0010014", 0);
+        check(exp_m1(f::<SingleS>("0.001")), "0.00100050017", 0);
+        check(exp_m1(f::<DoubleS>("0.001")), "0.0010005001667083417", 0);
+        check(exp_m1(f::<QuadS>("0.001")), "0.00100050016670834166805575399305831153", F128_ULPS);
+        check(exp_m1(f::<HalfS>("-0.75")), "-0.52783", 0);
+        check(exp_m1(f::<SingleS>("-0.75")), "-0.527633429", 0);
+        check(exp_m1(f::<DoubleS>("-0.75")), "-0.52763344725898531", 0);
+        check(exp_m1(f::<QuadS>("-0.75")), "-0.527633447258985292861953449056732126", F128_ULPS);
+        check(ln(f::<HalfS>("0.1")), "-2.3027", 0);
+        check(ln(f::<SingleS>("0.1")), "-2.30258512", 0);
+        check(ln(f::<DoubleS>("0.1")), "-2.3025850929940455", 0);
+        check(ln(f::<QuadS>("0.1")), "-2.30258509299404568401799145468436418", F128_ULPS);
+        check(ln(f::<HalfS>("10")), "2.3027", 0);
+        check(ln(f::<SingleS>("10")), "2.30258512", 0);
+        check(ln(f::<DoubleS>("10")), "2.3025850929940459", 0);
+        check(ln(f::<QuadS>("10")), "2.30258509299404568401799145468436418", F128_ULPS);
+        check(ln(f::<SingleS>("1e30")), "69.0775528", 0);
+        check(ln(f::<DoubleS>("1e30")), "69.077552789821368", 0);
+        check(ln(f::<QuadS>("1e30")), "69.0775527898213705205397436405309299", F128_ULPS);
+        check(log2(f::<HalfS>("3")), "1.585", 0);
+        check(log2(f::<SingleS>("3")), "1.58496249", 0);
+        check(log2(f::<DoubleS>("3")), "1.5849625007211561", 0);
+        check(log2(f::<QuadS>("3")), "1.58496250072115618145373894394781657", F128_ULPS);
+        check(log10(f::<HalfS>("5")), "0.69873", 0);
+        check(log10(f::<SingleS>("5")), "0.69897002", 0);
+        check(log10(f::<DoubleS>("5")), "0.69897000433601886", 0);
+        check(log10(f::<QuadS>("5")), "0.698970004336018804786261105275506932", F128_ULPS);
+        check(ln_1p(f::<HalfS>("0.0001")), "0.00010002", 0);
+        check(ln_1p(f::<SingleS>("0.0001")), "9.99949989e-5", 0);
+        check(ln_1p(f::<DoubleS>("0.0001")), "9.9995000333308339e-5", 0);
+        check(ln_1p(f::<QuadS>("0.0001")), "9.99950003333083353331666809511310574e-5", F128_ULPS);
+        check(ln_1p(f::<HalfS>("2")), "1.0986", 0);
+        check(ln_1p(f::<SingleS>("2")), "1.09861231", 0);
+        check(ln_1p(f::<DoubleS>("2")), "1.0986122886681098", 0);
+        check(ln_1p(f::<QuadS>("2")), "1.09861228866810969139524523692252561", F128_ULPS);
+    }
+
+    #[test]
+    fn test_trig() {
+        const F128_ULPS: u128 = 4;
+        check(sin(f::<HalfS>("0.5")), "0.47949", 0);
+        check(sin(f::<SingleS>("0.5")), "0.47942555", 0);
+        check(sin(f::<DoubleS>("0.5")), "0.47942553860420301", 0);
+        check(sin(f::<QuadS>("0.5")), "0.479425538604203000273287935215571402", F128_ULPS);
+        check(sin(f::<HalfS>("2")), "0.90918", 0);
+        check(sin(f::<SingleS>("2")), "0.909297407", 0);
+        check(sin(f::<DoubleS>("2")), "0.90929742682568171", 0);
+        check(sin(f::<QuadS>("2")), "0.909297426825681695396019865911744868", F128_ULPS);
+        check(sin(f::<HalfS>("100")), "-0.50635", 0);
+        check(sin(f::<SingleS>("100")), "-0.506365657", 0);
+        check(sin(f::<DoubleS>("100")), "-0.50636564110975879", 0);
+        check(sin(f::<QuadS>("100")), "-0.506365641109758793656557610459785419", F128_ULPS);
+        check(sin(f::<DoubleS>("1e22")), "-0.85220084976718879", 0);
+        check(sin(f::<QuadS>("1e22")), "-0.852200849767188801772705893753029394", F128_ULPS);
+        check(sin(f::<QuadS>("1e4000")), "0.356948813963500428491908884841406747", F128_ULPS);
+        check(cos(f::<HalfS>("0.5")), "0.87744", 0);
+        check(cos(f::<SingleS>("0.5")), "0.87758255", 0);
+        check(cos(f::<DoubleS>("0.5")), "0.87758256189037276", 0);
+        check(cos(f::<QuadS>("0.5")), "0.877582561890372716116281582603829681", F128_ULPS);
+        check(cos(f::<HalfS>("2")), "-0.41626", 0);
+        check(cos(f::<SingleS>("2")), "-0.416146845", 0);
+        check(cos(f::<DoubleS>("2")), "-0.41614683654714241", 0);
+        check(cos(f::<QuadS>("2")), "-0.416146836547142386997568229500762197", F128_ULPS);
+        check(cos(f::<HalfS>("100")), "0.8623", 0);
+        check(cos(f::<SingleS>("100")), "0.862318873", 0);
+        check(cos(f::<DoubleS>("100")), "0.86231887228768389", 0);
+        check(cos(f::<QuadS>("100")), "0.862318872287683934101938513950842527", F128_ULPS);
+        check(cos(f::<DoubleS>("1e22")), "0.52321478539513899", 0);
+        check(cos(f::<QuadS>("1e22")), "0.5232147853951389454975944733847095", F128_ULPS);
+        check(tan(f::<HalfS>("0.5")), "0.54639", 0);
+        check(tan(f::<SingleS>("0.5")), "0.546302497", 0);
+        check(tan(f::<DoubleS>("0.5")), "0.54630248984379048", 0);
+        check(tan(f::<QuadS>("0.5")), "0.546302489843790513255179465780285354", F128_ULPS);
+        check(tan(f::<HalfS>("2")), "-2.1855", 0);
+        check(tan(f::<SingleS>("2")), "-2.18503976", 0);
+        check(tan(f::<DoubleS>("2")), "-2.1850398632615189", 0);
+        check(tan(f::<QuadS>("2")), "-2.18503986326151899164330610231368255", F128_ULPS);
+        check(tan(f::<HalfS>("100")), "-0.5874", 0);
+        check(tan(f::<SingleS>("100")), "-0.587213933", 0);
+        check(tan(f::<DoubleS>("100")), "-0.58721391515692911", 0);
+        check(tan(f::<QuadS>("100")), "-0.587213915156929076677809635644587924", F128_ULPS);
+        check(asin(f::<HalfS>("0.3")), "0.30469", 0);
+        check(asin(f::<SingleS>("0.3")), "0.304692656", 0);
+        check(asin(f::<DoubleS>("0.3")), "0.30469265401539752", 0);
+        check(asin(f::<QuadS>("0.3")), "0.304692654015397507972002961227529153", F128_ULPS);
+        check(asin(f::<HalfS>("-0.9")), "-1.1191", 0);
+        check(asin(f::<SingleS>("-0.9")), "-1.11976945", 0);
+        check(asin(f::<DoubleS>("-0.9")), "-1.1197695149986342", 0);
+        check(asin(f::<QuadS>("-0.9")), "-1.11976951499863418668667705584539975", F128_ULPS);
+        check(acos(f::<HalfS>("0.3")), "1.2656", 0);
+        check(acos(f::<SingleS>("0.3")), "1.26610363", 0);
+        check(acos(f::<DoubleS>("0.3")), "1.2661036727794992", 0);
+        check(acos(f::<QuadS>("0.3")), "1.26610367277949911125931873041222225", F128_ULPS);
+        check(acos(f::<HalfS>("-0.9")), "2.6895", 0);
+        check(acos(f::<SingleS>("-0.9")), "2.69056582", 0);
+        check(acos(f::<DoubleS>("-0.9")), "2.6905658417935308", 0);
+        check(acos(f::<QuadS>("-0.9")), "2.69056584179353080591799874748515096", F128_ULPS);
+        check(atan(f::<HalfS>("0.3")), "0.2915", 0);
+        check(atan(f::<SingleS>("0.3")), "0.291456819", 0);
+        check(atan(f::<DoubleS>("0.3")), "0.2914567944778671", 0);
+        check(atan(f::<QuadS>("0.3")), "0.291456794477867091995604621432891175", F128_ULPS);
+        check(atan(f::<HalfS>("-5")), "-1.373", 0);
+        check(atan(f::<SingleS>("-5")), "-1.37340081", 0);
+        check(atan(f::<DoubleS>("-5")), "-1.3734007669450159", 0);
+        check(atan(f::<QuadS>("-5")), "-1.37340076694501586086127192644496106", F128_ULPS);
+        check(atan2(f::<HalfS>("1"), f::<HalfS>("-2")), "2.6777", 0);
+        check(atan2(f::<SingleS>("1"), f::<SingleS>("-2")), "2.67794514", 0);
+        check(atan2(f::<DoubleS>("1"), f::<DoubleS>("-2")), "2.677945044588987", 0);
+        check(
+            atan2(f::<QuadS>("1"), f::<QuadS>("-2")),
+            "2.67794504458898712224838715181828864",
+            F128_ULPS,
+        );
+    }
+
+    #[test]
+    fn test_hyperbolic() {
+        const F128_ULPS: u128 = 4;
+        check(sinh(f::<HalfS>("0.25")), "0.25269", 0);
+        check(sinh(f::<SingleS>("0.25")), "0.252612323", 0);
+        check(sinh(f::<DoubleS>("0.25")), "0.25261231680816831", 0);
+        check(sinh(f::<QuadS>("0.25")), "0.252612316808168307914125150542057918", F128_ULPS);
+        check(sinh(f::<HalfS>("3")), "10.016", 0);
+        check(sinh(f::<SingleS>("3")), "10.0178747", 0);
+        check(sinh(f::<DoubleS>("3")), "10.017874927409903", 0);
+        check(sinh(f::<QuadS>("3")), "10.0178749274099018989745936194658287", F128_ULPS);
+        check(cosh(f::<HalfS>("0.25")), "1.0313", 0);
+        check(cosh(f::<SingleS>("0.25")), "1.03141308", 0);
+        check(cosh(f::<DoubleS>("0.25")), "1.0314130998795732", 0);
+        check(cosh(f::<QuadS>("0.25")), "1.03141309987957317615929541752037862", F128_ULPS);
+        check(cosh(f::<HalfS>("3")), "10.07", 0);
+        check(cosh(f::<SingleS>("3")), "10.0676622", 0);
+        check(cosh(f::<DoubleS>("3")), "10.067661995777765", 0);
+        check(cosh(f::<QuadS>("3")), "10.0676619957777658419539360351158903", F128_ULPS);
+        check(tanh(f::<HalfS>("0.25")), "0.24487", 0);
+        check(tanh(f::<SingleS>("0.25")), "0.244918659", 0);
+        check(tanh(f::<DoubleS>("0.25")), "0.24491866240370913", 0);
+        check(tanh(f::<QuadS>("0.25")), "0.244918662403709129277801131491016969", F128_ULPS);
+        check(tanh(f::<HalfS>("3")), "0.99512", 0);
+        check(tanh(f::<SingleS>("3")), "0.995054781", 0);
+        check(tanh(f::<DoubleS>("3")), "0.99505475368673046", 0);
+        check(tanh(f::<QuadS>("3")), "0.995054753686730451331880185255488485", F128_ULPS);
+    }
+
+    #[test]
+    fn test_pow() {
+        const F128_ULPS: u128 = 16;
+        check(powf(f::<HalfS>("2.5"), f::<HalfS>("3.5")), "24.703", 0);
+        check(powf(f::<SingleS>("2.5"), f::<SingleS>("3.5")), "24.7052937", 0);
+        check(powf(f::<DoubleS>("2.5"), f::<DoubleS>("3.5")), "24.705294220065465", 0);
+        check(
+            powf(f::<QuadS>("2.5"), f::<QuadS>("3.5")),
+            "24.7052942200654635312413558158806125",
+            F128_ULPS,
+        );
+        check(powf(f::<HalfS>("0.7"), f::<HalfS>("-12.25")), "78.688", 0);
+        check(powf(f::<SingleS>("0.7"), f::<SingleS>("-12.25")), "78.985817", 0);
+        check(powf(f::<DoubleS>("0.7"), f::<DoubleS>("-12.25")), "78.98579792854153", 0);
+        check(
+            powf(f::<QuadS>("0.7"), f::<QuadS>("-12.25")),
+            "78.9857979285414750497671064409782545",
+            F128_ULPS,
+        );
+        check(powi(f::<HalfS>("1.1"), 7), "1.9443", 0);
+        check(powi(f::<SingleS>("1.1"), 7), "1.94871736", 0);
+        check(powi(f::<DoubleS>("1.1"), 7), "1.9487171000000012", 0);
+        check(powi(f::<QuadS>("1.1"), 7), "1.94871710000000000000000000000000101", F128_ULPS);
+        check(powi(f::<HalfS>("3"), -3), "0.037048", 0);
+        check(powi(f::<SingleS>("3"), -3), "0.0370370373", 0);
+        check(powi(f::<DoubleS>("3"), -3), "0.037037037037037035", 0);
+        check(powi(f::<QuadS>("3"), -3), "0.0370370370370370370370370370370370373", F128_ULPS);
+        check(cbrt(f::<HalfS>("10")), "2.1543", 0);
+        check(cbrt(f::<SingleS>("10")), "2.15443468", 0);
+        check(cbrt(f::<DoubleS>("10")), "2.1544346900318838", 0);
+        check(cbrt(f::<QuadS>("10")), "2.15443469003188372175929356651935046", F128_ULPS);
+        check(cbrt(f::<HalfS>("-0.002")), "-0.12598", 0);
+        check(cbrt(f::<SingleS>("-0.002")), "-0.125992104", 0);
+        check(cbrt(f::<DoubleS>("-0.002")), "-0.12599210498948732", 0);
+        check(cbrt(f::<QuadS>("-0.002")), "-0.125992104989487316476721060727822844", F128_ULPS);
+        check(hypot(f::<HalfS>("1.5"), f::<HalfS>("2.25")), "2.7051", 0);
+        check(hypot(f::<SingleS>("1.5"), f::<SingleS>("2.25")), "2.70416355", 0);
+        check(hypot(f::<DoubleS>("1.5"), f::<DoubleS>("2.25")), "2.7041634565979922", 0);
+        check(
+            hypot(f::<QuadS>("1.5"), f::<QuadS>("2.25")),
+            "2.70416345659799196983941595060287181",
+            F128_ULPS,
+        );
+    }
+
+    #[test]
+    fn test_gamma() {
+        const F128_ULPS: u128 = 256;
+        check(gamma(f::<HalfS>("4.5")), "11.633", 0);
+        check(gamma(f::<SingleS>("4.5")), "11.6317282", 0);
+        check(gamma(f::<DoubleS>("4.5")), "11.631728396567448", 0);
+        check(gamma(f::<QuadS>("4.5")), "11.6317283965674489291442241094262654", F128_ULPS);
+        check(gamma(f::<HalfS>("-2.5")), "-0.94531", 0);
+        check(gamma(f::<SingleS>("-2.5")), "-0.945308745", 0);
+        check(gamma(f::<DoubleS>("-2.5")), "-0.9453087204829419", 0);
+        check(gamma(f::<QuadS>("-2.5")), "-0.945308720482941881225689324448610793", F128_ULPS);
+        check(gamma(f::<HalfS>("0.1")), "9.5156", 0);
+        check(gamma(f::<SingleS>("0.1")), "9.51350784", 0);
+        check(gamma(f::<DoubleS>("0.1")), "9.5135076986687306", 0);
+        check(gamma(f::<QuadS>("0.1")), "9.51350769866873183629248717726540114", F128_ULPS);
+        check_ln_gamma(f::<HalfS>("10.5"), "13.938", 1, 0);
+        check_ln_gamma(f::<SingleS>("10.5"), "13.9406252", 1, 0);
+        check_ln_gamma(f::<DoubleS>("10.5"), "13.940625219403763", 1, 0);
+        check_ln_gamma(f::<QuadS>("10.5"), "13.94062521940376363316123788797185", 1, F128_ULPS);
+        check_ln_gamma(f::<HalfS>("-0.5"), "1.2656", -1, 0);
+        check_ln_gamma(f::<SingleS>("-0.5"), "1.26551211", -1, 0);
+        check_ln_gamma(f::<DoubleS>("-0.5"), "1.2655121234846454", -1, 0);
+        check_ln_gamma(f::<QuadS>("-0.5"), "1.265512123484645396488945797134706", -1, F128_ULPS);
+    }
+
+    #[test]
+    fn test_special_values() {
+        let d = f::<DoubleS>;
+        let zero = IeeeFloat::<DoubleS>::ZERO;
+        let inf = IeeeFloat::<DoubleS>::INFINITY;
+        let nan = IeeeFloat::<DoubleS>::NAN;
+        let pi = d("3.141592653589793");
+
+        assert!(sin(-zero).is_neg_zero());
+        assert!(atan(-zero).is_neg_zero());
+        assert!(exp_m1(-zero).is_neg_zero());
+        assert!(ln_1p(-zero).is_neg_zero());
+        assert!(cbrt(-zero).is_neg_zero());
+        assert!(sin(inf).is_nan());
+        assert!(ln(d("-1")).is_nan());
+        assert_eq!(ln(zero), -inf);
+        assert_eq!(exp(-inf), zero);
+        assert_eq!(exp_m1(-inf), d("-1"));
+        assert_eq!(tanh(inf), d("1"));
+        assert_eq!(atan2(zero, -zero), pi);
+        assert_eq!(atan2(d("-1"), -inf), -pi);
+        assert_eq!(hypot(inf, nan), inf);
+        assert_eq!(fdim(d("1"), d("3")), zero);
+
+        assert_eq!(powf(nan, zero), d("1"));
+        assert_eq!(powf(d("1"), nan), d("1"));
+        assert_eq!(powf(d("-1"), inf), d("1"));
+        assert_eq!(powf(d("0.5"), -inf), inf);
+        assert_eq!(powf(-zero, d("-3")), -inf);
+        assert!(powf(d("-8"), d("0.5")).is_nan());
+        assert_eq!(powi(nan, 0), d("1"));
+        assert_eq!(powi(d("-2"), -3), d("-0.125"));
+
+        assert_eq!(gamma(-zero), -inf);
+        assert!(gamma(d("-3")).is_nan());
+        assert_eq!(ln_gamma(-zero), (inf, -1));
+        assert_eq!(ln_gamma(d("-3")), (inf, 1));
+        assert_eq!(ln_gamma(d("1")), (zero, 1));
+        assert_eq!(ln_gamma(d("2")), (zero, 1));
+
+        // Results that are exact.
+        assert_eq!(exp2(d("-1074")), d("4.9406564584124654e-324"));
+        assert_eq!(log2(d("1024")), d("10"));
+        assert_eq!(log10(d("1000")), d("3"));
+        assert_eq!(powf(d("2"), d("10")), d("1024"));
+        assert_eq!(cbrt(d("27")), d("3"));
+        assert_eq!(hypot(d("3"), d("4")), d("5"));
+        assert_eq!(gamma(d("5")), d("24"));
+    }
 }
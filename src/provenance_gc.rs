@@ -197,7 +197,8 @@ impl LiveAllocs<'_, '_> {
 
 fn remove_unreachable_tags<'tcx>(ecx: &mut MiriInterpCx<'tcx>, tags: FxHashSet<BorTag>) {
     // Avoid iterating all allocations if there's no borrow tracker anyway.
-    if ecx.machine.borrow_tracker.is_some() {
+    if let Some(borrow_tracker) = &ecx.machine.borrow_tracker {
+        borrow_tracker.borrow_mut().remove_unreachable_aliases(&tags);
         ecx.memory.alloc_map().iter(|it| {
             for (_id, (_kind, alloc)) in it {
                 alloc.extra.borrow_tracker.as_ref().unwrap().remove_unreachable_tags(&tags);
@@ -238,5 +239,6 @@ pub trait EvalContextExt<'tcx>: MiriInterpCxExt<'tcx> {
         // Based on this, clean up the interpreter state.
         remove_unreachable_tags(this, tags);
         remove_unreachable_allocs(this, alloc_ids);
+        uninit_origin::remove_unreachable_origins(this);
     }
 }
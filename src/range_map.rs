@@ -4,6 +4,16 @@
 //! necessary (e.g., when [0,5) is first associated with X, and then [1,2) is mutated).
 //! Users must not depend on whether a range is coalesced or not, even though this is observable
 //! via the iteration APIs.
+//!
+//! Byte-wise access patterns (e.g. a loop writing one byte at a time) would fragment the map
+//! into one element per byte if we only ever split. Therefore, `iter_mut` merges the elements it
+//! handed out last time with their neighbours once they have been mutated (since merging has to
+//! wait until the caller is done with them), and occasionally compacts the entire map. This only
+//! helps if neighbouring bytes end up with equal data, as they do under sequential accesses by a
+//! single thread. Strided or interleaved access patterns still leave one element per byte: there
+//! is no page-level summary of ranges whose data differs. To keep such patterns from paying for a
+//! merge attempt on every access, a map that keeps failing to merge backs off exponentially and
+//! leaves merging to the occasional compaction for a while.
 
 use std::ops;
 
@@ -19,8 +29,29 @@ struct Elem<T> {
 #[derive(Clone, Debug)]
 pub struct RangeMap<T> {
     v: Vec<Elem<T>>,
+    /// The indices of the elements handed out by the last `iter_mut`, which may since have become
+    /// equal to their neighbours.
+    dirty: Option<ops::Range<usize>>,
+    /// The number of elements at which the next full compaction happens.
+    compact_at: usize,
+    /// The number of merge attempts in a row that did not merge anything.
+    failed_merges: u32,
+    /// The number of upcoming merge attempts that are skipped because of `failed_merges`.
+    merge_skip: u32,
 }
 
+/// The smallest number of elements at which a map is fully compacted. Small maps are cheap
+/// anyway, and this keeps the exact splitting of small maps predictable.
+const MIN_COMPACT_AT: usize = 64;
+
+/// The number of merge attempts in a row that may fail before the map backs off from merging.
+/// Sequential accesses fail at most once in a row (when they first diverge from the data after
+/// them), so they never back off.
+const FAILED_MERGES_BEFORE_BACKOFF: u32 = 8;
+
+/// The base-2 logarithm of the largest number of merge attempts that are skipped in a row.
+const MAX_MERGE_BACKOFF_SHIFT: u32 = 6;
+
 impl<T> RangeMap<T> {
     /// Creates a new `RangeMap` for the given size, and with the given initial value used for
     /// the entire range.
@@ -28,7 +59,7 @@ impl<T> RangeMap<T> {
     pub fn new(size: Size, init: T) -> RangeMap<T> {
         let size = size.bytes();
         let v = if size > 0 { vec![Elem { range: 0..size, data: init }] } else { Vec::new() };
-        RangeMap { v }
+        RangeMap { v, dirty: None, compact_at: MIN_COMPACT_AT, failed_merges: 0, merge_skip: 0 }
     }
 
     /// Finds the index containing the given offset.
@@ -149,6 +180,15 @@ impl<T> RangeMap<T> {
             // any splitting.
             &mut []
         } else {
+            // Merge what the last call mutated before looking for our elements; this is what
+            // keeps sequential accesses from fragmenting the map.
+            self.merge_dirty();
+            // Once the map has grown a lot since the last compaction, compact it entirely. Since
+            // the threshold grows along with the map, this is amortized constant time per call.
+            if self.v.len() >= self.compact_at {
+                self.merge_adjacent_thorough();
+                self.compact_at = (self.v.len() * 2).max(MIN_COMPACT_AT);
+            }
             // Make sure we got a clear beginning
             let mut first_idx = self.find_offset(offset);
             if self.split_index(first_idx, offset) {
@@ -214,17 +254,58 @@ impl<T> RangeMap<T> {
             // split, we don't have to adjust our index as we only care about
             // the first part of the split.
             self.split_index(end_idx, offset + len);
+            // The caller may make these elements equal to their neighbours.
+            self.dirty = Some(first_idx..end_idx + 1);
             // Now we yield the slice. `end` is inclusive.
             &mut self.v[first_idx..=end_idx]
         };
         slice.iter_mut().map(|elem| (elem.range.clone(), &mut elem.data))
     }
 
+    /// Merges the elements handed out by the last `iter_mut` with each other and with their
+    /// direct neighbours, where they are equal. After `FAILED_MERGES_BEFORE_BACKOFF` attempts in
+    /// a row that merged nothing, the following attempts are skipped, for exponentially longer
+    /// stretches the more attempts fail.
+    fn merge_dirty(&mut self)
+    where
+        T: PartialEq,
+    {
+        let Some(dirty) = self.dirty.take() else { return };
+        if self.merge_skip > 0 {
+            self.merge_skip -= 1;
+            return;
+        }
+        let start = dirty.start.saturating_sub(1);
+        let end = (dirty.end + 1).min(self.v.len());
+        // `write` is the last element of the merged prefix of `start..end`.
+        let mut write = start;
+        for read in start + 1..end {
+            if self.v[write].data == self.v[read].data {
+                debug_assert_eq!(self.v[write].range.end, self.v[read].range.start);
+                self.v[write].range.end = self.v[read].range.end;
+            } else {
+                write += 1;
+                self.v.swap(write, read);
+            }
+        }
+        if write + 1 < end {
+            self.failed_merges = 0;
+        } else {
+            self.failed_merges = self.failed_merges.saturating_add(1);
+            if let Some(shift) = self.failed_merges.checked_sub(FAILED_MERGES_BEFORE_BACKOFF) {
+                self.merge_skip = 1 << shift.min(MAX_MERGE_BACKOFF_SHIFT);
+            }
+        }
+        self.v.drain(write + 1..end);
+    }
+
     /// Remove all adjacent duplicates
     pub fn merge_adjacent_thorough(&mut self)
     where
         T: PartialEq,
     {
+        // This changes the indices of the elements.
+        self.dirty = None;
         let clean = Vec::with_capacity(self.v.len());
         for elem in std::mem::replace(&mut self.v, clean) {
             if let Some(prev) = self.v.last_mut() {
@@ -314,6 +395,68 @@ mod tests {
         assert_eq!(to_vec(&map, 10, 10), vec![23, 42, 23, 23, 23, 19, 19, 19, 19, 19]);
     }
 
+    #[test]
+    fn sequential_writes() {
+        let mut map = RangeMap::<i32>::new(Size::from_bytes(1000), -1);
+        // Writing one byte at a time must not fragment the map.
+        for i in 0..1000 {
+            for (_, x) in map.iter_mut(Size::from_bytes(i), Size::from_bytes(1)) {
+                *x = 42;
+            }
+            assert!(map.v.len() <= 3);
+        }
+        // The same backwards.
+        for i in (0..1000).rev() {
+            for (_, x) in map.iter_mut(Size::from_bytes(i), Size::from_bytes(1)) {
+                *x = 23;
+            }
+            assert!(map.v.len() <= 3);
+        }
+        assert_eq!(to_vec(&map, 0, 1000), vec![23; 1000]);
+    }
+
+    #[test]
+    fn compaction() {
+        let mut map = RangeMap::<i32>::new(Size::from_bytes(1000), -1);
+        // Fragment the map by writing every other byte.
+        for i in (0..1000).step_by(2) {
+            for (_, x) in map.iter_mut(Size::from_bytes(i), Size::from_bytes(1)) {
+                *x = 42;
+            }
+        }
+        assert_eq!(map.v.len(), 1000);
+        // Make all elements equal without `iter_mut`, which does not merge anything...
+        for (_, x) in map.iter_mut_all() {
+            *x = 23;
+        }
+        assert_eq!(map.v.len(), 1000);
+        // ... until the next `iter_mut` that finds the map has grown enough compacts it entirely.
+        map.compact_at = MIN_COMPACT_AT;
+        for _ in map.iter_mut(Size::from_bytes(0), Size::from_bytes(1)) {}
+        assert_eq!(map.v.len(), 2);
+        assert_eq!(to_vec(&map, 0, 1000), vec![23; 1000]);
+    }
+
+    #[test]
+    fn strided_writes_back_off() {
+        let mut map = RangeMap::<i32>::new(Size::from_bytes(1000), -1);
+        // Writing every other byte never leaves equal neighbours, so merging backs off.
+        for i in (0..200).step_by(2) {
+            for (_, x) in map.iter_mut(Size::from_bytes(i), Size::from_bytes(1)) {
+                *x = 42;
+            }
+        }
+        assert!(map.failed_merges > FAILED_MERGES_BEFORE_BACKOFF);
+        // Sequential writes merge again once the back-off is over, which ends it.
+        for i in 200..1000 {
+            for (_, x) in map.iter_mut(Size::from_bytes(i), Size::from_bytes(1)) {
+                *x = 42;
+            }
+        }
+        assert_eq!(map.failed_merges, 0);
+        assert_eq!(to_vec(&map, 200, 800), vec![42; 800]);
+    }
+
     #[test]
     #[should_panic]
     fn out_of_range_iter_mut() {
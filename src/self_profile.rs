@@ -0,0 +1,240 @@
+//! Measuring the overhead of Miri's own checking machinery (`-Zmiri-self-profile`).
+//!
+//! Unlike the `measureme` profiler, which records where the *interpreted program* spends its time,
+//! this attributes the host time spent in the individual Miri subsystems (the borrow trackers, the
+//! data race detector, provenance GC) to the interpreted function that was executing when the
+//! subsystem was invoked. This answers questions like "which function makes Stacked Borrows slow".
+//!
+//! Validity checks are not listed: they are performed by the interpreter core, which does not tell
+//! Miri when a check starts or ends, so their time cannot be told apart from the other subsystems.
+
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::Instance;
+
+use crate::*;
+
+/// The parts of Miri whose overhead is measured.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Subsystem {
+    StackedBorrows,
+    TreeBorrows,
+    /// Both of the above, with `-Zmiri-borrow-tracker=both`.
+    BothBorrowTrackers,
+    /// Vector clock checks on memory accesses, and clock joins at synchronization points.
+    DataRace,
+    ProvenanceGc,
+}
+
+impl Subsystem {
+    const ALL: [Subsystem; 5] = [
+        Subsystem::StackedBorrows,
+        Subsystem::TreeBorrows,
+        Subsystem::BothBorrowTrackers,
+        Subsystem::DataRace,
+        Subsystem::ProvenanceGc,
+    ];
+
+    /// The subsystems that are enabled by `config`, which are the ones listed in the profile.
+    pub fn active(config: &MiriConfig) -> Vec<Subsystem> {
+        let borrow_tracker = config.borrow_tracker.map(|method| {
+            match method {
+                BorrowTrackerMethod::StackedBorrows => Subsystem::StackedBorrows,
+                BorrowTrackerMethod::TreeBorrows => Subsystem::TreeBorrows,
+                BorrowTrackerMethod::Both => Subsystem::BothBorrowTrackers,
+            }
+        });
+        let data_race = config.data_race_detector.then_some(Subsystem::DataRace);
+        borrow_tracker.into_iter().chain(data_race).chain([Subsystem::ProvenanceGc]).collect()
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Subsystem::StackedBorrows => "Stacked Borrows",
+            Subsystem::TreeBorrows => "Tree Borrows",
+            Subsystem::BothBorrowTrackers => "Stacked and Tree Borrows",
+            Subsystem::DataRace => "data race",
+            Subsystem::ProvenanceGc => "provenance GC",
+        }
+    }
+}
+
+#[derive(Debug, Default, Copy, Clone)]
+struct Counter {
+    calls: u64,
+    time: Duration,
+}
+
+#[derive(Debug, Default)]
+struct FunctionProfile {
+    subsystems: [Counter; Subsystem::ALL.len()],
+    /// The number of interpreter allocations created by this function.
+    allocations: u64,
+}
+
+impl FunctionProfile {
+    fn time(&self) -> Duration {
+        self.subsystems.iter().map(|counter| counter.time).sum()
+    }
+}
+
+/// The number of functions listed in the summary printed at exit.
+const SUMMARY_FUNCTIONS: usize = 10;
+
+pub struct SelfProfile<'tcx> {
+    /// Where the full per-function table is written to, or `None` for stderr.
+    out: Option<PathBuf>,
+    /// When the profile was started, to relate the subsystem times to the total time.
+    start: std::time::Instant,
+    /// The subsystems listed in the profile.
+    subsystems: Vec<Subsystem>,
+    /// The measurements, per function. `None` is used for work done while the active thread has no
+    /// frames, e.g. when a thread is being set up.
+    functions: RefCell<FxHashMap<Option<Instance<'tcx>>, FunctionProfile>>,
+}
+
+impl<'tcx> SelfProfile<'tcx> {
+    pub fn new(out: &str, subsystems: Vec<Subsystem>) -> Self {
+        let out = (out != "-").then(|| PathBuf::from(out));
+        if let Some(out) = &out {
+            // Fail early rather than after the entire program has been interpreted.
+            File::create(out).expect("Couldn't create self-profile output file");
+        }
+        SelfProfile {
+            out,
+            start: std::time::Instant::now(),
+            subsystems,
+            functions: Default::default(),
+        }
+    }
+
+    pub fn record(&self, function: Option<Instance<'tcx>>, subsystem: Subsystem, time: Duration) {
+        let mut functions = self.functions.borrow_mut();
+        let counter = &mut functions.entry(function).or_default().subsystems[subsystem as usize];
+        counter.calls += 1;
+        counter.time += time;
+    }
+
+    pub fn record_allocation(&self, function: Option<Instance<'tcx>>) {
+        self.functions.borrow_mut().entry(function).or_default().allocations += 1;
+    }
+
+    /// Writes the full table to the output file, and prints a summary to stderr.
+    pub fn report(&self) {
+        let total = self.start.elapsed();
+        let functions = self.functions.borrow();
+        let mut functions: Vec<_> = functions
+            .iter()
+            .map(|(function, profile)| {
+                let name = match function {
+                    Some(instance) => instance.to_string(),
+                    None => "<no frame>".to_string(),
+                };
+                (name, profile)
+            })
+            .collect();
+        functions.sort_by(|(name1, profile1), (name2, profile2)| {
+            profile2.time().cmp(&profile1.time()).then_with(|| name1.cmp(name2))
+        });
+
+        let mut totals = [Counter::default(); Subsystem::ALL.len()];
+        for (_, profile) in &functions {
+            for (total, counter) in totals.iter_mut().zip(&profile.subsystems) {
+                total.calls += counter.calls;
+                total.time += counter.time;
+            }
+        }
+        eprintln!("Miri self-profile: {:.3}s total", total.as_secs_f64());
+        eprintln!("{:<16} {:>12} {:>12} {:>7}", "subsystem", "calls", "time", "share");
+        for &subsystem in &self.subsystems {
+            let Counter { calls, time } = totals[subsystem as usize];
+            let share = time.as_secs_f64() / total.as_secs_f64() * 100.0;
+            let time = format!("{:.3}s", time.as_secs_f64());
+            eprintln!("{:<16} {calls:>12} {time:>12} {share:>6.1}%", subsystem.name());
+        }
+        eprintln!("functions with the most subsystem time:");
+        for (name, profile) in functions.iter().take(SUMMARY_FUNCTIONS) {
+            eprintln!("{:>10.3}s  {name}", profile.time().as_secs_f64());
+        }
+        let res = match &self.out {
+            Some(out) => {
+                eprintln!("full profile written to `{}`", out.display());
+                File::create(out).and_then(|file| self.write_table(file, &functions))
+            }
+            None => {
+                eprintln!("full profile:");
+                self.write_table(io::stderr().lock(), &functions)
+            }
+        };
+        res.expect("Couldn't write self-profile output file");
+    }
+
+    /// Writes one tab-separated line per function, with the number of calls and the time in
+    /// microseconds for each subsystem.
+    fn write_table(
+        &self,
+        out: impl Write,
+        functions: &[(String, &FunctionProfile)],
+    ) -> io::Result<()> {
+        let mut file = BufWriter::new(out);
+        write!(file, "function\tallocations")?;
+        for subsystem in &self.subsystems {
+            write!(file, "\t{0} calls\t{0} time (us)", subsystem.name())?;
+        }
+        writeln!(file)?;
+        for (name, profile) in functions {
+            write!(file, "{name}\t{}", profile.allocations)?;
+            for &subsystem in &self.subsystems {
+                let counter = profile.subsystems[subsystem as usize];
+                write!(file, "\t{}\t{}", counter.calls, counter.time.as_micros())?;
+            }
+            writeln!(file)?;
+        }
+        file.flush()
+    }
+}
+
+impl<'tcx> MiriMachine<'tcx> {
+    /// Starts measuring an operation for `-Zmiri-self-profile`. The result must be passed to
+    /// `self_profile_end` once the operation is done.
+    #[inline(always)]
+    pub(crate) fn self_profile_start(&self) -> Option<std::time::Instant> {
+        self.self_profile.is_some().then(std::time::Instant::now)
+    }
+
+    /// Attributes the time since `start` to `subsystem` and the currently executing function.
+    #[inline(always)]
+    pub(crate) fn self_profile_end(&self, start: Option<std::time::Instant>, subsystem: Subsystem) {
+        if let Some(profile) = &self.self_profile
+            && let Some(start) = start
+        {
+            profile.record(self.self_profile_function(), subsystem, start.elapsed());
+        }
+    }
+
+    /// Counts an allocation created by the currently executing function.
+    #[inline(always)]
+    pub(crate) fn self_profile_allocation(&self) {
+        if let Some(profile) = &self.self_profile {
+            profile.record_allocation(self.self_profile_function());
+        }
+    }
+
+    /// The subsystem that the configured borrow tracker is measured as.
+    pub(crate) fn borrow_tracker_subsystem(&self) -> Subsystem {
+        match self.borrow_tracker.as_ref().unwrap().borrow().borrow_tracker_method() {
+            BorrowTrackerMethod::StackedBorrows => Subsystem::StackedBorrows,
+            BorrowTrackerMethod::TreeBorrows => Subsystem::TreeBorrows,
+            BorrowTrackerMethod::Both => Subsystem::BothBorrowTrackers,
+        }
+    }
+
+    fn self_profile_function(&self) -> Option<Instance<'tcx>> {
+        self.threads.active_thread_stack().last().map(|frame| frame.instance())
+    }
+}
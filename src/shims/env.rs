@@ -97,6 +97,16 @@ impl<'tcx> EnvVars<'tcx> {
     }
 }
 
+#[cfg(unix)]
+fn host_ppid() -> u32 {
+    std::os::unix::process::parent_id()
+}
+
+#[cfg(not(unix))]
+fn host_ppid() -> u32 {
+    1
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     /// Try to get an environment variable from the interpreted program's environment. This is
@@ -114,4 +124,10 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let this = self.eval_context_ref();
         if this.machine.communicate() { std::process::id() } else { 1000 }
     }
+
+    fn get_ppid(&self) -> u32 {
+        let this = self.eval_context_ref();
+        // Under isolation, pretend we were started by `init`.
+        if this.machine.communicate() { host_ppid() } else { 1 }
+    }
 }
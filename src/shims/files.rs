@@ -236,6 +236,8 @@ impl FileDescriptionRef {
             Some(fd) => {
                 // Remove entry from the global epoll_event_interest table.
                 ecx.machine.epoll_interests.remove(id);
+                // Forget the metadata changed with isolation enabled.
+                ecx.machine.isolated_metadata.remove(&id);
 
                 fd.file_description.close(communicate_allowed, ecx)
             }
@@ -20,7 +20,11 @@ pub mod time;
 pub mod tls;
 
 pub use self::files::FdTable;
-pub use self::unix::{DirTable, EpollInterestTable};
+pub use self::unix::{
+    DirTable, EpollInterestTable, IsolatedMetadataTable, MmapTable, PageProt, PageProtections,
+    RLIMIT_RESOURCES, ResourceLimit, init_rlimits,
+};
+pub use self::windows::{WindowsObjectTable, windows_thread_terminated};
 
 /// What needs to be done after emulating an item (a shim or an intrinsic) is done.
 pub enum EmulateItemResult {
@@ -3,10 +3,12 @@ use std::ops::Deref;
 
 use libffi::high::call as ffi;
 use libffi::low::CodePtr;
-use rustc_abi::{BackendRepr, HasDataLayout};
+use rustc_abi::{BackendRepr, HasDataLayout, Size};
+use rustc_data_structures::fx::FxHashSet;
 use rustc_middle::ty::{self as ty, IntTy, UintTy};
 use rustc_span::Symbol;
 
+use crate::concurrency::data_race::{NaReadType, NaWriteType};
 use crate::*;
 
 impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -121,6 +123,54 @@ trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
         // Return a pointer to the function.
         Some(CodePtr(*func.deref() as *mut _))
     }
+
+    /// Prepares the memory reachable from the pointer arguments of a native call, which start
+    /// in the allocations `roots`: all pointers stored in that memory are exposed, since native
+    /// code can use them, and the call is treated as a read and (for mutable memory) a write of
+    /// every reachable allocation by the active thread, so that the data race detector can
+    /// report races between native code and other threads.
+    ///
+    /// The borrow trackers only learn about the exposed pointers: we do not know which parts of
+    /// an allocation native code actually accesses, and accessing all of it would report
+    /// Undefined Behavior for pointers that were derived from references to only a part of it.
+    fn prepare_for_native_call(&mut self, roots: Vec<AllocId>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let mut done = FxHashSet::default();
+        let mut todo = roots;
+        while let Some(alloc_id) = todo.pop() {
+            if !done.insert(alloc_id) {
+                continue;
+            }
+            let info = this.get_alloc_info(alloc_id);
+            // Functions and vtables are not memory, and dead allocations cannot be accessed.
+            if !matches!(info.kind, AllocKind::LiveData) {
+                continue;
+            }
+            let alloc = this.get_alloc_raw(alloc_id)?;
+            let mutable = alloc.mutability.is_mut();
+            let provenances: Vec<Provenance> = alloc.provenance().provenances().collect();
+            for prov in provenances {
+                if let Provenance::Concrete { alloc_id, tag } = prov {
+                    this.expose_ptr(alloc_id, tag)?;
+                    todo.push(alloc_id);
+                }
+            }
+            let range = alloc_range(Size::ZERO, info.size);
+            if mutable {
+                let (alloc_extra, machine) = this.get_alloc_extra_mut(alloc_id)?;
+                if let Some(data_race) = &mut alloc_extra.data_race {
+                    data_race.read(alloc_id, range, NaReadType::Read, None, machine)?;
+                    data_race.write(alloc_id, range, NaWriteType::Write, None, machine)?;
+                }
+            } else {
+                let alloc_extra = this.get_alloc_extra(alloc_id)?;
+                if let Some(data_race) = &alloc_extra.data_race {
+                    data_race.read(alloc_id, range, NaReadType::Read, None, &this.machine)?;
+                }
+            }
+        }
+        interp_ok(())
+    }
 }
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -148,12 +198,25 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         // Get the function arguments, and convert them to `libffi`-compatible form.
         let mut libffi_args = Vec::<CArg>::with_capacity(args.len());
+        // The allocations that pointer arguments point to.
+        let mut roots = Vec::new();
         for arg in args.iter() {
             if !matches!(arg.layout.backend_repr, BackendRepr::Scalar(_)) {
                 throw_unsup_format!("only scalar argument types are support for native calls")
             }
-            libffi_args.push(imm_to_carg(this.read_immediate(arg)?, this)?);
+            let imm = this.read_immediate(arg)?;
+            if imm.layout.ty.is_unsafe_ptr() {
+                let ptr = imm.to_scalar().to_pointer(this)?;
+                if let Ok((alloc_id, ..)) = this.ptr_try_get_alloc_id(ptr, 0) {
+                    roots.push(alloc_id);
+                }
+                if let Some(Provenance::Concrete { alloc_id, tag }) = ptr.provenance {
+                    this.expose_ptr(alloc_id, tag)?;
+                }
+            }
+            libffi_args.push(imm_to_carg(imm, this)?);
         }
+        this.prepare_for_native_call(roots)?;
 
         // Convert them to `libffi::high::Arg` type.
         let libffi_args = libffi_args
@@ -4,7 +4,7 @@
 use std::io;
 use std::io::ErrorKind;
 
-use rustc_abi::Size;
+use rustc_abi::{Align, Size};
 
 use crate::helpers::check_min_arg_count;
 use crate::shims::files::FileDescription;
@@ -52,6 +52,35 @@ pub trait UnixFileDescription: FileDescription {
         throw_unsup_format!("cannot pwrite to {}", self.name());
     }
 
+    /// Reads up to `len` bytes starting at a given offset into a host buffer, for shims like
+    /// `mmap` that do not read into interpreter memory directly. Returns fewer bytes only at the
+    /// end of the file.
+    fn read_at<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        _offset: u64,
+        _len: u64,
+    ) -> InterpResult<'tcx, io::Result<Vec<u8>>> {
+        throw_unsup_format!("cannot map {}", self.name());
+    }
+
+    /// Whether this file description was opened for writing. `mmap` only allows shared writable
+    /// mappings of such descriptions; those that cannot be mapped at all need not override this.
+    fn is_writable(&self) -> bool {
+        true
+    }
+
+    /// Writes all of `bytes` starting at a given offset, for shims like `msync` that do not
+    /// write from interpreter memory directly.
+    fn write_at<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        _offset: u64,
+        _bytes: &[u8],
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        throw_unsup_format!("cannot write back a mapping of {}", self.name());
+    }
+
     fn flock<'tcx>(
         &self,
         _communicate_allowed: bool,
@@ -66,6 +95,134 @@ pub trait UnixFileDescription: FileDescription {
     }
 }
 
+/// The maximum number of buffers in a vectored I/O call. This is the value of `IOV_MAX` on all
+/// supported targets.
+const IOV_MAX: u64 = 1024;
+
+/// The most bytes that `copy_file_range`, `sendfile` and `splice` copy in one call. Copying fewer
+/// bytes than requested is allowed, and callers loop anyway.
+const COPY_CHUNK_SIZE: u64 = 0x10000;
+
+impl<'tcx> EvalContextExtPrivate<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPrivate<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Reads the `iovec` array of a vectored I/O call and checks that each buffer is valid
+    /// memory, like `read` and `write` do for their buffer. Returns `None` if `iovcnt` is out of
+    /// range or the total length does not fit into an `ssize_t`.
+    fn read_iovecs(
+        &mut self,
+        iov_op: &OpTy<'tcx>,
+        iovcnt: i32,
+    ) -> InterpResult<'tcx, Option<Vec<(Pointer, u64)>>> {
+        let this = self.eval_context_mut();
+
+        let Ok(iovcnt) = u64::try_from(iovcnt) else {
+            return interp_ok(None);
+        };
+        if iovcnt > IOV_MAX {
+            return interp_ok(None);
+        }
+        let max_total = u64::try_from(this.target_isize_max()).unwrap();
+
+        let iovs = this.deref_pointer_as(iov_op, this.libc_array_ty_layout("iovec", iovcnt))?;
+        let mut bufs = Vec::new();
+        let mut total: u64 = 0;
+        for i in 0..iovcnt {
+            let iov = this.project_index(&iovs, i)?;
+            let base = this.read_pointer(&this.project_field_named(&iov, "iov_base")?)?;
+            let len = this.read_target_usize(&this.project_field_named(&iov, "iov_len")?)?;
+            // Check that the *entire* buffer is actually valid memory.
+            this.check_ptr_access(base, Size::from_bytes(len), CheckInAllocMsg::MemoryAccessTest)?;
+            match total.checked_add(len) {
+                Some(new_total) if new_total <= max_total => total = new_total,
+                _ => return interp_ok(None),
+            }
+            bufs.push((base, len));
+        }
+        interp_ok(Some(bufs))
+    }
+
+    /// Reads the optional offset pointer of `copy_file_range`, `sendfile` and `splice`.
+    /// Returns the place it points to and its value, or `None` if it is null.
+    fn read_offset_ptr(
+        &mut self,
+        offset_op: &OpTy<'tcx>,
+        offset_ty: &str,
+    ) -> InterpResult<'tcx, Option<(MPlaceTy<'tcx>, i128)>> {
+        let this = self.eval_context_mut();
+
+        if this.ptr_is_null(this.read_pointer(offset_op)?)? {
+            return interp_ok(None);
+        }
+        let place = this.deref_pointer_as(offset_op, this.libc_ty_layout(offset_ty))?;
+        let offset = this.read_scalar(&place)?.to_int(place.layout.size)?;
+        interp_ok(Some((place, offset)))
+    }
+
+    /// Copies up to `len` bytes from `in_fd` to `out_fd`, by reading them into a temporary buffer
+    /// and writing them out again with the `read`/`write` of the file descriptions. The offsets
+    /// work like for `pread`/`pwrite`. Returns the number of bytes copied, or `-1` after setting
+    /// the last error.
+    ///
+    /// If the write is short and `in_offset` is `None`, the position of `in_fd` is moved back by
+    /// the number of bytes that were not written, so that no data is lost. File descriptions that
+    /// cannot seek report this as unsupported.
+    fn copy_between_fds(
+        &mut self,
+        in_fd: i32,
+        in_offset: Option<i128>,
+        out_fd: i32,
+        out_offset: Option<i128>,
+        len: u64,
+    ) -> InterpResult<'tcx, i64> {
+        let this = self.eval_context_mut();
+
+        if !this.machine.fds.is_fd_num(out_fd) {
+            this.set_last_error(LibcError("EBADF"))?;
+            return interp_ok(-1);
+        }
+
+        let len = len.min(COPY_CHUNK_SIZE);
+        let buf =
+            this.allocate_ptr(Size::from_bytes(len), Align::ONE, MiriMemoryKind::Runtime.into())?;
+        let result_place =
+            this.allocate(this.machine.layouts.isize, MiriMemoryKind::Runtime.into())?;
+
+        this.read(in_fd, buf.into(), len, in_offset, &result_place)?;
+        if this.active_thread_ref().is_blocked() {
+            throw_unsup_format!("copying from a blocking file descriptor is not supported");
+        }
+        let mut result = this.read_scalar(&result_place)?.to_target_isize(this)?;
+        if result > 0 {
+            let read_size = u64::try_from(result).unwrap();
+            this.write(out_fd, buf.into(), read_size, out_offset, &result_place)?;
+            if this.active_thread_ref().is_blocked() {
+                throw_unsup_format!("copying to a blocking file descriptor is not supported");
+            }
+            result = this.read_scalar(&result_place)?.to_target_isize(this)?;
+            // A failed write did not write anything either.
+            let written = u64::try_from(result).unwrap_or(0);
+            if in_offset.is_none() && written < read_size {
+                // The input position has already moved past the bytes that were not written.
+                let unwritten = i64::try_from(read_size.strict_sub(written)).unwrap();
+                let fd = this.machine.fds.get(in_fd).unwrap();
+                let seek_result = fd.seek(
+                    this.machine.communicate(),
+                    io::SeekFrom::Current(unwritten.strict_neg()),
+                )?;
+                drop(fd);
+                if let Err(err) = seek_result {
+                    this.set_last_error(err)?;
+                    result = -1;
+                }
+            }
+        }
+
+        this.deallocate_ptr(buf.into(), None, MiriMemoryKind::Runtime.into())?;
+        this.deallocate_ptr(result_place.ptr(), None, MiriMemoryKind::Runtime.into())?;
+        interp_ok(result)
+    }
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn dup(&mut self, old_fd_num: i32) -> InterpResult<'tcx, Scalar> {
@@ -296,4 +453,217 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         };
         interp_ok(())
     }
+
+    /// Reads data from `fd` into the buffers described by the `iovec` array `iov`. The offset
+    /// works like for `read`.
+    ///
+    /// Like the real `readv`, this is atomic: all data is obtained by a single `read` of the file
+    /// description, which then gets scattered across the buffers.
+    fn readv(
+        &mut self,
+        fd_num: i32,
+        iov_op: &OpTy<'tcx>,
+        iovcnt: i32,
+        offset: Option<i128>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(bufs) = this.read_iovecs(iov_op, iovcnt)? else {
+            return this.set_last_error_and_return(LibcError("EINVAL"), dest);
+        };
+
+        // With only one non-empty buffer, we can read into it directly.
+        let mut nonempty = bufs.iter().filter(|(_, len)| *len > 0);
+        if let (Some(&(ptr, len)), None) = (nonempty.next(), nonempty.next()) {
+            return this.read(fd_num, ptr, len, offset, dest);
+        }
+
+        let total = bufs.iter().map(|(_, len)| len).sum::<u64>();
+        let buf =
+            this.allocate_ptr(Size::from_bytes(total), Align::ONE, MiriMemoryKind::Runtime.into())?;
+        this.read(fd_num, buf.into(), total, offset, dest)?;
+        if this.active_thread_ref().is_blocked() {
+            throw_unsup_format!("blocking `readv` into more than one buffer is not supported");
+        }
+
+        // On failure, `dest` is -1 and there is nothing to scatter.
+        let read_size = this.read_scalar(dest)?.to_target_isize(this)?;
+        let mut remaining = u64::try_from(read_size).unwrap_or(0);
+        let mut src: Pointer = buf.into();
+        for (ptr, len) in bufs {
+            if remaining == 0 {
+                break;
+            }
+            let len = len.min(remaining);
+            this.mem_copy(src, ptr, Size::from_bytes(len), /*nonoverlapping*/ true)?;
+            src = src.wrapping_offset(Size::from_bytes(len), this);
+            remaining = remaining.strict_sub(len);
+        }
+
+        this.deallocate_ptr(buf.into(), None, MiriMemoryKind::Runtime.into())?;
+        interp_ok(())
+    }
+
+    /// Writes the data in the buffers described by the `iovec` array `iov` to `fd`. The offset
+    /// works like for `write`.
+    ///
+    /// Like the real `writev`, this is atomic: the buffers are gathered and then written by a
+    /// single `write` of the file description.
+    fn writev(
+        &mut self,
+        fd_num: i32,
+        iov_op: &OpTy<'tcx>,
+        iovcnt: i32,
+        offset: Option<i128>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let Some(bufs) = this.read_iovecs(iov_op, iovcnt)? else {
+            return this.set_last_error_and_return(LibcError("EINVAL"), dest);
+        };
+
+        // With only one non-empty buffer, we can write from it directly.
+        let mut nonempty = bufs.iter().filter(|(_, len)| *len > 0);
+        if let (Some(&(ptr, len)), None) = (nonempty.next(), nonempty.next()) {
+            return this.write(fd_num, ptr, len, offset, dest);
+        }
+
+        let total = bufs.iter().map(|(_, len)| len).sum::<u64>();
+        let buf =
+            this.allocate_ptr(Size::from_bytes(total), Align::ONE, MiriMemoryKind::Runtime.into())?;
+        let mut dst: Pointer = buf.into();
+        for (ptr, len) in bufs {
+            this.mem_copy(ptr, dst, Size::from_bytes(len), /*nonoverlapping*/ true)?;
+            dst = dst.wrapping_offset(Size::from_bytes(len), this);
+        }
+        this.write(fd_num, buf.into(), total, offset, dest)?;
+        if this.active_thread_ref().is_blocked() {
+            throw_unsup_format!("blocking `writev` from more than one buffer is not supported");
+        }
+
+        this.deallocate_ptr(buf.into(), None, MiriMemoryKind::Runtime.into())?;
+        interp_ok(())
+    }
+
+    fn copy_file_range(
+        &mut self,
+        fd_in_op: &OpTy<'tcx>,
+        off_in_op: &OpTy<'tcx>,
+        fd_out_op: &OpTy<'tcx>,
+        off_out_op: &OpTy<'tcx>,
+        len_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_in = this.read_scalar(fd_in_op)?.to_i32()?;
+        let off_in = this.read_offset_ptr(off_in_op, "loff_t")?;
+        let fd_out = this.read_scalar(fd_out_op)?.to_i32()?;
+        let off_out = this.read_offset_ptr(off_out_op, "loff_t")?;
+        let len = this.read_target_usize(len_op)?;
+        let flags = this.read_scalar(flags_op)?.to_u32()?;
+
+        if flags != 0 {
+            this.set_last_error(LibcError("EINVAL"))?;
+            return interp_ok(Scalar::from_target_isize(-1, this));
+        }
+
+        let result = this.copy_between_fds(
+            fd_in,
+            off_in.as_ref().map(|(_, offset)| *offset),
+            fd_out,
+            off_out.as_ref().map(|(_, offset)| *offset),
+            len,
+        )?;
+        // Offsets that were given are advanced instead of the file positions.
+        if result > 0 {
+            for (place, offset) in off_in.iter().chain(off_out.iter()) {
+                let offset = offset.strict_add(result.into());
+                this.write_scalar(Scalar::from_int(offset, place.layout.size), place)?;
+            }
+        }
+        interp_ok(Scalar::from_target_isize(result, this))
+    }
+
+    fn sendfile(
+        &mut self,
+        out_fd_op: &OpTy<'tcx>,
+        in_fd_op: &OpTy<'tcx>,
+        offset_op: &OpTy<'tcx>,
+        count_op: &OpTy<'tcx>,
+        offset_ty: &str,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let out_fd = this.read_scalar(out_fd_op)?.to_i32()?;
+        let in_fd = this.read_scalar(in_fd_op)?.to_i32()?;
+        let offset = this.read_offset_ptr(offset_op, offset_ty)?;
+        let count = this.read_target_usize(count_op)?;
+
+        let result = this.copy_between_fds(
+            in_fd,
+            offset.as_ref().map(|(_, offset)| *offset),
+            out_fd,
+            None,
+            count,
+        )?;
+        // If an offset was given, it is advanced instead of the file position.
+        if let Some((place, offset)) = offset
+            && result > 0
+        {
+            let offset = offset.strict_add(result.into());
+            this.write_scalar(Scalar::from_int(offset, place.layout.size), &place)?;
+        }
+        interp_ok(Scalar::from_target_isize(result, this))
+    }
+
+    fn splice(
+        &mut self,
+        fd_in_op: &OpTy<'tcx>,
+        off_in_op: &OpTy<'tcx>,
+        fd_out_op: &OpTy<'tcx>,
+        off_out_op: &OpTy<'tcx>,
+        len_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_in = this.read_scalar(fd_in_op)?.to_i32()?;
+        let off_in = this.read_offset_ptr(off_in_op, "loff_t")?;
+        let fd_out = this.read_scalar(fd_out_op)?.to_i32()?;
+        let off_out = this.read_offset_ptr(off_out_op, "loff_t")?;
+        let len = this.read_target_usize(len_op)?;
+        let flags = this.read_scalar(flags_op)?.to_u32()?;
+
+        // All flags are merely hints. `SPLICE_F_NONBLOCK` makes no difference since our pipes
+        // never block in `read`/`write` (they report blocking as unsupported instead).
+        let known_flags = this.eval_libc_u32("SPLICE_F_MOVE")
+            | this.eval_libc_u32("SPLICE_F_NONBLOCK")
+            | this.eval_libc_u32("SPLICE_F_MORE")
+            | this.eval_libc_u32("SPLICE_F_GIFT");
+        if flags & !known_flags != 0 {
+            this.set_last_error(LibcError("EINVAL"))?;
+            return interp_ok(Scalar::from_target_isize(-1, this));
+        }
+        // We do not check that one of the file descriptors is a pipe, which the real `splice`
+        // requires.
+
+        let result = this.copy_between_fds(
+            fd_in,
+            off_in.as_ref().map(|(_, offset)| *offset),
+            fd_out,
+            off_out.as_ref().map(|(_, offset)| *offset),
+            len,
+        )?;
+        // Offsets that were given are advanced instead of the file positions.
+        if result > 0 {
+            for (place, offset) in off_in.iter().chain(off_out.iter()) {
+                let offset = offset.strict_add(result.into());
+                this.write_scalar(Scalar::from_int(offset, place.layout.size), place)?;
+            }
+        }
+        interp_ok(Scalar::from_target_isize(result, this))
+    }
 }
@@ -58,11 +58,17 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             // 512 seems to be a reasonable default. The value is not critical, in
             // the sense that getpwuid_r takes and checks the buffer length.
             ("_SC_GETPW_R_SIZE_MAX", |this| Scalar::from_int(512, this.pointer_size())),
-            // Miri doesn't have a fixed limit on FDs, but we may be limited in terms of how
-            // many *host* FDs we can open. Just use some arbitrary, pretty big value;
-            // this can be adjusted if it causes problems.
+            // Miri doesn't have a fixed limit on FDs, so we report the soft `RLIMIT_NOFILE`, like
+            // glibc does. That defaults to some arbitrary, pretty big value.
             // The spec imposes a minimum of `_POSIX_OPEN_MAX` (20).
-            ("_SC_OPEN_MAX", |this| Scalar::from_int(2_i32.pow(16), this.pointer_size())),
+            ("_SC_OPEN_MAX", |this| {
+                let open_max = match this.open_max() {
+                    Some(max) =>
+                        i64::try_from(max).unwrap_or(i64::MAX).min(this.target_isize_max()),
+                    None => -1,
+                };
+                Scalar::from_int(open_max, this.pointer_size())
+            }),
         ];
         for &(sysconf_name, value) in sysconfs {
             let sysconf_name = this.eval_libc_i32(sysconf_name);
@@ -141,6 +147,35 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.getpid()?;
                 this.write_scalar(result, dest)?;
             }
+            "getppid" => {
+                let [] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.write_int(this.get_ppid(), dest)?;
+            }
+            "uname" => {
+                let [buf] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.uname(buf)?;
+                this.write_scalar(result, dest)?;
+            }
+            "gethostname" => {
+                let [name, len] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.gethostname(name, len)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getrlimit" => {
+                let [resource, rlim] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.getrlimit(resource, rlim)?;
+                this.write_scalar(result, dest)?;
+            }
+            "setrlimit" => {
+                let [resource, rlim] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.setrlimit(resource, rlim)?;
+                this.write_scalar(result, dest)?;
+            }
+            "getloadavg" => {
+                let [loadavg, nelem] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.getloadavg(loadavg, nelem)?;
+                this.write_scalar(result, dest)?;
+            }
 
             "sysconf" => {
                 let [val] =
@@ -199,6 +234,46 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 trace!("Called pwrite64({:?}, {:?}, {:?}, {:?})", fd, buf, count, offset);
                 this.write(fd, buf, count, Some(offset), dest)?;
             }
+            "readv" => {
+                let [fd, iov, iovcnt] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                this.readv(fd, iov, iovcnt, None, dest)?;
+            }
+            "writev" => {
+                let [fd, iov, iovcnt] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                this.writev(fd, iov, iovcnt, None, dest)?;
+            }
+            "preadv" => {
+                let [fd, iov, iovcnt, offset] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                this.readv(fd, iov, iovcnt, Some(offset), dest)?;
+            }
+            "pwritev" => {
+                let [fd, iov, iovcnt, offset] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off_t").size)?;
+                this.writev(fd, iov, iovcnt, Some(offset), dest)?;
+            }
+            "preadv64" => {
+                let [fd, iov, iovcnt, offset] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off64_t").size)?;
+                this.readv(fd, iov, iovcnt, Some(offset), dest)?;
+            }
+            "pwritev64" => {
+                let [fd, iov, iovcnt, offset] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let fd = this.read_scalar(fd)?.to_i32()?;
+                let iovcnt = this.read_scalar(iovcnt)?.to_i32()?;
+                let offset = this.read_scalar(offset)?.to_int(this.libc_ty_layout("off64_t").size)?;
+                this.writev(fd, iov, iovcnt, Some(offset), dest)?;
+            }
             "close" => {
                 let [fd] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.close(fd)?;
@@ -239,26 +314,107 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.open(args)?;
                 this.write_scalar(result, dest)?;
             }
+            "openat" | "openat64" => {
+                // `openat` is variadic like `open`, see above
+                this.check_abi_and_shim_symbol_clash(abi, ExternAbi::C { unwind: false }, link_name)?;
+                let result = this.openat(args)?;
+                this.write_scalar(result, dest)?;
+            }
             "unlink" => {
                 let [path] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.unlink(path)?;
                 this.write_scalar(result, dest)?;
             }
+            "unlinkat" => {
+                let [dirfd, path, flags] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.unlinkat(dirfd, path, flags)?;
+                this.write_scalar(result, dest)?;
+            }
             "symlink" => {
                 let [target, linkpath] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.symlink(target, linkpath)?;
                 this.write_scalar(result, dest)?;
             }
+            "link" => {
+                let [oldpath, newpath] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.link(oldpath, newpath)?;
+                this.write_scalar(result, dest)?;
+            }
+            "linkat" => {
+                let [olddirfd, oldpath, newdirfd, newpath, flags] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.linkat(olddirfd, oldpath, newdirfd, newpath, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "chmod" => {
+                let [path, mode] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.chmod(path, mode)?;
+                this.write_scalar(result, dest)?;
+            }
+            "fchmod" => {
+                let [fd, mode] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.fchmod(fd, mode)?;
+                this.write_scalar(result, dest)?;
+            }
+            "chown" => {
+                let [path, owner, group] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.chown(path, owner, group)?;
+                this.write_scalar(result, dest)?;
+            }
+            "fchown" => {
+                let [fd, owner, group] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.fchown(fd, owner, group)?;
+                this.write_scalar(result, dest)?;
+            }
+            "lchown" => {
+                let [path, owner, group] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.lchown(path, owner, group)?;
+                this.write_scalar(result, dest)?;
+            }
+            "utimensat" => {
+                let [dirfd, path, times, flags] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.utimensat(dirfd, path, times, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "futimens" => {
+                let [fd, times] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.futimens(fd, times)?;
+                this.write_scalar(result, dest)?;
+            }
+            "access" => {
+                let [path, mode] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.access(path, mode)?;
+                this.write_scalar(result, dest)?;
+            }
+            "faccessat" => {
+                let [dirfd, path, mode, flags] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.faccessat(dirfd, path, mode, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "umask" => {
+                let [mask] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.umask(mask)?;
+                this.write_scalar(result, dest)?;
+            }
             "rename" => {
                 let [oldpath, newpath] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.rename(oldpath, newpath)?;
                 this.write_scalar(result, dest)?;
             }
+            "renameat" => {
+                let [olddirfd, oldpath, newdirfd, newpath] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.renameat(olddirfd, oldpath, newdirfd, newpath, None)?;
+                this.write_scalar(result, dest)?;
+            }
             "mkdir" => {
                 let [path, mode] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.mkdir(path, mode)?;
                 this.write_scalar(result, dest)?;
             }
+            "mkdirat" => {
+                let [dirfd, path, mode] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.mkdirat(dirfd, path, mode)?;
+                this.write_scalar(result, dest)?;
+            }
             "rmdir" => {
                 let [path] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.rmdir(path)?;
@@ -269,6 +425,16 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.opendir(name)?;
                 this.write_scalar(result, dest)?;
             }
+            "fdopendir" => {
+                let [fd] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.fdopendir(fd)?;
+                this.write_scalar(result, dest)?;
+            }
+            "dirfd" => {
+                let [dirp] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.dirfd(dirp)?;
+                this.write_scalar(result, dest)?;
+            }
             "closedir" => {
                 let [dirp] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.closedir(dirp)?;
@@ -405,6 +571,21 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.munmap(addr, length)?;
                 this.write_scalar(result, dest)?;
             }
+            "msync" => {
+                let [addr, length, flags] = this.check_shim(abi, ExternAbi::C {unwind: false}, link_name, args)?;
+                let result = this.msync(addr, length, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "mprotect" => {
+                let [addr, length, prot] = this.check_shim(abi, ExternAbi::C {unwind: false}, link_name, args)?;
+                let result = this.mprotect(addr, length, prot)?;
+                this.write_scalar(result, dest)?;
+            }
+            "madvise" => {
+                let [addr, length, advice] = this.check_shim(abi, ExternAbi::C {unwind: false}, link_name, args)?;
+                let result = this.madvise(addr, length, advice)?;
+                this.write_scalar(result, dest)?;
+            }
 
             "reallocarray" => {
                 // Currently this function does not exist on all Unixes, e.g. on macOS.
@@ -630,8 +811,104 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.pthread_cond_destroy(cond)?;
                 this.write_null(dest)?;
             }
+            "pthread_spin_init" => {
+                let [lock, pshared] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_spin_init(lock, pshared)?;
+                this.write_null(dest)?;
+            }
+            "pthread_spin_lock" => {
+                let [lock] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_spin_lock(lock, dest)?;
+            }
+            "pthread_spin_trylock" => {
+                let [lock] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_spin_trylock(lock)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_spin_unlock" => {
+                let [lock] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_spin_unlock(lock)?;
+                this.write_null(dest)?;
+            }
+            "pthread_spin_destroy" => {
+                let [lock] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_spin_destroy(lock)?;
+                this.write_null(dest)?;
+            }
+            "pthread_barrier_init" => {
+                let [barrier, attr, count] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_barrier_init(barrier, attr, count)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_barrier_wait" => {
+                let [barrier] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_barrier_wait(barrier, dest)?;
+            }
+            "pthread_barrier_destroy" => {
+                let [barrier] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_barrier_destroy(barrier)?;
+                this.write_null(dest)?;
+            }
+            "sem_init" => {
+                let [sem, pshared, value] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.sem_init(sem, pshared, value)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sem_wait" => {
+                let [sem] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.sem_wait(sem, dest)?;
+            }
+            "sem_trywait" => {
+                let [sem] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.sem_trywait(sem)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sem_timedwait" => {
+                let [sem, abstime] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.sem_timedwait(sem, abstime, dest)?;
+            }
+            "sem_post" => {
+                let [sem] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.sem_post(sem)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sem_getvalue" => {
+                let [sem, sval] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.sem_getvalue(sem, sval)?;
+                this.write_null(dest)?;
+            }
+            "sem_destroy" => {
+                let [sem] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.sem_destroy(sem)?;
+                this.write_null(dest)?;
+            }
 
             // Threading
+            "pthread_attr_init" => {
+                let [attr] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_attr_init(attr)?;
+                this.write_null(dest)?;
+            }
+            "pthread_attr_destroy" => {
+                let [attr] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_attr_destroy(attr)?;
+                this.write_null(dest)?;
+            }
+            "pthread_attr_setstacksize" => {
+                let [attr, stack_size] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_attr_setstacksize(attr, stack_size)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_attr_getstacksize" => {
+                let [attr, stack_size] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_attr_getstacksize(attr, stack_size)?;
+                this.write_null(dest)?;
+            }
+            "pthread_attr_getstack" => {
+                let [attr, addr, stack_size] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                this.pthread_attr_getstack(attr, addr, stack_size)?;
+                this.write_null(dest)?;
+            }
             "pthread_create" => {
                 let [thread, attr, start, arg] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 this.pthread_create(thread, attr, start, arg)?;
@@ -852,11 +1129,31 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.handle_miri_start_unwind(payload)?;
                 return interp_ok(EmulateItemResult::NeedsUnwind);
             }
-            "getuid" => {
+            "getuid" | "geteuid" => {
                 let [] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 // For now, just pretend we always have this fixed UID.
                 this.write_int(UID, dest)?;
             }
+            "getgid" | "getegid" => {
+                let [] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                // Same for the GID.
+                this.write_int(GID, dest)?;
+            }
+            "getgroups" => {
+                let [size, list] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let size = this.read_scalar(size)?.to_i32()?;
+                let list = this.read_pointer(list)?;
+                // We are only a member of our own group.
+                if size == 0 {
+                    this.write_int(1, dest)?;
+                } else if size < 1 {
+                    this.set_last_error_and_return(LibcError("EINVAL"), dest)?;
+                } else {
+                    let gid_layout = this.libc_ty_layout("gid_t");
+                    this.write_int(GID, &this.ptr_to_mplace(list, gid_layout))?;
+                    this.write_int(1, dest)?;
+                }
+            }
 
             // Incomplete shims that we "stub out" just to get pre-main initialization code to work.
             // These shims are enabled only when the caller is in the standard library.
@@ -871,41 +1168,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.write_null(dest)?;
             }
 
-            | "pthread_attr_init"
-            | "pthread_attr_destroy"
-            if this.frame_in_std() => {
-                let [_] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
-                this.write_null(dest)?;
-            }
-            | "pthread_attr_setstacksize"
-            if this.frame_in_std() => {
-                let [_, _] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
-                this.write_null(dest)?;
-            }
-
-            "pthread_attr_getstack"
-            if this.frame_in_std() => {
-                // We don't support "pthread_attr_setstack", so we just pretend all stacks have the same values here.
-                // Hence we can mostly ignore the input `attr_place`.
-                let [attr_place, addr_place, size_place] =
-                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
-                let _attr_place = this.deref_pointer_as(attr_place, this.libc_ty_layout("pthread_attr_t"))?;
-                let addr_place = this.deref_pointer(addr_place)?;
-                let size_place = this.deref_pointer(size_place)?;
-
-                this.write_scalar(
-                    Scalar::from_uint(this.machine.stack_addr, this.pointer_size()),
-                    &addr_place,
-                )?;
-                this.write_scalar(
-                    Scalar::from_uint(this.machine.stack_size, this.pointer_size()),
-                    &size_place,
-                )?;
-
-                // Return success (`0`).
-                this.write_null(dest)?;
-            }
-
             | "signal"
             | "sigaltstack"
             if this.frame_in_std() => {
@@ -913,7 +1175,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.write_null(dest)?;
             }
             | "sigaction"
-            | "mprotect"
             if this.frame_in_std() => {
                 let [_, _, _] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 this.write_null(dest)?;
@@ -1,9 +1,11 @@
 //! File and file system access
 
 use std::borrow::Cow;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
 use std::fs::{
-    DirBuilder, File, FileType, Metadata, OpenOptions, ReadDir, read_dir, remove_dir, remove_file,
-    rename,
+    DirBuilder, File, FileTimes, FileType, Metadata, OpenOptions, Permissions, ReadDir, read_dir,
+    remove_dir, remove_file, rename,
 };
 use std::io::{self, ErrorKind, IsTerminal, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
@@ -14,9 +16,15 @@ use rustc_data_structures::fx::FxHashMap;
 
 use self::shims::time::system_time_to_duration;
 use crate::helpers::check_min_arg_count;
-use crate::shims::files::{EvalContextExt as _, FileDescription, FileDescriptionRef};
+use crate::shims::files::{
+    EvalContextExt as _, FdId, FileDescription, FileDescriptionRef, NullOutput,
+};
 use crate::shims::os_str::bytes_to_os_str;
 use crate::shims::unix::fd::{FlockOp, UnixFileDescription};
+use crate::shims::unix::linux::procfs::{
+    EvalContextExt as _, VirtualEntry, VirtualFile, VirtualFileType,
+};
+use crate::shims::unix::{GID, UID};
 use crate::*;
 
 #[derive(Debug)]
@@ -177,6 +185,53 @@ impl UnixFileDescription for FileHandle {
         }
     }
 
+    fn read_at<'tcx>(
+        &self,
+        communicate_allowed: bool,
+        offset: u64,
+        len: u64,
+    ) -> InterpResult<'tcx, io::Result<Vec<u8>>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        // Emulated using seek + read + seek to restore cursor position, like `pread`.
+        let file = &mut &self.file;
+        let mut f = || {
+            let cursor_pos = file.stream_position()?;
+            file.seek(SeekFrom::Start(offset))?;
+            let mut bytes = Vec::new();
+            let res = Read::take(&mut *file, len).read_to_end(&mut bytes);
+            // Attempt to restore cursor position even if the read has failed
+            file.seek(SeekFrom::Start(cursor_pos))
+                .expect("failed to restore file position, this shouldn't be possible");
+            res.map(|_| bytes)
+        };
+        interp_ok(f())
+    }
+
+    fn is_writable(&self) -> bool {
+        self.writable
+    }
+
+    fn write_at<'tcx>(
+        &self,
+        communicate_allowed: bool,
+        offset: u64,
+        bytes: &[u8],
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        assert!(communicate_allowed, "isolation should have prevented even opening a file");
+        // Emulated using seek + write + seek to restore cursor position, like `pwrite`.
+        let file = &mut &self.file;
+        let mut f = || {
+            let cursor_pos = file.stream_position()?;
+            file.seek(SeekFrom::Start(offset))?;
+            let res = file.write_all(bytes);
+            // Attempt to restore cursor position even if the write has failed
+            file.seek(SeekFrom::Start(cursor_pos))
+                .expect("failed to restore file position, this shouldn't be possible");
+            res
+        };
+        interp_ok(f())
+    }
+
     fn flock<'tcx>(
         &self,
         communicate_allowed: bool,
@@ -273,6 +328,40 @@ impl UnixFileDescription for FileHandle {
     }
 }
 
+/// A directory opened with `O_DIRECTORY` or `O_PATH`.
+///
+/// There is no portable way to hold a host handle to a directory, so we just remember its
+/// (absolute) path. Paths relative to this directory are resolved by joining them onto it,
+/// which means that renaming the directory on the host while it is open is not reflected.
+#[derive(Debug)]
+struct DirHandle {
+    path: PathBuf,
+}
+
+impl FileDescription for DirHandle {
+    fn name(&self) -> &'static str {
+        "directory"
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        interp_ok(Ok(()))
+    }
+
+    fn metadata<'tcx>(&self) -> InterpResult<'tcx, io::Result<Metadata>> {
+        interp_ok(std::fs::metadata(&self.path))
+    }
+
+    fn as_unix(&self) -> &dyn UnixFileDescription {
+        self
+    }
+}
+
+impl UnixFileDescription for DirHandle {}
+
 impl<'tcx> EvalContextExtPrivate<'tcx> for crate::MiriInterpCx<'tcx> {}
 trait EvalContextExtPrivate<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn macos_fbsd_solaris_write_buf(
@@ -292,10 +381,10 @@ trait EvalContextExtPrivate<'tcx>: crate::MiriInterpCxExt<'tcx> {
             &[
                 ("st_dev", 0),
                 ("st_mode", mode.try_into().unwrap()),
-                ("st_nlink", 0),
+                ("st_nlink", metadata.nlink.into()),
                 ("st_ino", 0),
-                ("st_uid", 0),
-                ("st_gid", 0),
+                ("st_uid", metadata.uid.into()),
+                ("st_gid", metadata.gid.into()),
                 ("st_rdev", 0),
                 ("st_atime", access_sec.into()),
                 ("st_mtime", modified_sec.into()),
@@ -369,21 +458,459 @@ trait EvalContextExtPrivate<'tcx>: crate::MiriInterpCxExt<'tcx> {
             }
         }
     }
+
+    /// Creates a directory stream for `opendir`, or sets the error and returns null.
+    fn insert_dir_stream(
+        &mut self,
+        result: Result<(DirEntries, PathBuf), IoError>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        match result {
+            Ok((entries, path)) => {
+                // Like libc, we back the stream by a directory file descriptor, which `dirfd`
+                // returns and `closedir` closes.
+                let fd = this.machine.fds.insert_new(DirHandle { path });
+                let id = this.machine.dirs.insert_new(entries, fd);
+
+                // The libc API for opendir says that this method returns a pointer to an opaque
+                // structure, but we are returning an ID number. Thus, pass it as a scalar of
+                // pointer width.
+                interp_ok(Scalar::from_target_usize(id, this))
+            }
+            Err(e) => {
+                this.set_last_error(e)?;
+                interp_ok(Scalar::null_ptr(this))
+            }
+        }
+    }
+
+    /// Reads the next entry of the directory stream `dirp` and returns its name, inode number and
+    /// `d_type`, or `None` at the end of the stream. `name` is the calling function.
+    fn next_dir_entry(
+        &mut self,
+        name: &str,
+        dirp: u64,
+    ) -> InterpResult<'tcx, Option<io::Result<(OsString, u64, i32)>>> {
+        let this = self.eval_context_mut();
+
+        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
+            err_unsup_format!("the DIR pointer passed to {name} did not come from opendir")
+        })?;
+        match &mut open_dir.entries {
+            DirEntries::Host(read_dir) =>
+                match read_dir.next() {
+                    Some(Ok(dir_entry)) => {
+                        // If the host is a Unix system, fill in the inode number with its real
+                        // value. If not, use 0 as a fallback value.
+                        #[cfg(unix)]
+                        let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
+                        #[cfg(not(unix))]
+                        let ino = 0u64;
+
+                        let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
+                        // not a Path as there are no separators!
+                        interp_ok(Some(Ok((dir_entry.file_name(), ino, file_type))))
+                    }
+                    Some(Err(e)) => interp_ok(Some(Err(e))),
+                    None => interp_ok(None),
+                },
+            DirEntries::Virtual(entries) =>
+                match entries.next() {
+                    Some((file_name, file_type)) => {
+                        let file_type = this.eval_libc(file_type.d_type()).to_u8()?.into();
+                        interp_ok(Some(Ok((file_name.into(), 0, file_type))))
+                    }
+                    None => interp_ok(None),
+                },
+        }
+    }
+
+    /// Resolves `path` the way the `*at` family of functions does: absolute paths are used
+    /// as-is, relative paths are relative to the current working directory if `dirfd` is
+    /// `AT_FDCWD` and relative to the directory `dirfd` refers to otherwise.
+    fn resolve_path_at(
+        &mut self,
+        dirfd: i32,
+        path: Cow<'_, Path>,
+    ) -> InterpResult<'tcx, Result<PathBuf, IoError>> {
+        let this = self.eval_context_mut();
+
+        if path.is_absolute() || dirfd == this.eval_libc_i32("AT_FDCWD") {
+            return interp_ok(Ok(path.into_owned()));
+        }
+        let Some(fd) = this.machine.fds.get(dirfd) else {
+            return interp_ok(Err(LibcError("EBADF")));
+        };
+        let Some(dir) = fd.downcast::<DirHandle>() else {
+            return interp_ok(Err(LibcError("ENOTDIR")));
+        };
+        interp_ok(Ok(dir.path.join(path)))
+    }
+
+    /// Opens the directory at `path` as a file descriptor. This backs `open` with `O_DIRECTORY`
+    /// or `O_PATH`.
+    fn open_dir_handle(
+        &mut self,
+        path: &Path,
+        follow_symlink: bool,
+        must_be_dir: bool,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let metadata =
+            if follow_symlink { std::fs::metadata(path) } else { std::fs::symlink_metadata(path) };
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+        if metadata.is_symlink() {
+            // Only reachable with `O_NOFOLLOW`.
+            return this.set_last_error_and_return_i32(LibcError("ELOOP"));
+        }
+        if !metadata.is_dir() {
+            if must_be_dir {
+                return this.set_last_error_and_return_i32(LibcError("ENOTDIR"));
+            }
+            throw_unsup_format!("`O_PATH` is only supported for directories");
+        }
+        let path = match std::path::absolute(path) {
+            Ok(path) => path,
+            Err(e) => return this.set_last_error_and_return_i32(e),
+        };
+
+        let fd = this.machine.fds.insert_new(DirHandle { path });
+        interp_ok(Scalar::from_i32(fd))
+    }
+
+    /// Reads a `mode_t` argument. It is only 16 bits wide on some targets.
+    fn read_mode_t(&mut self, mode_op: &OpTy<'tcx>) -> InterpResult<'tcx, u32> {
+        let this = self.eval_context_mut();
+        let mode = this.read_scalar(mode_op)?.to_uint(this.libc_ty_layout("mode_t").size)?;
+        interp_ok(mode.try_into().unwrap())
+    }
+
+    /// Reads the `times` argument of `utimensat` and `futimens`: a pointer to the new access and
+    /// modification timestamps, where either can be `UTIME_NOW` or `UTIME_OMIT`, or null to set
+    /// both to the current time of the machine clock. Returns `None` if a timestamp is invalid.
+    fn read_file_times(
+        &mut self,
+        times_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Option<RequestedTimes>> {
+        let this = self.eval_context_mut();
+
+        let now = this.machine.clock.system_time_now();
+        if this.ptr_is_null(this.read_pointer(times_op)?)? {
+            return interp_ok(Some(RequestedTimes {
+                accessed: Some(now),
+                modified: Some(now),
+                only_now: true,
+            }));
+        }
+
+        let utime_now = this.eval_libc("UTIME_NOW").to_target_isize(this)?;
+        let utime_omit = this.eval_libc("UTIME_OMIT").to_target_isize(this)?;
+        let times = this.deref_pointer_as(times_op, this.libc_array_ty_layout("timespec", 2))?;
+        let mut requested = RequestedTimes { accessed: None, modified: None, only_now: true };
+        for i in 0..2 {
+            let timespec = this.project_index(&times, i)?;
+            let nsec = this.project_field_named(&timespec, "tv_nsec")?;
+            let nsec = this.read_scalar(&nsec)?.to_target_isize(this)?;
+            let time = if nsec == utime_now {
+                now
+            } else if nsec == utime_omit {
+                continue;
+            } else {
+                let Some(time) = this
+                    .read_timespec(&timespec)?
+                    .and_then(|duration| SystemTime::UNIX_EPOCH.checked_add(duration))
+                else {
+                    return interp_ok(None);
+                };
+                requested.only_now = false;
+                time
+            };
+            if i == 0 {
+                requested.accessed = Some(time);
+            } else {
+                requested.modified = Some(time);
+            }
+        }
+        interp_ok(Some(requested))
+    }
+
+    /// The metadata of `fd` in the in-memory model that is used with isolation enabled, or `None`
+    /// if the model does not cover this kind of file description.
+    fn isolated_fd_metadata(&self, fd: &FileDescriptionRef) -> Option<IsolatedMetadata> {
+        let this = self.eval_context_ref();
+        if let Some(metadata) = this.machine.isolated_metadata.get(&fd.get_id()) {
+            Some(metadata.clone())
+        } else if fd.downcast::<VirtualFile>().is_some() {
+            Some(IsolatedMetadata::virtual_file(VirtualFileType::File))
+        } else if let Some(dir) = fd.downcast::<DirHandle>()
+            && this.is_virtual_path(&dir.path)
+        {
+            Some(IsolatedMetadata::virtual_file(VirtualFileType::Dir))
+        } else if fd.downcast::<io::Stdin>().is_some()
+            || fd.downcast::<io::Stdout>().is_some()
+            || fd.downcast::<io::Stderr>().is_some()
+            || fd.downcast::<NullOutput>().is_some()
+        {
+            Some(IsolatedMetadata::terminal())
+        } else {
+            None
+        }
+    }
+
+    /// The metadata of `path` in the in-memory model that is used with isolation enabled, or
+    /// `None` if the model does not cover this path. The host file system is not available with
+    /// isolation, so the model only covers the virtual procfs and sysfs.
+    fn isolated_path_metadata(
+        &mut self,
+        path: &Path,
+    ) -> InterpResult<'tcx, Option<Result<IsolatedMetadata, IoError>>> {
+        let this = self.eval_context_mut();
+        if !this.is_virtual_path(path) {
+            return interp_ok(None);
+        }
+        let Some(entry) = this.virtual_entry(path)? else {
+            return interp_ok(Some(Err(LibcError("ENOENT"))));
+        };
+        interp_ok(Some(Ok(IsolatedMetadata::virtual_file(entry.file_type()))))
+    }
+
+    /// Shared implementation of `fchmod`, `fchown` and `futimens` with isolation enabled: applies
+    /// `change` to the in-memory metadata of the file description. The host is never touched.
+    /// Returns `None` if the model does not cover this file description.
+    fn change_isolated_fd_metadata(
+        &mut self,
+        fd_num: i32,
+        change: impl FnOnce(&mut IsolatedMetadata) -> Result<(), IoError>,
+    ) -> InterpResult<'tcx, Option<Scalar>> {
+        let this = self.eval_context_mut();
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            return interp_ok(Some(this.set_last_error_and_return_i32(LibcError("EBADF"))?));
+        };
+        let Some(mut metadata) = this.isolated_fd_metadata(&fd) else {
+            return interp_ok(None);
+        };
+        if let Err(err) = change(&mut metadata) {
+            return interp_ok(Some(this.set_last_error_and_return_i32(err)?));
+        }
+        this.machine.isolated_metadata.insert(fd.get_id(), metadata);
+        interp_ok(Some(Scalar::from_i32(0)))
+    }
+
+    /// Shared implementation of `chmod`, `chown` and `utimensat` with isolation enabled: checks
+    /// whether `change` may be applied to the in-memory metadata of `path`. The model only covers
+    /// virtual files, which are owned by root and never change, so there is nothing to apply.
+    /// Returns `None` if the model does not cover this path.
+    fn change_isolated_path_metadata(
+        &mut self,
+        path: &Path,
+        change: impl FnOnce(&mut IsolatedMetadata) -> Result<(), IoError>,
+    ) -> InterpResult<'tcx, Option<Scalar>> {
+        let this = self.eval_context_mut();
+        let result = match this.isolated_path_metadata(path)? {
+            None => return interp_ok(None),
+            Some(Ok(mut metadata)) => change(&mut metadata),
+            Some(Err(err)) => Err(err),
+        };
+        interp_ok(Some(match result {
+            Ok(()) => Scalar::from_i32(0),
+            Err(err) => this.set_last_error_and_return_i32(err)?,
+        }))
+    }
+
+    /// Shared implementation of `link` and `linkat`.
+    fn link_at(
+        &mut self,
+        name: &str,
+        olddirfd: i32,
+        oldpath_op: &OpTy<'tcx>,
+        newdirfd: i32,
+        newpath_op: &OpTy<'tcx>,
+        follow_symlink: bool,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let oldpath = this.read_path_from_c_str(this.read_pointer(oldpath_op)?)?;
+        let newpath = this.read_path_from_c_str(this.read_pointer(newpath_op)?)?;
+
+        // With isolation, only the virtual files exist, and they cannot be linked.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(metadata) = this.isolated_path_metadata(&oldpath)? {
+                let err = metadata.err().unwrap_or(LibcError("EPERM"));
+                return this.set_last_error_and_return_i32(err);
+            }
+            this.reject_in_isolation(name, reject_with)?;
+            return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
+        }
+
+        let oldpath = match this.resolve_path_at(olddirfd, oldpath)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+        let newpath = match this.resolve_path_at(newdirfd, newpath)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        // `hard_link` does not follow symlinks, so we resolve them ourselves if requested.
+        let result = if follow_symlink { std::fs::canonicalize(oldpath) } else { Ok(oldpath) }
+            .and_then(|oldpath| std::fs::hard_link(oldpath, newpath));
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+    }
+
+    /// Shared implementation of `access` and `faccessat`.
+    fn access_at(
+        &mut self,
+        name: &str,
+        dirfd: i32,
+        path_op: &OpTy<'tcx>,
+        mode_op: &OpTy<'tcx>,
+        follow_symlink: bool,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+        let mode = this.read_scalar(mode_op)?.to_i32()?;
+
+        let r_ok = this.eval_libc_i32("R_OK");
+        let w_ok = this.eval_libc_i32("W_OK");
+        let x_ok = this.eval_libc_i32("X_OK");
+        if mode & !(r_ok | w_ok | x_ok) != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        // With isolation, check the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(metadata) = this.isolated_path_metadata(&path)? {
+                let mut wanted = 0;
+                for (bit, permission) in [(r_ok, 0o4), (w_ok, 0o2), (x_ok, 0o1)] {
+                    if mode & bit != 0 {
+                        wanted |= permission;
+                    }
+                }
+                let result = metadata.and_then(|metadata| {
+                    if metadata.may_access(wanted) { Ok(()) } else { Err(LibcError("EACCES")) }
+                });
+                return match result {
+                    Ok(()) => interp_ok(Scalar::from_i32(0)),
+                    Err(err) => this.set_last_error_and_return_i32(err),
+                };
+            }
+            this.reject_in_isolation(name, reject_with)?;
+            return this.set_last_error_and_return_i32(LibcError("EACCES"));
+        }
+
+        let path = match this.resolve_path_at(dirfd, path)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        // On Unix hosts, we ask the host, which knows about users and groups.
+        #[cfg(unix)]
+        let result = {
+            use std::os::unix::ffi::OsStrExt;
+
+            let mut host_mode = libc::F_OK;
+            for (target, host) in [(r_ok, libc::R_OK), (w_ok, libc::W_OK), (x_ok, libc::X_OK)] {
+                if mode & target != 0 {
+                    host_mode |= host;
+                }
+            }
+            let flags = if follow_symlink { 0 } else { libc::AT_SYMLINK_NOFOLLOW };
+            std::ffi::CString::new(path.into_os_string().as_bytes())
+                .map_err(io::Error::from)
+                .and_then(|path| {
+                    let ret =
+                        unsafe { libc::faccessat(libc::AT_FDCWD, path.as_ptr(), host_mode, flags) };
+                    if ret == 0 { Ok(()) } else { Err(io::Error::last_os_error()) }
+                })
+        };
+        // Elsewhere, we can only check existence and the read-only flag.
+        #[cfg(not(unix))]
+        let result =
+            if follow_symlink { std::fs::metadata(path) } else { std::fs::symlink_metadata(path) }
+                .and_then(|metadata| {
+                    if mode & w_ok != 0 && metadata.permissions().readonly() {
+                        Err(io::ErrorKind::PermissionDenied.into())
+                    } else {
+                        Ok(())
+                    }
+                });
+
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+    }
+
+    /// Shared implementation of `chown` and `lchown`.
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn chown_path(
+        &mut self,
+        name: &str,
+        path_op: &OpTy<'tcx>,
+        owner_op: &OpTy<'tcx>,
+        group_op: &OpTy<'tcx>,
+        follow_symlink: bool,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+        let owner = owner_id(this.read_scalar(owner_op)?.to_u32()?);
+        let group = owner_id(this.read_scalar(group_op)?.to_u32()?);
+
+        // With isolation, only change the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(ret) =
+                this.change_isolated_path_metadata(&path, |metadata| metadata.chown(owner, group))?
+            {
+                return interp_ok(ret);
+            }
+            this.reject_in_isolation(name, reject_with)?;
+            return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
+        }
+
+        #[cfg(unix)]
+        {
+            let result = if follow_symlink {
+                std::os::unix::fs::chown(path, owner, group)
+            } else {
+                std::os::unix::fs::lchown(path, owner, group)
+            };
+            interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+        }
+        #[cfg(not(unix))]
+        {
+            throw_unsup_format!("{name} is only supported on Unix hosts");
+        }
+    }
+}
+
+/// The entries of an open directory.
+#[derive(Debug)]
+enum DirEntries {
+    /// The directory reader on the host.
+    Host(ReadDir),
+    /// The remaining entries of a directory of the virtual procfs or sysfs.
+    Virtual(std::vec::IntoIter<(String, VirtualFileType)>),
 }
 
 /// An open directory, tracked by DirHandler.
 #[derive(Debug)]
 struct OpenDir {
-    /// The directory reader on the host.
-    read_dir: ReadDir,
+    entries: DirEntries,
     /// The most recent entry returned by readdir().
     /// Will be freed by the next call.
     entry: Option<Pointer>,
+    /// The directory file descriptor underlying this stream, as returned by `dirfd`.
+    /// It is closed by closedir().
+    fd: i32,
 }
 
 impl OpenDir {
-    fn new(read_dir: ReadDir) -> Self {
-        Self { read_dir, entry: None }
+    fn new(entries: DirEntries, fd: i32) -> Self {
+        Self { entries, entry: None, fd }
     }
 }
 
@@ -408,12 +935,20 @@ pub struct DirTable {
 
 impl DirTable {
     #[expect(clippy::arithmetic_side_effects)]
-    fn insert_new(&mut self, read_dir: ReadDir) -> u64 {
+    fn insert_new(&mut self, entries: DirEntries, fd: i32) -> u64 {
         let id = self.next_id;
         self.next_id += 1;
-        self.streams.try_insert(id, OpenDir::new(read_dir)).unwrap();
+        self.streams.try_insert(id, OpenDir::new(entries, fd)).unwrap();
         id
     }
+
+    /// Whether `dirp` is a directory stream of the virtual procfs or sysfs, which are available
+    /// even with isolation enabled.
+    fn is_virtual(&self, dirp: u64) -> bool {
+        self.streams
+            .get(&dirp)
+            .is_some_and(|open_dir| matches!(open_dir.entries, DirEntries::Virtual(_)))
+    }
 }
 
 impl Default for DirTable {
@@ -452,6 +987,149 @@ fn maybe_sync_file(
     }
 }
 
+/// Converts the permission bits of a target `mode_t` to host permissions. Hosts other than Unix
+/// only support toggling the read-only flag, which is why we might need the current metadata.
+#[cfg_attr(unix, allow(unused_variables))]
+fn permissions_from_mode(
+    mode: u32,
+    metadata: impl FnOnce() -> io::Result<Metadata>,
+) -> io::Result<Permissions> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        Ok(Permissions::from_mode(mode))
+    }
+    #[cfg(not(unix))]
+    {
+        let mut permissions = metadata()?.permissions();
+        permissions.set_readonly(mode & 0o222 == 0);
+        Ok(permissions)
+    }
+}
+
+/// Interprets a `uid_t` or `gid_t` argument of the `chown` family, where `-1` means that the ID
+/// should not be changed.
+fn owner_id(id: u32) -> Option<u32> {
+    if id == u32::MAX { None } else { Some(id) }
+}
+
+/// The in-memory metadata model used with isolation enabled, keyed by file description.
+pub type IsolatedMetadataTable = BTreeMap<FdId, IsolatedMetadata>;
+
+/// The timestamps requested by `utimensat` or `futimens`. `None` leaves a timestamp unchanged.
+struct RequestedTimes {
+    accessed: Option<SystemTime>,
+    modified: Option<SystemTime>,
+    /// Whether every timestamp that gets set is set to the current time. This only requires write
+    /// access to the file instead of owning it.
+    only_now: bool,
+}
+
+impl RequestedTimes {
+    fn to_file_times(&self) -> FileTimes {
+        let mut file_times = FileTimes::new();
+        if let Some(accessed) = self.accessed {
+            file_times = file_times.set_accessed(accessed);
+        }
+        if let Some(modified) = self.modified {
+            file_times = file_times.set_modified(modified);
+        }
+        file_times
+    }
+}
+
+/// The metadata of a file in the in-memory model that is used with isolation enabled. Changing
+/// metadata with isolation never touches the host; the changes are only recorded in
+/// `MiriMachine::isolated_metadata` for the file description they were made on. The model covers
+/// the standard streams, which belong to the user the program runs as, and the virtual procfs and
+/// sysfs, whose files belong to root. Times start at the Unix epoch, like the virtual clock.
+#[derive(Clone, Debug)]
+pub struct IsolatedMetadata {
+    /// The `S_IF*` constant for the file type.
+    file_type: &'static str,
+    /// The permission bits of the mode.
+    permissions: u32,
+    uid: u32,
+    gid: u32,
+    accessed: SystemTime,
+    modified: SystemTime,
+}
+
+impl IsolatedMetadata {
+    fn virtual_file(file_type: VirtualFileType) -> Self {
+        IsolatedMetadata {
+            file_type: file_type.mode_type(),
+            permissions: file_type.permissions(),
+            uid: 0,
+            gid: 0,
+            accessed: SystemTime::UNIX_EPOCH,
+            modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    fn terminal() -> Self {
+        IsolatedMetadata {
+            file_type: "S_IFCHR",
+            permissions: 0o620,
+            uid: UID,
+            gid: GID,
+            accessed: SystemTime::UNIX_EPOCH,
+            modified: SystemTime::UNIX_EPOCH,
+        }
+    }
+
+    /// Whether the program may access the file in all the ways given by `wanted`, a combination
+    /// of the read (4), write (2) and execute (1) permission bits.
+    fn may_access(&self, wanted: u32) -> bool {
+        let shift = if self.uid == UID {
+            6
+        } else if self.gid == GID {
+            3
+        } else {
+            0
+        };
+        self.permissions.strict_shr(shift) & wanted == wanted
+    }
+
+    fn chmod(&mut self, mode: u32) -> Result<(), IoError> {
+        if self.uid != UID {
+            return Err(LibcError("EPERM"));
+        }
+        self.permissions = mode & 0o7777;
+        Ok(())
+    }
+
+    /// Without privileges, the owner cannot be changed, and the group can only be changed by the
+    /// owner to a group the owner is a member of.
+    fn chown(&mut self, owner: Option<u32>, group: Option<u32>) -> Result<(), IoError> {
+        let new_gid = group.unwrap_or(self.gid);
+        if owner.is_some_and(|owner| owner != self.uid)
+            || (new_gid != self.gid && (self.uid != UID || new_gid != GID))
+        {
+            return Err(LibcError("EPERM"));
+        }
+        self.gid = new_gid;
+        Ok(())
+    }
+
+    fn set_times(&mut self, times: &RequestedTimes) -> Result<(), IoError> {
+        if times.accessed.is_none() && times.modified.is_none() {
+            return Ok(());
+        }
+        if self.uid != UID {
+            if !times.only_now {
+                return Err(LibcError("EPERM"));
+            }
+            if !self.may_access(0o2) {
+                return Err(LibcError("EACCES"));
+            }
+        }
+        self.accessed = times.accessed.unwrap_or(self.accessed);
+        self.modified = times.modified.unwrap_or(self.modified);
+        Ok(())
+    }
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn open(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
@@ -463,6 +1141,55 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let path = this.read_path_from_c_str(path_raw)?;
         let flag = this.read_scalar(flag)?.to_i32()?;
 
+        let o_creat = this.eval_libc_i32("O_CREAT");
+        let mode = if flag & o_creat == o_creat {
+            // Get the mode.  On macOS, the argument type `mode_t` is actually `u16`, but
+            // C integer promotion rules mean that on the ABI level, it gets passed as `u32`
+            // (see https://github.com/rust-lang/rust/issues/71915).
+            let [_, _, mode] = check_min_arg_count("open(pathname, O_CREAT, ...)", args)?;
+            Some(this.read_scalar(mode)?.to_u32()?)
+        } else {
+            None
+        };
+
+        let at_fdcwd = this.eval_libc_i32("AT_FDCWD");
+        this.open_at("`open`", at_fdcwd, path, flag, mode)
+    }
+
+    fn openat(&mut self, args: &[OpTy<'tcx>]) -> InterpResult<'tcx, Scalar> {
+        let [dirfd, path_raw, flag] = check_min_arg_count("openat", args)?;
+
+        let this = self.eval_context_mut();
+
+        let dirfd = this.read_scalar(dirfd)?.to_i32()?;
+        let path_raw = this.read_pointer(path_raw)?;
+        let path = this.read_path_from_c_str(path_raw)?;
+        let flag = this.read_scalar(flag)?.to_i32()?;
+
+        let o_creat = this.eval_libc_i32("O_CREAT");
+        let mode = if flag & o_creat == o_creat {
+            // See `open` for why this is read as `u32`.
+            let [_, _, _, mode] =
+                check_min_arg_count("openat(dirfd, pathname, O_CREAT, ...)", args)?;
+            Some(this.read_scalar(mode)?.to_u32()?)
+        } else {
+            None
+        };
+
+        this.open_at("`openat`", dirfd, path, flag, mode)
+    }
+
+    /// Shared implementation of `open` and `openat`. `mode` is only present if `O_CREAT` is set.
+    fn open_at(
+        &mut self,
+        name: &str,
+        dirfd: i32,
+        path: Cow<'_, Path>,
+        flag: i32,
+        mode: Option<u32>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
         let mut options = OpenOptions::new();
 
         let o_rdonly = this.eval_libc_i32("O_RDONLY");
@@ -506,17 +1233,14 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         }
         let o_creat = this.eval_libc_i32("O_CREAT");
         if flag & o_creat == o_creat {
-            // Get the mode.  On macOS, the argument type `mode_t` is actually `u16`, but
-            // C integer promotion rules mean that on the ABI level, it gets passed as `u32`
-            // (see https://github.com/rust-lang/rust/issues/71915).
-            let [_, _, mode] = check_min_arg_count("open(pathname, O_CREAT, ...)", args)?;
-            let mode = this.read_scalar(mode)?.to_u32()?;
+            let mode = mode.unwrap();
 
             #[cfg(unix)]
             {
-                // Support all modes on UNIX host
+                // Support all modes on UNIX host. We apply the emulated umask here; the host
+                // applies its own umask on top of that.
                 use std::os::unix::fs::OpenOptionsExt;
-                options.mode(mode);
+                options.mode(mode & !this.machine.umask);
             }
             #[cfg(not(unix))]
             {
@@ -560,32 +1284,76 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 use std::os::unix::fs::OpenOptionsExt;
                 options.custom_flags(libc::O_NOFOLLOW);
             }
-            // Strictly speaking, this emulation is not equivalent to the O_NOFOLLOW flag behavior:
-            // the path could change between us checking it here and the later call to `open`.
-            // But it's good enough for Miri purposes.
-            #[cfg(not(unix))]
-            {
-                // O_NOFOLLOW only fails when the trailing component is a symlink;
-                // the entire rest of the path can still contain symlinks.
-                if path.is_symlink() {
-                    return this.set_last_error_and_return_i32(LibcError("ELOOP"));
-                }
-            }
             mirror |= o_nofollow;
         }
 
+        // Directories do not get a host file handle, see `DirHandle`.
+        let mut directory = false;
+        let o_directory = this.eval_libc_i32("O_DIRECTORY");
+        if flag & o_directory == o_directory {
+            directory = true;
+            mirror |= o_directory;
+        }
+        if this.tcx.sess.target.os == "linux" {
+            let o_path = this.eval_libc_i32("O_PATH");
+            if flag & o_path == o_path {
+                directory = true;
+                mirror |= o_path;
+            }
+        }
+
         // If `flag` is not equal to `mirror`, there is an unsupported option enabled in `flag`,
         // then we throw an error.
         if flag != mirror {
             throw_unsup_format!("unsupported flags {:#x}", flag & !mirror);
         }
 
+        if directory && flag & o_creat == o_creat {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        if directory && writable {
+            return this.set_last_error_and_return_i32(LibcError("EISDIR"));
+        }
+
+        // Resolve `dirfd` first, so that relative paths into the virtual procfs and sysfs are
+        // recognized.
+        let path = match this.resolve_path_at(dirfd, path)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        // The virtual procfs and sysfs are available even with isolation enabled.
+        if this.is_virtual_path(&path) {
+            if directory {
+                throw_unsup_format!(
+                    "{name}: `O_DIRECTORY` and `O_PATH` are not supported in the virtual procfs and sysfs"
+                );
+            }
+            return this.open_virtual_file(&path, writable);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`open`", reject_with)?;
+            this.reject_in_isolation(name, reject_with)?;
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
+        if directory {
+            return this.open_dir_handle(&path, flag & o_nofollow == 0, flag & o_directory != 0);
+        }
+
+        // Strictly speaking, this emulation is not equivalent to the O_NOFOLLOW flag behavior:
+        // the path could change between us checking it here and the later call to `open`.
+        // But it's good enough for Miri purposes.
+        #[cfg(not(unix))]
+        {
+            // O_NOFOLLOW only fails when the trailing component is a symlink;
+            // the entire rest of the path can still contain symlinks.
+            if flag & o_nofollow == o_nofollow && path.is_symlink() {
+                return this.set_last_error_and_return_i32(LibcError("ELOOP"));
+            }
+        }
+
         let fd = options
             .open(path)
             .map(|file| this.machine.fds.insert_new(FileHandle { file, writable }));
@@ -640,6 +1408,38 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
 
+    fn unlinkat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx>,
+        path_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        let at_removedir = this.eval_libc_i32("AT_REMOVEDIR");
+        if flags & !at_removedir != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`unlinkat`", reject_with)?;
+            return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
+        }
+
+        let path = match this.resolve_path_at(dirfd, path)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        let result = if flags & at_removedir != 0 { remove_dir(path) } else { remove_file(path) };
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|_| 0))?))
+    }
+
     fn symlink(
         &mut self,
         target_op: &OpTy<'tcx>,
@@ -747,8 +1547,10 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         let fd = this.read_scalar(fd_op)?.to_i32()?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        // Reject if isolation is enabled, unless the in-memory metadata model covers `fd`.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.machine.fds.get(fd).is_some_and(|fd| this.isolated_fd_metadata(&fd).is_some())
+        {
             this.reject_in_isolation("`fstat`", reject_with)?;
             // Set error code as "EBADF" (bad fd)
             return this.set_last_error_and_return_i32(LibcError("EBADF"));
@@ -761,6 +1563,51 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         interp_ok(Scalar::from_i32(this.macos_fbsd_solaris_write_buf(metadata, buf_op)?))
     }
 
+    fn macos_fbsd_solaris_fstatat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx>,
+        path_op: &OpTy<'tcx>,
+        buf_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        if !matches!(&*this.tcx.sess.target.os, "macos" | "freebsd" | "solaris" | "illumos") {
+            panic!(
+                "`macos_fbsd_solaris_fstatat` should not be called on {}",
+                this.tcx.sess.target.os
+            );
+        }
+
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        let at_symlink_nofollow = this.eval_libc_i32("AT_SYMLINK_NOFOLLOW");
+        if flags & !at_symlink_nofollow != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`fstatat`", reject_with)?;
+            return this.set_last_error_and_return_i32(LibcError("EACCES"));
+        }
+
+        let path = match this.resolve_path_at(dirfd, path)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        let follow_symlink = flags & at_symlink_nofollow == 0;
+        let metadata = match FileMetadata::from_path(this, &path, follow_symlink)? {
+            Ok(metadata) => metadata,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        interp_ok(Scalar::from_i32(this.macos_fbsd_solaris_write_buf(metadata, buf_op)?))
+    }
+
     fn linux_statx(
         &mut self,
         dirfd_op: &OpTy<'tcx>,    // Should be an `int`
@@ -790,36 +1637,29 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         // See <https://github.com/rust-lang/rust/pull/79196> for a discussion of argument sizes.
         let at_empty_path = this.eval_libc_i32("AT_EMPTY_PATH");
         let empty_path_flag = flags & at_empty_path == at_empty_path;
-        // We only support:
-        // * interpreting `path` as an absolute directory,
-        // * interpreting `path` as a path relative to `dirfd` when the latter is `AT_FDCWD`, or
-        // * interpreting `dirfd` as any file descriptor when `path` is empty and AT_EMPTY_PATH is
-        // set.
-        // Other behaviors cannot be tested from `libstd` and thus are not implemented. If you
-        // found this error, please open an issue reporting it.
-        if !(path.is_absolute()
-            || dirfd == this.eval_libc_i32("AT_FDCWD")
-            || (path.as_os_str().is_empty() && empty_path_flag))
-        {
-            throw_unsup_format!(
-                "using statx is only supported with absolute paths, relative paths with the file \
-                descriptor `AT_FDCWD`, and empty paths with the `AT_EMPTY_PATH` flag set and any \
-                file descriptor"
-            )
-        }
+
+        // The virtual procfs and sysfs, and the file descriptions covered by the in-memory
+        // metadata model, are available even with isolation enabled.
+        let in_model = if path.as_os_str().is_empty() && empty_path_flag {
+            this.machine.fds.get(dirfd).is_some_and(|fd| this.isolated_fd_metadata(&fd).is_some())
+        } else {
+            this.resolve_path_at(dirfd, Cow::Borrowed(&path))?
+                .is_ok_and(|path| this.is_virtual_path(&path))
+        };
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !in_model
+        {
             this.reject_in_isolation("`statx`", reject_with)?;
             let ecode = if path.is_absolute() || dirfd == this.eval_libc_i32("AT_FDCWD") {
                 // since `path` is provided, either absolute or
                 // relative to CWD, `EACCES` is the most relevant.
                 LibcError("EACCES")
             } else {
-                // `dirfd` is set to target file, and `path` is empty
-                // (or we would have hit the `throw_unsup_format`
-                // above). `EACCES` would violate the spec.
-                assert!(empty_path_flag);
+                // `path` is relative to `dirfd`, or empty with `AT_EMPTY_PATH`. Either way,
+                // `dirfd` cannot be a file or directory that was opened, since that is rejected
+                // in isolation. `EACCES` would violate the spec.
                 LibcError("EBADF")
             };
             return this.set_last_error_and_return_i32(ecode);
@@ -829,7 +1669,12 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         // However `statx` is allowed to return information that was not requested or to not
         // return information that was requested. This `mask` represents the information we can
         // actually provide for any target.
-        let mut mask = this.eval_libc_u32("STATX_TYPE") | this.eval_libc_u32("STATX_SIZE");
+        let mut mask = this.eval_libc_u32("STATX_TYPE")
+            | this.eval_libc_u32("STATX_MODE")
+            | this.eval_libc_u32("STATX_NLINK")
+            | this.eval_libc_u32("STATX_UID")
+            | this.eval_libc_u32("STATX_GID")
+            | this.eval_libc_u32("STATX_SIZE");
 
         // If the `AT_SYMLINK_NOFOLLOW` flag is set, we query the file's metadata without following
         // symbolic links.
@@ -837,10 +1682,14 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         // If the path is empty, and the AT_EMPTY_PATH flag is set, we query the open file
         // represented by dirfd, whether it's a directory or otherwise.
+        // Otherwise, `path` is resolved relative to `dirfd`.
         let metadata = if path.as_os_str().is_empty() && empty_path_flag {
             FileMetadata::from_fd_num(this, dirfd)?
         } else {
-            FileMetadata::from_path(this, &path, follow_symlink)?
+            match this.resolve_path_at(dirfd, path.into())? {
+                Ok(path) => FileMetadata::from_path(this, &path, follow_symlink)?,
+                Err(err) => Err(err),
+            }
         };
         let metadata = match metadata {
             Ok(metadata) => metadata,
@@ -848,9 +1697,8 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         };
 
         // The `mode` field specifies the type of the file and the permissions over the file for
-        // the owner, its group and other users. Given that we can only provide the file type
-        // without using platform specific methods, we only set the bits corresponding to the file
-        // type. This should be an `__u16` but `libc` provides its values as `u32`.
+        // the owner, its group and other users. This should be an `__u16` but `libc` provides its
+        // values as `u32`.
         let mode: u16 = metadata
             .mode
             .to_u32()?
@@ -889,9 +1737,9 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 ("stx_mask", mask.into()),
                 ("stx_blksize", 0),
                 ("stx_attributes", 0),
-                ("stx_nlink", 0),
-                ("stx_uid", 0),
-                ("stx_gid", 0),
+                ("stx_nlink", metadata.nlink.into()),
+                ("stx_uid", metadata.uid.into()),
+                ("stx_gid", metadata.gid.into()),
                 ("stx_mode", mode.into()),
                 ("stx_ino", 0),
                 ("stx_size", metadata.size.into()),
@@ -968,7 +1816,99 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
     }
 
-    fn mkdir(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+    fn renameat(
+        &mut self,
+        olddirfd_op: &OpTy<'tcx>,
+        oldpath_op: &OpTy<'tcx>,
+        newdirfd_op: &OpTy<'tcx>,
+        newpath_op: &OpTy<'tcx>,
+        flags_op: Option<&OpTy<'tcx>>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let olddirfd = this.read_scalar(olddirfd_op)?.to_i32()?;
+        let oldpath_ptr = this.read_pointer(oldpath_op)?;
+        let newdirfd = this.read_scalar(newdirfd_op)?.to_i32()?;
+        let newpath_ptr = this.read_pointer(newpath_op)?;
+        // Only `renameat2` has flags.
+        let flags = match flags_op {
+            Some(flags_op) => this.read_scalar(flags_op)?.to_u32()?,
+            None => 0,
+        };
+
+        if this.ptr_is_null(oldpath_ptr)? || this.ptr_is_null(newpath_ptr)? {
+            return this.set_last_error_and_return_i32(LibcError("EFAULT"));
+        }
+
+        let oldpath = this.read_path_from_c_str(oldpath_ptr)?;
+        let newpath = this.read_path_from_c_str(newpath_ptr)?;
+
+        let noreplace = if flags_op.is_some() {
+            let rename_noreplace = this.eval_libc_u32("RENAME_NOREPLACE");
+            let rename_exchange = this.eval_libc_u32("RENAME_EXCHANGE");
+            if flags & rename_exchange != 0 {
+                throw_unsup_format!("`renameat2`: `RENAME_EXCHANGE` is not supported");
+            }
+            if flags & !rename_noreplace != 0 {
+                throw_unsup_format!("`renameat2`: unsupported flags {:#x}", flags);
+            }
+            flags & rename_noreplace != 0
+        } else {
+            false
+        };
+
+        // Reject if isolation is enabled.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            this.reject_in_isolation("`renameat`", reject_with)?;
+            return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
+        }
+
+        let oldpath = match this.resolve_path_at(olddirfd, oldpath)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+        let newpath = match this.resolve_path_at(newdirfd, newpath)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
+        // Strictly speaking, this emulation of `RENAME_NOREPLACE` is not atomic: the destination
+        // could be created between us checking it here and the later call to `rename`.
+        // But it's good enough for Miri purposes.
+        if noreplace && newpath.symlink_metadata().is_ok() {
+            return this.set_last_error_and_return_i32(LibcError("EEXIST"));
+        }
+
+        let result = rename(oldpath, newpath).map(|_| 0);
+
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result)?))
+    }
+
+    fn mkdir(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let at_fdcwd = this.eval_libc_i32("AT_FDCWD");
+        this.mkdir_at("`mkdir`", at_fdcwd, path_op, mode_op)
+    }
+
+    fn mkdirat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx>,
+        path_op: &OpTy<'tcx>,
+        mode_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        this.mkdir_at("`mkdirat`", dirfd, path_op, mode_op)
+    }
+
+    /// Shared implementation of `mkdir` and `mkdirat`.
+    fn mkdir_at(
+        &mut self,
+        name: &str,
+        dirfd: i32,
+        path_op: &OpTy<'tcx>,
+        mode_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
         let this = self.eval_context_mut();
 
         #[cfg_attr(not(unix), allow(unused_variables))]
@@ -982,10 +1922,15 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`mkdir`", reject_with)?;
+            this.reject_in_isolation(name, reject_with)?;
             return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
         }
 
+        let path = match this.resolve_path_at(dirfd, path)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+
         #[cfg_attr(not(unix), allow(unused_mut))]
         let mut builder = DirBuilder::new();
 
@@ -994,7 +1939,8 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         #[cfg(unix)]
         {
             use std::os::unix::fs::DirBuilderExt;
-            builder.mode(mode);
+            // Apply the emulated umask; the host applies its own umask on top of that.
+            builder.mode(mode & !this.machine.umask);
         }
 
         let result = builder.create(path).map(|_| 0i32);
@@ -1023,6 +1969,14 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         let name = this.read_path_from_c_str(this.read_pointer(name_op)?)?;
 
+        // The virtual procfs and sysfs are available even with isolation enabled.
+        if this.is_virtual_path(&name) {
+            let result = this
+                .virtual_dir_entries(&name)?
+                .map(|entries| (DirEntries::Virtual(entries.into_iter()), name.into_owned()));
+            return this.insert_dir_stream(result);
+        }
+
         // Reject if isolation is enabled.
         if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
             this.reject_in_isolation("`opendir`", reject_with)?;
@@ -1030,15 +1984,44 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             return interp_ok(Scalar::null_ptr(this));
         }
 
-        let result = read_dir(name);
+        let result = read_dir(&name)
+            .and_then(|dir_iter| Ok((DirEntries::Host(dir_iter), std::path::absolute(&name)?)));
+        this.insert_dir_stream(result.map_err(IoError::from))
+    }
 
-        match result {
-            Ok(dir_iter) => {
-                let id = this.machine.dirs.insert_new(dir_iter);
+    fn fdopendir(&mut self, fd_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
 
-                // The libc API for opendir says that this method returns a pointer to an opaque
-                // structure, but we are returning an ID number. Thus, pass it as a scalar of
-                // pointer width.
+        let fd_num = this.read_scalar(fd_op)?.to_i32()?;
+
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            this.set_last_error(LibcError("EBADF"))?;
+            return interp_ok(Scalar::null_ptr(this));
+        };
+        let Some(DirHandle { path }) = fd.downcast::<DirHandle>() else {
+            this.set_last_error(LibcError("ENOTDIR"))?;
+            return interp_ok(Scalar::null_ptr(this));
+        };
+        let path = path.clone();
+        drop(fd);
+
+        let result = if this.is_virtual_path(&path) {
+            // The virtual procfs and sysfs are available even with isolation enabled.
+            this.virtual_dir_entries(&path)?.map(|entries| DirEntries::Virtual(entries.into_iter()))
+        } else {
+            // Reject if isolation is enabled.
+            if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                this.reject_in_isolation("`fdopendir`", reject_with)?;
+                this.set_last_error(LibcError("EBADF"))?;
+                return interp_ok(Scalar::null_ptr(this));
+            }
+            read_dir(path).map(DirEntries::Host).map_err(IoError::from)
+        };
+
+        match result {
+            Ok(entries) => {
+                // The stream now owns `fd_num`; it gets closed by `closedir`.
+                let id = this.machine.dirs.insert_new(entries, fd_num);
                 interp_ok(Scalar::from_target_usize(id, this))
             }
             Err(e) => {
@@ -1048,6 +2031,17 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         }
     }
 
+    fn dirfd(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let dirp = this.read_target_usize(dirp_op)?;
+
+        let open_dir = this.machine.dirs.streams.get(&dirp).ok_or_else(|| {
+            err_unsup_format!("the DIR pointer passed to dirfd did not come from opendir")
+        })?;
+        interp_ok(Scalar::from_i32(open_dir.fd))
+    }
+
     fn linux_readdir64(&mut self, dirp_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let this = self.eval_context_mut();
 
@@ -1056,18 +2050,16 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.machine.dirs.is_virtual(dirp)
+        {
             this.reject_in_isolation("`readdir`", reject_with)?;
             this.set_last_error(LibcError("EBADF"))?;
             return interp_ok(Scalar::null_ptr(this));
         }
 
-        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
-            err_unsup_format!("the DIR pointer passed to readdir64 did not come from opendir")
-        })?;
-
-        let entry = match open_dir.read_dir.next() {
-            Some(Ok(dir_entry)) => {
+        let entry = match this.next_dir_entry("readdir64", dirp)? {
+            Some(Ok((mut name, ino, file_type))) => {
                 // Write the directory entry into a newly allocated buffer.
                 // The name is written with write_bytes, while the rest of the
                 // dirent64 struct is written using write_int_fields.
@@ -1081,7 +2073,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 //     pub d_name: [c_char; 256],
                 // }
 
-                let mut name = dir_entry.file_name(); // not a Path as there are no separators!
                 name.push("\0"); // Add a NUL terminator
                 let name_bytes = name.as_encoded_bytes();
                 let name_len = u64::try_from(name_bytes.len()).unwrap();
@@ -1097,15 +2088,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 )?;
                 let entry: Pointer = entry.into();
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
-
-                let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
-
                 this.write_int_fields_named(
                     &[
                         ("d_ino", ino.into()),
@@ -1155,17 +2137,16 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.machine.dirs.is_virtual(dirp)
+        {
             this.reject_in_isolation("`readdir_r`", reject_with)?;
             // Return error code, do *not* set `errno`.
             return interp_ok(this.eval_libc("EBADF"));
         }
 
-        let open_dir = this.machine.dirs.streams.get_mut(&dirp).ok_or_else(|| {
-            err_unsup_format!("the DIR pointer passed to readdir_r did not come from opendir")
-        })?;
-        interp_ok(match open_dir.read_dir.next() {
-            Some(Ok(dir_entry)) => {
+        interp_ok(match this.next_dir_entry("readdir_r", dirp)? {
+            Some(Ok((file_name, ino, file_type))) => {
                 // Write into entry, write pointer to result, return 0 on success.
                 // The name is written with write_os_str_to_c_str, while the rest of the
                 // dirent struct is written using write_int_fields.
@@ -1183,7 +2164,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let entry_place = this.deref_pointer_as(entry_op, this.libc_ty_layout("dirent"))?;
                 let name_place = this.project_field_named(&entry_place, "d_name")?;
 
-                let file_name = dir_entry.file_name(); // not a Path as there are no separators!
                 let (name_fits, file_name_buf_len) = this.write_os_str_to_c_str(
                     &file_name,
                     name_place.ptr(),
@@ -1196,15 +2176,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                     );
                 }
 
-                // If the host is a Unix system, fill in the inode number with its real value.
-                // If not, use 0 as a fallback value.
-                #[cfg(unix)]
-                let ino = std::os::unix::fs::DirEntryExt::ino(&dir_entry);
-                #[cfg(not(unix))]
-                let ino = 0u64;
-
-                let file_type = this.file_type_to_d_type(dir_entry.file_type())?;
-
                 // Common fields.
                 this.write_int_fields_named(
                     &[
@@ -1262,7 +2233,9 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let dirp = this.read_target_usize(dirp_op)?;
 
         // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op
+            && !this.machine.dirs.is_virtual(dirp)
+        {
             this.reject_in_isolation("`closedir`", reject_with)?;
             return this.set_last_error_and_return_i32(LibcError("EBADF"));
         }
@@ -1273,10 +2246,16 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         if let Some(entry) = open_dir.entry.take() {
             this.deallocate_ptr(entry, None, MiriMemoryKind::Runtime.into())?;
         }
+        // Close the underlying directory file descriptor. Like with a real libc, this closes
+        // whatever is behind that fd number if the program (incorrectly) closed it already.
+        let result = match this.machine.fds.remove(open_dir.fd) {
+            Some(fd) => fd.close(this.machine.communicate(), this)?,
+            None => Ok(()),
+        };
         // We drop the `open_dir`, which will close the host dir handle.
         drop(open_dir);
 
-        interp_ok(Scalar::from_i32(0))
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0i32))?))
     }
 
     fn ftruncate64(&mut self, fd_num: i32, length: i128) -> InterpResult<'tcx, Scalar> {
@@ -1428,14 +2407,18 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let buf = this.read_pointer(buf_op)?;
         let bufsize = this.read_target_usize(bufsize_op)?;
 
-        // Reject if isolation is enabled.
-        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
-            this.reject_in_isolation("`readlink`", reject_with)?;
-            this.set_last_error(LibcError("EACCES"))?;
-            return interp_ok(-1);
-        }
-
-        let result = std::fs::read_link(pathname);
+        let result = if this.is_virtual_path(&pathname) {
+            // The virtual procfs and sysfs are available even with isolation enabled.
+            this.virtual_link_target(&pathname)?
+        } else {
+            // Reject if isolation is enabled.
+            if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                this.reject_in_isolation("`readlink`", reject_with)?;
+                this.set_last_error(LibcError("EACCES"))?;
+                return interp_ok(-1);
+            }
+            std::fs::read_link(pathname).map_err(IoError::from)
+        };
         match result {
             Ok(resolved) => {
                 // 'readlink' truncates the resolved path if the provided buffer is not large
@@ -1460,6 +2443,301 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         }
     }
 
+    fn link(
+        &mut self,
+        oldpath_op: &OpTy<'tcx>,
+        newpath_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let at_fdcwd = this.eval_libc_i32("AT_FDCWD");
+        this.link_at("`link`", at_fdcwd, oldpath_op, at_fdcwd, newpath_op, false)
+    }
+
+    fn linkat(
+        &mut self,
+        olddirfd_op: &OpTy<'tcx>,
+        oldpath_op: &OpTy<'tcx>,
+        newdirfd_op: &OpTy<'tcx>,
+        newpath_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let olddirfd = this.read_scalar(olddirfd_op)?.to_i32()?;
+        let newdirfd = this.read_scalar(newdirfd_op)?.to_i32()?;
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        let at_symlink_follow = this.eval_libc_i32("AT_SYMLINK_FOLLOW");
+        if flags & !at_symlink_follow != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let follow_symlink = flags & at_symlink_follow != 0;
+
+        this.link_at("`linkat`", olddirfd, oldpath_op, newdirfd, newpath_op, follow_symlink)
+    }
+
+    fn chmod(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let path = this.read_path_from_c_str(this.read_pointer(path_op)?)?;
+        let mode = this.read_mode_t(mode_op)?;
+
+        // With isolation, only change the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(ret) =
+                this.change_isolated_path_metadata(&path, |metadata| metadata.chmod(mode))?
+            {
+                return interp_ok(ret);
+            }
+            this.reject_in_isolation("`chmod`", reject_with)?;
+            return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
+        }
+
+        let result = permissions_from_mode(mode, || std::fs::metadata(&path))
+            .and_then(|permissions| std::fs::set_permissions(&path, permissions));
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+    }
+
+    fn fchmod(&mut self, fd_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(fd_op)?.to_i32()?;
+        let mode = this.read_mode_t(mode_op)?;
+
+        // With isolation, only change the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(ret) =
+                this.change_isolated_fd_metadata(fd_num, |metadata| metadata.chmod(mode))?
+            {
+                return interp_ok(ret);
+            }
+            this.reject_in_isolation("`fchmod`", reject_with)?;
+            // Set error code as "EBADF" (bad fd)
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        }
+
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        };
+        let result = if let Some(FileHandle { file, .. }) = fd.downcast::<FileHandle>() {
+            permissions_from_mode(mode, || file.metadata())
+                .and_then(|permissions| file.set_permissions(permissions))
+        } else if let Some(DirHandle { path }) = fd.downcast::<DirHandle>() {
+            permissions_from_mode(mode, || std::fs::metadata(path))
+                .and_then(|permissions| std::fs::set_permissions(path, permissions))
+        } else {
+            throw_unsup_format!("`fchmod` is only supported on file-backed file descriptors");
+        };
+        drop(fd);
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+    }
+
+    fn chown(
+        &mut self,
+        path_op: &OpTy<'tcx>,
+        owner_op: &OpTy<'tcx>,
+        group_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        self.chown_path("`chown`", path_op, owner_op, group_op, true)
+    }
+
+    fn lchown(
+        &mut self,
+        path_op: &OpTy<'tcx>,
+        owner_op: &OpTy<'tcx>,
+        group_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        self.chown_path("`lchown`", path_op, owner_op, group_op, false)
+    }
+
+    #[cfg_attr(not(unix), allow(unused_variables))]
+    fn fchown(
+        &mut self,
+        fd_op: &OpTy<'tcx>,
+        owner_op: &OpTy<'tcx>,
+        group_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(fd_op)?.to_i32()?;
+        let owner = owner_id(this.read_scalar(owner_op)?.to_u32()?);
+        let group = owner_id(this.read_scalar(group_op)?.to_u32()?);
+
+        // With isolation, only change the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(ret) =
+                this.change_isolated_fd_metadata(fd_num, |metadata| metadata.chown(owner, group))?
+            {
+                return interp_ok(ret);
+            }
+            this.reject_in_isolation("`fchown`", reject_with)?;
+            // Set error code as "EBADF" (bad fd)
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        }
+
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        };
+        #[cfg(unix)]
+        {
+            let result = if let Some(FileHandle { file, .. }) = fd.downcast::<FileHandle>() {
+                std::os::unix::fs::fchown(file, owner, group)
+            } else if let Some(DirHandle { path }) = fd.downcast::<DirHandle>() {
+                std::os::unix::fs::chown(path, owner, group)
+            } else {
+                throw_unsup_format!("`fchown` is only supported on file-backed file descriptors");
+            };
+            drop(fd);
+            interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+        }
+        #[cfg(not(unix))]
+        {
+            throw_unsup_format!("`fchown` is only supported on Unix hosts");
+        }
+    }
+
+    fn utimensat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx>,
+        path_op: &OpTy<'tcx>,
+        times_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        let path_ptr = this.read_pointer(path_op)?;
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        if this.ptr_is_null(path_ptr)? {
+            return this.set_last_error_and_return_i32(LibcError("EFAULT"));
+        }
+        let path = this.read_path_from_c_str(path_ptr)?;
+
+        let at_symlink_nofollow = this.eval_libc_i32("AT_SYMLINK_NOFOLLOW");
+        if flags & !at_symlink_nofollow != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let Some(times) = this.read_file_times(times_op)? else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+
+        // With isolation, only change the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(ret) =
+                this.change_isolated_path_metadata(&path, |metadata| metadata.set_times(&times))?
+            {
+                return interp_ok(ret);
+            }
+            this.reject_in_isolation("`utimensat`", reject_with)?;
+            return this.set_last_error_and_return_i32(ErrorKind::PermissionDenied);
+        }
+
+        let path = match this.resolve_path_at(dirfd, path)? {
+            Ok(path) => path,
+            Err(err) => return this.set_last_error_and_return_i32(err),
+        };
+        if flags & at_symlink_nofollow != 0 && path.is_symlink() {
+            throw_unsup_format!(
+                "`utimensat`: setting the timestamps of a symlink is not supported"
+            );
+        }
+
+        // There is no portable way to set the timestamps of a path, so we open the file and
+        // set them on the handle.
+        let mut options = OpenOptions::new();
+        #[cfg(unix)]
+        options.read(true);
+        #[cfg(windows)]
+        {
+            use std::os::windows::fs::OpenOptionsExt;
+
+            use windows_sys::Win32::Storage::FileSystem::{
+                FILE_FLAG_BACKUP_SEMANTICS, FILE_WRITE_ATTRIBUTES,
+            };
+            // This is also needed to open directories.
+            options.access_mode(FILE_WRITE_ATTRIBUTES).custom_flags(FILE_FLAG_BACKUP_SEMANTICS);
+        }
+        let result = options.open(path).and_then(|file| file.set_times(times.to_file_times()));
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+    }
+
+    fn futimens(
+        &mut self,
+        fd_op: &OpTy<'tcx>,
+        times_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let fd_num = this.read_scalar(fd_op)?.to_i32()?;
+        let Some(times) = this.read_file_times(times_op)? else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+
+        // With isolation, only change the in-memory metadata.
+        if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+            if let Some(ret) =
+                this.change_isolated_fd_metadata(fd_num, |metadata| metadata.set_times(&times))?
+            {
+                return interp_ok(ret);
+            }
+            this.reject_in_isolation("`futimens`", reject_with)?;
+            // Set error code as "EBADF" (bad fd)
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        }
+
+        let Some(fd) = this.machine.fds.get(fd_num) else {
+            return this.set_last_error_and_return_i32(LibcError("EBADF"));
+        };
+        let FileHandle { file, .. } = fd.downcast::<FileHandle>().ok_or_else(|| {
+            err_unsup_format!("`futimens` is only supported on file-backed file descriptors")
+        })?;
+        let result = file.set_times(times.to_file_times());
+        drop(fd);
+        interp_ok(Scalar::from_i32(this.try_unwrap_io_result(result.map(|()| 0))?))
+    }
+
+    fn access(&mut self, path_op: &OpTy<'tcx>, mode_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let at_fdcwd = this.eval_libc_i32("AT_FDCWD");
+        this.access_at("`access`", at_fdcwd, path_op, mode_op, true)
+    }
+
+    fn faccessat(
+        &mut self,
+        dirfd_op: &OpTy<'tcx>,
+        path_op: &OpTy<'tcx>,
+        mode_op: &OpTy<'tcx>,
+        flags_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let dirfd = this.read_scalar(dirfd_op)?.to_i32()?;
+        let flags = this.read_scalar(flags_op)?.to_i32()?;
+
+        // Miri programs have no separate real and effective IDs, so `AT_EACCESS` makes no
+        // difference.
+        let at_eaccess = this.eval_libc_i32("AT_EACCESS");
+        let at_symlink_nofollow = this.eval_libc_i32("AT_SYMLINK_NOFOLLOW");
+        if flags & !(at_eaccess | at_symlink_nofollow) != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let follow_symlink = flags & at_symlink_nofollow == 0;
+
+        this.access_at("`faccessat`", dirfd, path_op, mode_op, follow_symlink)
+    }
+
+    fn umask(&mut self, mask_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let mask = this.read_mode_t(mask_op)?;
+        // This only affects the modes that the program passes to `open` and `mkdir`, so it
+        // works even under isolation.
+        let old_mask = std::mem::replace(&mut this.machine.umask, mask & 0o777);
+
+        interp_ok(Scalar::from_uint(old_mask, this.libc_ty_layout("mode_t").size))
+    }
+
     fn isatty(&mut self, miri_fd: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
         let this = self.eval_context_mut();
         // "returns 1 if fd is an open file descriptor referring to a terminal;
@@ -1676,7 +2954,11 @@ fn extract_sec_and_nsec<'tcx>(
 /// Stores a file's metadata in order to avoid code duplication in the different metadata related
 /// shims.
 struct FileMetadata {
+    /// File type and permission bits.
     mode: Scalar,
+    uid: u32,
+    gid: u32,
+    nlink: u64,
     size: u64,
     created: Option<(u64, u32)>,
     accessed: Option<(u64, u32)>,
@@ -1689,6 +2971,15 @@ impl FileMetadata {
         path: &Path,
         follow_symlink: bool,
     ) -> InterpResult<'tcx, Result<FileMetadata, IoError>> {
+        if ecx.is_virtual_path(path) {
+            return match ecx.virtual_entry(path)? {
+                Some(VirtualEntry::Symlink(target)) if follow_symlink =>
+                    FileMetadata::from_path(ecx, &target, follow_symlink),
+                Some(entry) => FileMetadata::virtual_file(ecx, entry.file_type()),
+                None => interp_ok(Err(LibcError("ENOENT"))),
+            };
+        }
+
         let metadata =
             if follow_symlink { std::fs::metadata(path) } else { std::fs::symlink_metadata(path) };
 
@@ -1702,12 +2993,68 @@ impl FileMetadata {
         let Some(fd) = ecx.machine.fds.get(fd_num) else {
             return interp_ok(Err(LibcError("EBADF")));
         };
+        if fd.downcast::<VirtualFile>().is_some() {
+            return FileMetadata::virtual_file(ecx, VirtualFileType::File);
+        }
+        if let Some(dir) = fd.downcast::<DirHandle>()
+            && ecx.is_virtual_path(&dir.path)
+        {
+            return FileMetadata::virtual_file(ecx, VirtualFileType::Dir);
+        }
+        if !ecx.machine.communicate()
+            && let Some(metadata) = ecx.isolated_fd_metadata(&fd)
+        {
+            return FileMetadata::isolated(ecx, metadata);
+        }
 
         let metadata = fd.metadata()?;
         drop(fd);
         FileMetadata::from_meta(ecx, metadata)
     }
 
+    /// The metadata of an entry of the virtual procfs or sysfs. Like on Linux, these entries are
+    /// read-only, owned by root, and report a size of 0.
+    fn virtual_file<'tcx>(
+        ecx: &mut MiriInterpCx<'tcx>,
+        file_type: VirtualFileType,
+    ) -> InterpResult<'tcx, Result<FileMetadata, IoError>> {
+        let mode_size = ecx.libc_ty_layout("mode_t").size;
+        let mode = ecx.eval_libc(file_type.mode_type()).to_uint(mode_size)?
+            | u128::from(file_type.permissions());
+        let mode = Scalar::from_uint(mode, mode_size);
+        interp_ok(Ok(FileMetadata {
+            mode,
+            uid: 0,
+            gid: 0,
+            nlink: 1,
+            size: 0,
+            created: None,
+            accessed: None,
+            modified: None,
+        }))
+    }
+
+    /// The metadata of a file description in the in-memory model used with isolation enabled.
+    fn isolated<'tcx>(
+        ecx: &mut MiriInterpCx<'tcx>,
+        metadata: IsolatedMetadata,
+    ) -> InterpResult<'tcx, Result<FileMetadata, IoError>> {
+        let mode_size = ecx.libc_ty_layout("mode_t").size;
+        let mode = ecx.eval_libc(metadata.file_type).to_uint(mode_size)?
+            | u128::from(metadata.permissions);
+        let mode = Scalar::from_uint(mode, mode_size);
+        interp_ok(Ok(FileMetadata {
+            mode,
+            uid: metadata.uid,
+            gid: metadata.gid,
+            nlink: 1,
+            size: 0,
+            created: None,
+            accessed: extract_sec_and_nsec(Ok(metadata.accessed))?,
+            modified: extract_sec_and_nsec(Ok(metadata.modified))?,
+        }))
+    }
+
     fn from_meta<'tcx>(
         ecx: &mut MiriInterpCx<'tcx>,
         metadata: Result<std::fs::Metadata, std::io::Error>,
@@ -1729,7 +3076,20 @@ impl FileMetadata {
             "S_IFLNK"
         };
 
-        let mode = ecx.eval_libc(mode_name);
+        // Permissions and ownership are only available on Unix hosts. Elsewhere, we approximate
+        // the permissions from the read-only flag and report the files as owned by root.
+        #[cfg(unix)]
+        let (permissions, uid, gid, nlink) = {
+            use std::os::unix::fs::MetadataExt;
+            (metadata.mode() & 0o7777, metadata.uid(), metadata.gid(), metadata.nlink())
+        };
+        #[cfg(not(unix))]
+        let (permissions, uid, gid, nlink) =
+            (if metadata.permissions().readonly() { 0o555 } else { 0o777 }, 0, 0, 1);
+
+        let mode_size = ecx.libc_ty_layout("mode_t").size;
+        let mode = ecx.eval_libc(mode_name).to_uint(mode_size)? | u128::from(permissions);
+        let mode = Scalar::from_uint(mode, mode_size);
 
         let size = metadata.len();
 
@@ -1738,6 +3098,6 @@ impl FileMetadata {
         let modified = extract_sec_and_nsec(metadata.modified())?;
 
         // FIXME: Provide more fields using platform specific methods.
-        interp_ok(Ok(FileMetadata { mode, size, created, accessed, modified }))
+        interp_ok(Ok(FileMetadata { mode, uid, gid, nlink, size, created, accessed, modified }))
     }
 }
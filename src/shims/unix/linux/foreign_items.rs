@@ -46,6 +46,36 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.sync_file_range(fd, offset, nbytes, flags)?;
                 this.write_scalar(result, dest)?;
             }
+            "renameat2" => {
+                let [olddirfd, oldpath, newdirfd, newpath, flags] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.renameat(olddirfd, oldpath, newdirfd, newpath, Some(flags))?;
+                this.write_scalar(result, dest)?;
+            }
+            "copy_file_range" => {
+                let [fd_in, off_in, fd_out, off_out, len, flags] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.copy_file_range(fd_in, off_in, fd_out, off_out, len, flags)?;
+                this.write_scalar(result, dest)?;
+            }
+            "sendfile" => {
+                let [out_fd, in_fd, offset, count] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.sendfile(out_fd, in_fd, offset, count, "off_t")?;
+                this.write_scalar(result, dest)?;
+            }
+            "sendfile64" => {
+                let [out_fd, in_fd, offset, count] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.sendfile(out_fd, in_fd, offset, count, "off64_t")?;
+                this.write_scalar(result, dest)?;
+            }
+            "splice" => {
+                let [fd_in, off_in, fd_out, off_out, len, flags] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.splice(fd_in, off_in, fd_out, off_out, len, flags)?;
+                this.write_scalar(result, dest)?;
+            }
             "statx" => {
                 let [dirfd, pathname, flags, mask, statxbuf] =
                     this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
@@ -124,6 +154,25 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.linux_gettid()?;
                 this.write_scalar(result, dest)?;
             }
+            "sysinfo" => {
+                let [info] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.linux_sysinfo(info)?;
+                this.write_scalar(result, dest)?;
+            }
+            "prlimit" | "prlimit64" => {
+                let [pid, resource, new_rlim, old_rlim] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let is_64 = link_name.as_str() == "prlimit64";
+                let result = this.prlimit(pid, resource, new_rlim, old_rlim, is_64)?;
+                this.write_scalar(result, dest)?;
+            }
+            "pthread_getattr_np" => {
+                let [thread, attr] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.pthread_getattr_np(thread, attr)?;
+                this.write_scalar(result, dest)?;
+            }
 
             // Dynamically invoked syscalls
             "syscall" => {
@@ -166,14 +215,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.write_int(SIGRTMAX, dest)?;
             }
 
-            // Incomplete shims that we "stub out" just to get pre-main initialization code to work.
-            // These shims are enabled only when the caller is in the standard library.
-            "pthread_getattr_np" if this.frame_in_std() => {
-                let [_thread, _attr] =
-                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
-                this.write_null(dest)?;
-            }
-
             _ => return interp_ok(EmulateItemResult::NotSupported),
         };
 
@@ -3,6 +3,7 @@
 
 use rustc_abi::Size;
 
+use crate::shims::{PageProt, PageProtections};
 use crate::*;
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -36,6 +37,23 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             throw_unsup_format!("Miri does not support mremap wth MREMAP_DONTUNMAP");
         }
 
+        if let Ok((alloc_id, ..)) = this.ptr_try_get_alloc_id(old_address, 0) {
+            if this.machine.mmaps.is_file_mapping(alloc_id) {
+                throw_unsup_format!("Miri does not support mremap of file mappings");
+            }
+            if this
+                .get_alloc_extra(alloc_id)?
+                .page_protections
+                .as_ref()
+                .is_some_and(|page_protections| !page_protections.is_read_write())
+            {
+                throw_unsup_format!(
+                    "Miri does not support mremap of memory with protections other than \
+                     PROT_READ|PROT_WRITE"
+                );
+            }
+        }
+
         if flags & this.eval_libc_i32("MREMAP_MAYMOVE") == 0 {
             // We only support MREMAP_MAYMOVE, so not passing the flag is just a failure
             this.set_last_error(LibcError("EINVAL"))?;
@@ -59,6 +77,9 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             )
             .unwrap();
         }
+        let alloc_id = ptr.provenance.get_alloc_id().unwrap();
+        this.get_alloc_extra_mut(alloc_id)?.0.page_protections =
+            Some(PageProtections::new(Size::from_bytes(new_size), PageProt::READ_WRITE));
 
         interp_ok(Scalar::from_pointer(ptr, this))
     }
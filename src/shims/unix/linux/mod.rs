@@ -1,2 +1,3 @@
 pub mod foreign_items;
 pub mod mem;
+pub mod procfs;
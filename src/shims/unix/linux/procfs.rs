@@ -0,0 +1,474 @@
+//! A synthesized, read-only procfs and sysfs. Programs read files like `/proc/self/status` or
+//! `/sys/devices/system/cpu/online` to learn about the machine they run on, so we serve these
+//! files with contents that describe the emulated machine instead of the host. They are
+//! available even with isolation enabled. Every path under `/proc` and `/sys` is virtual; paths
+//! that are not one of the entries below do not exist. Directories can be listed with `opendir`,
+//! and symlinks can be read with `readlink`.
+
+use std::cell::Cell;
+use std::fmt::Write as _;
+use std::io::{self, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+
+use rustc_hir::def_id::LOCAL_CRATE;
+
+use crate::shims::files::{EvalContextExt as _, FileDescription, FileDescriptionRef};
+use crate::shims::unix::UnixFileDescription;
+use crate::*;
+
+/// The amount of memory that the emulated machine reports in `/proc/meminfo`.
+pub(crate) const MEM_TOTAL: u64 = 8 * 1024 * 1024 * 1024;
+
+/// The longest name that `comm` files report, like `TASK_COMM_LEN` minus the null terminator.
+const COMM_MAX_LEN: usize = 15;
+
+/// The type of an entry of the virtual procfs or sysfs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VirtualFileType {
+    File,
+    Dir,
+    Symlink,
+}
+
+impl VirtualFileType {
+    /// The `DT_*` constant that `readdir` reports for this type.
+    pub fn d_type(self) -> &'static str {
+        match self {
+            VirtualFileType::File => "DT_REG",
+            VirtualFileType::Dir => "DT_DIR",
+            VirtualFileType::Symlink => "DT_LNK",
+        }
+    }
+
+    /// The `S_IF*` constant for this type.
+    pub fn mode_type(self) -> &'static str {
+        match self {
+            VirtualFileType::File => "S_IFREG",
+            VirtualFileType::Dir => "S_IFDIR",
+            VirtualFileType::Symlink => "S_IFLNK",
+        }
+    }
+
+    /// The permission bits of entries of this type. Like on Linux, they are read-only for
+    /// everyone.
+    pub fn permissions(self) -> u32 {
+        match self {
+            VirtualFileType::File => 0o444,
+            VirtualFileType::Dir => 0o555,
+            VirtualFileType::Symlink => 0o777,
+        }
+    }
+}
+
+/// An entry of the virtual procfs or sysfs.
+#[derive(Debug)]
+pub enum VirtualEntry {
+    /// A file with the given contents.
+    File(Vec<u8>),
+    /// A directory with the given entries, not including `.` and `..`.
+    Dir(Vec<(String, VirtualFileType)>),
+    /// A symlink to the given path.
+    Symlink(PathBuf),
+}
+
+impl VirtualEntry {
+    pub fn file_type(&self) -> VirtualFileType {
+        match self {
+            VirtualEntry::File(_) => VirtualFileType::File,
+            VirtualEntry::Dir(_) => VirtualFileType::Dir,
+            VirtualEntry::Symlink(_) => VirtualFileType::Symlink,
+        }
+    }
+}
+
+/// An open file of the virtual procfs or sysfs. The contents are generated when the file is
+/// opened.
+#[derive(Debug)]
+pub struct VirtualFile {
+    contents: Vec<u8>,
+    offset: Cell<u64>,
+}
+
+impl VirtualFile {
+    /// Copies the contents starting at `offset` into `ptr`, like `pread`.
+    fn read_at_offset<'tcx>(
+        &self,
+        offset: u64,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, usize> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX).min(self.contents.len());
+        let bytes = &self.contents[start..];
+        let read_size = bytes.len().min(len);
+        ecx.return_read_success(ptr, bytes, read_size, dest)?;
+        interp_ok(read_size)
+    }
+}
+
+impl FileDescription for VirtualFile {
+    fn name(&self) -> &'static str {
+        "virtual file"
+    }
+
+    fn read<'tcx>(
+        &self,
+        _self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let read_size = self.read_at_offset(self.offset.get(), ptr, len, dest, ecx)?;
+        self.offset.set(self.offset.get().strict_add(u64::try_from(read_size).unwrap()));
+        interp_ok(())
+    }
+
+    fn write<'tcx>(
+        &self,
+        _self_ref: &FileDescriptionRef,
+        _communicate_allowed: bool,
+        _ptr: Pointer,
+        _len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        // Virtual files are only ever opened for reading.
+        ecx.set_last_error_and_return(LibcError("EBADF"), dest)
+    }
+
+    fn seek<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        offset: SeekFrom,
+    ) -> InterpResult<'tcx, io::Result<u64>> {
+        let (base, delta) = match offset {
+            SeekFrom::Start(offset) => (0, i128::from(offset)),
+            SeekFrom::End(delta) =>
+                (u64::try_from(self.contents.len()).unwrap(), i128::from(delta)),
+            SeekFrom::Current(delta) => (self.offset.get(), i128::from(delta)),
+        };
+        let Ok(new_offset) = u64::try_from(i128::from(base).strict_add(delta)) else {
+            return interp_ok(Err(io::ErrorKind::InvalidInput.into()));
+        };
+        self.offset.set(new_offset);
+        interp_ok(Ok(new_offset))
+    }
+
+    fn close<'tcx>(
+        self: Box<Self>,
+        _communicate_allowed: bool,
+        _ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        interp_ok(Ok(()))
+    }
+
+    fn as_unix(&self) -> &dyn UnixFileDescription {
+        self
+    }
+}
+
+impl UnixFileDescription for VirtualFile {
+    fn pread<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        offset: u64,
+        ptr: Pointer,
+        len: usize,
+        dest: &MPlaceTy<'tcx>,
+        ecx: &mut MiriInterpCx<'tcx>,
+    ) -> InterpResult<'tcx> {
+        self.read_at_offset(offset, ptr, len, dest, ecx)?;
+        interp_ok(())
+    }
+
+    fn read_at<'tcx>(
+        &self,
+        _communicate_allowed: bool,
+        offset: u64,
+        len: u64,
+    ) -> InterpResult<'tcx, io::Result<Vec<u8>>> {
+        let start = usize::try_from(offset).unwrap_or(usize::MAX).min(self.contents.len());
+        let end = usize::try_from(len).unwrap_or(usize::MAX).saturating_add(start);
+        interp_ok(Ok(self.contents[start..end.min(self.contents.len())].to_vec()))
+    }
+}
+
+/// Formats a set of CPUs the way the kernel does in `Cpus_allowed_list` and sysfs, as a
+/// comma-separated list of ranges like `0-3,6`.
+fn format_cpu_list(cpus: impl Iterator<Item = usize>) -> String {
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if end.strict_add(1) == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    let ranges: Vec<String> = ranges
+        .into_iter()
+        .map(|(start, end)| if start == end { start.to_string() } else { format!("{start}-{end}") })
+        .collect();
+    ranges.join(",")
+}
+
+/// Splits an absolute path into its components, resolving `.` and `..`. Returns `None` if the
+/// path is not valid UTF-8, in which case it cannot name a virtual file.
+fn normalized_components(path: &Path) -> Option<Vec<&str>> {
+    let mut components = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(name) => components.push(name.to_str()?),
+            Component::ParentDir => {
+                components.pop();
+            }
+            Component::RootDir | Component::CurDir | Component::Prefix(_) => {}
+        }
+    }
+    Some(components)
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The thread with the given Linux thread ID, which is computed the same way as by `gettid`.
+    fn thread_from_tid(&self, tid: &str) -> Option<ThreadId> {
+        let this = self.eval_context_ref();
+        let index = tid.parse::<u32>().ok()?.checked_sub(this.get_pid())?;
+        let thread = this.thread_id_try_from(index).ok()?;
+        this.machine.threads.get_live_threads().any(|live| live == thread).then_some(thread)
+    }
+
+    /// The name that `comm` reports for the given thread. Like on Linux, the main thread and
+    /// threads without a name report the name of the program.
+    fn comm(&self, thread: ThreadId) -> Vec<u8> {
+        let this = self.eval_context_ref();
+        let name = match this.get_thread_name(thread) {
+            Some(name) if thread != ThreadId::MAIN_THREAD => name.to_vec(),
+            _ => this.tcx.crate_name(LOCAL_CRATE).as_str().as_bytes().to_vec(),
+        };
+        let mut comm = name[..name.len().min(COMM_MAX_LEN)].to_vec();
+        comm.push(b'\n');
+        comm
+    }
+
+    /// The CPUs that the given thread may run on.
+    fn cpus_allowed(&self, thread: ThreadId) -> String {
+        let this = self.eval_context_ref();
+        let cpus = 0..usize::try_from(this.machine.num_cpus).unwrap();
+        match this.machine.thread_cpu_affinity.get(&thread) {
+            Some(mask) => format_cpu_list(cpus.filter(|&cpu| mask.contains(this, cpu))),
+            None => format_cpu_list(cpus),
+        }
+    }
+
+    fn proc_self_status(&self) -> String {
+        let this = self.eval_context_ref();
+        let pid = this.get_pid();
+        let mut name = this.comm(ThreadId::MAIN_THREAD);
+        name.pop(); // Remove the trailing newline.
+        let mut status = String::new();
+        writeln!(status, "Name:\t{}", String::from_utf8_lossy(&name)).unwrap();
+        writeln!(status, "State:\tR (running)").unwrap();
+        writeln!(status, "Tgid:\t{pid}").unwrap();
+        writeln!(status, "Pid:\t{pid}").unwrap();
+        writeln!(status, "PPid:\t{}", this.get_ppid()).unwrap();
+        writeln!(status, "VmRSS:\t{} kB", this.memory_in_use().div_ceil(1024)).unwrap();
+        writeln!(status, "Threads:\t{}", this.machine.threads.get_live_thread_count()).unwrap();
+        writeln!(status, "Cpus_allowed_list:\t{}", this.cpus_allowed(this.active_thread()))
+            .unwrap();
+        status
+    }
+
+    fn proc_cpuinfo(&self) -> String {
+        let this = self.eval_context_ref();
+        let num_cpus = this.machine.num_cpus;
+        let mut cpuinfo = String::new();
+        for cpu in 0..num_cpus {
+            writeln!(cpuinfo, "processor\t: {cpu}").unwrap();
+            writeln!(cpuinfo, "model name\t: Miri virtual CPU").unwrap();
+            writeln!(cpuinfo, "physical id\t: 0").unwrap();
+            writeln!(cpuinfo, "siblings\t: {num_cpus}").unwrap();
+            writeln!(cpuinfo, "core id\t\t: {cpu}").unwrap();
+            writeln!(cpuinfo, "cpu cores\t: {num_cpus}").unwrap();
+            writeln!(cpuinfo).unwrap();
+        }
+        cpuinfo
+    }
+
+    fn proc_meminfo(&self) -> String {
+        let this = self.eval_context_ref();
+        let total = MEM_TOTAL / 1024;
+        let free = MEM_TOTAL.saturating_sub(this.memory_in_use()) / 1024;
+        let mut meminfo = String::new();
+        writeln!(meminfo, "MemTotal:       {total:8} kB").unwrap();
+        writeln!(meminfo, "MemFree:        {free:8} kB").unwrap();
+        writeln!(meminfo, "MemAvailable:   {free:8} kB").unwrap();
+        meminfo
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The total size of all live allocations, which we report as the memory in use.
+    fn memory_in_use(&self) -> u64 {
+        let this = self.eval_context_ref();
+        this.memory.alloc_map().iter(|it| {
+            it.map(|(_id, (_kind, alloc))| alloc.size().bytes()).fold(0, u64::saturating_add)
+        })
+    }
+
+    /// Whether `path` is in the virtual procfs or sysfs.
+    fn is_virtual_path(&self, path: &Path) -> bool {
+        let this = self.eval_context_ref();
+        this.tcx.sess.target.os == "linux"
+            && path.is_absolute()
+            && normalized_components(path)
+                .is_some_and(|components| matches!(components.first(), Some(&("proc" | "sys"))))
+    }
+
+    /// Looks up the virtual entry at `path`, generating its contents. Returns `None` if there
+    /// is no such entry.
+    fn virtual_entry(&mut self, path: &Path) -> InterpResult<'tcx, Option<VirtualEntry>> {
+        use VirtualFileType::*;
+        let this = self.eval_context_mut();
+
+        let Some(components) = normalized_components(path) else { return interp_ok(None) };
+        let pid = this.get_pid().to_string();
+        let active_tid = this.get_pid().strict_add(this.active_thread().to_u32()).to_string();
+        // `/proc/<pid>` is the same as `/proc/self`, and `/proc/thread-self` is the same as
+        // `/proc/self/task/<tid>` of the active thread.
+        let components: Vec<&str> = match components.as_slice() {
+            ["proc", process, rest @ ..] if *process == pid =>
+                ["proc", "self"].iter().chain(rest).copied().collect(),
+            ["proc", "thread-self", rest @ ..] =>
+                ["proc", "self", "task", active_tid.as_str()].iter().chain(rest).copied().collect(),
+            _ => components,
+        };
+
+        let dir = |entries: &[(&str, VirtualFileType)]| {
+            VirtualEntry::Dir(entries.iter().map(|&(name, ty)| (name.to_owned(), ty)).collect())
+        };
+        let entry = match components.as_slice() {
+            ["proc"] =>
+                dir(&[
+                    ("cpuinfo", File),
+                    ("meminfo", File),
+                    ("self", Symlink),
+                    ("thread-self", Symlink),
+                    (pid.as_str(), Dir),
+                ]),
+            ["proc", "cpuinfo"] => VirtualEntry::File(this.proc_cpuinfo().into_bytes()),
+            ["proc", "meminfo"] => VirtualEntry::File(this.proc_meminfo().into_bytes()),
+            ["proc", "self"] =>
+                dir(&[
+                    ("comm", File),
+                    ("exe", Symlink),
+                    ("maps", File),
+                    ("status", File),
+                    ("task", Dir),
+                ]),
+            ["proc", "self", "status"] => VirtualEntry::File(this.proc_self_status().into_bytes()),
+            ["proc", "self", "maps"] => VirtualEntry::File(this.proc_self_maps()?.into_bytes()),
+            ["proc", "self", "comm"] => VirtualEntry::File(this.comm(ThreadId::MAIN_THREAD)),
+            ["proc", "self", "exe"] => {
+                // Using the host current_exe is a bit off, but consistent with the other targets.
+                // With isolation, this would reveal the host, so the link appears to not exist.
+                if let IsolatedOp::Reject(reject_with) = this.machine.isolated_op {
+                    this.reject_in_isolation("`/proc/self/exe`", reject_with)?;
+                    return interp_ok(None);
+                }
+                VirtualEntry::Symlink(std::env::current_exe().unwrap())
+            }
+            ["proc", "self", "task"] => {
+                let pid = this.get_pid();
+                let tids = this
+                    .machine
+                    .threads
+                    .get_live_threads()
+                    .map(|thread| (pid.strict_add(thread.to_u32()).to_string(), Dir))
+                    .collect();
+                VirtualEntry::Dir(tids)
+            }
+            ["proc", "self", "task", tid] => {
+                if this.thread_from_tid(tid).is_none() {
+                    return interp_ok(None);
+                }
+                dir(&[("comm", File)])
+            }
+            ["proc", "self", "task", tid, "comm"] => {
+                let Some(thread) = this.thread_from_tid(tid) else { return interp_ok(None) };
+                VirtualEntry::File(this.comm(thread))
+            }
+            ["sys"] => dir(&[("devices", Dir)]),
+            ["sys", "devices"] => dir(&[("system", Dir)]),
+            ["sys", "devices", "system"] => dir(&[("cpu", Dir)]),
+            ["sys", "devices", "system", "cpu"] =>
+                dir(&[("online", File), ("possible", File), ("present", File)]),
+            ["sys", "devices", "system", "cpu", "online" | "possible" | "present"] => {
+                let cpus = 0..usize::try_from(this.machine.num_cpus).unwrap();
+                VirtualEntry::File(format!("{}\n", format_cpu_list(cpus)).into_bytes())
+            }
+            _ => return interp_ok(None),
+        };
+        interp_ok(Some(entry))
+    }
+
+    /// Generates the contents of the virtual file at `path`. Returns `None` if there is no such
+    /// file.
+    fn virtual_file_contents(&mut self, path: &Path) -> InterpResult<'tcx, Option<Vec<u8>>> {
+        let this = self.eval_context_mut();
+        match this.virtual_entry(path)? {
+            Some(VirtualEntry::File(contents)) => interp_ok(Some(contents)),
+            _ => interp_ok(None),
+        }
+    }
+
+    /// The entries of the virtual directory at `path`, for `opendir`.
+    fn virtual_dir_entries(
+        &mut self,
+        path: &Path,
+    ) -> InterpResult<'tcx, Result<Vec<(String, VirtualFileType)>, IoError>> {
+        let this = self.eval_context_mut();
+        interp_ok(match this.virtual_entry(path)? {
+            Some(VirtualEntry::Dir(entries)) => Ok(entries),
+            Some(VirtualEntry::File(_) | VirtualEntry::Symlink(_)) => Err(LibcError("ENOTDIR")),
+            None => Err(LibcError("ENOENT")),
+        })
+    }
+
+    /// The target of the virtual symlink at `path`, for `readlink`.
+    fn virtual_link_target(&mut self, path: &Path) -> InterpResult<'tcx, Result<PathBuf, IoError>> {
+        let this = self.eval_context_mut();
+        // `virtual_entry` follows these two symlinks, since they only lead to other virtual
+        // directories.
+        let pid = this.get_pid();
+        match normalized_components(path).as_deref() {
+            Some(["proc", "self"]) => return interp_ok(Ok(PathBuf::from(pid.to_string()))),
+            Some(["proc", "thread-self"]) => {
+                let tid = pid.strict_add(this.active_thread().to_u32());
+                return interp_ok(Ok(PathBuf::from(format!("{pid}/task/{tid}"))));
+            }
+            _ => {}
+        }
+        interp_ok(match this.virtual_entry(path)? {
+            Some(VirtualEntry::Symlink(target)) => Ok(target),
+            Some(VirtualEntry::File(_) | VirtualEntry::Dir(_)) => Err(LibcError("EINVAL")),
+            None => Err(LibcError("ENOENT")),
+        })
+    }
+
+    /// Opens the virtual file at `path`, which must satisfy `is_virtual_path`.
+    fn open_virtual_file(&mut self, path: &Path, writable: bool) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let Some(contents) = this.virtual_file_contents(path)? else {
+            return this.set_last_error_and_return_i32(LibcError("ENOENT"));
+        };
+        if writable {
+            return this.set_last_error_and_return_i32(LibcError("EACCES"));
+        }
+        let fd = this.machine.fds.insert_new(VirtualFile { contents, offset: Cell::new(0) });
+        interp_ok(Scalar::from_i32(fd))
+    }
+}
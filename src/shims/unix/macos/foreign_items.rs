@@ -55,12 +55,23 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.macos_fbsd_solaris_fstat(fd, buf)?;
                 this.write_scalar(result, dest)?;
             }
+            "fstatat" | "fstatat64" | "fstatat$INODE64" => {
+                let [dirfd, path, buf, flags] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.macos_fbsd_solaris_fstatat(dirfd, path, buf, flags)?;
+                this.write_scalar(result, dest)?;
+            }
             "opendir$INODE64" => {
                 let [name] =
                     this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
                 let result = this.opendir(name)?;
                 this.write_scalar(result, dest)?;
             }
+            "fdopendir$INODE64" => {
+                let [fd] = this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.fdopendir(fd)?;
+                this.write_scalar(result, dest)?;
+            }
             "readdir_r" | "readdir_r$INODE64" => {
                 let [dirp, entry, result] =
                     this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
@@ -162,8 +173,12 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             "pthread_get_stacksize_np" => {
                 let [thread] =
                     this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
-                this.read_target_usize(thread)?;
-                let stack_size = Scalar::from_uint(this.machine.stack_size, this.pointer_size());
+                let thread = this.read_target_usize(thread)?;
+                let Ok(thread) = this.thread_id_try_from(thread) else {
+                    throw_ub_format!("`pthread_get_stacksize_np` called with an invalid thread");
+                };
+                let stack_size =
+                    Scalar::from_uint(this.get_thread_stack_size(thread), this.pointer_size());
                 this.write_scalar(stack_size, dest)?;
             }
 
@@ -1,11 +1,22 @@
 //! This is an incomplete implementation of mmap/munmap which is restricted in order to be
 //! implementable on top of the existing memory system. The point of these function as-written is
-//! to allow memory allocators written entirely in Rust to be executed by Miri. This implementation
-//! does not support other uses of mmap such as file mappings.
+//! to allow memory allocators written entirely in Rust, as well as simple file mappings, to be
+//! executed by Miri.
 //!
 //! mmap/munmap behave a lot like alloc/dealloc, and for simple use they are exactly
-//! equivalent. That is the only part we support: no MAP_FIXED or MAP_SHARED or anything
-//! else that goes beyond a basic allocation API.
+//! equivalent. Every mapping is a separate allocation; we do not support MAP_FIXED or anything
+//! else that would require placing a mapping at a particular address.
+//!
+//! File-backed mappings are filled with the contents of the file when they are created. For
+//! MAP_SHARED mappings, `msync` and `munmap` write the mapped bytes back to the file. Until then,
+//! the file does not see the changes. Since several shared mappings of the same part of a file
+//! could not be kept coherent, a second such mapping is not supported. Within a single mapping,
+//! accesses from different threads are checked by the data race detector like any other memory,
+//! and `msync` counts as a read.
+//!
+//! Every mapping keeps track of the protection of each of its pages, as set by mmap and mprotect.
+//! An access that the protection of a page does not allow stops execution with a segmentation
+//! fault. PROT_EXEC is accepted but has no effect, since we never execute code from memory.
 //!
 //! Note that in addition to only supporting malloc-like calls to mmap, we only support free-like
 //! calls to munmap, but for a very different reason. In principle, according to the man pages, it
@@ -14,10 +25,216 @@
 //! munmap shim which would partially unmap a region of address space previously mapped by mmap will
 //! report UB.
 
+use std::cell::RefCell;
+use std::fmt::Write;
+use std::io;
+
 use rustc_abi::Size;
+use rustc_data_structures::fx::FxHashMap;
 
+use crate::shims::files::FileDescriptionRef;
 use crate::*;
 
+/// A mapping of a file.
+#[derive(Debug, Clone)]
+pub struct FileMapping {
+    fd: FileDescriptionRef,
+    /// Whether this is a MAP_SHARED mapping, whose changes are written back to the file.
+    shared: bool,
+    /// The offset in the file at which the mapping starts.
+    offset: u64,
+    /// The length of the mapping, as requested by the program (not rounded up to the page size).
+    len: u64,
+}
+
+impl FileMapping {
+    /// Whether this mapping and a mapping of `len` bytes of `fd` at `offset` share some bytes of
+    /// the same file. Descriptions that were opened separately can only be recognized as the
+    /// same file on Unix hosts.
+    fn overlaps<'tcx>(
+        &self,
+        fd: &FileDescriptionRef,
+        offset: u64,
+        len: u64,
+    ) -> InterpResult<'tcx, bool> {
+        if self.offset >= offset.saturating_add(len)
+            || offset >= self.offset.saturating_add(self.len)
+        {
+            return interp_ok(false);
+        }
+        if self.fd.get_id() == fd.get_id() {
+            return interp_ok(true);
+        }
+        #[cfg(unix)]
+        if let (Ok(a), Ok(b)) = (self.fd.metadata()?, fd.metadata()?) {
+            use std::os::unix::fs::MetadataExt;
+            return interp_ok(a.dev() == b.dev() && a.ino() == b.ino());
+        }
+        interp_ok(false)
+    }
+}
+
+/// The file mappings, keyed by the allocation that backs them.
+#[derive(Debug, Default)]
+pub struct MmapTable {
+    files: FxHashMap<AllocId, FileMapping>,
+}
+
+impl MmapTable {
+    pub fn is_file_mapping(&self, alloc_id: AllocId) -> bool {
+        self.files.contains_key(&alloc_id)
+    }
+}
+
+impl VisitProvenance for MmapTable {
+    fn visit_provenance(&self, _visit: &mut VisitWith<'_>) {
+        // The mappings are removed when their allocation is unmapped, and file descriptions
+        // do not have any tags.
+    }
+}
+
+/// The protection of a page. On all common hardware, pages that can be written can also be read,
+/// so there is no write-only protection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PageProt {
+    read: bool,
+    write: bool,
+}
+
+impl PageProt {
+    pub const READ_WRITE: PageProt = PageProt { read: true, write: true };
+
+    fn name(self) -> &'static str {
+        match (self.read, self.write) {
+            (false, _) => "PROT_NONE",
+            (true, false) => "PROT_READ",
+            (true, true) => "PROT_READ|PROT_WRITE",
+        }
+    }
+}
+
+/// The page protections of a mapping.
+#[derive(Debug)]
+pub struct PageProtections(RangeMap<PageProt>);
+
+impl PageProtections {
+    pub fn new(size: Size, prot: PageProt) -> Self {
+        PageProtections(RangeMap::new(size, prot))
+    }
+
+    fn set(&mut self, offset: Size, len: Size, prot: PageProt) {
+        for (_, page_prot) in self.0.iter_mut(offset, len) {
+            *page_prot = prot;
+        }
+    }
+
+    pub fn is_read_write(&self) -> bool {
+        self.0.iter_all().all(|(_, prot)| *prot == PageProt::READ_WRITE)
+    }
+
+    /// Stops execution with a segmentation fault if the protection of a page in `range` does not
+    /// allow the access.
+    pub fn check_access<'tcx>(
+        &self,
+        alloc_id: AllocId,
+        range: AllocRange,
+        access: AccessKind,
+    ) -> InterpResult<'tcx> {
+        for (bytes, prot) in self.0.iter(range.start, range.size) {
+            let allowed = match access {
+                AccessKind::Read => prot.read,
+                AccessKind::Write => prot.write,
+            };
+            if !allowed {
+                let offset = Size::from_bytes(bytes.start.max(range.start.bytes()));
+                throw_machine_stop!(TerminationInfo::SegmentationFault {
+                    ptr: interpret::Pointer::new(alloc_id, offset),
+                    access,
+                    prot: prot.name().to_string(),
+                });
+            }
+        }
+        interp_ok(())
+    }
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// Parses the `prot` argument of mmap and mprotect. Returns `None` if it has unknown bits.
+    fn parse_prot(&self, prot: i32) -> Option<PageProt> {
+        let this = self.eval_context_ref();
+        let prot_read = this.eval_libc_i32("PROT_READ");
+        let prot_write = this.eval_libc_i32("PROT_WRITE");
+        let prot_exec = this.eval_libc_i32("PROT_EXEC");
+        if prot & !(prot_read | prot_write | prot_exec) != 0 {
+            return None;
+        }
+        Some(PageProt { read: prot & (prot_read | prot_write) != 0, write: prot & prot_write != 0 })
+    }
+
+    /// Returns the allocation and offset of `ptr` if it points into memory mapped by mmap.
+    fn mapping_of(&self, ptr: Pointer) -> InterpResult<'tcx, Option<(AllocId, Size)>> {
+        let this = self.eval_context_ref();
+        let Ok((alloc_id, offset, _)) = this.ptr_try_get_alloc_id(ptr, 0) else {
+            return interp_ok(None);
+        };
+        if !matches!(this.get_alloc_info(alloc_id).kind, AllocKind::LiveData)
+            || this.get_alloc_extra(alloc_id)?.page_protections.is_none()
+        {
+            return interp_ok(None);
+        }
+        interp_ok(Some((alloc_id, offset)))
+    }
+
+    /// Runs `f` with the page protections of `alloc_id` lifted, for accesses that the kernel makes
+    /// on behalf of the program, like writing back a file mapping.
+    fn without_page_protections<T>(
+        &mut self,
+        alloc_id: AllocId,
+        f: impl FnOnce(&mut MiriInterpCx<'tcx>) -> InterpResult<'tcx, T>,
+    ) -> InterpResult<'tcx, T> {
+        let this = self.eval_context_mut();
+        let prot = this.get_alloc_extra_mut(alloc_id)?.0.page_protections.take();
+        let result = f(this);
+        this.get_alloc_extra_mut(alloc_id)?.0.page_protections = prot;
+        result
+    }
+
+    /// Writes `len` bytes of `mapping`, starting `offset` bytes into the mapping, back to the file.
+    /// `ptr` points to the first of these bytes. Only the part of the mapping that is still backed
+    /// by the file is written back.
+    fn write_back_mapping(
+        &mut self,
+        mapping: &FileMapping,
+        alloc_id: AllocId,
+        ptr: Pointer,
+        offset: u64,
+        len: u64,
+    ) -> InterpResult<'tcx, io::Result<()>> {
+        let this = self.eval_context_mut();
+        let file_len = match mapping.fd.metadata()? {
+            Ok(metadata) => metadata.len(),
+            Err(e) => return interp_ok(Err(e)),
+        };
+        let end = offset
+            .saturating_add(len)
+            .min(mapping.len)
+            .min(file_len.saturating_sub(mapping.offset));
+        if end <= offset {
+            return interp_ok(Ok(()));
+        }
+        let len = Size::from_bytes(end.strict_sub(offset));
+        let bytes = this.without_page_protections(alloc_id, |this| {
+            interp_ok(this.read_bytes_ptr_strip_provenance(ptr, len)?.to_vec())
+        })?;
+        mapping.fd.as_unix().write_at(
+            this.machine.communicate(),
+            mapping.offset.strict_add(offset),
+            &bytes,
+        )
+    }
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn mmap(
@@ -52,9 +269,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             return interp_ok(Scalar::from_maybe_pointer(Pointer::from_addr_invalid(addr), this));
         }
 
-        let prot_read = this.eval_libc_i32("PROT_READ");
-        let prot_write = this.eval_libc_i32("PROT_WRITE");
-
         // First, we do some basic argument validation as required by mmap
         if (flags & (map_private | map_shared)).count_ones() != 1 {
             this.set_last_error(LibcError("EINVAL"))?;
@@ -65,13 +279,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             return interp_ok(this.eval_libc("MAP_FAILED"));
         }
 
-        // If a user tries to map a file, we want to loudly inform them that this is not going
-        // to work. It is possible that POSIX gives us enough leeway to return an error, but the
-        // outcome for the user (I need to add cfg(miri)) is the same, just more frustrating.
-        if fd != -1 {
-            throw_unsup_format!("Miri does not support file-backed memory mappings");
-        }
-
         // Miri doesn't support MAP_FIXED.
         if flags & map_fixed != 0 {
             throw_unsup_format!(
@@ -79,27 +286,58 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             );
         }
 
-        // Miri doesn't support protections other than PROT_READ|PROT_WRITE.
-        if prot != prot_read | prot_write {
-            throw_unsup_format!(
-                "Miri does not support calls to mmap with protections other than \
-                 PROT_READ|PROT_WRITE",
-            );
-        }
+        let Some(prot) = this.parse_prot(prot) else {
+            this.set_last_error(LibcError("EINVAL"))?;
+            return interp_ok(this.eval_libc("MAP_FAILED"));
+        };
 
-        // Miri does not support shared mappings, or any of the other extensions that for example
-        // Linux has added to the flags arguments.
-        if flags != map_private | map_anonymous {
+        // Miri does not support any of the extensions that for example Linux has added to the
+        // flags arguments.
+        if flags & !(map_private | map_shared | map_anonymous) != 0 {
             throw_unsup_format!(
                 "Miri only supports calls to mmap which set the flags argument to \
-                 MAP_PRIVATE|MAP_ANONYMOUS",
+                 MAP_PRIVATE or MAP_SHARED, optionally combined with MAP_ANONYMOUS",
             );
         }
 
-        // This is only used for file mappings, which we don't support anyway.
-        if offset != 0 {
-            throw_unsup_format!("Miri does not support non-zero offsets to mmap");
-        }
+        let file = if flags & map_anonymous != 0 {
+            // The offset is only used for file mappings.
+            if offset != 0 {
+                throw_unsup_format!("Miri does not support non-zero offsets to anonymous mmap");
+            }
+            None
+        } else {
+            let Some(fd) = this.machine.fds.get(fd) else {
+                this.set_last_error(LibcError("EBADF"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            };
+            // The offset must be a multiple of the page size.
+            let Ok(offset) = u64::try_from(offset) else {
+                this.set_last_error(LibcError("EINVAL"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            };
+            #[expect(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+            if offset % this.machine.page_size != 0 {
+                this.set_last_error(LibcError("EINVAL"))?;
+                return interp_ok(this.eval_libc("MAP_FAILED"));
+            }
+            if flags & map_shared != 0 {
+                // Changes made through a shared mapping end up in the file.
+                if prot.write && !fd.as_unix().is_writable() {
+                    this.set_last_error(LibcError("EACCES"))?;
+                    return interp_ok(this.eval_libc("MAP_FAILED"));
+                }
+                for mapping in this.machine.mmaps.files.values() {
+                    if mapping.shared && mapping.overlaps(&fd, offset, length)? {
+                        throw_unsup_format!(
+                            "Miri does not support several MAP_SHARED mappings of the same part \
+                             of a file, since it cannot keep them coherent"
+                        );
+                    }
+                }
+            }
+            Some((fd, offset))
+        };
 
         let align = this.machine.page_align();
         let Some(map_length) = length.checked_next_multiple_of(this.machine.page_size) else {
@@ -120,6 +358,29 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             std::iter::repeat(0u8).take(usize::try_from(map_length).unwrap()),
         )
         .unwrap();
+        let alloc_id = ptr.provenance.get_alloc_id().unwrap();
+
+        if let Some((fd, offset)) = file {
+            // Fill the mapping with the contents of the file. Bytes past the end of the file
+            // stay zero.
+            let bytes = match fd.as_unix().read_at(this.machine.communicate(), offset, length)? {
+                Ok(bytes) => bytes,
+                Err(e) => {
+                    this.deallocate_ptr(ptr.into(), None, MiriMemoryKind::Mmap.into())?;
+                    this.set_last_error(e)?;
+                    return interp_ok(this.eval_libc("MAP_FAILED"));
+                }
+            };
+            this.write_bytes_ptr(ptr.into(), bytes)?;
+            let shared = flags & map_shared != 0;
+            this.machine
+                .mmaps
+                .files
+                .insert(alloc_id, FileMapping { fd, shared, offset, len: length });
+        }
+        // Only now that the contents are in place do the protections take effect.
+        this.get_alloc_extra_mut(alloc_id)?.0.page_protections =
+            Some(PageProtections::new(Size::from_bytes(map_length), prot));
 
         interp_ok(Scalar::from_pointer(ptr, this))
     }
@@ -145,6 +406,15 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             return interp_ok(this.eval_libc("MAP_FAILED"));
         }
 
+        // Write shared file mappings back to the file. Like the kernel, which does that lazily,
+        // we ignore any errors.
+        if let Ok((alloc_id, offset, _)) = this.ptr_try_get_alloc_id(addr, 0)
+            && let Some(mapping) = this.machine.mmaps.files.remove(&alloc_id)
+            && mapping.shared
+        {
+            this.write_back_mapping(&mapping, alloc_id, addr, offset.bytes(), length)?.ok();
+        }
+
         let length = Size::from_bytes(length);
         this.deallocate_ptr(
             addr,
@@ -154,4 +424,202 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         interp_ok(Scalar::from_i32(0))
     }
+
+    fn msync(
+        &mut self,
+        addr: &OpTy<'tcx>,
+        length: &OpTy<'tcx>,
+        flags: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        let length = this.read_target_usize(length)?;
+        let flags = this.read_scalar(flags)?.to_i32()?;
+
+        let ms_async = this.eval_libc_i32("MS_ASYNC");
+        let ms_sync = this.eval_libc_i32("MS_SYNC");
+        let ms_invalidate = this.eval_libc_i32("MS_INVALIDATE");
+        if flags & !(ms_async | ms_sync | ms_invalidate) != 0
+            || flags & (ms_async | ms_sync) == ms_async | ms_sync
+        {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        #[expect(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+        if addr.addr().bytes() % this.machine.page_size != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+
+        let Ok((alloc_id, offset, _)) = this.ptr_try_get_alloc_id(addr, 0) else {
+            // The memory is not mapped.
+            return this.set_last_error_and_return_i32(LibcError("ENOMEM"));
+        };
+        // Only shared file mappings need to be written back. We write back synchronously even for
+        // MS_ASYNC, and MS_INVALIDATE has nothing to do since we never cache file contents.
+        let Some(mapping) = this.machine.mmaps.files.get(&alloc_id).cloned() else {
+            return interp_ok(Scalar::from_i32(0));
+        };
+        if !mapping.shared {
+            return interp_ok(Scalar::from_i32(0));
+        }
+        match this.write_back_mapping(&mapping, alloc_id, addr, offset.bytes(), length)? {
+            Ok(()) => interp_ok(Scalar::from_i32(0)),
+            Err(e) => this.set_last_error_and_return_i32(e),
+        }
+    }
+
+    fn mprotect(
+        &mut self,
+        addr: &OpTy<'tcx>,
+        length: &OpTy<'tcx>,
+        prot: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        let length = this.read_target_usize(length)?;
+        let prot = this.read_scalar(prot)?.to_i32()?;
+
+        #[expect(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+        if addr.addr().bytes() % this.machine.page_size != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let Some(prot) = this.parse_prot(prot) else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+        let Some(length) = length.checked_next_multiple_of(this.machine.page_size) else {
+            return this.set_last_error_and_return_i32(LibcError("ENOMEM"));
+        };
+
+        let Some((alloc_id, offset)) = this.mapping_of(addr)? else {
+            // The standard library uses mprotect to set up stack guard pages. We have no page
+            // protections outside of `mmap`ed memory, so we can only pretend that this worked.
+            if this.frame_in_std() {
+                // The first time this happens, show a warning.
+                thread_local! {
+                    static WARNING_SHOWN: RefCell<bool> = const { RefCell::new(false) };
+                }
+                WARNING_SHOWN.with_borrow_mut(|shown| {
+                    if !*shown {
+                        *shown = true;
+                        this.emit_diagnostic(NonHaltingDiagnostic::IgnoredMprotect);
+                    }
+                });
+                return interp_ok(Scalar::from_i32(0));
+            }
+            if this.ptr_try_get_alloc_id(addr, 0).is_ok() {
+                throw_unsup_format!("`mprotect` is only supported on memory allocated with `mmap`");
+            }
+            return this.set_last_error_and_return_i32(LibcError("ENOMEM"));
+        };
+        // We only support changing the protection of pages within a single mapping.
+        let size = this.get_alloc_info(alloc_id).size;
+        if offset.bytes().checked_add(length).is_none_or(|end| end > size.bytes()) {
+            return this.set_last_error_and_return_i32(LibcError("ENOMEM"));
+        }
+
+        let page_protections =
+            this.get_alloc_extra_mut(alloc_id)?.0.page_protections.as_mut().unwrap();
+        page_protections.set(offset, Size::from_bytes(length), prot);
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn madvise(
+        &mut self,
+        addr: &OpTy<'tcx>,
+        length: &OpTy<'tcx>,
+        advice: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let addr = this.read_pointer(addr)?;
+        let length = this.read_target_usize(length)?;
+        let advice = this.read_scalar(advice)?.to_i32()?;
+
+        #[expect(clippy::arithmetic_side_effects)] // PAGE_SIZE is nonzero
+        if addr.addr().bytes() % this.machine.page_size != 0 {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        let Some(length) = length.checked_next_multiple_of(this.machine.page_size) else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+
+        // These are only hints, which we can ignore.
+        let hints = ["MADV_NORMAL", "MADV_RANDOM", "MADV_SEQUENTIAL", "MADV_WILLNEED"];
+        if hints.iter().any(|hint| advice == this.eval_libc_i32(hint)) {
+            return interp_ok(Scalar::from_i32(0));
+        }
+        if advice != this.eval_libc_i32("MADV_DONTNEED") {
+            throw_unsup_format!("Miri does not support madvise with advice {advice}");
+        }
+
+        let Some((alloc_id, offset)) = this.mapping_of(addr)? else {
+            return this.set_last_error_and_return_i32(LibcError("ENOMEM"));
+        };
+        let size = this.get_alloc_info(alloc_id).size;
+        if offset.bytes().checked_add(length).is_none_or(|end| end > size.bytes()) {
+            return this.set_last_error_and_return_i32(LibcError("ENOMEM"));
+        }
+
+        // The pages of shared file mappings keep their contents. Other pages lose their contents:
+        // the next access sees the contents of the file for private file mappings, and zeros
+        // otherwise.
+        let mapping = this.machine.mmaps.files.get(&alloc_id).cloned();
+        if mapping.as_ref().is_some_and(|mapping| mapping.shared) {
+            return interp_ok(Scalar::from_i32(0));
+        }
+        let mut bytes = vec![0u8; usize::try_from(length).unwrap()];
+        if let Some(mapping) = mapping {
+            let file_len = mapping.len.saturating_sub(offset.bytes()).min(length);
+            if file_len > 0 {
+                let file_offset = mapping.offset.strict_add(offset.bytes());
+                let contents = match mapping.fd.as_unix().read_at(
+                    this.machine.communicate(),
+                    file_offset,
+                    file_len,
+                )? {
+                    Ok(contents) => contents,
+                    Err(e) => return this.set_last_error_and_return_i32(e),
+                };
+                bytes[..contents.len()].copy_from_slice(&contents);
+            }
+        }
+        this.without_page_protections(alloc_id, |this| this.write_bytes_ptr(addr, bytes))?;
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    /// Describes the memory mapped by mmap in the format of `/proc/self/maps`, with one line per
+    /// range of pages that have the same protection.
+    fn proc_self_maps(&self) -> InterpResult<'tcx, String> {
+        let this = self.eval_context_ref();
+        let mut maps = String::new();
+        for (base_addr, alloc_id) in this.live_alloc_addresses(MiriMemoryKind::Mmap.into())? {
+            let Some(page_protections) = &this.get_alloc_extra(alloc_id)?.page_protections else {
+                continue;
+            };
+            let mapping = this.machine.mmaps.files.get(&alloc_id);
+            let shared = mapping.is_some_and(|mapping| mapping.shared);
+            // We do not track the path of mapped files, so we only report their offset.
+            let file_offset = mapping.map(|mapping| mapping.offset);
+            let mut runs = page_protections.0.iter_all().peekable();
+            while let Some((range, &prot)) = runs.next() {
+                let mut end = range.end;
+                while let Some((next, _)) = runs.next_if(|(_, next_prot)| **next_prot == prot) {
+                    end = next.end;
+                }
+                writeln!(
+                    maps,
+                    "{:08x}-{:08x} {}{}-{} {:08x} 00:00 0",
+                    base_addr.strict_add(range.start),
+                    base_addr.strict_add(end),
+                    if prot.read { 'r' } else { '-' },
+                    if prot.write { 'w' } else { '-' },
+                    if shared { 's' } else { 'p' },
+                    file_offset.map_or(0, |offset| offset.strict_add(range.start)),
+                )
+                .unwrap();
+            }
+        }
+        interp_ok(maps)
+    }
 }
@@ -4,6 +4,7 @@ mod env;
 mod fd;
 mod fs;
 mod mem;
+mod process;
 mod sync;
 mod thread;
 mod unnamed_socket;
@@ -18,12 +19,14 @@ mod solarish;
 // All the Unix-specific extension traits
 pub use self::env::{EvalContextExt as _, UnixEnvVars};
 pub use self::fd::{EvalContextExt as _, UnixFileDescription};
-pub use self::fs::{DirTable, EvalContextExt as _};
+pub use self::fs::{DirTable, EvalContextExt as _, IsolatedMetadataTable};
 pub use self::linux_like::epoll::EpollInterestTable;
-pub use self::mem::EvalContextExt as _;
+pub use self::mem::{EvalContextExt as _, MmapTable, PageProt, PageProtections};
+pub use self::process::{EvalContextExt as _, RLIMIT_RESOURCES, ResourceLimit, init_rlimits};
 pub use self::sync::EvalContextExt as _;
 pub use self::thread::{EvalContextExt as _, ThreadNameResult};
 pub use self::unnamed_socket::EvalContextExt as _;
 
 // Make up some constants.
 const UID: u32 = 1000;
+const GID: u32 = 1000;
@@ -0,0 +1,518 @@
+//! Shims that describe the process and the machine it runs on: resource limits, the kernel and
+//! host name, and system statistics. Under isolation these report fixed, configurable values;
+//! only with isolation disabled do we forward to the host.
+
+use std::iter;
+
+use rustc_abi::Size;
+use rustc_data_structures::fx::FxHashMap;
+use rustc_middle::ty::layout::LayoutOf;
+
+use crate::helpers::ToSoft;
+use crate::shims::unix::linux::procfs::{EvalContextExt as _, MEM_TOTAL};
+use crate::*;
+
+/// The resource limits that we emulate. These are the ones POSIX defines, so they exist on all
+/// Unix targets.
+pub const RLIMIT_RESOURCES: &[&str] = &[
+    "RLIMIT_CPU",
+    "RLIMIT_FSIZE",
+    "RLIMIT_DATA",
+    "RLIMIT_STACK",
+    "RLIMIT_CORE",
+    "RLIMIT_NOFILE",
+    "RLIMIT_AS",
+];
+
+/// The host name reported under isolation, unless `-Zmiri-hostname` says otherwise.
+const DEFAULT_HOSTNAME: &str = "miri";
+
+/// The soft and hard limit of a resource. `None` means there is no limit, i.e. `RLIM_INFINITY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceLimit {
+    pub soft: Option<u64>,
+    pub hard: Option<u64>,
+}
+
+impl ResourceLimit {
+    pub const UNLIMITED: ResourceLimit = ResourceLimit { soft: None, hard: None };
+
+    /// Whether this limit is invalid because the soft limit is larger than the hard limit.
+    pub fn soft_exceeds_hard(&self) -> bool {
+        limit_exceeds(self.soft, self.hard)
+    }
+
+    /// The limit the emulated machine starts out with, modelled after a typical Linux system.
+    fn default_for(resource: &str) -> ResourceLimit {
+        match resource {
+            // The main thread's stack gets sized after this, see `ThreadManager::init`.
+            "RLIMIT_STACK" => ResourceLimit { soft: Some(MAIN_THREAD_STACK_SIZE), hard: None },
+            "RLIMIT_CORE" => ResourceLimit { soft: Some(0), hard: None },
+            "RLIMIT_NOFILE" => ResourceLimit { soft: Some(1 << 16), hard: Some(1 << 16) },
+            _ => ResourceLimit::UNLIMITED,
+        }
+    }
+}
+
+/// Whether limit `a` is larger than limit `b`, where `None` is larger than everything.
+fn limit_exceeds(a: Option<u64>, b: Option<u64>) -> bool {
+    match (a, b) {
+        (_, None) => false,
+        (None, Some(_)) => true,
+        (Some(a), Some(b)) => a > b,
+    }
+}
+
+/// Builds the initial resource limits of the emulated process: values set on the command line
+/// win, then the limits of the host process if isolation is disabled, then our defaults.
+pub fn init_rlimits(config: &MiriConfig) -> FxHashMap<&'static str, ResourceLimit> {
+    let communicate = config.isolated_op == IsolatedOp::Allow;
+    RLIMIT_RESOURCES
+        .iter()
+        .map(|&resource| {
+            let limit = config
+                .rlimits
+                .get(resource)
+                .copied()
+                .or_else(|| if communicate { host_rlimit(resource) } else { None })
+                .unwrap_or_else(|| ResourceLimit::default_for(resource));
+            (resource, limit)
+        })
+        .collect()
+}
+
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)]
+fn host_rlimit(resource: &str) -> Option<ResourceLimit> {
+    let resource = match resource {
+        "RLIMIT_CPU" => libc::RLIMIT_CPU,
+        "RLIMIT_FSIZE" => libc::RLIMIT_FSIZE,
+        "RLIMIT_DATA" => libc::RLIMIT_DATA,
+        "RLIMIT_STACK" => libc::RLIMIT_STACK,
+        "RLIMIT_CORE" => libc::RLIMIT_CORE,
+        "RLIMIT_NOFILE" => libc::RLIMIT_NOFILE,
+        "RLIMIT_AS" => libc::RLIMIT_AS,
+        _ => return None,
+    };
+    let mut rlim = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    if unsafe { libc::getrlimit(resource, &mut rlim) } != 0 {
+        return None;
+    }
+    let convert = |value: libc::rlim_t| {
+        if value == libc::RLIM_INFINITY { None } else { u64::try_from(value).ok() }
+    };
+    Some(ResourceLimit { soft: convert(rlim.rlim_cur), hard: convert(rlim.rlim_max) })
+}
+
+#[cfg(not(unix))]
+fn host_rlimit(_resource: &str) -> Option<ResourceLimit> {
+    None
+}
+
+/// The name of the host, if we can determine it.
+#[cfg(unix)]
+fn host_hostname() -> Option<Vec<u8>> {
+    let mut buf = [0u8; 256];
+    if unsafe { libc::gethostname(buf.as_mut_ptr().cast(), buf.len()) } != 0 {
+        return None;
+    }
+    let len = buf.iter().position(|&b| b == 0)?;
+    Some(buf[..len].to_vec())
+}
+
+#[cfg(not(unix))]
+fn host_hostname() -> Option<Vec<u8>> {
+    None
+}
+
+/// The `release` and `version` fields of `uname` on the host.
+#[cfg(unix)]
+fn host_uname() -> Option<(Vec<u8>, Vec<u8>)> {
+    use std::ffi::CStr;
+
+    let mut uts: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut uts) } != 0 {
+        return None;
+    }
+    let field =
+        |field: &[libc::c_char]| unsafe { CStr::from_ptr(field.as_ptr()) }.to_bytes().to_vec();
+    Some((field(&uts.release), field(&uts.version)))
+}
+
+#[cfg(not(unix))]
+fn host_uname() -> Option<(Vec<u8>, Vec<u8>)> {
+    None
+}
+
+/// The system statistics reported by `sysinfo`. Memory sizes are in bytes.
+struct SysInfo {
+    uptime: u64,
+    loads: [u64; 3],
+    totalram: u64,
+    freeram: u64,
+    sharedram: u64,
+    bufferram: u64,
+    totalswap: u64,
+    freeswap: u64,
+    procs: u16,
+}
+
+#[cfg(target_os = "linux")]
+#[allow(clippy::useless_conversion)]
+fn host_sysinfo() -> Option<SysInfo> {
+    let mut info: libc::sysinfo = unsafe { std::mem::zeroed() };
+    if unsafe { libc::sysinfo(&mut info) } != 0 {
+        return None;
+    }
+    let unit = u64::from(info.mem_unit);
+    let bytes = |value: libc::c_ulong| u64::try_from(value).ok()?.checked_mul(unit);
+    Some(SysInfo {
+        uptime: u64::try_from(info.uptime).ok()?,
+        loads: info.loads.map(|load| u64::try_from(load).unwrap_or(0)),
+        totalram: bytes(info.totalram)?,
+        freeram: bytes(info.freeram)?,
+        sharedram: bytes(info.sharedram)?,
+        bufferram: bytes(info.bufferram)?,
+        totalswap: bytes(info.totalswap)?,
+        freeswap: bytes(info.freeswap)?,
+        procs: info.procs,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn host_sysinfo() -> Option<SysInfo> {
+    None
+}
+
+#[cfg(any(target_os = "linux", target_os = "macos", target_os = "freebsd"))]
+fn host_loadavg() -> Option<[f64; 3]> {
+    let mut loads = [0.0; 3];
+    if unsafe { libc::getloadavg(loads.as_mut_ptr(), 3) } != 3 {
+        return None;
+    }
+    Some(loads)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "freebsd")))]
+fn host_loadavg() -> Option<[f64; 3]> {
+    None
+}
+
+impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
+trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The host name of the emulated machine.
+    fn hostname(&self) -> Vec<u8> {
+        let this = self.eval_context_ref();
+        if let Some(hostname) = &this.machine.hostname {
+            return hostname.as_bytes().to_vec();
+        }
+        if this.machine.communicate()
+            && let Some(hostname) = host_hostname()
+        {
+            return hostname;
+        }
+        DEFAULT_HOSTNAME.as_bytes().to_vec()
+    }
+
+    /// Finds the name of the resource with the given value of its `RLIMIT_*` constant, or `None`
+    /// if we do not emulate that resource.
+    fn rlimit_resource(&self, resource: u32) -> Option<&'static str> {
+        let this = self.eval_context_ref();
+        RLIMIT_RESOURCES.iter().find(|&&name| this.eval_libc_u32(name) == resource).copied()
+    }
+
+    fn read_rlimit(
+        &self,
+        rlim: &MPlaceTy<'tcx>,
+        infinity: u128,
+    ) -> InterpResult<'tcx, ResourceLimit> {
+        let this = self.eval_context_ref();
+        let read = |field: &str| -> InterpResult<'tcx, Option<u64>> {
+            let field = this.project_field_named(rlim, field)?;
+            let value = this.read_scalar(&field)?.to_uint(field.layout.size)?;
+            interp_ok(if value >= infinity { None } else { Some(u64::try_from(value).unwrap()) })
+        };
+        interp_ok(ResourceLimit { soft: read("rlim_cur")?, hard: read("rlim_max")? })
+    }
+
+    fn write_rlimit(
+        &mut self,
+        limit: ResourceLimit,
+        rlim: &MPlaceTy<'tcx>,
+        infinity: u128,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let value = |limit: Option<u64>| {
+            let value = limit.map_or(infinity, |limit| u128::from(limit).min(infinity));
+            i128::try_from(value).unwrap()
+        };
+        this.write_int_fields_named(
+            &[("rlim_cur", value(limit.soft)), ("rlim_max", value(limit.hard))],
+            rlim,
+        )
+    }
+
+    /// The common implementation of `getrlimit`, `setrlimit` and `prlimit`. `infinity` is the
+    /// value of `RLIM_INFINITY` for the layout of `new_rlim` and `old_rlim`.
+    fn update_rlimit(
+        &mut self,
+        resource: &OpTy<'tcx>,
+        new_rlim: Option<MPlaceTy<'tcx>>,
+        old_rlim: Option<MPlaceTy<'tcx>>,
+        infinity: u128,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let resource = this.read_scalar(resource)?.to_u32()?;
+        // Like the kernel does for resources it does not know about.
+        let Some(resource) = this.rlimit_resource(resource) else {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        };
+        let current = this.machine.rlimits[resource];
+
+        let new = new_rlim.map(|rlim| this.read_rlimit(&rlim, infinity)).transpose()?;
+        if let Some(new) = new {
+            if new.soft_exceeds_hard() {
+                return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+            }
+            // We are not a privileged process, so we cannot raise the hard limit.
+            if limit_exceeds(new.hard, current.hard) {
+                return this.set_last_error_and_return_i32(LibcError("EPERM"));
+            }
+        }
+
+        if let Some(old_rlim) = old_rlim {
+            this.write_rlimit(current, &old_rlim, infinity)?;
+        }
+        if let Some(new) = new {
+            this.machine.rlimits.insert(resource, new);
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn rlim_infinity(&self) -> InterpResult<'tcx, u128> {
+        let this = self.eval_context_ref();
+        let size = this.libc_ty_layout("rlim_t").size;
+        this.eval_libc("RLIM_INFINITY").to_uint(size)
+    }
+
+    fn sysinfo_emulated(&self) -> SysInfo {
+        let this = self.eval_context_ref();
+        let uptime = this.machine.clock.now().duration_since(this.machine.clock.epoch());
+        SysInfo {
+            uptime: uptime.as_secs(),
+            loads: [0; 3],
+            totalram: MEM_TOTAL,
+            freeram: MEM_TOTAL.saturating_sub(this.memory_in_use()),
+            sharedram: 0,
+            bufferram: 0,
+            totalswap: 0,
+            freeswap: 0,
+            procs: u16::try_from(this.machine.threads.get_live_thread_count()).unwrap_or(u16::MAX),
+        }
+    }
+}
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    /// The limit on the number of open file descriptors, as reported by `sysconf(_SC_OPEN_MAX)`.
+    fn open_max(&self) -> Option<u64> {
+        let this = self.eval_context_ref();
+        this.machine.rlimits["RLIMIT_NOFILE"].soft
+    }
+
+    fn getrlimit(
+        &mut self,
+        resource: &OpTy<'tcx>,
+        rlim: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let rlim = this.deref_pointer_as(rlim, this.libc_ty_layout("rlimit"))?;
+        let infinity = this.rlim_infinity()?;
+        this.update_rlimit(resource, None, Some(rlim), infinity)
+    }
+
+    /// Changes the emulated resource limit. This never affects the limits of the host process.
+    fn setrlimit(
+        &mut self,
+        resource: &OpTy<'tcx>,
+        rlim: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let rlim = this.deref_pointer_as(rlim, this.libc_ty_layout("rlimit"))?;
+        let infinity = this.rlim_infinity()?;
+        this.update_rlimit(resource, Some(rlim), None, infinity)
+    }
+
+    /// Implements both `prlimit` and `prlimit64`, which differ only in the type of the limits.
+    fn prlimit(
+        &mut self,
+        pid: &OpTy<'tcx>,
+        resource: &OpTy<'tcx>,
+        new_rlim: &OpTy<'tcx>,
+        old_rlim: &OpTy<'tcx>,
+        is_64: bool,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let pid = this.read_scalar(pid)?.to_i32()?;
+        // There are no other processes in the emulated machine.
+        if pid != 0 && u32::try_from(pid).ok() != Some(this.get_pid()) {
+            return this.set_last_error_and_return_i32(LibcError("ESRCH"));
+        }
+
+        let (layout, infinity) = if is_64 {
+            (this.libc_ty_layout("rlimit64"), u128::from(u64::MAX))
+        } else {
+            (this.libc_ty_layout("rlimit"), this.rlim_infinity()?)
+        };
+        let new_rlim = this.read_pointer(new_rlim)?;
+        let old_rlim = this.read_pointer(old_rlim)?;
+        let new_rlim = (!this.ptr_is_null(new_rlim)?).then(|| this.ptr_to_mplace(new_rlim, layout));
+        let old_rlim = (!this.ptr_is_null(old_rlim)?).then(|| this.ptr_to_mplace(old_rlim, layout));
+        this.update_rlimit(resource, new_rlim, old_rlim, infinity)
+    }
+
+    fn uname(&mut self, buf: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let target = &this.tcx.sess.target;
+        let target_os = &*target.os;
+
+        let (sysname, release, version) = match target_os {
+            "linux" | "android" => ("Linux", "6.1.0", "#1 SMP PREEMPT_DYNAMIC"),
+            "macos" => ("Darwin", "23.0.0", "Darwin Kernel Version 23.0.0"),
+            "freebsd" => ("FreeBSD", "14.0-RELEASE", "FreeBSD 14.0-RELEASE"),
+            "solaris" | "illumos" => ("SunOS", "5.11", "miri"),
+            _ => throw_unsup_format!("`uname` is not supported on {target_os}"),
+        };
+        let machine = match (target_os, &*target.arch) {
+            ("macos" | "freebsd", "aarch64") => "arm64",
+            ("freebsd", "x86_64") => "amd64",
+            ("freebsd", "x86") => "i386",
+            ("solaris" | "illumos", "x86_64" | "x86") => "i86pc",
+            (_, "x86") => "i686",
+            (_, arch) => arch,
+        };
+        // The kernel release and version only make sense if the host runs the same kernel.
+        let (release, version) = if this.machine.communicate()
+            && std::env::consts::OS == target_os
+            && let Some(host) = host_uname()
+        {
+            host
+        } else {
+            (release.as_bytes().to_vec(), version.as_bytes().to_vec())
+        };
+        let machine = machine.as_bytes().to_vec();
+        let hostname = this.hostname();
+
+        let buf = this.deref_pointer_as(buf, this.libc_ty_layout("utsname"))?;
+        let mut fields = vec![
+            ("sysname", sysname.as_bytes()),
+            ("nodename", &hostname[..]),
+            ("release", &release[..]),
+            ("version", &version[..]),
+            ("machine", &machine[..]),
+        ];
+        if matches!(target_os, "linux" | "android") {
+            fields.push(("domainname", &b"(none)"[..]));
+        }
+        for (name, value) in fields {
+            let field = this.project_field_named(&buf, name)?;
+            // Truncate the value so that it fits, including the null terminator.
+            let size = field.layout.size.bytes();
+            let len = usize::try_from(size.saturating_sub(1)).unwrap().min(value.len());
+            this.write_c_str(&value[..len], field.ptr(), size)?;
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn gethostname(&mut self, name: &OpTy<'tcx>, len: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let name = this.read_pointer(name)?;
+        let len = this.read_target_usize(len)?;
+
+        let hostname = this.hostname();
+        let (written, _) = this.write_c_str(&hostname, name, len)?;
+        if !written {
+            return this.set_last_error_and_return_i32(LibcError("ENAMETOOLONG"));
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn getloadavg(
+        &mut self,
+        loadavg: &OpTy<'tcx>,
+        nelem: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let loadavg = this.read_pointer(loadavg)?;
+        let nelem = this.read_scalar(nelem)?.to_i32()?;
+        let Ok(nelem) = u64::try_from(nelem) else {
+            return interp_ok(Scalar::from_i32(-1));
+        };
+
+        // The emulated machine is idle.
+        let loads = if this.machine.communicate() { host_loadavg() } else { None };
+        let loads = loads.unwrap_or([0.0; 3]);
+        let count = nelem.min(3);
+        let f64_layout = this.layout_of(this.tcx.types.f64)?;
+        for (i, &load) in (0..count).zip(loads.iter()) {
+            let offset = Size::from_bytes(i.strict_mul(8));
+            let place = this.ptr_to_mplace(loadavg.wrapping_offset(offset, this), f64_layout);
+            this.write_scalar(Scalar::from_f64(load.to_soft()), &place)?;
+        }
+        interp_ok(Scalar::from_i32(i32::try_from(count).unwrap()))
+    }
+
+    fn linux_sysinfo(&mut self, info: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        this.assert_target_os("linux", "sysinfo");
+
+        let info = this.deref_pointer_as(info, this.libc_ty_layout("sysinfo"))?;
+        let stats = if this.machine.communicate() { host_sysinfo() } else { None };
+        let stats = stats.unwrap_or_else(|| this.sysinfo_emulated());
+
+        // Like the kernel, report memory sizes in bytes if they fit, and in pages otherwise.
+        let ulong_size = this.libc_ty_layout("c_ulong").size;
+        let memory = [
+            stats.totalram,
+            stats.freeram,
+            stats.sharedram,
+            stats.bufferram,
+            stats.totalswap,
+            stats.freeswap,
+        ];
+        let mem_unit =
+            if memory.iter().all(|&bytes| u128::from(bytes) <= ulong_size.unsigned_int_max()) {
+                1
+            } else {
+                this.machine.page_size
+            };
+        let memory = memory.map(|bytes| i128::from(bytes.strict_div(mem_unit)));
+
+        // Initialize with `0`.
+        this.write_bytes_ptr(info.ptr(), iter::repeat(0u8).take(info.layout.size.bytes_usize()))?;
+        this.write_int_fields_named(
+            &[
+                ("uptime", stats.uptime.into()),
+                ("totalram", memory[0]),
+                ("freeram", memory[1]),
+                ("sharedram", memory[2]),
+                ("bufferram", memory[3]),
+                ("totalswap", memory[4]),
+                ("freeswap", memory[5]),
+                ("procs", stats.procs.into()),
+                ("mem_unit", mem_unit.into()),
+            ],
+            &info,
+        )?;
+        let loads = this.project_field_named(&info, "loads")?;
+        for (i, load) in (0..).zip(stats.loads) {
+            let load_place = this.project_index(&loads, i)?;
+            this.write_int(load, &load_place)?;
+        }
+        interp_ok(Scalar::from_i32(0))
+    }
+}
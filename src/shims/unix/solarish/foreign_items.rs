@@ -76,6 +76,12 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let result = this.macos_fbsd_solaris_fstat(fd, buf)?;
                 this.write_scalar(result, dest)?;
             }
+            "fstatat" | "fstatat64" => {
+                let [dirfd, path, buf, flags] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+                let result = this.macos_fbsd_solaris_fstatat(dirfd, path, buf, flags)?;
+                this.write_scalar(result, dest)?;
+            }
 
             // Miscellaneous
             "___errno" => {
@@ -92,7 +98,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.write_int_fields_named(
                     &[
                         ("ss_sp", this.machine.stack_addr.into()),
-                        ("ss_size", this.machine.stack_size.into()),
+                        ("ss_size", this.get_thread_stack_size(this.active_thread()).into()),
                         // field set to 0 means not in an alternate signal stack
                         // https://docs.oracle.com/cd/E86824_01/html/E54766/stack-getbounds-3c.html
                         ("ss_flags", 0),
@@ -422,6 +422,124 @@ where
     )
 }
 
+// # pthread_spinlock_t
+// We store some data directly inside the type, ignoring the platform layout:
+// - init: u32
+
+#[derive(Debug, Clone)]
+struct PthreadSpinlock {
+    mutex_ref: MutexRef,
+}
+
+fn spin_init_offset<'tcx>(ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, Size> {
+    let offset = match &*ecx.tcx.sess.target.os {
+        "linux" | "illumos" | "solaris" | "freebsd" | "android" => 0,
+        os => throw_unsup_format!("`pthread_spinlock` is not supported on {os}"),
+    };
+    interp_ok(Size::from_bytes(offset))
+}
+
+fn spin_get_data<'tcx, 'a>(
+    ecx: &'a mut MiriInterpCx<'tcx>,
+    lock_ptr: &OpTy<'tcx>,
+) -> InterpResult<'tcx, &'a PthreadSpinlock>
+where
+    'tcx: 'a,
+{
+    // Check the offset first, so that we fail gracefully on targets without this type.
+    let init_offset = spin_init_offset(ecx)?;
+    let lock = ecx.deref_pointer_as(lock_ptr, ecx.libc_ty_layout("pthread_spinlock_t"))?;
+    ecx.lazy_sync_get_data(
+        &lock,
+        init_offset,
+        || throw_ub_format!("`pthread_spinlock_t` can't be moved after first use"),
+        |_| throw_ub_format!("`pthread_spinlock_t` was used without being initialized"),
+    )
+}
+
+// # sem_t
+// We store some data directly inside the type, ignoring the platform layout:
+// - init: u32
+
+/// The largest value of a semaphore. This is `INT_MAX` on all supported targets.
+const SEM_VALUE_MAX: u32 = 0x7fff_ffff;
+
+#[derive(Debug, Clone)]
+struct PosixSemaphore {
+    sem_ref: SemaphoreRef,
+}
+
+fn sem_init_offset<'tcx>(ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, Size> {
+    let offset = match &*ecx.tcx.sess.target.os {
+        "linux" | "illumos" | "solaris" | "freebsd" | "android" => 0,
+        // macOS only has named semaphores; `sem_init` always fails there.
+        os => throw_unsup_format!("unnamed semaphores are not supported on {os}"),
+    };
+    interp_ok(Size::from_bytes(offset))
+}
+
+fn sem_get_data<'tcx, 'a>(
+    ecx: &'a mut MiriInterpCx<'tcx>,
+    sem_ptr: &OpTy<'tcx>,
+) -> InterpResult<'tcx, &'a PosixSemaphore>
+where
+    'tcx: 'a,
+{
+    // Check the offset first, so that we fail gracefully on targets without this type.
+    let init_offset = sem_init_offset(ecx)?;
+    let sem = ecx.deref_pointer_as(sem_ptr, ecx.libc_ty_layout("sem_t"))?;
+    ecx.lazy_sync_get_data(
+        &sem,
+        init_offset,
+        || throw_ub_format!("`sem_t` can't be moved after first use"),
+        |_| throw_ub_format!("`sem_t` was used without being initialized"),
+    )
+}
+
+// # pthread_barrier_t
+// We store some data directly inside the type, ignoring the platform layout:
+// - init: u32
+
+#[derive(Debug, Clone)]
+struct PthreadBarrier {
+    barrier_ref: BarrierRef,
+}
+
+fn barrier_init_offset<'tcx>(ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, Size> {
+    let offset = match &*ecx.tcx.sess.target.os {
+        "linux" | "illumos" | "solaris" | "freebsd" | "android" => 0,
+        os => throw_unsup_format!("`pthread_barrier` is not supported on {os}"),
+    };
+    interp_ok(Size::from_bytes(offset))
+}
+
+/// The value of `PTHREAD_BARRIER_SERIAL_THREAD`, which not all versions of the `libc` crate have.
+fn barrier_serial_thread<'tcx>(ecx: &MiriInterpCx<'tcx>) -> InterpResult<'tcx, i32> {
+    interp_ok(match &*ecx.tcx.sess.target.os {
+        "linux" | "freebsd" | "android" => -1,
+        "illumos" | "solaris" => -2,
+        os => throw_unsup_format!("`pthread_barrier` is not supported on {os}"),
+    })
+}
+
+fn barrier_get_data<'tcx, 'a>(
+    ecx: &'a mut MiriInterpCx<'tcx>,
+    barrier_ptr: &OpTy<'tcx>,
+) -> InterpResult<'tcx, &'a PthreadBarrier>
+where
+    'tcx: 'a,
+{
+    // Check the offset first, so that we fail gracefully on targets without this type.
+    let init_offset = barrier_init_offset(ecx)?;
+    let barrier = ecx.deref_pointer_as(barrier_ptr, ecx.libc_ty_layout("pthread_barrier_t"))?;
+    ecx.lazy_sync_get_data(
+        &barrier,
+        init_offset,
+        || throw_ub_format!("`pthread_barrier_t` can't be moved after first use"),
+        |_| throw_ub_format!("`pthread_barrier_t` was used without being initialized"),
+    )
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
     fn pthread_mutexattr_init(&mut self, attr_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
@@ -624,7 +742,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let id = rwlock_get_data(this, rwlock_op)?.id;
 
         if this.rwlock_is_write_locked(id) {
-            this.rwlock_enqueue_and_block_reader(id, Scalar::from_i32(0), dest.clone());
+            this.rwlock_enqueue_and_block_reader(id, Some((Scalar::from_i32(0), dest.clone())));
         } else {
             this.rwlock_reader_lock(id);
             this.write_null(dest)?;
@@ -668,7 +786,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             // report the deadlock only when no thread can continue execution,
             // but we could detect that this lock is already locked and report
             // an error.)
-            this.rwlock_enqueue_and_block_writer(id, Scalar::from_i32(0), dest.clone());
+            this.rwlock_enqueue_and_block_writer(id, Some((Scalar::from_i32(0), dest.clone())));
         } else {
             this.rwlock_writer_lock(id);
             this.write_null(dest)?;
@@ -905,4 +1023,290 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         interp_ok(())
     }
+
+    fn pthread_spin_init(
+        &mut self,
+        lock_op: &OpTy<'tcx>,
+        _pshared_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        let init_offset = spin_init_offset(this)?;
+        let lock = this.deref_pointer_as(lock_op, this.libc_ty_layout("pthread_spinlock_t"))?;
+
+        // There is only one process, so `pshared` makes no difference.
+        let mutex_ref = this.machine.sync.mutex_create();
+        this.lazy_sync_init(&lock, init_offset, PthreadSpinlock { mutex_ref })?;
+
+        interp_ok(())
+    }
+
+    fn pthread_spin_lock(
+        &mut self,
+        lock_op: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref = spin_get_data(this, lock_op)?.mutex_ref.clone();
+
+        if this.mutex_is_locked(&mutex_ref) {
+            if this.mutex_get_owner(&mutex_ref) == this.active_thread() {
+                // Spinning on a lock we hold ourselves will never finish.
+                throw_machine_stop!(TerminationInfo::Deadlock);
+            }
+            // A real spinlock busy-waits, but that is not observable, so we block instead.
+            this.mutex_enqueue_and_block(&mutex_ref, Some((Scalar::from_i32(0), dest.clone())));
+        } else {
+            this.mutex_lock(&mutex_ref);
+            this.write_null(dest)?;
+        }
+
+        interp_ok(())
+    }
+
+    fn pthread_spin_trylock(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref = spin_get_data(this, lock_op)?.mutex_ref.clone();
+
+        if this.mutex_is_locked(&mutex_ref) {
+            interp_ok(Scalar::from_i32(this.eval_libc_i32("EBUSY")))
+        } else {
+            this.mutex_lock(&mutex_ref);
+            interp_ok(Scalar::from_i32(0))
+        }
+    }
+
+    fn pthread_spin_unlock(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref = spin_get_data(this, lock_op)?.mutex_ref.clone();
+
+        if this.mutex_unlock(&mutex_ref)?.is_none() {
+            throw_ub_format!("unlocked a spinlock that was not locked by the current thread");
+        }
+
+        interp_ok(())
+    }
+
+    fn pthread_spin_destroy(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        // Reading the field also has the side-effect that we detect double-`destroy`
+        // since we make the field uninit below.
+        let mutex_ref = spin_get_data(this, lock_op)?.mutex_ref.clone();
+
+        if this.mutex_is_locked(&mutex_ref) {
+            throw_ub_format!("destroyed a locked spinlock");
+        }
+
+        // This might lead to false positives, see comment in pthread_mutexattr_destroy
+        let lock = this.deref_pointer_as(lock_op, this.libc_ty_layout("pthread_spinlock_t"))?;
+        this.lazy_sync_remove(&lock)?;
+        this.write_uninit(&lock)?;
+
+        interp_ok(())
+    }
+
+    fn sem_init(
+        &mut self,
+        sem_op: &OpTy<'tcx>,
+        _pshared_op: &OpTy<'tcx>,
+        value_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let init_offset = sem_init_offset(this)?;
+        let sem = this.deref_pointer_as(sem_op, this.libc_ty_layout("sem_t"))?;
+        let value = this.read_scalar(value_op)?.to_u32()?;
+
+        if value > SEM_VALUE_MAX {
+            return this.set_last_error_and_return_i32(LibcError("EINVAL"));
+        }
+        if this.lazy_sync_has_data::<PosixSemaphore>(&sem)? {
+            throw_ub_format!("`sem_init` called on an already initialized semaphore");
+        }
+
+        // There is only one process, so `pshared` makes no difference.
+        let sem_ref = this.machine.sync.semaphore_create(value);
+        this.lazy_sync_init(&sem, init_offset, PosixSemaphore { sem_ref })?;
+
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn sem_wait(&mut self, sem_op: &OpTy<'tcx>, dest: &MPlaceTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let sem_ref = sem_get_data(this, sem_op)?.sem_ref.clone();
+
+        this.semaphore_wait(
+            sem_ref,
+            None, // no timeout
+            Scalar::from_i32(0),
+            Scalar::from_i32(-1), // retval_timeout -- unused
+            dest.clone(),
+            LibcError("ETIMEDOUT"), // errno_timeout -- unused
+        )
+    }
+
+    fn sem_trywait(&mut self, sem_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let sem_ref = sem_get_data(this, sem_op)?.sem_ref.clone();
+
+        if this.semaphore_try_wait(&sem_ref) {
+            interp_ok(Scalar::from_i32(0))
+        } else {
+            this.set_last_error_and_return_i32(LibcError("EAGAIN"))
+        }
+    }
+
+    fn sem_timedwait(
+        &mut self,
+        sem_op: &OpTy<'tcx>,
+        abstime_op: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let sem_ref = sem_get_data(this, sem_op)?.sem_ref.clone();
+
+        // The timeout need not be checked if the semaphore can be decremented immediately.
+        if this.semaphore_try_wait(&sem_ref) {
+            return this.write_null(dest);
+        }
+
+        let Some(duration) = this
+            .read_timespec(&this.deref_pointer_as(abstime_op, this.libc_ty_layout("timespec"))?)?
+        else {
+            return this.set_last_error_and_return(LibcError("EINVAL"), dest);
+        };
+        this.check_no_isolation("`sem_timedwait`")?;
+
+        this.semaphore_wait(
+            sem_ref,
+            Some((TimeoutClock::RealTime, TimeoutAnchor::Absolute, duration)),
+            Scalar::from_i32(0),
+            Scalar::from_i32(-1),
+            dest.clone(),
+            LibcError("ETIMEDOUT"),
+        )
+    }
+
+    fn sem_post(&mut self, sem_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let sem_ref = sem_get_data(this, sem_op)?.sem_ref.clone();
+
+        if this.semaphore_post(&sem_ref, SEM_VALUE_MAX)? {
+            interp_ok(Scalar::from_i32(0))
+        } else {
+            this.set_last_error_and_return_i32(LibcError("EOVERFLOW"))
+        }
+    }
+
+    fn sem_getvalue(
+        &mut self,
+        sem_op: &OpTy<'tcx>,
+        sval_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        let sem_ref = sem_get_data(this, sem_op)?.sem_ref.clone();
+
+        // The value never exceeds `SEM_VALUE_MAX`, so it fits into an `int`.
+        let value = i32::try_from(this.semaphore_get_value(&sem_ref)).unwrap();
+        this.write_scalar(
+            Scalar::from_i32(value),
+            &this.deref_pointer_as(sval_op, this.machine.layouts.i32)?,
+        )?;
+
+        interp_ok(())
+    }
+
+    fn sem_destroy(&mut self, sem_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        // Reading the field also has the side-effect that we detect double-`destroy`
+        // since we make the field uninit below.
+        let sem_ref = sem_get_data(this, sem_op)?.sem_ref.clone();
+
+        if this.semaphore_is_awaited(&sem_ref) {
+            throw_ub_format!("destroying a semaphore that threads are waiting on");
+        }
+
+        // This might lead to false positives, see comment in pthread_mutexattr_destroy
+        let sem = this.deref_pointer_as(sem_op, this.libc_ty_layout("sem_t"))?;
+        this.lazy_sync_remove(&sem)?;
+        this.write_uninit(&sem)?;
+
+        interp_ok(())
+    }
+
+    fn pthread_barrier_init(
+        &mut self,
+        barrier_op: &OpTy<'tcx>,
+        _attr_op: &OpTy<'tcx>,
+        count_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let init_offset = barrier_init_offset(this)?;
+        let barrier =
+            this.deref_pointer_as(barrier_op, this.libc_ty_layout("pthread_barrier_t"))?;
+        let count = this.read_scalar(count_op)?.to_u32()?;
+
+        if count == 0 {
+            return interp_ok(this.eval_libc("EINVAL"));
+        }
+        if this.lazy_sync_has_data::<PthreadBarrier>(&barrier)? {
+            throw_ub_format!("`pthread_barrier_init` called on an already initialized barrier");
+        }
+
+        // The only barrier attribute is `pshared`, which makes no difference since there is only
+        // one process.
+        let barrier_ref = this.machine.sync.barrier_create(count);
+        this.lazy_sync_init(&barrier, init_offset, PthreadBarrier { barrier_ref })?;
+
+        interp_ok(Scalar::from_i32(0))
+    }
+
+    fn pthread_barrier_wait(
+        &mut self,
+        barrier_op: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let barrier_ref = barrier_get_data(this, barrier_op)?.barrier_ref.clone();
+        let serial_thread = barrier_serial_thread(this)?;
+
+        this.barrier_wait(
+            barrier_ref,
+            Scalar::from_i32(0),
+            Scalar::from_i32(serial_thread),
+            dest.clone(),
+        )
+    }
+
+    fn pthread_barrier_destroy(&mut self, barrier_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        // Reading the field also has the side-effect that we detect double-`destroy`
+        // since we make the field uninit below.
+        let barrier_ref = barrier_get_data(this, barrier_op)?.barrier_ref.clone();
+
+        if this.barrier_is_awaited(&barrier_ref) {
+            throw_ub_format!("destroying a barrier that threads are waiting on");
+        }
+
+        // This might lead to false positives, see comment in pthread_mutexattr_destroy
+        let barrier =
+            this.deref_pointer_as(barrier_op, this.libc_ty_layout("pthread_barrier_t"))?;
+        this.lazy_sync_remove(&barrier)?;
+        this.write_uninit(&barrier)?;
+
+        interp_ok(())
+    }
 }
@@ -9,12 +9,126 @@ pub enum ThreadNameResult {
     ThreadNotFound,
 }
 
+// # pthread_attr_t
+// We store some data directly inside the type, ignoring the platform layout:
+// - 0: the stack size of the thread, as a usize.
+
+fn attr_get_stack_size<'tcx>(
+    ecx: &MiriInterpCx<'tcx>,
+    attr_ptr: &OpTy<'tcx>,
+) -> InterpResult<'tcx, u64> {
+    ecx.deref_pointer_and_read(
+        attr_ptr,
+        0,
+        ecx.libc_ty_layout("pthread_attr_t"),
+        ecx.machine.layouts.usize,
+    )?
+    .to_target_usize(ecx)
+}
+
+fn attr_set_stack_size<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    attr_ptr: &OpTy<'tcx>,
+    stack_size: u64,
+) -> InterpResult<'tcx, ()> {
+    ecx.deref_pointer_and_write(
+        attr_ptr,
+        0,
+        Scalar::from_target_usize(stack_size, ecx),
+        ecx.libc_ty_layout("pthread_attr_t"),
+        ecx.machine.layouts.usize,
+    )
+}
+
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
 pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn pthread_attr_init(&mut self, attr_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        let stack_size = this.machine.stack_size;
+        attr_set_stack_size(this, attr_op, stack_size)
+    }
+
+    fn pthread_attr_destroy(&mut self, attr_op: &OpTy<'tcx>) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        // Destroying an uninit pthread_attr is UB, so check to make sure it's not uninit.
+        attr_get_stack_size(this, attr_op)?;
+
+        // To catch double-destroys, we de-initialize the attribute.
+        this.write_uninit(&this.deref_pointer_as(attr_op, this.libc_ty_layout("pthread_attr_t"))?)?;
+
+        interp_ok(())
+    }
+
+    fn pthread_attr_setstacksize(
+        &mut self,
+        attr_op: &OpTy<'tcx>,
+        stack_size_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let stack_size = this.read_target_usize(stack_size_op)?;
+        if stack_size == 0 {
+            return interp_ok(this.eval_libc("EINVAL"));
+        }
+        attr_set_stack_size(this, attr_op, stack_size)?;
+
+        interp_ok(Scalar::from_u32(0))
+    }
+
+    fn pthread_attr_getstacksize(
+        &mut self,
+        attr_op: &OpTy<'tcx>,
+        stack_size_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        let stack_size = attr_get_stack_size(this, attr_op)?;
+        let stack_size_place =
+            this.deref_pointer_as(stack_size_op, this.libc_ty_layout("size_t"))?;
+        this.write_scalar(Scalar::from_target_usize(stack_size, this), &stack_size_place)
+    }
+
+    fn pthread_attr_getstack(
+        &mut self,
+        attr_op: &OpTy<'tcx>,
+        addr_op: &OpTy<'tcx>,
+        stack_size_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, ()> {
+        let this = self.eval_context_mut();
+
+        // We don't support "pthread_attr_setstack", so all stacks report the same address.
+        let stack_size = attr_get_stack_size(this, attr_op)?;
+        let addr_place = this.deref_pointer_as(addr_op, this.machine.layouts.mut_raw_ptr)?;
+        let stack_size_place =
+            this.deref_pointer_as(stack_size_op, this.libc_ty_layout("size_t"))?;
+        this.write_scalar(Scalar::from_target_usize(this.machine.stack_addr, this), &addr_place)?;
+        this.write_scalar(Scalar::from_target_usize(stack_size, this), &stack_size_place)
+    }
+
+    /// Initializes `attr` with the attributes of the given thread. This implements both
+    /// `pthread_getattr_np` and `pthread_attr_get_np`.
+    fn pthread_getattr_np(
+        &mut self,
+        thread: &OpTy<'tcx>,
+        attr_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let thread = this.read_scalar(thread)?.to_int(this.libc_ty_layout("pthread_t").size)?;
+        let Ok(thread) = this.thread_id_try_from(thread) else {
+            return interp_ok(this.eval_libc("ESRCH"));
+        };
+        attr_set_stack_size(this, attr_op, this.get_thread_stack_size(thread))?;
+
+        interp_ok(Scalar::from_u32(0))
+    }
+
     fn pthread_create(
         &mut self,
         thread: &OpTy<'tcx>,
-        _attr: &OpTy<'tcx>,
+        attr: &OpTy<'tcx>,
         start_routine: &OpTy<'tcx>,
         arg: &OpTy<'tcx>,
     ) -> InterpResult<'tcx, ()> {
@@ -26,12 +140,19 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         let func_arg = this.read_immediate(arg)?;
 
+        let stack_size = if this.ptr_is_null(this.read_pointer(attr)?)? {
+            this.machine.stack_size
+        } else {
+            attr_get_stack_size(this, attr)?
+        };
+
         this.start_regular_thread(
             Some(thread_info_place),
             start_routine,
             ExternAbi::C { unwind: false },
             func_arg,
             this.machine.layouts.mut_raw_ptr,
+            stack_size,
         )?;
 
         interp_ok(())
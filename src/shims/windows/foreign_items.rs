@@ -472,6 +472,130 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
                 this.WakeByAddressAll(ptr_op)?;
             }
+            "WaitForSingleObject" => {
+                let [handle, timeout] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.WaitForSingleObject(handle, timeout, dest)?;
+            }
+            "WaitForMultipleObjects" => {
+                let [count, handles, wait_all, timeout] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.WaitForMultipleObjects(count, handles, wait_all, timeout, dest)?;
+            }
+            "CreateEventW" => {
+                let [attributes, manual_reset, initial_state, name] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let handle = this.CreateEventW(attributes, manual_reset, initial_state, name)?;
+                this.write_scalar(handle, dest)?;
+            }
+            "SetEvent" => {
+                let [handle] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.SetEvent(handle)?;
+                this.write_scalar(ret, dest)?;
+            }
+            "ResetEvent" => {
+                let [handle] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.ResetEvent(handle)?;
+                this.write_scalar(ret, dest)?;
+            }
+            "CreateMutexW" => {
+                let [attributes, initial_owner, name] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let handle = this.CreateMutexW(attributes, initial_owner, name)?;
+                this.write_scalar(handle, dest)?;
+            }
+            "ReleaseMutex" => {
+                let [handle] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.ReleaseMutex(handle)?;
+                this.write_scalar(ret, dest)?;
+            }
+            "CreateSemaphoreW" => {
+                let [attributes, initial_count, maximum_count, name] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let handle =
+                    this.CreateSemaphoreW(attributes, initial_count, maximum_count, name)?;
+                this.write_scalar(handle, dest)?;
+            }
+            "ReleaseSemaphore" => {
+                let [handle, release_count, previous_count] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.ReleaseSemaphore(handle, release_count, previous_count)?;
+                this.write_scalar(ret, dest)?;
+            }
+            "InitializeSRWLock" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.InitializeSRWLock(lock)?;
+            }
+            "AcquireSRWLockExclusive" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.AcquireSRWLockExclusive(lock)?;
+            }
+            "AcquireSRWLockShared" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.AcquireSRWLockShared(lock)?;
+            }
+            "ReleaseSRWLockExclusive" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.ReleaseSRWLockExclusive(lock)?;
+            }
+            "ReleaseSRWLockShared" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.ReleaseSRWLockShared(lock)?;
+            }
+            "TryAcquireSRWLockExclusive" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.TryAcquireSRWLockExclusive(lock)?;
+                this.write_scalar(ret, dest)?;
+            }
+            "TryAcquireSRWLockShared" => {
+                let [lock] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.TryAcquireSRWLockShared(lock)?;
+                this.write_scalar(ret, dest)?;
+            }
+            "InitializeCriticalSection" => {
+                let [section] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.InitializeCriticalSection(section)?;
+            }
+            "InitializeCriticalSectionAndSpinCount" => {
+                let [section, spin_count] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.read_scalar(spin_count)?.to_u32()?;
+                // We do not emulate spinning, so the spin count has no effect.
+                this.InitializeCriticalSection(section)?;
+                this.write_scalar(this.eval_windows("c", "TRUE"), dest)?;
+            }
+            "EnterCriticalSection" => {
+                let [section] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.EnterCriticalSection(section)?;
+            }
+            "LeaveCriticalSection" => {
+                let [section] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.LeaveCriticalSection(section)?;
+            }
+            "DeleteCriticalSection" => {
+                let [section] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                this.DeleteCriticalSection(section)?;
+            }
+            "TryEnterCriticalSection" => {
+                let [section] =
+                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
+                let ret = this.TryEnterCriticalSection(section)?;
+                this.write_scalar(ret, dest)?;
+            }
 
             // Dynamic symbol loading
             "GetProcAddress" => {
@@ -500,13 +624,6 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
                 this.write_scalar(Handle::Thread(thread_id).to_scalar(this), dest)?;
             }
-            "WaitForSingleObject" => {
-                let [handle, timeout] =
-                    this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
-
-                let ret = this.WaitForSingleObject(handle, timeout)?;
-                this.write_scalar(ret, dest)?;
-            }
             "GetCurrentThread" => {
                 let [] =
                     this.check_shim(abi, ExternAbi::System { unwind: false }, link_name, args)?;
@@ -526,7 +643,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let thread = match Handle::try_from_scalar(handle, this)? {
                     Ok(Handle::Thread(thread)) => Ok(thread),
                     Ok(Handle::Pseudo(PseudoHandle::CurrentThread)) => Ok(this.active_thread()),
-                    Ok(_) | Err(HandleError::InvalidHandle) =>
+                    Ok(_) | Err(HandleError::InvalidHandle | HandleError::ObjectNotFound) =>
                         this.invalid_handle("SetThreadDescription")?,
                     Err(HandleError::ThreadNotFound(e)) => Err(e),
                 };
@@ -551,7 +668,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 let thread = match Handle::try_from_scalar(handle, this)? {
                     Ok(Handle::Thread(thread)) => Ok(thread),
                     Ok(Handle::Pseudo(PseudoHandle::CurrentThread)) => Ok(this.active_thread()),
-                    Ok(_) | Err(HandleError::InvalidHandle) =>
+                    Ok(_) | Err(HandleError::InvalidHandle | HandleError::ObjectNotFound) =>
                         this.invalid_handle("GetThreadDescription")?,
                     Err(HandleError::ThreadNotFound(e)) => Err(e),
                 };
@@ -3,6 +3,7 @@ use std::mem::variant_count;
 use rustc_abi::HasDataLayout;
 
 use crate::concurrency::thread::ThreadNotFound;
+use crate::shims::windows::sync::EvalContextExt as _;
 use crate::*;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
@@ -16,6 +17,12 @@ pub enum Handle {
     Null,
     Pseudo(PseudoHandle),
     Thread(ThreadId),
+    /// An event object created by `CreateEventW`.
+    Event(u32),
+    /// A mutex object created by `CreateMutexW`.
+    Mutex(u32),
+    /// A semaphore object created by `CreateSemaphoreW`.
+    Semaphore(u32),
 }
 
 impl PseudoHandle {
@@ -39,6 +46,8 @@ impl PseudoHandle {
 pub enum HandleError {
     /// There is no thread with the given ID.
     ThreadNotFound(ThreadNotFound),
+    /// There is no kernel object of the right kind with the given ID.
+    ObjectNotFound,
     /// Can't convert scalar to handle because it is structurally invalid.
     InvalidHandle,
 }
@@ -47,12 +56,18 @@ impl Handle {
     const NULL_DISCRIMINANT: u32 = 0;
     const PSEUDO_DISCRIMINANT: u32 = 1;
     const THREAD_DISCRIMINANT: u32 = 2;
+    const EVENT_DISCRIMINANT: u32 = 3;
+    const MUTEX_DISCRIMINANT: u32 = 4;
+    const SEMAPHORE_DISCRIMINANT: u32 = 5;
 
     fn discriminant(self) -> u32 {
         match self {
             Self::Null => Self::NULL_DISCRIMINANT,
             Self::Pseudo(_) => Self::PSEUDO_DISCRIMINANT,
             Self::Thread(_) => Self::THREAD_DISCRIMINANT,
+            Self::Event(_) => Self::EVENT_DISCRIMINANT,
+            Self::Mutex(_) => Self::MUTEX_DISCRIMINANT,
+            Self::Semaphore(_) => Self::SEMAPHORE_DISCRIMINANT,
         }
     }
 
@@ -61,6 +76,7 @@ impl Handle {
             Self::Null => 0,
             Self::Pseudo(pseudo_handle) => pseudo_handle.value(),
             Self::Thread(thread) => thread.to_u32(),
+            Self::Event(id) | Self::Mutex(id) | Self::Semaphore(id) => id,
         }
     }
 
@@ -105,6 +121,9 @@ impl Handle {
             Self::NULL_DISCRIMINANT if data == 0 => Some(Self::Null),
             Self::PSEUDO_DISCRIMINANT => Some(Self::Pseudo(PseudoHandle::from_value(data)?)),
             Self::THREAD_DISCRIMINANT => Some(Self::Thread(ThreadId::new_unchecked(data))),
+            Self::EVENT_DISCRIMINANT => Some(Self::Event(data)),
+            Self::MUTEX_DISCRIMINANT => Some(Self::Mutex(data)),
+            Self::SEMAPHORE_DISCRIMINANT => Some(Self::Semaphore(data)),
             _ => None,
         }
     }
@@ -138,7 +157,7 @@ impl Handle {
     /// Convert a scalar into a structured `Handle`.
     /// Structurally invalid handles return [`HandleError::InvalidHandle`].
     /// If the handle is structurally valid but semantically invalid, e.g. a for non-existent thread
-    /// ID, returns [`HandleError::ThreadNotFound`].
+    /// ID, returns [`HandleError::ThreadNotFound`] or [`HandleError::ObjectNotFound`].
     pub fn try_from_scalar<'tcx>(
         handle: Scalar,
         cx: &MiriInterpCx<'tcx>,
@@ -161,6 +180,14 @@ impl Handle {
                     Err(e) => interp_ok(Err(HandleError::ThreadNotFound(e))),
                 }
             }
+            Some(handle @ (Self::Event(_) | Self::Mutex(_) | Self::Semaphore(_))) => {
+                // validate the kernel object id
+                if cx.machine.windows_objects.contains(handle) {
+                    interp_ok(Ok(handle))
+                } else {
+                    interp_ok(Err(HandleError::ObjectNotFound))
+                }
+            }
             Some(handle) => interp_ok(Ok(handle)),
             None => interp_ok(Err(HandleError::InvalidHandle)),
         }
@@ -186,6 +213,10 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 this.detach_thread(thread, /*allow_terminated_joined*/ true)?;
                 this.eval_windows("c", "TRUE")
             }
+            Ok(handle @ (Handle::Event(_) | Handle::Mutex(_) | Handle::Semaphore(_))) => {
+                this.close_kernel_object(handle)?;
+                this.eval_windows("c", "TRUE")
+            }
             _ => this.invalid_handle("CloseHandle")?,
         };
 
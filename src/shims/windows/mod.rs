@@ -8,5 +8,5 @@ mod thread;
 // All the Windows-specific extension traits
 pub use self::env::{EvalContextExt as _, WindowsEnvVars};
 pub use self::handle::EvalContextExt as _;
-pub use self::sync::EvalContextExt as _;
+pub use self::sync::{EvalContextExt as _, WindowsObjectTable, windows_thread_terminated};
 pub use self::thread::EvalContextExt as _;
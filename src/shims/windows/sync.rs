@@ -1,11 +1,22 @@
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::rc::Rc;
 use std::time::Duration;
 
 use rustc_abi::Size;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_middle::ty::Ty;
+use rustc_middle::ty::layout::LayoutOf;
 
+use self::shims::windows::handle::{EvalContextExt as _, Handle, PseudoHandle};
+use crate::concurrency::VClock;
 use crate::concurrency::init_once::InitOnceStatus;
 use crate::concurrency::sync::FutexRef;
 use crate::*;
 
+/// The maximum number of handles `WaitForMultipleObjects` can wait on.
+const MAXIMUM_WAIT_OBJECTS: u32 = 64;
+
 #[derive(Copy, Clone)]
 struct WindowsInitOnce {
     id: InitOnceId,
@@ -15,6 +26,97 @@ struct WindowsFutex {
     futex: FutexRef,
 }
 
+#[derive(Copy, Clone)]
+struct WindowsSrwLock {
+    id: RwLockId,
+}
+
+#[derive(Clone)]
+struct WindowsCriticalSection {
+    mutex_ref: MutexRef,
+}
+
+struct Event {
+    /// Whether the event stays signaled when a wait on it is satisfied, or is reset automatically.
+    manual_reset: bool,
+    signaled: bool,
+    /// Each `SetEvent` happens-before the end of every wait that observes the event as signaled.
+    clock: VClock,
+}
+
+#[derive(Clone)]
+struct EventRef(Rc<RefCell<Event>>);
+
+/// A kernel object that a [`Handle`] can refer to.
+#[derive(Clone)]
+enum KernelObject {
+    Event(EventRef),
+    /// Kernel mutexes are recursive, just like Miri's mutexes.
+    Mutex(MutexRef),
+    Semaphore {
+        sem_ref: SemaphoreRef,
+        max: u32,
+    },
+}
+
+/// A thread blocked in `WaitForSingleObject` or `WaitForMultipleObjects`.
+struct ObjectWaiter {
+    thread: ThreadId,
+    handles: Vec<Handle>,
+    /// Whether the thread waits for all handles to be signaled, or only for one of them.
+    wait_all: bool,
+}
+
+/// The kernel objects that event, mutex and semaphore handles refer to.
+#[derive(Default)]
+pub struct WindowsObjectTable {
+    objects: FxHashMap<u32, KernelObject>,
+    next_id: u32,
+    /// The threads waiting for objects to be signaled, in the order in which they started waiting.
+    waiters: VecDeque<ObjectWaiter>,
+    /// The mutexes whose owner terminated without releasing them. The next wait that acquires
+    /// such a mutex reports that it was abandoned.
+    abandoned: FxHashSet<u32>,
+}
+
+impl WindowsObjectTable {
+    fn insert(&mut self, object: KernelObject) -> Handle {
+        self.next_id = self.next_id.strict_add(1);
+        let id = self.next_id;
+        let handle = match object {
+            KernelObject::Event(_) => Handle::Event(id),
+            KernelObject::Mutex(_) => Handle::Mutex(id),
+            KernelObject::Semaphore { .. } => Handle::Semaphore(id),
+        };
+        self.objects.insert(id, object);
+        handle
+    }
+
+    fn get(&self, handle: Handle) -> Option<&KernelObject> {
+        let (Handle::Event(id) | Handle::Mutex(id) | Handle::Semaphore(id)) = handle else {
+            return None;
+        };
+        let object = self.objects.get(&id)?;
+        let kind_matches = matches!(
+            (handle, object),
+            (Handle::Event(_), KernelObject::Event(_))
+                | (Handle::Mutex(_), KernelObject::Mutex(_))
+                | (Handle::Semaphore(_), KernelObject::Semaphore { .. })
+        );
+        kind_matches.then_some(object)
+    }
+
+    /// Returns whether `handle` refers to a live kernel object of the right kind.
+    pub fn contains(&self, handle: Handle) -> bool {
+        self.get(handle).is_some()
+    }
+
+    fn remove_waiter(&mut self, thread: ThreadId) -> ObjectWaiter {
+        let idx = self.waiters.iter().position(|waiter| waiter.thread == thread).unwrap();
+        self.waiters.remove(idx).unwrap()
+    }
+}
+
 impl<'tcx> EvalContextExtPriv<'tcx> for crate::MiriInterpCx<'tcx> {}
 trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
     // Windows sync primitives are pointer sized.
@@ -68,6 +170,274 @@ trait EvalContextExtPriv<'tcx>: crate::MiriInterpCxExt<'tcx> {
             InitOnceStatus::Begun => false,
         })
     }
+
+    fn srwlock_get_data<'a>(
+        &'a mut self,
+        lock_ptr: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, &'a WindowsSrwLock>
+    where
+        'tcx: 'a,
+    {
+        let this = self.eval_context_mut();
+
+        let lock = this.deref_pointer(lock_ptr)?;
+
+        this.lazy_sync_get_data(
+            &lock,
+            Size::ZERO,
+            || throw_ub_format!("`SRWLOCK` can't be moved after first use"),
+            |this| {
+                let id = this.machine.sync.rwlock_create();
+                interp_ok(WindowsSrwLock { id })
+            },
+        )
+    }
+
+    fn critical_section_get_data<'a>(
+        &'a mut self,
+        section_ptr: &OpTy<'tcx>,
+        function_name: &str,
+    ) -> InterpResult<'tcx, &'a WindowsCriticalSection>
+    where
+        'tcx: 'a,
+    {
+        let this = self.eval_context_mut();
+
+        let section = this.deref_pointer(section_ptr)?;
+
+        // Unlike `SRWLOCK`, a `CRITICAL_SECTION` has no static initializer.
+        this.lazy_sync_get_data(
+            &section,
+            Size::ZERO,
+            || throw_ub_format!("`CRITICAL_SECTION` can't be moved after first use"),
+            |_| {
+                throw_ub_format!(
+                    "`{function_name}` called on a `CRITICAL_SECTION` that was not initialized"
+                )
+            },
+        )
+    }
+
+    /// Reads a handle and returns the kernel object it refers to, if any.
+    fn read_kernel_object(
+        &mut self,
+        handle_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Option<KernelObject>> {
+        let this = self.eval_context_mut();
+
+        let handle = this.read_scalar(handle_op)?;
+        interp_ok(match Handle::try_from_scalar(handle, this)? {
+            Ok(handle) => this.machine.windows_objects.get(handle).cloned(),
+            Err(_) => None,
+        })
+    }
+
+    /// Returns whether a wait on `handle` by `thread` would be satisfied right now.
+    fn kernel_object_is_signaled(&self, handle: Handle, thread: ThreadId) -> bool {
+        let this = self.eval_context_ref();
+        match handle {
+            // A thread handle is signaled once the thread has terminated.
+            Handle::Thread(joined) => this.machine.threads.has_terminated(joined),
+            // The current thread has not terminated, it is running this very wait.
+            Handle::Null | Handle::Pseudo(_) => false,
+            Handle::Event(_) | Handle::Mutex(_) | Handle::Semaphore(_) =>
+                match this.machine.windows_objects.get(handle).unwrap() {
+                    KernelObject::Event(event) => event.0.borrow().signaled,
+                    KernelObject::Mutex(mutex_ref) =>
+                        !this.mutex_is_locked(mutex_ref)
+                            || this.mutex_get_owner(mutex_ref) == thread,
+                    KernelObject::Semaphore { sem_ref, .. } =>
+                        this.semaphore_get_value(sem_ref) > 0,
+                },
+        }
+    }
+
+    /// Consumes the signal of a signaled object on behalf of the active thread. Returns whether
+    /// the object is a mutex that was abandoned by its previous owner.
+    fn kernel_object_acquire(&mut self, handle: Handle) -> InterpResult<'tcx, bool> {
+        let this = self.eval_context_mut();
+        if let Handle::Thread(joined) = handle {
+            // The thread has already terminated, so this only synchronizes with it.
+            this.join_thread(joined)?;
+            return interp_ok(false);
+        }
+        match this.machine.windows_objects.get(handle).unwrap().clone() {
+            KernelObject::Event(event) => {
+                let mut event = event.0.borrow_mut();
+                if !event.manual_reset {
+                    event.signaled = false;
+                }
+                this.acquire_clock(&event.clock);
+            }
+            KernelObject::Mutex(mutex_ref) => {
+                this.mutex_lock(&mutex_ref);
+                let Handle::Mutex(id) = handle else { unreachable!() };
+                return interp_ok(this.machine.windows_objects.abandoned.remove(&id));
+            }
+            KernelObject::Semaphore { sem_ref, .. } => {
+                let acquired = this.semaphore_try_wait(&sem_ref);
+                assert!(acquired, "acquired a semaphore that was not signaled");
+            }
+        }
+        interp_ok(false)
+    }
+
+    /// Returns the index of the handle that satisfies a wait by `thread`, or `None` if the wait
+    /// would block. A satisfied wait for all handles always yields index 0.
+    fn kernel_wait_satisfied(
+        &self,
+        handles: &[Handle],
+        wait_all: bool,
+        thread: ThreadId,
+    ) -> Option<usize> {
+        let this = self.eval_context_ref();
+        let mut signaled =
+            handles.iter().map(|&handle| this.kernel_object_is_signaled(handle, thread));
+        if wait_all {
+            signaled.all(|signaled| signaled).then_some(0)
+        } else {
+            signaled.position(|signaled| signaled)
+        }
+    }
+
+    /// Acquires the objects of a satisfied wait on behalf of the active thread, and returns the
+    /// value the wait function returns. If one of the acquired objects is an abandoned mutex, that
+    /// value is based on `WAIT_ABANDONED_0` instead of `WAIT_OBJECT_0`.
+    fn kernel_wait_acquire(
+        &mut self,
+        handles: &[Handle],
+        wait_all: bool,
+        idx: usize,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+        let acquired = if wait_all { handles } else { std::slice::from_ref(&handles[idx]) };
+        let mut abandoned = false;
+        for &handle in acquired {
+            abandoned |= this.kernel_object_acquire(handle)?;
+        }
+        // `WAIT_ABANDONED` is `WAIT_ABANDONED_0`.
+        let base =
+            this.eval_windows_u32("c", if abandoned { "WAIT_ABANDONED" } else { "WAIT_OBJECT_0" });
+        interp_ok(Scalar::from_u32(base.strict_add(u32::try_from(idx).unwrap())))
+    }
+
+    /// Wakes up the waiting threads whose waits can now be satisfied, in the order in which they
+    /// started waiting. This must be called whenever an object becomes signaled.
+    fn kernel_objects_wake_waiters(&mut self) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        loop {
+            let Some(thread) = this
+                .machine
+                .windows_objects
+                .waiters
+                .iter()
+                .find(|waiter| {
+                    this.kernel_wait_satisfied(&waiter.handles, waiter.wait_all, waiter.thread)
+                        .is_some()
+                })
+                .map(|waiter| waiter.thread)
+            else {
+                return interp_ok(());
+            };
+            // The woken thread acquires the objects and removes itself from the waiters,
+            // so every iteration makes progress.
+            this.unblock_thread(thread, BlockReason::WindowsObject)?;
+        }
+    }
+
+    /// Waits until one of `handles`, or all of them if `wait_all` is set, is signaled.
+    /// The return value of the wait is written to `dest`.
+    fn kernel_objects_wait(
+        &mut self,
+        handles: Vec<Handle>,
+        wait_all: bool,
+        timeout_ms: u32,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+        let thread = this.active_thread();
+
+        for &handle in &handles {
+            if let Handle::Thread(joined) = handle
+                && this.machine.threads.is_detached(joined)
+            {
+                // This corresponds to waiting on a closed handle.
+                throw_ub_format!("trying to join a detached thread");
+            }
+        }
+
+        if let Some(idx) = this.kernel_wait_satisfied(&handles, wait_all, thread) {
+            let ret = this.kernel_wait_acquire(&handles, wait_all, idx)?;
+            return this.write_scalar(ret, dest);
+        }
+
+        if timeout_ms == 0 {
+            return this.write_scalar(this.eval_windows("c", "WAIT_TIMEOUT"), dest);
+        }
+
+        let timeout = if timeout_ms == this.eval_windows_u32("c", "INFINITE") {
+            None
+        } else {
+            let duration = Duration::from_millis(timeout_ms.into());
+            Some((TimeoutClock::Monotonic, TimeoutAnchor::Relative, duration))
+        };
+
+        this.machine.windows_objects.waiters.push_back(ObjectWaiter { thread, handles, wait_all });
+        let dest = dest.clone();
+        this.block_thread(
+            BlockReason::WindowsObject,
+            timeout,
+            callback!(
+                @capture<'tcx> {
+                    dest: MPlaceTy<'tcx>,
+                }
+                @unblock = |this| {
+                    let thread = this.active_thread();
+                    let waiter = this.machine.windows_objects.remove_waiter(thread);
+                    // We only get woken up once our wait can be satisfied.
+                    let idx = this
+                        .kernel_wait_satisfied(&waiter.handles, waiter.wait_all, thread)
+                        .unwrap();
+                    let ret = this.kernel_wait_acquire(&waiter.handles, waiter.wait_all, idx)?;
+                    this.write_scalar(ret, &dest)
+                }
+                @timeout = |this| {
+                    let thread = this.active_thread();
+                    this.machine.windows_objects.remove_waiter(thread);
+                    this.write_scalar(this.eval_windows("c", "WAIT_TIMEOUT"), &dest)
+                }
+            ),
+        );
+        interp_ok(())
+    }
+}
+
+/// Called when the active thread terminates, which signals its thread handle. The mutexes that the
+/// thread still owns are abandoned: they are released, and the next wait that acquires one of them
+/// is told so. Wakes up the threads whose waits are now satisfied.
+pub fn windows_thread_terminated<'tcx>(ecx: &mut MiriInterpCx<'tcx>) -> InterpResult<'tcx> {
+    let thread = ecx.active_thread();
+    let owned: Vec<(u32, MutexRef)> = ecx
+        .machine
+        .windows_objects
+        .objects
+        .iter()
+        .filter_map(|(&id, object)| {
+            match object {
+                KernelObject::Mutex(mutex_ref)
+                    if ecx.mutex_is_locked(mutex_ref)
+                        && ecx.mutex_get_owner(mutex_ref) == thread =>
+                    Some((id, mutex_ref.clone())),
+                _ => None,
+            }
+        })
+        .collect();
+    for (id, mutex_ref) in owned {
+        // Kernel mutexes are recursive, so the thread may hold several locks.
+        while ecx.mutex_unlock(&mutex_ref)?.unwrap() > 1 {}
+        ecx.machine.windows_objects.abandoned.insert(id);
+    }
+    ecx.kernel_objects_wake_waiters()
 }
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -265,4 +635,449 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
 
         interp_ok(())
     }
+
+    fn CreateEventW(
+        &mut self,
+        attributes_op: &OpTy<'tcx>,
+        manual_reset_op: &OpTy<'tcx>,
+        initial_state_op: &OpTy<'tcx>,
+        name_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let attributes = this.read_pointer(attributes_op)?;
+        let manual_reset = this.read_scalar(manual_reset_op)?.to_i32()? != 0;
+        let initial_state = this.read_scalar(initial_state_op)?.to_i32()? != 0;
+        let name = this.read_pointer(name_op)?;
+
+        if !this.ptr_is_null(attributes)? {
+            throw_unsup_format!("non-null `lpEventAttributes` in `CreateEventW`");
+        }
+
+        if !this.ptr_is_null(name)? {
+            throw_unsup_format!("non-null `lpName` in `CreateEventW`");
+        }
+
+        let event = Event { manual_reset, signaled: initial_state, clock: VClock::default() };
+        let event = KernelObject::Event(EventRef(Rc::new(RefCell::new(event))));
+        let handle = this.machine.windows_objects.insert(event);
+        interp_ok(handle.to_scalar(this))
+    }
+
+    fn SetEvent(&mut self, handle_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let Some(KernelObject::Event(event)) = this.read_kernel_object(handle_op)? else {
+            this.invalid_handle("SetEvent")?
+        };
+
+        {
+            let mut event = event.0.borrow_mut();
+            event.signaled = true;
+            this.release_clock(|clock| event.clock.join(clock));
+        }
+        this.kernel_objects_wake_waiters()?;
+
+        interp_ok(this.eval_windows("c", "TRUE"))
+    }
+
+    fn ResetEvent(&mut self, handle_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let Some(KernelObject::Event(event)) = this.read_kernel_object(handle_op)? else {
+            this.invalid_handle("ResetEvent")?
+        };
+
+        event.0.borrow_mut().signaled = false;
+
+        interp_ok(this.eval_windows("c", "TRUE"))
+    }
+
+    fn CreateMutexW(
+        &mut self,
+        attributes_op: &OpTy<'tcx>,
+        initial_owner_op: &OpTy<'tcx>,
+        name_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let attributes = this.read_pointer(attributes_op)?;
+        let initial_owner = this.read_scalar(initial_owner_op)?.to_i32()? != 0;
+        let name = this.read_pointer(name_op)?;
+
+        if !this.ptr_is_null(attributes)? {
+            throw_unsup_format!("non-null `lpMutexAttributes` in `CreateMutexW`");
+        }
+
+        if !this.ptr_is_null(name)? {
+            throw_unsup_format!("non-null `lpName` in `CreateMutexW`");
+        }
+
+        let mutex_ref = this.machine.sync.mutex_create();
+        if initial_owner {
+            this.mutex_lock(&mutex_ref);
+        }
+        let handle = this.machine.windows_objects.insert(KernelObject::Mutex(mutex_ref));
+        interp_ok(handle.to_scalar(this))
+    }
+
+    fn ReleaseMutex(&mut self, handle_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let Some(KernelObject::Mutex(mutex_ref)) = this.read_kernel_object(handle_op)? else {
+            this.invalid_handle("ReleaseMutex")?
+        };
+
+        if this.mutex_unlock(&mutex_ref)?.is_none() {
+            let not_owner = this.eval_windows("c", "ERROR_NOT_OWNER");
+            this.set_last_error(not_owner)?;
+            return interp_ok(this.eval_windows("c", "FALSE"));
+        }
+        this.kernel_objects_wake_waiters()?;
+
+        interp_ok(this.eval_windows("c", "TRUE"))
+    }
+
+    fn CreateSemaphoreW(
+        &mut self,
+        attributes_op: &OpTy<'tcx>,
+        initial_count_op: &OpTy<'tcx>,
+        maximum_count_op: &OpTy<'tcx>,
+        name_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let attributes = this.read_pointer(attributes_op)?;
+        let initial_count = this.read_scalar(initial_count_op)?.to_i32()?;
+        let maximum_count = this.read_scalar(maximum_count_op)?.to_i32()?;
+        let name = this.read_pointer(name_op)?;
+
+        if !this.ptr_is_null(attributes)? {
+            throw_unsup_format!("non-null `lpSemaphoreAttributes` in `CreateSemaphoreW`");
+        }
+
+        if !this.ptr_is_null(name)? {
+            throw_unsup_format!("non-null `lpName` in `CreateSemaphoreW`");
+        }
+
+        if maximum_count <= 0 || initial_count < 0 || initial_count > maximum_count {
+            let invalid_param = this.eval_windows("c", "ERROR_INVALID_PARAMETER");
+            this.set_last_error(invalid_param)?;
+            return interp_ok(Handle::Null.to_scalar(this));
+        }
+
+        let sem_ref = this.machine.sync.semaphore_create(initial_count.try_into().unwrap());
+        let max = maximum_count.try_into().unwrap();
+        let handle = this.machine.windows_objects.insert(KernelObject::Semaphore { sem_ref, max });
+        interp_ok(handle.to_scalar(this))
+    }
+
+    fn ReleaseSemaphore(
+        &mut self,
+        handle_op: &OpTy<'tcx>,
+        release_count_op: &OpTy<'tcx>,
+        previous_count_op: &OpTy<'tcx>,
+    ) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let Some(KernelObject::Semaphore { sem_ref, max }) = this.read_kernel_object(handle_op)?
+        else {
+            this.invalid_handle("ReleaseSemaphore")?
+        };
+        let release_count = this.read_scalar(release_count_op)?.to_i32()?;
+        let previous_count = this.read_pointer(previous_count_op)?;
+
+        let release_count = match u32::try_from(release_count) {
+            Ok(release_count) if release_count > 0 => release_count,
+            _ => {
+                let invalid_param = this.eval_windows("c", "ERROR_INVALID_PARAMETER");
+                this.set_last_error(invalid_param)?;
+                return interp_ok(this.eval_windows("c", "FALSE"));
+            }
+        };
+
+        let value = this.semaphore_get_value(&sem_ref);
+        if value.checked_add(release_count).is_none_or(|new_value| new_value > max) {
+            // The count is left unchanged.
+            let too_many_posts = this.eval_windows("c", "ERROR_TOO_MANY_POSTS");
+            this.set_last_error(too_many_posts)?;
+            return interp_ok(this.eval_windows("c", "FALSE"));
+        }
+
+        if !this.ptr_is_null(previous_count)? {
+            let previous_count =
+                this.deref_pointer_as(previous_count_op, this.machine.layouts.i32)?;
+            this.write_int(value, &previous_count)?;
+        }
+
+        for _ in 0..release_count {
+            let posted = this.semaphore_post(&sem_ref, max)?;
+            assert!(posted, "we checked above that the semaphore does not exceed its maximum");
+        }
+        this.kernel_objects_wake_waiters()?;
+
+        interp_ok(this.eval_windows("c", "TRUE"))
+    }
+
+    /// Closes an event, mutex or semaphore handle.
+    fn close_kernel_object(&mut self, handle: Handle) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let table = &mut this.machine.windows_objects;
+        if table.waiters.iter().any(|waiter| waiter.handles.contains(&handle)) {
+            throw_unsup_format!("closing a handle that another thread is waiting on");
+        }
+        // We do not support duplicating handles, so this was the only handle to the object.
+        let (Handle::Event(id) | Handle::Mutex(id) | Handle::Semaphore(id)) = handle else {
+            unreachable!("not a kernel object handle: {handle:?}")
+        };
+        table.objects.remove(&id);
+        table.abandoned.remove(&id);
+
+        interp_ok(())
+    }
+
+    fn WaitForSingleObject(
+        &mut self,
+        handle_op: &OpTy<'tcx>,
+        timeout_op: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let handle = this.read_scalar(handle_op)?;
+        let timeout = this.read_scalar(timeout_op)?.to_u32()?;
+
+        let handle = match Handle::try_from_scalar(handle, this)? {
+            // Unlike on posix, the outcome of joining the current thread is not documented.
+            // On current Windows, it just deadlocks.
+            Ok(
+                handle @ (Handle::Thread(_)
+                | Handle::Pseudo(PseudoHandle::CurrentThread)
+                | Handle::Event(_)
+                | Handle::Mutex(_)
+                | Handle::Semaphore(_)),
+            ) => handle,
+            _ => this.invalid_handle("WaitForSingleObject")?,
+        };
+
+        this.kernel_objects_wait(vec![handle], /* wait_all */ false, timeout, dest)
+    }
+
+    fn WaitForMultipleObjects(
+        &mut self,
+        count_op: &OpTy<'tcx>,
+        handles_op: &OpTy<'tcx>,
+        wait_all_op: &OpTy<'tcx>,
+        timeout_op: &OpTy<'tcx>,
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let count = this.read_scalar(count_op)?.to_u32()?;
+        let wait_all = this.read_scalar(wait_all_op)?.to_i32()? != 0;
+        let timeout = this.read_scalar(timeout_op)?.to_u32()?;
+
+        if count == 0 || count > MAXIMUM_WAIT_OBJECTS {
+            let invalid_param = this.eval_windows("c", "ERROR_INVALID_PARAMETER");
+            this.set_last_error(invalid_param)?;
+            return this.write_scalar(this.eval_windows("c", "WAIT_FAILED"), dest);
+        }
+
+        let handle_ty = this.windows_ty_layout("HANDLE").ty;
+        let handles_layout = this.layout_of(Ty::new_array(*this.tcx, handle_ty, count.into()))?;
+        let handles_place = this.deref_pointer_as(handles_op, handles_layout)?;
+        let mut handles = Vec::new();
+        for i in 0..u64::from(count) {
+            let handle = this.read_scalar(&this.project_index(&handles_place, i)?)?;
+            match Handle::try_from_scalar(handle, this)? {
+                Ok(handle) if handle != Handle::Null => handles.push(handle),
+                _ => this.invalid_handle("WaitForMultipleObjects")?,
+            }
+        }
+
+        // Several copies of the same handle are rejected.
+        let mut seen = FxHashSet::default();
+        if !handles.iter().all(|handle| seen.insert(*handle)) {
+            let invalid_param = this.eval_windows("c", "ERROR_INVALID_PARAMETER");
+            this.set_last_error(invalid_param)?;
+            return this.write_scalar(this.eval_windows("c", "WAIT_FAILED"), dest);
+        }
+
+        this.kernel_objects_wait(handles, wait_all, timeout, dest)
+    }
+
+    fn InitializeSRWLock(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        // `SRWLOCK_INIT` is all-zero, so clearing the lock makes its next use start from scratch.
+        let lock = this.deref_pointer(lock_op)?;
+        this.write_null(&lock.offset(Size::ZERO, this.machine.layouts.usize, this)?)
+    }
+
+    fn AcquireSRWLockExclusive(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let id = this.srwlock_get_data(lock_op)?.id;
+
+        if this.rwlock_is_locked(id) {
+            // Note: this will deadlock if the lock is already held by this thread, just like
+            // on Windows.
+            this.rwlock_enqueue_and_block_writer(id, None);
+        } else {
+            this.rwlock_writer_lock(id);
+        }
+
+        interp_ok(())
+    }
+
+    fn AcquireSRWLockShared(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let id = this.srwlock_get_data(lock_op)?.id;
+
+        if this.rwlock_is_write_locked(id) {
+            this.rwlock_enqueue_and_block_reader(id, None);
+        } else {
+            this.rwlock_reader_lock(id);
+        }
+
+        interp_ok(())
+    }
+
+    fn TryAcquireSRWLockExclusive(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let id = this.srwlock_get_data(lock_op)?.id;
+
+        // The return type is `BOOLEAN`, not `BOOL`.
+        if this.rwlock_is_locked(id) {
+            interp_ok(Scalar::from_u8(0))
+        } else {
+            this.rwlock_writer_lock(id);
+            interp_ok(Scalar::from_u8(1))
+        }
+    }
+
+    fn TryAcquireSRWLockShared(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let id = this.srwlock_get_data(lock_op)?.id;
+
+        // The return type is `BOOLEAN`, not `BOOL`.
+        if this.rwlock_is_write_locked(id) {
+            interp_ok(Scalar::from_u8(0))
+        } else {
+            this.rwlock_reader_lock(id);
+            interp_ok(Scalar::from_u8(1))
+        }
+    }
+
+    fn ReleaseSRWLockExclusive(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let id = this.srwlock_get_data(lock_op)?.id;
+
+        if !this.rwlock_writer_unlock(id)? {
+            throw_ub_format!(
+                "calling `ReleaseSRWLockExclusive` on an SRW lock that is not exclusively locked by the current thread"
+            );
+        }
+
+        interp_ok(())
+    }
+
+    fn ReleaseSRWLockShared(&mut self, lock_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let id = this.srwlock_get_data(lock_op)?.id;
+
+        if !this.rwlock_reader_unlock(id)? {
+            throw_ub_format!(
+                "calling `ReleaseSRWLockShared` on an SRW lock that is not share-locked by the current thread"
+            );
+        }
+
+        interp_ok(())
+    }
+
+    fn InitializeCriticalSection(&mut self, section_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let section = this.deref_pointer(section_op)?;
+        let mutex_ref = this.machine.sync.mutex_create();
+        this.lazy_sync_init(&section, Size::ZERO, WindowsCriticalSection { mutex_ref })?;
+
+        interp_ok(())
+    }
+
+    fn EnterCriticalSection(&mut self, section_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref =
+            this.critical_section_get_data(section_op, "EnterCriticalSection")?.mutex_ref.clone();
+
+        // Critical sections are recursive, so the owner can enter again.
+        if this.mutex_is_locked(&mutex_ref)
+            && this.mutex_get_owner(&mutex_ref) != this.active_thread()
+        {
+            this.mutex_enqueue_and_block(&mutex_ref, None);
+        } else {
+            this.mutex_lock(&mutex_ref);
+        }
+
+        interp_ok(())
+    }
+
+    fn TryEnterCriticalSection(&mut self, section_op: &OpTy<'tcx>) -> InterpResult<'tcx, Scalar> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref = this
+            .critical_section_get_data(section_op, "TryEnterCriticalSection")?
+            .mutex_ref
+            .clone();
+
+        if this.mutex_is_locked(&mutex_ref)
+            && this.mutex_get_owner(&mutex_ref) != this.active_thread()
+        {
+            interp_ok(this.eval_windows("c", "FALSE"))
+        } else {
+            this.mutex_lock(&mutex_ref);
+            interp_ok(this.eval_windows("c", "TRUE"))
+        }
+    }
+
+    fn LeaveCriticalSection(&mut self, section_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref =
+            this.critical_section_get_data(section_op, "LeaveCriticalSection")?.mutex_ref.clone();
+
+        if this.mutex_unlock(&mutex_ref)?.is_none() {
+            throw_ub_format!(
+                "calling `LeaveCriticalSection` on a critical section that is not owned by the current thread"
+            );
+        }
+
+        interp_ok(())
+    }
+
+    fn DeleteCriticalSection(&mut self, section_op: &OpTy<'tcx>) -> InterpResult<'tcx> {
+        let this = self.eval_context_mut();
+
+        let mutex_ref =
+            this.critical_section_get_data(section_op, "DeleteCriticalSection")?.mutex_ref.clone();
+
+        if this.mutex_is_locked(&mutex_ref) {
+            throw_ub_format!("deleted a critical section that is still owned");
+        }
+
+        // Using the critical section again before re-initializing it is UB, and making it
+        // uninit lets us detect that.
+        let section = this.deref_pointer(section_op)?;
+        this.lazy_sync_remove(&section)?;
+        this.write_uninit(&section)?;
+
+        interp_ok(())
+    }
 }
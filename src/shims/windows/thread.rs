@@ -1,7 +1,6 @@
 use rustc_abi::ExternAbi;
 use rustc_middle::ty::layout::LayoutOf;
 
-use self::shims::windows::handle::{EvalContextExt as _, Handle, PseudoHandle};
 use crate::*;
 
 impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
@@ -20,8 +19,11 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let this = self.eval_context_mut();
 
         let security = this.read_pointer(security_op)?;
-        // stacksize is ignored, but still needs to be a valid usize
-        this.read_target_usize(stacksize_op)?;
+        // A stack size of 0 means the default stack size.
+        let stack_size = match this.read_target_usize(stacksize_op)? {
+            0 => this.machine.stack_size,
+            size => size,
+        };
         let start_routine = this.read_pointer(start_op)?;
         let func_arg = this.read_immediate(arg_op)?;
         let flags = this.read_scalar(flags_op)?.to_u32()?;
@@ -36,7 +38,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
         let stack_size_param_is_a_reservation =
             this.eval_windows_u32("c", "STACK_SIZE_PARAM_IS_A_RESERVATION");
 
-        // We ignore the stack size, so we also ignore the
+        // We do not distinguish between committed and reserved stack memory, so we ignore the
         // `STACK_SIZE_PARAM_IS_A_RESERVATION` flag.
         if flags != 0 && flags != stack_size_param_is_a_reservation {
             throw_unsup_format!("unsupported `dwCreationFlags` {} in `CreateThread`", flags)
@@ -52,33 +54,7 @@ pub trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
             ExternAbi::System { unwind: false },
             func_arg,
             this.layout_of(this.tcx.types.u32)?,
+            stack_size,
         )
     }
-
-    fn WaitForSingleObject(
-        &mut self,
-        handle_op: &OpTy<'tcx>,
-        timeout_op: &OpTy<'tcx>,
-    ) -> InterpResult<'tcx, Scalar> {
-        let this = self.eval_context_mut();
-
-        let handle = this.read_scalar(handle_op)?;
-        let timeout = this.read_scalar(timeout_op)?.to_u32()?;
-
-        let thread = match Handle::try_from_scalar(handle, this)? {
-            Ok(Handle::Thread(thread)) => thread,
-            // Unlike on posix, the outcome of joining the current thread is not documented.
-            // On current Windows, it just deadlocks.
-            Ok(Handle::Pseudo(PseudoHandle::CurrentThread)) => this.active_thread(),
-            _ => this.invalid_handle("WaitForSingleObject")?,
-        };
-
-        if timeout != this.eval_windows_u32("c", "INFINITE") {
-            throw_unsup_format!("`WaitForSingleObject` with non-infinite timeout");
-        }
-
-        this.join_thread(thread)?;
-
-        interp_ok(this.eval_windows("c", "WAIT_OBJECT_0"))
-    }
 }
@@ -0,0 +1,662 @@
+use rustc_abi::{Align, ExternAbi, Size};
+use rustc_apfloat::ieee::{Double, Single};
+use rustc_middle::ty;
+use rustc_middle::ty::layout::LayoutOf as _;
+use rustc_span::Symbol;
+
+use super::{FloatBinOp, bin_op_float, rounding_from_imm};
+use crate::*;
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn emulate_x86_avx512_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: ExternAbi,
+        args: &[OpTy<'tcx>],
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, EmulateItemResult> {
+        let this = self.eval_context_mut();
+        this.expect_target_feature_for_intrinsic(link_name, "avx512f")?;
+        // The 128-bit and 256-bit versions of AVX-512 instructions are provided by AVX512VL.
+        if link_name.as_str().ends_with(".128") || link_name.as_str().ends_with(".256") {
+            this.expect_target_feature_for_intrinsic(link_name, "avx512vl")?;
+        }
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.avx512.").unwrap();
+
+        // Most intrinsics are named `<op>.<element>.<width>`. The instructions that work on 8-bit
+        // and 16-bit elements are part of other extensions than the rest.
+        let element = unprefixed_name.rsplit('.').nth(1).unwrap_or_default();
+
+        match unprefixed_name {
+            // Used to implement the `_mm{,256,512}_mask{,z}_load{,u}_{epi8,epi16,epi32,epi64,ps,pd}`
+            // functions.
+            // Loads the elements of `dest` whose bit in `mask` is set from `ptr`, and copies the
+            // others from `src`. Memory of masked-off elements is not accessed. For the aligned
+            // versions, `ptr` must be aligned to the size of the entire vector.
+            name if name.starts_with("mask.loadu.") || name.starts_with("mask.load.") => {
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?;
+                }
+                let [ptr, src, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let aligned = name.starts_with("mask.load.");
+                masked_load(this, ptr, src, mask, dest, aligned)?;
+            }
+            // Used to implement the `_mm{,256,512}_mask_store{,u}_{epi8,epi16,epi32,epi64,ps,pd}`
+            // functions.
+            // Stores the elements of `value` whose bit in `mask` is set to `ptr`. Memory of
+            // masked-off elements is not accessed. For the aligned versions, `ptr` must be aligned
+            // to the size of the entire vector.
+            name if name.starts_with("mask.storeu.") || name.starts_with("mask.store.") => {
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?;
+                }
+                let [ptr, value, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let aligned = name.starts_with("mask.store.");
+                masked_store(this, ptr, value, mask, aligned)?;
+            }
+            // Used to implement the `_mm{,256,512}_mask{,z}_compress_*` functions.
+            // Packs the elements of `op` whose bit in `mask` is set into the low elements of
+            // `dest`, and copies the remaining elements from `src`.
+            name if name.starts_with("mask.compress.") && !name.contains(".store.") => {
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512vbmi2")?;
+                }
+                let [op, src, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (src, src_len) = this.project_to_simd(src)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+                assert_eq!(dest_len, src_len);
+
+                let mask = read_mask(this, mask)?;
+                let mut j = 0;
+                for i in 0..op_len {
+                    if mask_bit(mask, i) {
+                        this.copy_op(&this.project_index(&op, i)?, &this.project_index(&dest, j)?)?;
+                        j = j.strict_add(1);
+                    }
+                }
+                for i in j..dest_len {
+                    this.copy_op(&this.project_index(&src, i)?, &this.project_index(&dest, i)?)?;
+                }
+            }
+            // Used to implement the `_mm{,256,512}_mask{,z}_expand_*` functions.
+            // The inverse of `compress`: the elements of `dest` whose bit in `mask` is set are
+            // taken from consecutive low elements of `op`, the others are copied from `src`.
+            name if name.starts_with("mask.expand.") && !name.contains(".load.") => {
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512vbmi2")?;
+                }
+                let [op, src, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (src, src_len) = this.project_to_simd(src)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+                assert_eq!(dest_len, src_len);
+
+                let mask = read_mask(this, mask)?;
+                let mut j = 0;
+                for i in 0..dest_len {
+                    let dest = this.project_index(&dest, i)?;
+                    if mask_bit(mask, i) {
+                        this.copy_op(&this.project_index(&op, j)?, &dest)?;
+                        j = j.strict_add(1);
+                    } else {
+                        this.copy_op(&this.project_index(&src, i)?, &dest)?;
+                    }
+                }
+            }
+            // Used to implement the `_mm{,256,512}_mask_compressstoreu_*` functions.
+            // Stores the elements of `value` whose bit in `mask` is set contiguously to `ptr`,
+            // which does not need to be aligned.
+            name if name.starts_with("mask.compress.store.") => {
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512vbmi2")?;
+                }
+                let [ptr, value, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (value, value_len) = this.project_to_simd(value)?;
+                let mask = read_mask(this, mask)?;
+                let ptr = this.read_pointer(ptr)?;
+
+                let mut j = 0;
+                for i in 0..value_len {
+                    if mask_bit(mask, i) {
+                        let value = this.project_index(&value, i)?;
+                        // *Non-inbounds* pointer arithmetic to compute the destination.
+                        let ptr = ptr.wrapping_offset(value.layout.size * j, &this.tcx);
+                        let dest = this.ptr_to_mplace_unaligned(ptr, value.layout);
+                        this.copy_op(&value, &dest)?;
+                        j = j.strict_add(1);
+                    }
+                }
+            }
+            // Used to implement the `_mm{,256,512}_mask{,z}_expandloadu_*` functions.
+            // The elements of `dest` whose bit in `mask` is set are loaded from consecutive
+            // elements at `ptr` (which does not need to be aligned), the others are copied from
+            // `src`.
+            name if name.starts_with("mask.expand.load.") => {
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512vbmi2")?;
+                }
+                let [ptr, src, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (src, src_len) = this.project_to_simd(src)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, src_len);
+
+                let mask = read_mask(this, mask)?;
+                let ptr = this.read_pointer(ptr)?;
+                let mut j = 0;
+                for i in 0..dest_len {
+                    let dest = this.project_index(&dest, i)?;
+                    if mask_bit(mask, i) {
+                        let ptr = ptr.wrapping_offset(dest.layout.size * j, &this.tcx);
+                        // Unaligned copy, which is what we want.
+                        this.mem_copy(
+                            ptr,
+                            dest.ptr(),
+                            dest.layout.size,
+                            /*nonoverlapping*/ true,
+                        )?;
+                        j = j.strict_add(1);
+                    } else {
+                        this.copy_op(&this.project_index(&src, i)?, &dest)?;
+                    }
+                }
+            }
+            // Used to implement the `_mm{,256,512}_permutex2var_*` functions.
+            // Each element of `idx` selects an element of the concatenation of `left` and
+            // `right`, across the entire vector.
+            name if name.starts_with("vpermi2var.") => {
+                match element {
+                    "hi" => this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?,
+                    "qi" => this.expect_target_feature_for_intrinsic(link_name, "avx512vbmi")?,
+                    _ => {}
+                }
+                let [left, idx, right] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (left, left_len) = this.project_to_simd(left)?;
+                let (idx, idx_len) = this.project_to_simd(idx)?;
+                let (right, right_len) = this.project_to_simd(right)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, left_len);
+                assert_eq!(dest_len, idx_len);
+                assert_eq!(dest_len, right_len);
+
+                for i in 0..dest_len {
+                    let idx = this.project_index(&idx, i)?;
+                    let idx = this.read_scalar(&idx)?.to_uint(idx.layout.size)?;
+                    // Only the bits needed to index into both vectors are used (vector lengths
+                    // are powers of two).
+                    let idx = u64::try_from(idx).unwrap() & dest_len.strict_mul(2).strict_sub(1);
+                    let src = if idx < dest_len {
+                        this.project_index(&left, idx)?
+                    } else {
+                        this.project_index(&right, idx.strict_sub(dest_len))?
+                    };
+                    this.copy_op(&src, &this.project_index(&dest, i)?)?;
+                }
+            }
+            // Used to implement the `_mm{,256,512}_permutexvar_*` functions.
+            // Each element of `idx` selects an element of `op`, across the entire vector.
+            name if name.starts_with("permvar.") => {
+                match element {
+                    "hi" => this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?,
+                    "qi" => this.expect_target_feature_for_intrinsic(link_name, "avx512vbmi")?,
+                    _ => {}
+                }
+                let [op, idx] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (idx, idx_len) = this.project_to_simd(idx)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+                assert_eq!(dest_len, idx_len);
+
+                for i in 0..dest_len {
+                    let idx = this.project_index(&idx, i)?;
+                    let idx = this.read_scalar(&idx)?.to_uint(idx.layout.size)?;
+                    // Only the bits needed to index into `op` are used (vector lengths are powers
+                    // of two).
+                    let idx = u64::try_from(idx).unwrap() & op_len.strict_sub(1);
+                    this.copy_op(&this.project_index(&op, idx)?, &this.project_index(&dest, i)?)?;
+                }
+            }
+            // Used to implement the `_mm{,256,512}_ternarylogic_epi{32,64}` functions.
+            // Computes an arbitrary three-input bitwise function of `a`, `b` and `c`: for each
+            // bit, the bits of `a`, `b` and `c` (from most to least significant) form a 3-bit
+            // index into the truth table `imm8`.
+            "pternlog.d.128" | "pternlog.d.256" | "pternlog.d.512" | "pternlog.q.128"
+            | "pternlog.q.256" | "pternlog.q.512" => {
+                let [a, b, c, imm8] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (a, a_len) = this.project_to_simd(a)?;
+                let (b, b_len) = this.project_to_simd(b)?;
+                let (c, c_len) = this.project_to_simd(c)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, a_len);
+                assert_eq!(dest_len, b_len);
+                assert_eq!(dest_len, c_len);
+
+                let imm8 = this.read_scalar(imm8)?.to_u32()? & 0xff;
+                for i in 0..dest_len {
+                    let dest = this.project_index(&dest, i)?;
+                    let size = dest.layout.size;
+                    let a = this.read_scalar(&this.project_index(&a, i)?)?.to_uint(size)?;
+                    let b = this.read_scalar(&this.project_index(&b, i)?)?.to_uint(size)?;
+                    let c = this.read_scalar(&this.project_index(&c, i)?)?.to_uint(size)?;
+
+                    let mut res = 0;
+                    for entry in 0..8 {
+                        if imm8 & (1 << entry) != 0 {
+                            let a = if entry & 0b100 != 0 { a } else { !a };
+                            let b = if entry & 0b010 != 0 { b } else { !b };
+                            let c = if entry & 0b001 != 0 { c } else { !c };
+                            res |= a & b & c;
+                        }
+                    }
+                    this.write_scalar(Scalar::from_uint(size.truncate(res), size), &dest)?;
+                }
+            }
+            // Used to implement the `_mm{,256,512}_{,mask_}cmp{,eq,lt,...}_ep{i,u}{8,16,32,64}_mask`
+            // functions.
+            // Compares each element of `left` and `right` (as signed integers for `cmp` and as
+            // unsigned integers for `ucmp`) with the predicate `imm`, and returns the results as
+            // a bit mask, and-ed with `mask`.
+            name if name.starts_with("mask.cmp.") || name.starts_with("mask.ucmp.") => {
+                if matches!(element, "ps" | "pd") {
+                    return emulate_float_cmp(this, link_name, abi, args, dest, element);
+                }
+                if matches!(element, "b" | "w") {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?;
+                }
+                let [left, right, imm, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (left, left_len) = this.project_to_simd(left)?;
+                let (right, right_len) = this.project_to_simd(right)?;
+
+                assert_eq!(left_len, right_len);
+
+                let signed = name.starts_with("mask.cmp.");
+                let imm = this.read_scalar(imm)?.to_i32()?;
+                let mask = read_mask(this, mask)?;
+
+                let mut res = 0u128;
+                for i in 0..left_len {
+                    let left = this.project_index(&left, i)?;
+                    let size = left.layout.size;
+                    let left = this.read_scalar(&left)?;
+                    let right = this.read_scalar(&this.project_index(&right, i)?)?;
+                    let ord = if signed {
+                        left.to_int(size)?.cmp(&right.to_int(size)?)
+                    } else {
+                        left.to_uint(size)?.cmp(&right.to_uint(size)?)
+                    };
+                    let bit = match imm & 0b111 {
+                        // Equal
+                        0 => ord.is_eq(),
+                        // Less-than
+                        1 => ord.is_lt(),
+                        // Less-or-equal
+                        2 => ord.is_le(),
+                        // False
+                        3 => false,
+                        // Not equal
+                        4 => ord.is_ne(),
+                        // Not less-than
+                        5 => ord.is_ge(),
+                        // Not less-or-equal
+                        6 => ord.is_gt(),
+                        // True
+                        7 => true,
+                        _ => unreachable!(),
+                    };
+                    res |= u128::from(bit) << i;
+                }
+                this.write_scalar(Scalar::from_uint(res & mask, dest.layout.size), dest)?;
+            }
+            // Used to implement the `_mm512_{,mask_,maskz_}cvt{,t}{,_round}{ps,pd}_ep{i,u}32`
+            // and `_mm{,256}_{,mask_,maskz_}cvt{,t}{ps,pd}_epu32` functions.
+            // Converts each element of `op` to a 32-bit integer (signed for `dq`, unsigned for
+            // `udq`), rounding as specified by `rounding` (or truncating, for `cvtt`). The elements
+            // whose bit in `mask` is not set are copied from `src` instead. The 512-bit versions
+            // take the rounding mode as an additional argument; the others use the current
+            // rounding mode.
+            "mask.cvtps2dq.512"
+            | "mask.cvttps2dq.512"
+            | "mask.cvtpd2dq.512"
+            | "mask.cvttpd2dq.512"
+            | "mask.cvtps2udq.512"
+            | "mask.cvttps2udq.512"
+            | "mask.cvtpd2udq.512"
+            | "mask.cvttpd2udq.512" => {
+                let [op, src, mask, rounding] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let truncate = unprefixed_name.starts_with("mask.cvtt");
+                let rnd = if truncate {
+                    // The last argument only controls whether exceptions are suppressed.
+                    rustc_apfloat::Round::TowardZero
+                } else {
+                    rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?
+                };
+                masked_convert_float_to_int(this, op, src, mask, rnd, dest)?;
+            }
+            "mask.cvtps2udq.128"
+            | "mask.cvtps2udq.256"
+            | "mask.cvttps2udq.128"
+            | "mask.cvttps2udq.256"
+            | "mask.cvtpd2udq.128"
+            | "mask.cvtpd2udq.256"
+            | "mask.cvttpd2udq.128"
+            | "mask.cvttpd2udq.256" => {
+                let [op, src, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let rnd = if unprefixed_name.starts_with("mask.cvtt") {
+                    rustc_apfloat::Round::TowardZero
+                } else {
+                    // "current SSE rounding mode", assume nearest
+                    rustc_apfloat::Round::NearestTiesToEven
+                };
+                masked_convert_float_to_int(this, op, src, mask, rnd, dest)?;
+            }
+            // Used to implement the `_mm512_cvt_roundep{i,u}32_ps` and similar functions.
+            // Converts each (signed for `sitofp`, unsigned for `uitofp`) integer element of `op`
+            // to a float, rounding as specified by `rounding`.
+            name if name.starts_with("sitofp.round.") || name.starts_with("uitofp.round.") => {
+                let [op, rounding] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, op_len);
+
+                let signed = name.starts_with("sitofp.");
+                let rnd = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+                for i in 0..dest_len {
+                    let op = this.project_index(&op, i)?;
+                    let size = op.layout.size;
+                    let op = this.read_scalar(&op)?;
+                    let dest = this.project_index(&dest, i)?;
+                    let res = match dest.layout.ty.kind() {
+                        ty::Float(ty::FloatTy::F32) =>
+                            Scalar::from_f32(int_to_float::<Single>(op, size, signed, rnd)?),
+                        ty::Float(ty::FloatTy::F64) =>
+                            Scalar::from_f64(int_to_float::<Double>(op, size, signed, rnd)?),
+                        _ => unreachable!(),
+                    };
+                    this.write_scalar(res, &dest)?;
+                }
+            }
+            // Used to implement the `_mm{,256,512}_{,mask_,maskz_}cvt{,s,us}ep{i,u}{16,32,64}_ep{i,u}{8,16,32}`
+            // functions.
+            // Narrows each element of `op` by truncating it (`pmov`), by saturating it as a signed
+            // integer (`pmovs`), or by saturating it as an unsigned integer (`pmovus`). The
+            // elements whose bit in `mask` is not set are copied from `src` instead. If `dest`
+            // has more elements than `op`, the remaining ones are set to zero.
+            name if name.starts_with("mask.pmov.")
+                || name.starts_with("mask.pmovs.")
+                || name.starts_with("mask.pmovus.") =>
+            {
+                if name.contains(".mem.") {
+                    return interp_ok(EmulateItemResult::NotSupported);
+                }
+                if element == "wb" {
+                    this.expect_target_feature_for_intrinsic(link_name, "avx512bw")?;
+                }
+                let [op, src, mask] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (src, src_len) = this.project_to_simd(src)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, src_len);
+                assert!(dest_len >= op_len);
+
+                let op_size = op.layout.field(this, 0).size;
+                let dest_size = dest.layout.field(this, 0).size;
+                let mask = read_mask(this, mask)?;
+                for i in 0..op_len {
+                    let dest = this.project_index(&dest, i)?;
+                    if !mask_bit(mask, i) {
+                        this.copy_op(&this.project_index(&src, i)?, &dest)?;
+                        continue;
+                    }
+                    let op = this.read_scalar(&this.project_index(&op, i)?)?;
+                    let res = if name.starts_with("mask.pmovs.") {
+                        let op = op.to_int(op_size)?;
+                        let res = op.clamp(dest_size.signed_int_min(), dest_size.signed_int_max());
+                        Scalar::from_int(res, dest_size)
+                    } else if name.starts_with("mask.pmovus.") {
+                        let op = op.to_uint(op_size)?;
+                        Scalar::from_uint(op.min(dest_size.unsigned_int_max()), dest_size)
+                    } else {
+                        Scalar::from_uint(dest_size.truncate(op.to_uint(op_size)?), dest_size)
+                    };
+                    this.write_scalar(res, &dest)?;
+                }
+                for i in op_len..dest_len {
+                    let dest = this.project_index(&dest, i)?;
+                    this.write_scalar(Scalar::from_int(0, dest_size), &dest)?;
+                }
+            }
+            _ => return interp_ok(EmulateItemResult::NotSupported),
+        }
+        interp_ok(EmulateItemResult::NeedsReturn)
+    }
+}
+
+/// Reads the value of a mask register, in which bit `i` controls element `i` of a vector.
+fn read_mask<'tcx>(ecx: &mut MiriInterpCx<'tcx>, mask: &OpTy<'tcx>) -> InterpResult<'tcx, u128> {
+    ecx.read_scalar(mask)?.to_uint(mask.layout.size)
+}
+
+fn mask_bit(mask: u128, i: u64) -> bool {
+    (mask >> i) & 1 != 0
+}
+
+/// Loads the elements of `dest` whose bit in `mask` is set from `ptr`, and copies the others
+/// from `src`. Only the loaded elements are accessed, but if `aligned` is set, `ptr` must be
+/// aligned to the size of `dest` regardless of `mask`.
+fn masked_load<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    ptr: &OpTy<'tcx>,
+    src: &OpTy<'tcx>,
+    mask: &OpTy<'tcx>,
+    dest: &MPlaceTy<'tcx>,
+    aligned: bool,
+) -> InterpResult<'tcx, ()> {
+    let ptr = ecx.read_pointer(ptr)?;
+    if aligned {
+        ecx.check_ptr_align(ptr, Align::from_bytes(dest.layout.size.bytes()).unwrap())?;
+    }
+
+    let (src, src_len) = ecx.project_to_simd(src)?;
+    let (dest, dest_len) = ecx.project_to_simd(dest)?;
+
+    assert_eq!(dest_len, src_len);
+
+    let mask = read_mask(ecx, mask)?;
+    for i in 0..dest_len {
+        let dest = ecx.project_index(&dest, i)?;
+        if mask_bit(mask, i) {
+            let ptr = ptr.wrapping_offset(dest.layout.size * i, &ecx.tcx);
+            // Unaligned copy, alignment has been checked above if required.
+            ecx.mem_copy(ptr, dest.ptr(), dest.layout.size, /*nonoverlapping*/ true)?;
+        } else {
+            ecx.copy_op(&ecx.project_index(&src, i)?, &dest)?;
+        }
+    }
+
+    interp_ok(())
+}
+
+/// Stores the elements of `value` whose bit in `mask` is set to `ptr`. Only the stored elements
+/// are accessed, but if `aligned` is set, `ptr` must be aligned to the size of `value` regardless
+/// of `mask`.
+fn masked_store<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    ptr: &OpTy<'tcx>,
+    value: &OpTy<'tcx>,
+    mask: &OpTy<'tcx>,
+    aligned: bool,
+) -> InterpResult<'tcx, ()> {
+    let ptr = ecx.read_pointer(ptr)?;
+    if aligned {
+        ecx.check_ptr_align(ptr, Align::from_bytes(value.layout.size.bytes()).unwrap())?;
+    }
+
+    let (value, value_len) = ecx.project_to_simd(value)?;
+
+    let mask = read_mask(ecx, mask)?;
+    for i in 0..value_len {
+        if mask_bit(mask, i) {
+            let value = ecx.project_index(&value, i)?;
+            // *Non-inbounds* pointer arithmetic to compute the destination.
+            // (That's why we can't use a place projection.)
+            let ptr = ptr.wrapping_offset(value.layout.size * i, &ecx.tcx);
+            // Deref the pointer *unaligned*, and do the copy.
+            let dest = ecx.ptr_to_mplace_unaligned(ptr, value.layout);
+            ecx.copy_op(&value, &dest)?;
+        }
+    }
+
+    interp_ok(())
+}
+
+/// Converts each float element of `op` whose bit in `mask` is set to an integer, and copies the
+/// other elements from `src`.
+///
+/// When the input value is NaN or out of range, the result is the "integer indefinite" value:
+/// the minimum value for signed and the maximum value for unsigned integers.
+///
+/// If `dest` has more elements than `op`, the rest is filled with zeros.
+fn masked_convert_float_to_int<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    op: &OpTy<'tcx>,
+    src: &OpTy<'tcx>,
+    mask: &OpTy<'tcx>,
+    rnd: rustc_apfloat::Round,
+    dest: &MPlaceTy<'tcx>,
+) -> InterpResult<'tcx, ()> {
+    let (op, op_len) = ecx.project_to_simd(op)?;
+    let (src, src_len) = ecx.project_to_simd(src)?;
+    let (dest, dest_len) = ecx.project_to_simd(dest)?;
+
+    assert_eq!(dest_len, src_len);
+    assert!(dest_len >= op_len);
+
+    let mask = read_mask(ecx, mask)?;
+    for i in 0..op_len {
+        let dest = ecx.project_index(&dest, i)?;
+        if !mask_bit(mask, i) {
+            ecx.copy_op(&ecx.project_index(&src, i)?, &dest)?;
+            continue;
+        }
+        let op = ecx.read_immediate(&ecx.project_index(&op, i)?)?;
+        let res = ecx.float_to_int_checked(&op, dest.layout, rnd)?.unwrap_or_else(|| {
+            let size = dest.layout.size;
+            match dest.layout.ty.kind() {
+                ty::Int(_) => ImmTy::from_int(size.signed_int_min(), dest.layout),
+                ty::Uint(_) => ImmTy::from_uint(size.unsigned_int_max(), dest.layout),
+                _ => unreachable!(),
+            }
+        });
+        ecx.write_immediate(*res, &dest)?;
+    }
+    for i in op_len..dest_len {
+        let dest = ecx.project_index(&dest, i)?;
+        ecx.write_scalar(Scalar::from_int(0, dest.layout.size), &dest)?;
+    }
+
+    interp_ok(())
+}
+
+/// Converts the integer `op` of size `size` to a float, rounding according to `rnd`.
+fn int_to_float<'tcx, F: rustc_apfloat::Float>(
+    op: Scalar,
+    size: Size,
+    signed: bool,
+    rnd: rustc_apfloat::Round,
+) -> InterpResult<'tcx, F> {
+    interp_ok(if signed {
+        F::from_i128_r(op.to_int(size)?, rnd).value
+    } else {
+        F::from_u128_r(op.to_uint(size)?, rnd).value
+    })
+}
+
+/// Used to implement the `_mm{,256,512}_{,mask_}cmp_{ps,pd}_mask` functions.
+/// Compares each element of `left` and `right` with the predicate `imm` (which is the same as
+/// for `_mm256_cmp_ps`), and returns the results as a bit mask, and-ed with `mask`. The 512-bit
+/// versions take an additional argument that only controls whether exceptions are suppressed.
+fn emulate_float_cmp<'tcx>(
+    ecx: &mut MiriInterpCx<'tcx>,
+    link_name: Symbol,
+    abi: ExternAbi,
+    args: &[OpTy<'tcx>],
+    dest: &MPlaceTy<'tcx>,
+    element: &str,
+) -> InterpResult<'tcx, EmulateItemResult> {
+    let (left, right, imm, mask) = if link_name.as_str().ends_with(".512") {
+        let [left, right, imm, mask, _sae] =
+            ecx.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+        (left, right, imm, mask)
+    } else {
+        let [left, right, imm, mask] =
+            ecx.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+        (left, right, imm, mask)
+    };
+
+    let (left, left_len) = ecx.project_to_simd(left)?;
+    let (right, right_len) = ecx.project_to_simd(right)?;
+
+    assert_eq!(left_len, right_len);
+
+    let imm = i8::try_from(ecx.read_scalar(imm)?.to_i32()?).unwrap();
+    let which = FloatBinOp::cmp_from_imm(ecx, imm, link_name)?;
+    let mask = read_mask(ecx, mask)?;
+
+    let mut res = 0u128;
+    for i in 0..left_len {
+        let left = ecx.read_immediate(&ecx.project_index(&left, i)?)?;
+        let right = ecx.read_immediate(&ecx.project_index(&right, i)?)?;
+        let bit = match element {
+            "ps" => bin_op_float::<Single>(which, &left, &right)?,
+            "pd" => bin_op_float::<Double>(which, &left, &right)?,
+            _ => unreachable!(),
+        };
+        if bit.to_uint(left.layout.size)? != 0 {
+            res |= 1 << i;
+        }
+    }
+    ecx.write_scalar(Scalar::from_uint(res & mask, dest.layout.size), dest)?;
+
+    interp_ok(EmulateItemResult::NeedsReturn)
+}
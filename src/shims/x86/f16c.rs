@@ -0,0 +1,70 @@
+use rustc_abi::ExternAbi;
+use rustc_apfloat::FloatConvert as _;
+use rustc_apfloat::ieee::{Half, Single};
+use rustc_span::Symbol;
+
+use super::rounding_from_imm;
+use crate::*;
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn emulate_x86_f16c_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: ExternAbi,
+        args: &[OpTy<'tcx>],
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, EmulateItemResult> {
+        let this = self.eval_context_mut();
+        this.expect_target_feature_for_intrinsic(link_name, "f16c")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement the _mm{,256}_cvtph_ps functions.
+            // Converts the low half-precision floats in `op` to single-precision floats. This is
+            // always exact.
+            "vcvtph2ps.128" | "vcvtph2ps.256" => {
+                let [op] = this.check_shim(abi, ExternAbi::Unadjusted, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert!(op_len >= dest_len);
+
+                for i in 0..dest_len {
+                    let op = this.read_scalar(&this.project_index(&op, i)?)?.to_u16()?;
+                    let op = Half::from_bits(op.into());
+                    let res: Single = op.convert(&mut false).value;
+                    this.write_scalar(Scalar::from_f32(res), &this.project_index(&dest, i)?)?;
+                }
+            }
+            // Used to implement the _mm{,256}_cvtps_ph functions.
+            // Converts the single-precision floats in `op` to half-precision floats, rounding
+            // according to `rounding`. If `dest` has more elements than `op`, the rest is
+            // filled with zeros.
+            "vcvtps2ph.128" | "vcvtps2ph.256" => {
+                let [op, rounding] =
+                    this.check_shim(abi, ExternAbi::Unadjusted, link_name, args)?;
+
+                let (op, op_len) = this.project_to_simd(op)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert!(dest_len >= op_len);
+
+                let rounding = rounding_from_imm(this.read_scalar(rounding)?.to_i32()?)?;
+                for i in 0..op_len {
+                    let op = this.read_scalar(&this.project_index(&op, i)?)?.to_f32()?;
+                    let res: Half = op.convert_r(rounding, &mut false).value;
+                    let res = u16::try_from(res.to_bits()).unwrap();
+                    this.write_scalar(Scalar::from_u16(res), &this.project_index(&dest, i)?)?;
+                }
+                for i in op_len..dest_len {
+                    this.write_scalar(Scalar::from_u16(0), &this.project_index(&dest, i)?)?;
+                }
+            }
+            _ => return interp_ok(EmulateItemResult::NotSupported),
+        }
+        interp_ok(EmulateItemResult::NeedsReturn)
+    }
+}
@@ -0,0 +1,73 @@
+use rustc_abi::ExternAbi;
+use rustc_middle::ty::{self, FloatTy};
+use rustc_span::Symbol;
+
+use crate::helpers::{ToHost, ToSoft};
+use crate::*;
+
+impl<'tcx> EvalContextExt<'tcx> for crate::MiriInterpCx<'tcx> {}
+pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
+    fn emulate_x86_fma_intrinsic(
+        &mut self,
+        link_name: Symbol,
+        abi: ExternAbi,
+        args: &[OpTy<'tcx>],
+        dest: &MPlaceTy<'tcx>,
+    ) -> InterpResult<'tcx, EmulateItemResult> {
+        let this = self.eval_context_mut();
+        this.expect_target_feature_for_intrinsic(link_name, "fma")?;
+        // Prefix should have already been checked.
+        let unprefixed_name = link_name.as_str().strip_prefix("llvm.x86.fma.").unwrap();
+
+        match unprefixed_name {
+            // Used to implement the _mm{,256}_fmaddsub_p{s,d} and _mm{,256}_fmsubadd_p{s,d}
+            // functions.
+            // Computes `a * b + c` or `a * b - c` with a single rounding. `vfmaddsub` subtracts
+            // in the even elements and adds in the odd ones, `vfmsubadd` does the opposite.
+            "vfmaddsub.ps" | "vfmaddsub.pd" | "vfmaddsub.ps.256" | "vfmaddsub.pd.256"
+            | "vfmsubadd.ps" | "vfmsubadd.pd" | "vfmsubadd.ps.256" | "vfmsubadd.pd.256" => {
+                let [a, b, c] =
+                    this.check_shim(abi, ExternAbi::C { unwind: false }, link_name, args)?;
+
+                let (a, a_len) = this.project_to_simd(a)?;
+                let (b, b_len) = this.project_to_simd(b)?;
+                let (c, c_len) = this.project_to_simd(c)?;
+                let (dest, dest_len) = this.project_to_simd(dest)?;
+
+                assert_eq!(dest_len, a_len);
+                assert_eq!(dest_len, b_len);
+                assert_eq!(dest_len, c_len);
+
+                let sub_in_even = unprefixed_name.starts_with("vfmaddsub");
+                for i in 0..dest_len {
+                    let a = this.read_scalar(&this.project_index(&a, i)?)?;
+                    let b = this.read_scalar(&this.project_index(&b, i)?)?;
+                    let c = this.read_scalar(&this.project_index(&c, i)?)?;
+                    let dest = this.project_index(&dest, i)?;
+
+                    let sub = (i % 2 == 0) == sub_in_even;
+                    let ty::Float(float_ty) = dest.layout.ty.kind() else { unreachable!() };
+                    // FIXME: Using host floats, to work around https://github.com/rust-lang/rustc_apfloat/issues/11
+                    let res = match float_ty {
+                        FloatTy::F32 => {
+                            let (a, b, c) = (a.to_f32()?, b.to_f32()?, c.to_f32()?);
+                            let c_host = if sub { -c.to_host() } else { c.to_host() };
+                            let res = a.to_host().mul_add(b.to_host(), c_host).to_soft();
+                            Scalar::from_f32(this.adjust_nan(res, &[a, b, c]))
+                        }
+                        FloatTy::F64 => {
+                            let (a, b, c) = (a.to_f64()?, b.to_f64()?, c.to_f64()?);
+                            let c_host = if sub { -c.to_host() } else { c.to_host() };
+                            let res = a.to_host().mul_add(b.to_host(), c_host).to_soft();
+                            Scalar::from_f64(this.adjust_nan(res, &[a, b, c]))
+                        }
+                        FloatTy::F16 | FloatTy::F128 => unreachable!(),
+                    };
+                    this.write_scalar(res, &dest)?;
+                }
+            }
+            _ => return interp_ok(EmulateItemResult::NotSupported),
+        }
+        interp_ok(EmulateItemResult::NeedsReturn)
+    }
+}
@@ -1,3 +1,4 @@
+use rand::Rng as _;
 use rustc_abi::{ExternAbi, Size};
 use rustc_apfloat::Float;
 use rustc_apfloat::ieee::Single;
@@ -12,7 +13,10 @@ use crate::*;
 mod aesni;
 mod avx;
 mod avx2;
+mod avx512;
 mod bmi;
+mod f16c;
+mod fma;
 mod gfni;
 mod sha;
 mod sse;
@@ -94,6 +98,44 @@ pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 }
             }
 
+            // Used to implement the `_rdrand{16,32,64}_step` and `_rdseed{16,32,64}_step`
+            // functions.
+            // Returns a random number and 1 to indicate success. The number is taken from Miri's
+            // random number generator, so it is reproducible with `-Zmiri-seed`.
+            "rdrand.16" | "rdrand.32" | "rdrand.64" | "rdseed.16" | "rdseed.32" | "rdseed.64" => {
+                let feature = if unprefixed_name.starts_with("rdrand") { "rdrand" } else { "rdseed" };
+                this.expect_target_feature_for_intrinsic(link_name, feature)?;
+                if unprefixed_name.ends_with("64") && this.tcx.sess.target.arch != "x86_64" {
+                    return interp_ok(EmulateItemResult::NotSupported);
+                }
+
+                let [] = this.check_shim(abi, ExternAbi::Unadjusted, link_name, args)?;
+
+                let val = this.project_field(dest, 0)?;
+                let size = val.layout.size;
+                let rand = this.machine.rng.get_mut().gen::<u64>();
+                this.write_scalar(Scalar::from_uint(size.truncate(rand.into()), size), &val)?;
+                this.write_scalar(Scalar::from_i32(1), &this.project_field(dest, 1)?)?;
+            }
+
+            // Used to implement the `_rdtsc` and `__rdtscp` functions.
+            // Reads the time-stamp counter. We pretend that it counts at 1 GHz since the
+            // program was started, based on Miri's clock (which is virtual under isolation).
+            // The processor ID returned by `rdtscp` is always 0.
+            "rdtsc" | "rdtscp" => {
+                let [] = this.check_shim(abi, ExternAbi::Unadjusted, link_name, args)?;
+
+                let clock = &this.machine.clock;
+                let tsc = clock.now().duration_since(clock.epoch()).as_nanos();
+                let tsc = Scalar::from_u64(u64::try_from(tsc).unwrap_or(u64::MAX));
+                if unprefixed_name == "rdtsc" {
+                    this.write_scalar(tsc, dest)?;
+                } else {
+                    this.write_scalar(tsc, &this.project_field(dest, 0)?)?;
+                    this.write_scalar(Scalar::from_u32(0), &this.project_field(dest, 1)?)?;
+                }
+            }
+
             "pclmulqdq" | "pclmulqdq.256" | "pclmulqdq.512" => {
                 let mut len = 2; // in units of 64bits
                 this.expect_target_feature_for_intrinsic(link_name, "pclmulqdq")?;
@@ -112,6 +154,18 @@ pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                 pclmulqdq(this, left, right, imm, dest, len)?;
             }
 
+            name if name.starts_with("fma.") => {
+                return fma::EvalContextExt::emulate_x86_fma_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
+            // The F16C extension does not get its own namespace.
+            // Check for instruction names instead.
+            name if name.starts_with("vcvtph2ps.") || name.starts_with("vcvtps2ph.") => {
+                return f16c::EvalContextExt::emulate_x86_f16c_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
             name if name.starts_with("bmi.") => {
                 return bmi::EvalContextExt::emulate_x86_bmi_intrinsic(
                     this, link_name, abi, args, dest,
@@ -174,6 +228,11 @@ pub(super) trait EvalContextExt<'tcx>: crate::MiriInterpCxExt<'tcx> {
                     this, link_name, abi, args, dest,
                 );
             }
+            name if name.starts_with("avx512.") => {
+                return avx512::EvalContextExt::emulate_x86_avx512_intrinsic(
+                    this, link_name, abi, args, dest,
+                );
+            }
 
             _ => return interp_ok(EmulateItemResult::NotSupported),
         }
@@ -0,0 +1,261 @@
+//! Implements `-Zmiri-track-uninit-origins`, which explains where the uninitialized bytes
+//! behind a "using uninitialized data" error came from, similar to the origins of MemorySanitizer.
+//!
+//! Every byte of an allocation is mapped to the event that last determined its contents: the
+//! creation of the allocation, or the last write to it. When a read finds uninitialized bytes,
+//! that event is their origin: a write that produced uninitialized bytes must have been a write
+//! of uninitialized data (e.g. a `MaybeUninit::uninit()` or the padding of a typed copy), or a
+//! copy of uninitialized bytes from elsewhere. In the latter case the origin also points to the
+//! origin of the source bytes, which yields a chain leading back to where the bytes were first
+//! left uninitialized.
+//!
+//! Locals only get an allocation once their address is taken. At that point, the bytes that are
+//! still uninitialized were never initialized since the storage of the local became live, so their
+//! origin is the declaration of the local. This also covers locals whose value was discarded by
+//! their storage ending and starting again, e.g. in each iteration of a loop.
+//!
+//! We do not know during a write whether the written bytes are initialized, so every write is
+//! recorded. To keep this bounded, origins are interned by their span and predecessor, and the
+//! origins no allocation refers to anymore are removed by the provenance GC.
+
+use std::cell::{Cell, RefCell};
+use std::ops::Range;
+
+use rustc_abi::Size;
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use rustc_span::{Span, SpanData};
+
+use crate::*;
+
+/// Key of an origin in `GlobalState::origins`. Ids are never reused.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct OriginId(u64);
+
+#[derive(Copy, Clone, Debug)]
+enum OriginKind {
+    /// The allocation was created here.
+    Alloc(AllocId, MemoryKind),
+    /// The bytes were written here, but not copied from another allocation.
+    Write,
+    /// The bytes were copied here from bytes with the given origin.
+    Copy(OriginId),
+    /// The bytes belong to a local declared here, which got moved to memory before they were
+    /// initialized after the last `StorageLive` of the local.
+    StorageLive,
+}
+
+#[derive(Debug)]
+struct Origin {
+    kind: OriginKind,
+    span: Span,
+}
+
+/// Global state of uninit origin tracking.
+#[derive(Debug, Default)]
+pub struct GlobalState {
+    origins: RefCell<FxHashMap<OriginId, Origin>>,
+    /// The id of the next origin.
+    next_id: Cell<u64>,
+    /// Interned `Write` and `Copy` origins, keyed by the origin copied from (if any) and span.
+    interned: RefCell<FxHashMap<(Option<OriginId>, Span), OriginId>>,
+    /// The last read. A write of the same size at the same span right after it is a copy,
+    /// e.g. a `memcpy` or an untyped move.
+    last_read: RefCell<LastRead>,
+}
+
+/// The last read and the origins of the bytes it read.
+#[derive(Debug, Default)]
+struct LastRead {
+    /// The allocation, range and span of the read.
+    access: Option<(AllocId, AllocRange, Span)>,
+    /// Whether a write has already been treated as a copy of this read.
+    copied: bool,
+    /// The origins, with byte ranges relative to the start of the read.
+    origins: Vec<(Range<u64>, OriginId)>,
+}
+
+impl GlobalState {
+    fn push(&self, kind: OriginKind, span: Span) -> OriginId {
+        let id = OriginId(self.next_id.get());
+        self.next_id.set(id.0.strict_add(1));
+        self.origins.borrow_mut().insert(id, Origin { kind, span });
+        id
+    }
+
+    /// The origin of a write at `span`, which copied bytes with the origin `from` if any.
+    fn intern(&self, from: Option<OriginId>, span: Span) -> OriginId {
+        if let Some(id) = self.interned.borrow().get(&(from, span)) {
+            return *id;
+        }
+        let kind = match from {
+            Some(from) => OriginKind::Copy(from),
+            None => OriginKind::Write,
+        };
+        let id = self.push(kind, span);
+        self.interned.borrow_mut().insert((from, span), id);
+        id
+    }
+
+    /// Describes `origin` and its predecessors, starting with the most recent event.
+    fn chain(&self, mut origin: OriginId) -> Vec<(SpanData, String)> {
+        let origins = self.origins.borrow();
+        let mut chain = Vec::new();
+        loop {
+            let Origin { kind, span } = &origins[&origin];
+            let msg = match kind {
+                OriginKind::Alloc(alloc_id, mem_kind) =>
+                    format!(
+                        "the uninitialized bytes are part of {alloc_id:?}, a {mem_kind} allocation created here"
+                    ),
+                OriginKind::Write =>
+                    "the uninitialized bytes were written here, e.g. by moving a `MaybeUninit::uninit()` or the padding of a value".to_owned(),
+                OriginKind::Copy(_) =>
+                    "the uninitialized bytes were copied here from other memory".to_owned(),
+                OriginKind::StorageLive =>
+                    "the uninitialized bytes belong to this local variable, which was not initialized since its storage became live (a local loses its value whenever its storage ends, e.g. at the end of each loop iteration)".to_owned(),
+            };
+            chain.push((span.data(), msg));
+            match kind {
+                OriginKind::Copy(from) => origin = *from,
+                _ => return chain,
+            }
+        }
+    }
+
+    /// Removes all origins that are neither in `live` nor a predecessor of an origin in `live`.
+    fn remove_unreachable_origins(&self, mut live: FxHashSet<OriginId>) {
+        let mut origins = self.origins.borrow_mut();
+        let mut todo: Vec<OriginId> = live.iter().copied().collect();
+        while let Some(origin) = todo.pop() {
+            if let OriginKind::Copy(from) = origins[&origin].kind
+                && live.insert(from)
+            {
+                todo.push(from);
+            }
+        }
+        origins.retain(|origin, _| live.contains(origin));
+        self.interned.borrow_mut().retain(|_, origin| live.contains(origin));
+    }
+}
+
+/// Per-allocation origins.
+#[derive(Debug)]
+pub struct AllocState {
+    origins: RefCell<RangeMap<OriginId>>,
+}
+
+impl AllocState {
+    pub fn new_allocation(
+        global: &GlobalState,
+        id: AllocId,
+        size: Size,
+        kind: MemoryKind,
+        span: Span,
+    ) -> Self {
+        let origin = global.push(OriginKind::Alloc(id, kind), span);
+        AllocState { origins: RefCell::new(RangeMap::new(size, origin)) }
+    }
+
+    /// The origin of the byte at `offset`.
+    fn origin(&self, offset: Size) -> OriginId {
+        let origins = self.origins.borrow();
+        let (_, origin) = origins.iter(offset, Size::from_bytes(1)).next().unwrap();
+        *origin
+    }
+
+    pub fn read(&self, global: &GlobalState, alloc_id: AllocId, range: AllocRange, span: Span) {
+        let mut last_read = global.last_read.borrow_mut();
+        last_read.access = Some((alloc_id, range, span));
+        last_read.copied = false;
+        last_read.origins.clear();
+        let origins = self.origins.borrow();
+        last_read.origins.extend(origins.iter(range.start, range.size).map(|(bytes, origin)| {
+            // Make the range relative to the start of the read.
+            let start = bytes.start.max(range.start.bytes()) - range.start.bytes();
+            let end = bytes.end.min(range.end().bytes()) - range.start.bytes();
+            (start..end, *origin)
+        }));
+    }
+
+    /// The allocation of a local declared at `span` was just created and filled with the value of
+    /// the local.
+    pub fn local_moved_to_memory(&mut self, global: &GlobalState, span: Span) {
+        let origin = global.push(OriginKind::StorageLive, span);
+        for (_, o) in self.origins.get_mut().iter_mut_all() {
+            *o = origin;
+        }
+    }
+
+    pub fn write(&mut self, global: &GlobalState, range: AllocRange, span: Span) {
+        let origins = self.origins.get_mut();
+        let mut last_read = global.last_read.borrow_mut();
+        if !last_read.copied
+            && let Some((_, read, read_span)) = last_read.access
+            && read_span == span
+            && read.size == range.size
+        {
+            last_read.copied = true;
+            for (bytes, src_origin) in last_read.origins.iter().cloned() {
+                let origin = global.intern(Some(src_origin), span);
+                let start = range.start + Size::from_bytes(bytes.start);
+                for (_, o) in origins.iter_mut(start, Size::from_bytes(bytes.end - bytes.start)) {
+                    *o = origin;
+                }
+            }
+        } else {
+            let origin = global.intern(None, span);
+            for (_, o) in origins.iter_mut(range.start, range.size) {
+                *o = origin;
+            }
+        }
+    }
+}
+
+/// Notes explaining the origin of the uninitialized bytes behind an `InvalidUninitBytes` error.
+/// `bad` is the allocation and offset of the first uninitialized byte, if the error says so.
+pub fn origin_notes<'tcx>(
+    ecx: &MiriInterpCx<'tcx>,
+    bad: Option<(AllocId, Size)>,
+) -> Vec<(Option<SpanData>, String)> {
+    let Some(global) = &ecx.machine.uninit_origins else {
+        return Vec::new();
+    };
+    let bad = bad.or_else(|| {
+        // Errors from reading a scalar do not say where the uninitialized bytes are. They come
+        // from the read that happened just before, at the same span.
+        let (alloc_id, range, span) = global.last_read.borrow().access?;
+        if span != ecx.machine.current_span() {
+            return None;
+        }
+        let alloc = ecx.get_alloc_raw(alloc_id).report_err().ok()?;
+        let bad = alloc.init_mask().is_range_initialized(range).err()?;
+        Some((alloc_id, bad.start))
+    });
+    let Some((alloc_id, offset)) = bad else {
+        return Vec::new();
+    };
+    let Ok(extra) = ecx.get_alloc_extra(alloc_id).report_err() else {
+        return Vec::new();
+    };
+    let Some(alloc) = &extra.uninit_origins else {
+        return Vec::new();
+    };
+    global.chain(alloc.origin(offset)).into_iter().map(|(span, msg)| (Some(span), msg)).collect()
+}
+
+/// Removes the origins that no live allocation refers to anymore.
+pub fn remove_unreachable_origins<'tcx>(ecx: &MiriInterpCx<'tcx>) {
+    let Some(global) = &ecx.machine.uninit_origins else {
+        return;
+    };
+    let mut live = FxHashSet::default();
+    ecx.memory.alloc_map().iter(|it| {
+        for (_id, (_kind, alloc)) in it {
+            if let Some(alloc) = &alloc.extra.uninit_origins {
+                live.extend(alloc.origins.borrow().iter_all().map(|(_, origin)| *origin));
+            }
+        }
+    });
+    live.extend(global.last_read.borrow().origins.iter().map(|(_, origin)| *origin));
+    global.remove_unreachable_origins(live);
+}
@@ -0,0 +1,11 @@
+//@only-target: linux
+
+/// Test that initializing a barrier twice is detected.
+
+fn main() {
+    unsafe {
+        let mut barrier = std::mem::MaybeUninit::<libc::pthread_barrier_t>::uninit();
+        assert_eq!(libc::pthread_barrier_init(barrier.as_mut_ptr(), std::ptr::null(), 2), 0);
+        libc::pthread_barrier_init(barrier.as_mut_ptr(), std::ptr::null(), 2); //~ ERROR: already initialized barrier
+    }
+}
@@ -0,0 +1,21 @@
+//@only-target: linux
+//@compile-flags: -Zmiri-preemption-rate=0
+
+// Test that destroying a semaphore that a thread is waiting on is detected.
+use std::cell::UnsafeCell;
+use std::thread;
+
+struct Sem(UnsafeCell<libc::sem_t>);
+unsafe impl Sync for Sem {}
+
+fn main() {
+    unsafe {
+        let sem: &'static Sem = Box::leak(Box::new(Sem(UnsafeCell::new(std::mem::zeroed()))));
+        assert_eq!(libc::sem_init(sem.0.get(), 0, 0), 0);
+        let _t = thread::spawn(move || {
+            libc::sem_wait(sem.0.get());
+        });
+        thread::yield_now();
+        libc::sem_destroy(sem.0.get()); //~ ERROR: destroying a semaphore that threads are waiting on
+    }
+}
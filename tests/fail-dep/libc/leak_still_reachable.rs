@@ -0,0 +1,19 @@
+//@ignore-target: windows # No mmap on Windows
+//@normalize-stderr-test: ".*│.*" -> "$$stripped$$"
+
+// A leak that is still referenced from memory that is exempt from the leak check is reported as
+// "still reachable".
+fn main() {
+    unsafe {
+        let page = libc::mmap(
+            std::ptr::null_mut(),
+            4096,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        assert_ne!(page, libc::MAP_FAILED);
+        page.cast::<*mut i32>().write(Box::into_raw(Box::new(42))); //~ERROR: memory leaked
+    }
+}
@@ -0,0 +1,21 @@
+//@ignore-target: windows # No mmap on Windows
+//@compile-flags: -Zmiri-disable-isolation
+
+use std::fs::File;
+use std::os::unix::io::AsRawFd;
+use std::ptr;
+
+#[path = "../../utils/mod.rs"]
+mod utils;
+
+fn main() {
+    let path = utils::prepare_with_content("fail-libc-mmap-shared-overlap.txt", &[1u8; 4096]);
+    let file = File::options().read(true).write(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+    let prot = libc::PROT_READ | libc::PROT_WRITE;
+    let first = unsafe { libc::mmap(ptr::null_mut(), 4096, prot, libc::MAP_SHARED, fd, 0) };
+    assert_ne!(first, libc::MAP_FAILED);
+    // The two mappings would not see each other's changes.
+    let _second = unsafe { libc::mmap(ptr::null_mut(), 4096, prot, libc::MAP_SHARED, fd, 0) };
+    //~^ ERROR: unsupported operation
+}
@@ -0,0 +1,22 @@
+//@compile-flags: -Zmiri-disable-isolation
+//@ignore-target: windows # No mmap on Windows
+
+#![feature(rustc_private)]
+
+fn main() {
+    unsafe {
+        let ptr = libc::mmap(
+            std::ptr::null_mut(),
+            2 * 4096,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        );
+        let ptr = ptr as *mut u8;
+        assert_eq!(libc::mprotect(ptr.add(4096).cast(), 4096, libc::PROT_READ), 0);
+        *ptr = 1;
+        let _x = *ptr.add(4096);
+        *ptr.add(4096) = 1; //~ ERROR: segmentation fault
+    }
+}
@@ -0,0 +1,10 @@
+//@ignore-target: windows # No mprotect on Windows
+
+// Page protections are only supported for memory allocated with `mmap`.
+use std::alloc::{Layout, alloc};
+
+fn main() {
+    let ptr = unsafe { alloc(Layout::from_size_align(4096, 4096).unwrap()) };
+    let _res = unsafe { libc::mprotect(ptr.cast(), 4096, libc::PROT_READ) };
+    //~^ ERROR: only supported on memory allocated with `mmap`
+}
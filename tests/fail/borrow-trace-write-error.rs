@@ -0,0 +1,7 @@
+//! Failing to write the borrow trace is reported as an error.
+// The trace directory is a file, so it cannot be created.
+//@compile-flags: -Zmiri-borrow-trace=1,tests/fail/borrow-trace-write-error.rs
+//@normalize-stderr-test: "the borrow trace: .*" -> "the borrow trace: $$ERR"
+//@error-in-other-file: failed to write the borrow trace
+
+fn main() {}
@@ -0,0 +1,19 @@
+//@compile-flags: -Zmiri-borrow-tracker=both
+//@error-in-other-file: Undefined Behavior under both Stacked Borrows and Tree Borrows
+
+// A write through a pointer derived from a shared reference is a violation under both models.
+// Stacked Borrows checks the access first and only reports it, then Tree Borrows halts the
+// program on the same access.
+#![allow(invalid_reference_casting)]
+
+fn main() {
+    let target = Box::new(42); // has an implicit raw
+    let xref = &*target;
+    {
+        let x: *mut u32 = xref as *const _ as *mut _;
+        unsafe { *x = 42 };
+        //~^ ERROR: Undefined Behavior under Stacked Borrows
+        //~| ERROR: /write access through .* is forbidden/
+    }
+    let _x = *xref;
+}
@@ -0,0 +1,18 @@
+//@compile-flags: -Zmiri-borrow-tracker=both
+//@error-in-other-file: Undefined Behavior under Stacked Borrows only
+
+// A callee reads through the raw pointer that our `&mut` was derived from. This invalidates the
+// `&mut` under Stacked Borrows, but Tree Borrows lets the reference survive foreign reads, so the
+// execution goes on with Tree Borrows only and ends without any further violation.
+fn main() {
+    let mut x = 15;
+    let xraw = &mut x as *mut _;
+    let xref = unsafe { &mut *xraw };
+    callee(xraw);
+    let _val = *xref;
+    //~^ ERROR: Undefined Behavior under Stacked Borrows
+}
+
+fn callee(xraw: *mut i32) {
+    let _val = unsafe { *xraw };
+}
@@ -0,0 +1,17 @@
+//@compile-flags: -Zmiri-borrow-tracker=both
+//@error-in-other-file: Undefined Behavior under Tree Borrows only
+
+// A read from the local variable freezes the `&mut` and the raw pointer derived from it under
+// Tree Borrows, so the second write is a violation there. Stacked Borrows keeps the raw pointer
+// usable, so the execution goes on with Stacked Borrows only and ends without any further
+// violation.
+fn main() {
+    unsafe {
+        let mut root = 6u8;
+        let mref = &mut root;
+        let ptr = mref as *mut u8;
+        *ptr = 0; // Write
+        assert_eq!(root, 0); // Parent Read
+        *ptr = 0; //~ ERROR: Undefined Behavior under Tree Borrows
+    }
+}
@@ -0,0 +1,12 @@
+//@compile-flags: -Zmiri-leak-suppressions=tests/fail/leak_suppressions_broad.supp
+//@normalize-stderr-test: ".*│.*" -> "$$stripped$$"
+
+// Suppressions are matched against the function that allocated a leak, so patterns that match its
+// callers do not hide it.
+fn make_leak() {
+    std::mem::forget(Box::new(42)); //~ERROR: memory leaked
+}
+
+fn main() {
+    make_leak();
+}
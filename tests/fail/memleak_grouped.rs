@@ -0,0 +1,8 @@
+//@normalize-stderr-test: ".*│.*" -> "$$stripped$$"
+
+// Leaks that were allocated by the same backtrace are reported together.
+fn main() {
+    for i in 0..3 {
+        std::mem::forget(Box::new(i)); //~ERROR: memory leaked
+    }
+}
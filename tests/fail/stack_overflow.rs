@@ -0,0 +1,19 @@
+//@normalize-stderr-test: "\d+ frames of recursion" -> "N frames of recursion"
+//! Unbounded recursion on a thread with a small stack is reported as a stack overflow.
+
+use std::thread;
+
+#[allow(unconditional_recursion)]
+fn recurse(depth: u64) -> u64 {
+    let buf = [depth; 64]; //~ERROR: overflowed its stack
+    recurse(buf[0] + 1) + buf[63]
+}
+
+fn main() {
+    let handle = thread::Builder::new()
+        .name("small".into())
+        .stack_size(64 * 1024)
+        .spawn(|| recurse(0))
+        .unwrap();
+    handle.join().unwrap();
+}
@@ -0,0 +1,17 @@
+//@compile-flags: -Zmiri-auto-track
+//@normalize-stderr-test: "created tag [0-9]+" -> "created tag <TAG>"
+//@normalize-stderr-test: "with id [0-9]+" -> "with id $$ALLOC"
+// The tracked re-run of `-Zmiri-auto-track` reaches the same violation, so its report lists the
+// events of the tags and the allocation involved.
+
+fn main() {
+    let mut x = 15;
+    let xraw = &mut x as *mut i32;
+    let xref = unsafe { &mut *xraw };
+    callee(xraw);
+    let _val = *xref; //~ ERROR: /read access .* tag does not exist in the borrow stack/
+}
+
+fn callee(xraw: *mut i32) {
+    let _val = unsafe { *xraw };
+}
@@ -0,0 +1,22 @@
+//@compile-flags: -Zmiri-auto-track -Zmiri-disable-isolation
+// The first run leaves a file behind, so the tracked re-run of `-Zmiri-auto-track` takes a
+// different path and never reaches the violation. The violation of the first run must still be
+// reported, and the program must not exit successfully.
+
+use std::fs;
+
+fn main() {
+    // Both runs happen in the same host process, so they agree on the file name.
+    let marker = std::env::temp_dir().join(format!("miri_auto_track_{}", std::process::id()));
+    if fs::remove_file(&marker).is_ok() {
+        // This is the re-run.
+        return;
+    }
+    fs::write(&marker, b"").unwrap();
+
+    let mut x = 15;
+    let xraw = &mut x as *mut i32;
+    let xref = unsafe { &mut *xraw };
+    let _val = unsafe { *xraw };
+    let _val = *xref; //~ ERROR: /read access .* tag does not exist in the borrow stack/
+}
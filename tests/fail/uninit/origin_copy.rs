@@ -0,0 +1,12 @@
+//@compile-flags: -Zmiri-track-uninit-origins
+// The uninitialized bytes were copied from other memory, so the origin of the source bytes is
+// reported as well.
+use std::mem::MaybeUninit;
+use std::ptr;
+
+fn main() {
+    let src = Box::new(MaybeUninit::<u32>::uninit());
+    let mut dst = Box::new(0u32);
+    unsafe { ptr::copy_nonoverlapping(src.as_ptr(), &mut *dst, 1) };
+    let _val = *dst; //~ ERROR: uninitialized
+}
@@ -0,0 +1,15 @@
+//@compile-flags: -Zmiri-track-uninit-origins
+// A local loses its value when its storage ends, so the value written in the first iteration of
+// the loop is gone in the second one.
+use std::mem::MaybeUninit;
+
+fn main() {
+    for i in 0..2 {
+        let mut x = MaybeUninit::<u32>::uninit();
+        let p = x.as_mut_ptr();
+        if i == 0 {
+            unsafe { p.write(1) };
+        }
+        let _val = unsafe { *p }; //~ ERROR: uninitialized
+    }
+}
@@ -0,0 +1,10 @@
+//@compile-flags: -Zmiri-track-uninit-origins
+// The uninitialized bytes were never written since the allocation was created.
+use std::alloc::{Layout, alloc};
+
+fn main() {
+    unsafe {
+        let p = alloc(Layout::new::<u32>()).cast::<u32>();
+        let _val = *p; //~ ERROR: uninitialized
+    }
+}
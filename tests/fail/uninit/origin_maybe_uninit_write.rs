@@ -0,0 +1,11 @@
+//@compile-flags: -Zmiri-track-uninit-origins
+// The bytes were initialized, but then overwritten with uninitialized bytes.
+use std::mem::MaybeUninit;
+
+fn main() {
+    let p = Box::into_raw(Box::new(0u32));
+    unsafe {
+        p.cast::<MaybeUninit<u32>>().write(MaybeUninit::uninit());
+        let _val = *p; //~ ERROR: uninitialized
+    }
+}
@@ -0,0 +1,275 @@
+//@only-target: windows # Uses win32 api functions
+// We are making scheduler assumptions here.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::os::windows::io::AsRawHandle;
+use std::ptr::{null, null_mut};
+use std::thread;
+
+use windows_sys::Win32::Foundation::{
+    CloseHandle, ERROR_INVALID_PARAMETER, ERROR_NOT_OWNER, ERROR_TOO_MANY_POSTS, FALSE,
+    GetLastError, HANDLE, TRUE, WAIT_FAILED, WAIT_OBJECT_0, WAIT_TIMEOUT,
+};
+use windows_sys::Win32::System::Threading::{
+    CreateEventW, CreateMutexW, CreateSemaphoreW, INFINITE, ReleaseMutex, ReleaseSemaphore,
+    ResetEvent, SetEvent, WAIT_ABANDONED_0, WaitForMultipleObjects, WaitForSingleObject,
+};
+
+#[derive(Copy, Clone)]
+struct SendPtr<T>(*mut T);
+
+unsafe impl<T> Send for SendPtr<T> {}
+
+fn events() {
+    unsafe {
+        let manual = CreateEventW(null(), TRUE, FALSE, null());
+        assert_ne!(manual, 0);
+        assert_eq!(WaitForSingleObject(manual, 0), WAIT_TIMEOUT);
+        // A finite timeout actually blocks before timing out.
+        assert_eq!(WaitForSingleObject(manual, 10), WAIT_TIMEOUT);
+
+        let mut data = 0;
+        let ptr = SendPtr(&mut data);
+        let setter = thread::spawn(move || {
+            let ptr = ptr; // avoid field capturing
+            *ptr.0 = 42;
+            assert_eq!(SetEvent(manual), TRUE);
+        });
+        // The wait synchronizes with the `SetEvent`, so reading `data` is not a data race.
+        assert_eq!(WaitForSingleObject(manual, INFINITE), WAIT_OBJECT_0);
+        assert_eq!(data, 42);
+        setter.join().unwrap();
+
+        // Manual-reset events stay signaled until they are reset.
+        assert_eq!(WaitForSingleObject(manual, 0), WAIT_OBJECT_0);
+        assert_eq!(ResetEvent(manual), TRUE);
+        assert_eq!(WaitForSingleObject(manual, 0), WAIT_TIMEOUT);
+        assert_eq!(CloseHandle(manual), TRUE);
+
+        // Auto-reset events are reset by the wait they satisfy.
+        let auto = CreateEventW(null(), FALSE, TRUE, null());
+        assert_eq!(WaitForSingleObject(auto, 0), WAIT_OBJECT_0);
+        assert_eq!(WaitForSingleObject(auto, 0), WAIT_TIMEOUT);
+
+        // Setting an auto-reset event wakes up only one waiter.
+        let waiters: Vec<_> =
+            (0..2).map(|_| thread::spawn(move || WaitForSingleObject(auto, 100))).collect();
+        // This yield ensures both waiters are blocked on the event by this point.
+        thread::yield_now();
+        assert_eq!(SetEvent(auto), TRUE);
+        let mut results: Vec<_> = waiters.into_iter().map(|t| t.join().unwrap()).collect();
+        results.sort();
+        assert_eq!(results, [WAIT_OBJECT_0, WAIT_TIMEOUT]);
+        assert_eq!(CloseHandle(auto), TRUE);
+    }
+}
+
+fn mutexes() {
+    unsafe {
+        let mutex = CreateMutexW(null(), TRUE, null());
+        assert_ne!(mutex, 0);
+
+        // Mutexes are recursive.
+        assert_eq!(WaitForSingleObject(mutex, 0), WAIT_OBJECT_0);
+
+        let other = thread::spawn(move || {
+            assert_eq!(WaitForSingleObject(mutex, 0), WAIT_TIMEOUT);
+            assert_eq!(ReleaseMutex(mutex), FALSE);
+            assert_eq!(GetLastError(), ERROR_NOT_OWNER);
+            assert_eq!(WaitForSingleObject(mutex, INFINITE), WAIT_OBJECT_0);
+            assert_eq!(ReleaseMutex(mutex), TRUE);
+        });
+        // This yield ensures `other` is blocked on the mutex by this point.
+        thread::yield_now();
+
+        assert_eq!(ReleaseMutex(mutex), TRUE);
+        // We still hold the mutex once.
+        thread::yield_now();
+        assert_eq!(ReleaseMutex(mutex), TRUE);
+        other.join().unwrap();
+
+        assert_eq!(ReleaseMutex(mutex), FALSE);
+        assert_eq!(GetLastError(), ERROR_NOT_OWNER);
+        assert_eq!(CloseHandle(mutex), TRUE);
+
+        // A mutex protects non-atomic accesses.
+        let mutex = CreateMutexW(null(), FALSE, null());
+        let mut counter = 0;
+        let ptr = SendPtr(&mut counter);
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(move || {
+                    let ptr = ptr; // avoid field capturing
+                    assert_eq!(WaitForSingleObject(mutex, INFINITE), WAIT_OBJECT_0);
+                    let value = *ptr.0;
+                    thread::yield_now();
+                    *ptr.0 = value + 1;
+                    assert_eq!(ReleaseMutex(mutex), TRUE);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(counter, 4);
+        assert_eq!(CloseHandle(mutex), TRUE);
+
+        // A mutex whose owner terminates without releasing it is abandoned, which wakes up the
+        // threads waiting for it.
+        let mutex = CreateMutexW(null(), FALSE, null());
+        let event = CreateEventW(null(), FALSE, FALSE, null());
+        let owner = thread::spawn(move || {
+            assert_eq!(WaitForSingleObject(mutex, 0), WAIT_OBJECT_0);
+            assert_eq!(WaitForSingleObject(mutex, 0), WAIT_OBJECT_0);
+            thread::yield_now();
+        });
+        // This yield ensures `owner` holds the mutex by this point.
+        thread::yield_now();
+        let handles: [HANDLE; 2] = [event, mutex];
+        assert_eq!(
+            WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE),
+            WAIT_ABANDONED_0 + 1
+        );
+        owner.join().unwrap();
+        // Only the first wait that acquires the mutex is told that it was abandoned.
+        assert_eq!(ReleaseMutex(mutex), TRUE);
+        assert_eq!(WaitForSingleObject(mutex, 0), WAIT_OBJECT_0);
+        assert_eq!(ReleaseMutex(mutex), TRUE);
+        for handle in [mutex, event] {
+            assert_eq!(CloseHandle(handle), TRUE);
+        }
+    }
+}
+
+fn semaphores() {
+    unsafe {
+        // Invalid counts are rejected.
+        assert_eq!(CreateSemaphoreW(null(), 0, 0, null()), 0);
+        assert_eq!(CreateSemaphoreW(null(), 2, 1, null()), 0);
+
+        let sem = CreateSemaphoreW(null(), 0, 2, null());
+        assert_ne!(sem, 0);
+        assert_eq!(WaitForSingleObject(sem, 0), WAIT_TIMEOUT);
+
+        let mut previous = -1;
+        assert_eq!(ReleaseSemaphore(sem, 3, &mut previous), FALSE);
+        assert_eq!(GetLastError(), ERROR_TOO_MANY_POSTS);
+        assert_eq!(previous, -1);
+        assert_eq!(ReleaseSemaphore(sem, 2, &mut previous), TRUE);
+        assert_eq!(previous, 0);
+        assert_eq!(WaitForSingleObject(sem, 0), WAIT_OBJECT_0);
+        assert_eq!(WaitForSingleObject(sem, 0), WAIT_OBJECT_0);
+        assert_eq!(WaitForSingleObject(sem, 0), WAIT_TIMEOUT);
+
+        let mut data = 0;
+        let ptr = SendPtr(&mut data);
+        let waiter = thread::spawn(move || {
+            let ptr = ptr; // avoid field capturing
+            assert_eq!(WaitForSingleObject(sem, INFINITE), WAIT_OBJECT_0);
+            assert_eq!(*ptr.0, 1);
+        });
+        // This yield ensures `waiter` is blocked on the semaphore by this point.
+        thread::yield_now();
+        *ptr.0 = 1;
+        assert_eq!(ReleaseSemaphore(sem, 1, null_mut()), TRUE);
+        waiter.join().unwrap();
+        assert_eq!(CloseHandle(sem), TRUE);
+    }
+}
+
+fn wait_multiple() {
+    unsafe {
+        let a = CreateEventW(null(), FALSE, FALSE, null());
+        let b = CreateEventW(null(), FALSE, FALSE, null());
+        let handles: [HANDLE; 2] = [a, b];
+
+        // Waiting for any handle returns the index of the signaled one.
+        assert_eq!(SetEvent(b), TRUE);
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), FALSE, 0), WAIT_OBJECT_0 + 1);
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), FALSE, 0), WAIT_TIMEOUT);
+
+        // Waiting for all handles does not consume anything until all of them are signaled.
+        assert_eq!(SetEvent(a), TRUE);
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), TRUE, 0), WAIT_TIMEOUT);
+        assert_eq!(SetEvent(b), TRUE);
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), TRUE, 0), WAIT_OBJECT_0);
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), FALSE, 0), WAIT_TIMEOUT);
+
+        // A blocked wait for any handle gets woken up by the first one to be signaled.
+        let waiter = thread::spawn(move || {
+            let handles: [HANDLE; 2] = [a, b];
+            WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE)
+        });
+        thread::yield_now();
+        assert_eq!(SetEvent(b), TRUE);
+        assert_eq!(waiter.join().unwrap(), WAIT_OBJECT_0 + 1);
+
+        // A blocked wait for all handles only gets woken up once all of them are signaled.
+        let mutex = CreateMutexW(null(), TRUE, null());
+        let sem = CreateSemaphoreW(null(), 0, 1, null());
+        let waiter = thread::spawn(move || {
+            let handles: [HANDLE; 3] = [a, mutex, sem];
+            let ret = WaitForMultipleObjects(3, handles.as_ptr(), TRUE, INFINITE);
+            assert_eq!(ReleaseMutex(mutex), TRUE);
+            ret
+        });
+        thread::yield_now();
+        assert_eq!(SetEvent(a), TRUE);
+        assert_eq!(ReleaseSemaphore(sem, 1, null_mut()), TRUE);
+        thread::yield_now();
+        // The event was not consumed by the waiter, as it still waits for the mutex.
+        assert_eq!(WaitForSingleObject(a, 0), WAIT_OBJECT_0);
+        assert_eq!(SetEvent(a), TRUE);
+        assert_eq!(ReleaseMutex(mutex), TRUE);
+        assert_eq!(waiter.join().unwrap(), WAIT_OBJECT_0);
+        assert_eq!(WaitForSingleObject(a, 0), WAIT_TIMEOUT);
+        assert_eq!(WaitForSingleObject(sem, 0), WAIT_TIMEOUT);
+
+        // Timeouts work for multiple handles, too.
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), TRUE, 10), WAIT_TIMEOUT);
+
+        // The same handle must not be passed twice.
+        let duplicates: [HANDLE; 2] = [a, a];
+        for wait_all in [FALSE, TRUE] {
+            assert_eq!(WaitForMultipleObjects(2, duplicates.as_ptr(), wait_all, 0), WAIT_FAILED);
+            assert_eq!(GetLastError(), ERROR_INVALID_PARAMETER);
+        }
+
+        for handle in [a, b, mutex, sem] {
+            assert_eq!(CloseHandle(handle), TRUE);
+        }
+    }
+}
+
+fn thread_handles() {
+    unsafe {
+        let event = CreateEventW(null(), TRUE, FALSE, null());
+        let waiter = thread::spawn(move || WaitForSingleObject(event, INFINITE));
+        let handle = waiter.as_raw_handle() as HANDLE;
+
+        // A thread handle is signaled once the thread has terminated.
+        assert_eq!(WaitForSingleObject(handle, 0), WAIT_TIMEOUT);
+        assert_eq!(WaitForSingleObject(handle, 10), WAIT_TIMEOUT);
+
+        // A blocked wait gets woken up by the termination of the thread.
+        let other = CreateEventW(null(), TRUE, FALSE, null());
+        let setter = thread::spawn(move || SetEvent(event));
+        let handles: [HANDLE; 2] = [other, handle];
+        assert_eq!(WaitForMultipleObjects(2, handles.as_ptr(), FALSE, INFINITE), WAIT_OBJECT_0 + 1);
+        assert_eq!(WaitForSingleObject(handle, 0), WAIT_OBJECT_0);
+
+        assert_eq!(setter.join().unwrap(), TRUE);
+        assert_eq!(waiter.join().unwrap(), WAIT_OBJECT_0);
+        for handle in [event, other] {
+            assert_eq!(CloseHandle(handle), TRUE);
+        }
+    }
+}
+
+fn main() {
+    events();
+    mutexes();
+    semaphores();
+    wait_multiple();
+    thread_handles();
+}
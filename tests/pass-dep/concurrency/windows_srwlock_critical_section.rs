@@ -0,0 +1,109 @@
+//@only-target: windows # Uses win32 api functions
+// We are making scheduler assumptions here.
+//@compile-flags: -Zmiri-preemption-rate=0
+
+use std::mem::MaybeUninit;
+use std::ptr::null_mut;
+use std::thread;
+
+use windows_sys::Win32::Foundation::{FALSE, TRUE};
+use windows_sys::Win32::System::Threading::{
+    AcquireSRWLockExclusive, AcquireSRWLockShared, CRITICAL_SECTION, DeleteCriticalSection,
+    EnterCriticalSection, InitializeCriticalSection, LeaveCriticalSection, ReleaseSRWLockExclusive,
+    ReleaseSRWLockShared, SRWLOCK, TryAcquireSRWLockExclusive, TryAcquireSRWLockShared,
+    TryEnterCriticalSection,
+};
+
+// not in windows-sys
+const SRWLOCK_INIT: SRWLOCK = SRWLOCK { Ptr: null_mut() };
+
+#[derive(Copy, Clone)]
+struct SendPtr<T>(*mut T);
+
+unsafe impl<T> Send for SendPtr<T> {}
+
+fn srwlock() {
+    let mut lock = SRWLOCK_INIT;
+    let mut data = 0;
+    let lock_ptr = SendPtr(&mut lock);
+    let data_ptr = SendPtr(&mut data);
+
+    unsafe {
+        AcquireSRWLockExclusive(lock_ptr.0);
+
+        let reader = thread::spawn(move || {
+            let (lock_ptr, data_ptr) = (lock_ptr, data_ptr); // avoid field capturing
+            assert_eq!(TryAcquireSRWLockShared(lock_ptr.0), 0);
+            assert_eq!(TryAcquireSRWLockExclusive(lock_ptr.0), 0);
+            AcquireSRWLockShared(lock_ptr.0);
+            assert_eq!(*data_ptr.0, 1);
+            ReleaseSRWLockShared(lock_ptr.0);
+        });
+        // This yield ensures `reader` is blocked on the lock by this point.
+        thread::yield_now();
+        *data_ptr.0 = 1;
+        ReleaseSRWLockExclusive(lock_ptr.0);
+        reader.join().unwrap();
+
+        // Several readers can hold the lock at once, but they exclude writers.
+        AcquireSRWLockShared(lock_ptr.0);
+        let reader = thread::spawn(move || {
+            let lock_ptr = lock_ptr; // avoid field capturing
+            assert_eq!(TryAcquireSRWLockShared(lock_ptr.0), 1);
+            ReleaseSRWLockShared(lock_ptr.0);
+            assert_eq!(TryAcquireSRWLockExclusive(lock_ptr.0), 0);
+        });
+        reader.join().unwrap();
+        ReleaseSRWLockShared(lock_ptr.0);
+
+        assert_eq!(TryAcquireSRWLockExclusive(lock_ptr.0), 1);
+        ReleaseSRWLockExclusive(lock_ptr.0);
+    }
+}
+
+fn critical_section() {
+    let mut section = MaybeUninit::<CRITICAL_SECTION>::uninit();
+    let mut counter = 0;
+    let section_ptr = SendPtr(section.as_mut_ptr());
+    let counter_ptr = SendPtr(&mut counter);
+
+    unsafe {
+        InitializeCriticalSection(section_ptr.0);
+
+        // Critical sections are recursive.
+        EnterCriticalSection(section_ptr.0);
+        assert_eq!(TryEnterCriticalSection(section_ptr.0), TRUE);
+        let other = thread::spawn(move || {
+            let section_ptr = section_ptr; // avoid field capturing
+            assert_eq!(TryEnterCriticalSection(section_ptr.0), FALSE);
+        });
+        other.join().unwrap();
+        LeaveCriticalSection(section_ptr.0);
+        LeaveCriticalSection(section_ptr.0);
+
+        let threads: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(move || {
+                    // avoid field capturing
+                    let (section_ptr, counter_ptr) = (section_ptr, counter_ptr);
+                    EnterCriticalSection(section_ptr.0);
+                    let value = *counter_ptr.0;
+                    thread::yield_now();
+                    *counter_ptr.0 = value + 1;
+                    LeaveCriticalSection(section_ptr.0);
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert_eq!(counter, 4);
+
+        DeleteCriticalSection(section_ptr.0);
+    }
+}
+
+fn main() {
+    srwlock();
+    critical_section();
+}
@@ -0,0 +1,107 @@
+//@only-target: linux # the virtual procfs only exists on Linux
+//@compile-flags: -Zmiri-isolation-error=warn-nobacktrace
+// With isolation, changing metadata only updates an in-memory model.
+
+use std::io::Error;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+fn errno() -> i32 {
+    Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn statx_fd(fd: i32) -> libc::statx {
+    let mut buf = MaybeUninit::<libc::statx>::uninit();
+    let flags = libc::AT_EMPTY_PATH;
+    assert_eq!(
+        unsafe { libc::statx(fd, c"".as_ptr(), flags, libc::STATX_BASIC_STATS, buf.as_mut_ptr()) },
+        0
+    );
+    unsafe { buf.assume_init() }
+}
+
+fn test_stdio() {
+    unsafe {
+        // The changes belong to the file description, so they are visible through all its fds.
+        let fd = libc::dup(1);
+        assert!(fd >= 0);
+
+        let stat = statx_fd(fd);
+        assert_eq!(u32::from(stat.stx_mode) & libc::S_IFMT, libc::S_IFCHR);
+        assert_eq!(stat.stx_mode & 0o7777, 0o620);
+        assert_eq!((stat.stx_uid, stat.stx_gid), (libc::getuid(), libc::getgid()));
+
+        assert_eq!(libc::fchmod(fd, 0o600), 0);
+        assert_eq!(statx_fd(1).stx_mode & 0o7777, 0o600);
+
+        // Without privileges, the owner cannot be changed, and the group can only be changed to
+        // a group we are a member of.
+        assert_eq!(libc::fchown(fd, u32::MAX, libc::getgid()), 0);
+        assert_eq!(libc::fchown(fd, 0, u32::MAX), -1);
+        assert_eq!(errno(), libc::EPERM);
+        assert_eq!(libc::fchown(fd, u32::MAX, 0), -1);
+        assert_eq!(errno(), libc::EPERM);
+
+        // The timestamps start at the Unix epoch, like the virtual clock.
+        let times = [
+            libc::timespec { tv_sec: 10, tv_nsec: 20 },
+            libc::timespec { tv_sec: 0, tv_nsec: libc::UTIME_OMIT },
+        ];
+        assert_eq!(libc::futimens(fd, times.as_ptr()), 0);
+        let stat = statx_fd(fd);
+        assert_eq!((stat.stx_atime.tv_sec, stat.stx_atime.tv_nsec), (10, 20));
+        assert_eq!((stat.stx_mtime.tv_sec, stat.stx_mtime.tv_nsec), (0, 0));
+
+        // `UTIME_NOW` uses the virtual clock.
+        assert_eq!(libc::futimens(fd, ptr::null()), 0);
+        let stat = statx_fd(fd);
+        assert_eq!(
+            (stat.stx_atime.tv_sec, stat.stx_atime.tv_nsec),
+            (stat.stx_mtime.tv_sec, stat.stx_mtime.tv_nsec)
+        );
+        assert!(stat.stx_mtime.tv_nsec > 0);
+
+        assert_eq!(libc::close(fd), 0);
+    }
+}
+
+fn test_virtual_files() {
+    let path = c"/proc/self/status";
+    unsafe {
+        // Virtual files are read-only and owned by root.
+        assert_eq!(libc::access(path.as_ptr(), libc::R_OK), 0);
+        assert_eq!(libc::access(path.as_ptr(), libc::W_OK), -1);
+        assert_eq!(errno(), libc::EACCES);
+        assert_eq!(libc::access(c"/proc/self/nonexistent".as_ptr(), libc::F_OK), -1);
+        assert_eq!(errno(), libc::ENOENT);
+
+        assert_eq!(libc::chmod(path.as_ptr(), 0o666), -1);
+        assert_eq!(errno(), libc::EPERM);
+        assert_eq!(libc::chown(path.as_ptr(), libc::getuid(), libc::getgid()), -1);
+        assert_eq!(errno(), libc::EPERM);
+        assert_eq!(libc::utimensat(libc::AT_FDCWD, path.as_ptr(), ptr::null(), 0), -1);
+        assert_eq!(errno(), libc::EACCES);
+        assert_eq!(libc::link(path.as_ptr(), c"/tmp/status".as_ptr()), -1);
+        assert_eq!(errno(), libc::EPERM);
+
+        let fd = libc::open(path.as_ptr(), libc::O_RDONLY);
+        assert!(fd >= 0);
+        assert_eq!(libc::fchmod(fd, 0o666), -1);
+        assert_eq!(errno(), libc::EPERM);
+        assert_eq!(libc::close(fd), 0);
+    }
+}
+
+fn test_host_files() {
+    // The host file system is not available.
+    unsafe {
+        assert_eq!(libc::chmod(c"foo.txt".as_ptr(), 0o644), -1);
+        assert_eq!(errno(), libc::EACCES);
+    }
+}
+
+fn main() {
+    test_stdio();
+    test_virtual_files();
+    test_host_files();
+}
@@ -38,6 +38,12 @@ fn main() {
     test_isatty();
     test_read_and_uninit();
     test_nofollow_not_symlink();
+    test_dir_fd_at_functions();
+    test_fdopendir();
+    test_access_umask();
+    test_readv_writev();
+    #[cfg(target_os = "linux")]
+    test_copy_file_range_sendfile();
 }
 
 fn test_file_open_unix_allow_two_args() {
@@ -431,3 +437,283 @@ fn test_nofollow_not_symlink() {
     let ret = unsafe { libc::open(cpath.as_ptr(), libc::O_NOFOLLOW | libc::O_CLOEXEC) };
     assert!(ret >= 0);
 }
+
+fn test_dir_fd_at_functions() {
+    use std::fs::{create_dir, read_to_string, remove_dir};
+
+    let dir_path = utils::prepare_dir("miri_test_libc_fs_at_functions");
+    create_dir(&dir_path).unwrap();
+    let c_dir_path = CString::new(dir_path.as_os_str().as_bytes()).unwrap();
+
+    unsafe {
+        let dirfd = libc::open(c_dir_path.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY);
+        assert!(dirfd >= 0);
+        // Directories cannot be opened for writing.
+        assert_eq!(libc::open(c_dir_path.as_ptr(), libc::O_RDWR | libc::O_DIRECTORY), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EISDIR));
+
+        // Create a file relative to the directory.
+        let fd = libc::openat(dirfd, c"file".as_ptr(), libc::O_WRONLY | libc::O_CREAT, 0o666);
+        assert!(fd >= 0);
+        assert_eq!(libc::write(fd, b"at".as_ptr().cast(), 2), 2);
+        assert_eq!(libc::close(fd), 0);
+        assert_eq!(read_to_string(dir_path.join("file")).unwrap(), "at");
+
+        // A regular file cannot be used as a directory.
+        let fd = libc::openat(dirfd, c"file".as_ptr(), libc::O_RDONLY);
+        assert!(fd >= 0);
+        assert_eq!(libc::openat(fd, c"file".as_ptr(), libc::O_RDONLY), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOTDIR));
+        assert_eq!(libc::openat(dirfd, c"file".as_ptr(), libc::O_DIRECTORY), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOTDIR));
+        assert_eq!(libc::close(fd), 0);
+
+        // `AT_FDCWD` resolves relative to the working directory, absolute paths ignore `dirfd`.
+        let fd = libc::openat(libc::AT_FDCWD, c_dir_path.as_ptr(), libc::O_DIRECTORY);
+        assert!(fd >= 0);
+        assert_eq!(libc::close(fd), 0);
+
+        // Create a subdirectory and move the file into it.
+        assert_eq!(libc::mkdirat(dirfd, c"subdir".as_ptr(), 0o755), 0);
+        let subdirfd = libc::openat(dirfd, c"subdir".as_ptr(), libc::O_DIRECTORY);
+        assert!(subdirfd >= 0);
+        assert_eq!(libc::renameat(dirfd, c"file".as_ptr(), subdirfd, c"moved".as_ptr()), 0);
+        assert_eq!(read_to_string(dir_path.join("subdir/moved")).unwrap(), "at");
+
+        #[cfg(target_os = "linux")]
+        {
+            let file =
+                libc::openat(dirfd, c"other".as_ptr(), libc::O_WRONLY | libc::O_CREAT, 0o666);
+            assert_eq!(libc::close(file), 0);
+            assert_eq!(
+                libc::renameat2(
+                    dirfd,
+                    c"other".as_ptr(),
+                    subdirfd,
+                    c"moved".as_ptr(),
+                    libc::RENAME_NOREPLACE
+                ),
+                -1
+            );
+            assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EEXIST));
+            assert_eq!(libc::unlinkat(dirfd, c"other".as_ptr(), 0), 0);
+
+            // `O_PATH` directories can be used as `dirfd` as well.
+            let pathfd = libc::open(c_dir_path.as_ptr(), libc::O_PATH);
+            assert!(pathfd >= 0);
+            let mut statxbuf = std::mem::zeroed::<libc::statx>();
+            assert_eq!(
+                libc::statx(pathfd, c"subdir/moved".as_ptr(), 0, libc::STATX_SIZE, &mut statxbuf),
+                0
+            );
+            assert_eq!(statxbuf.stx_size, 2);
+            assert_eq!(libc::close(pathfd), 0);
+        }
+        #[cfg(not(target_os = "linux"))]
+        {
+            let mut stat = std::mem::zeroed::<libc::stat>();
+            assert_eq!(libc::fstatat(subdirfd, c"moved".as_ptr(), &mut stat, 0), 0);
+            assert_eq!(stat.st_size, 2);
+            assert_eq!(
+                libc::fstatat(dirfd, c"subdir".as_ptr(), &mut stat, libc::AT_SYMLINK_NOFOLLOW),
+                0
+            );
+            assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFDIR);
+
+            // `fstat` works on directory file descriptors.
+            assert_eq!(libc::fstat(subdirfd, &mut stat), 0);
+            assert_eq!(stat.st_mode & libc::S_IFMT, libc::S_IFDIR);
+        }
+
+        // Clean up.
+        assert_eq!(libc::unlinkat(subdirfd, c"moved".as_ptr(), 0), 0);
+        assert_eq!(libc::close(subdirfd), 0);
+        // Without `AT_REMOVEDIR`, a directory cannot be removed.
+        assert_eq!(libc::unlinkat(dirfd, c"subdir".as_ptr(), 0), -1);
+        assert_eq!(libc::unlinkat(dirfd, c"subdir".as_ptr(), libc::AT_REMOVEDIR), 0);
+        assert_eq!(libc::close(dirfd), 0);
+    }
+    remove_dir(&dir_path).unwrap();
+}
+
+fn test_fdopendir() {
+    use std::fs::{create_dir, remove_dir_all};
+
+    let dir_path = utils::prepare_dir("miri_test_libc_fs_fdopendir");
+    create_dir(&dir_path).unwrap();
+    File::create(dir_path.join("entry")).unwrap();
+    let c_dir_path = CString::new(dir_path.as_os_str().as_bytes()).unwrap();
+
+    unsafe {
+        let fd = libc::open(c_dir_path.as_ptr(), libc::O_RDONLY | libc::O_DIRECTORY);
+        assert!(fd >= 0);
+        let dirp = libc::fdopendir(fd);
+        assert!(!dirp.is_null());
+        assert_eq!(libc::dirfd(dirp), fd);
+
+        #[cfg(target_os = "linux")]
+        {
+            let entry = libc::readdir64(dirp);
+            assert!(!entry.is_null());
+            assert_eq!(CStr::from_ptr((*entry).d_name.as_ptr()), c"entry");
+            assert!(libc::readdir64(dirp).is_null());
+        }
+
+        // `closedir` also closes the file descriptor.
+        assert_eq!(libc::closedir(dirp), 0);
+        assert_eq!(libc::close(fd), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EBADF));
+
+        // Streams from `opendir` have a file descriptor, too.
+        let dirp = libc::opendir(c_dir_path.as_ptr());
+        assert!(!dirp.is_null());
+        let fd = libc::dirfd(dirp);
+        assert!(fd >= 0);
+        let entry = libc::openat(fd, c"entry".as_ptr(), libc::O_RDONLY);
+        assert!(entry >= 0);
+        assert_eq!(libc::close(entry), 0);
+        assert_eq!(libc::closedir(dirp), 0);
+    }
+    remove_dir_all(&dir_path).unwrap();
+}
+
+fn test_access_umask() {
+    let path = utils::prepare_with_content("miri_test_libc_fs_access.txt", b"");
+    let c_path = CString::new(path.as_os_str().as_bytes()).unwrap();
+
+    unsafe {
+        assert_eq!(libc::access(c_path.as_ptr(), libc::F_OK), 0);
+        assert_eq!(libc::access(c_path.as_ptr(), libc::R_OK | libc::W_OK), 0);
+        assert_eq!(libc::faccessat(libc::AT_FDCWD, c_path.as_ptr(), libc::R_OK, 0), 0);
+        // Invalid modes are rejected.
+        assert_eq!(libc::access(c_path.as_ptr(), 0x100), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+        // `umask` returns the previous mask.
+        assert_eq!(libc::umask(0o077), 0o022);
+        assert_eq!(libc::umask(0o022), 0o077);
+    }
+
+    remove_file(&path).unwrap();
+    unsafe {
+        assert_eq!(libc::access(c_path.as_ptr(), libc::F_OK), -1);
+        assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::ENOENT));
+    }
+}
+
+fn test_readv_writev() {
+    use std::fs::OpenOptions;
+
+    let path = utils::prepare("miri_test_libc_fs_readv_writev.txt");
+    let file = OpenOptions::new().read(true).write(true).create(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // Gather from several buffers, including an empty one.
+    let (a, b, c) = (*b"Hello", *b"", *b", World!");
+    let iovs = [
+        libc::iovec { iov_base: a.as_ptr() as *mut libc::c_void, iov_len: a.len() },
+        libc::iovec { iov_base: b.as_ptr() as *mut libc::c_void, iov_len: b.len() },
+        libc::iovec { iov_base: c.as_ptr() as *mut libc::c_void, iov_len: c.len() },
+    ];
+    let res = unsafe { libc::writev(fd, iovs.as_ptr(), 3) };
+    assert_eq!(res, 13);
+
+    // Scatter into several buffers; the last one is only partially filled.
+    let (mut x, mut y) = ([0u8; 4], [0u8; 16]);
+    let iovs = [
+        libc::iovec { iov_base: x.as_mut_ptr().cast(), iov_len: x.len() },
+        libc::iovec { iov_base: y.as_mut_ptr().cast(), iov_len: y.len() },
+    ];
+    unsafe { libc::lseek(fd, 0, libc::SEEK_SET) };
+    let res = unsafe { libc::readv(fd, iovs.as_ptr(), 2) };
+    assert_eq!(res, 13);
+    assert_eq!(&x, b"Hell");
+    assert_eq!(&y[..9], b"o, World!");
+
+    // A single buffer at an explicit offset.
+    #[cfg(target_os = "linux")]
+    {
+        let mut z = [0u8; 5];
+        let iov = libc::iovec { iov_base: z.as_mut_ptr().cast(), iov_len: z.len() };
+        let res = unsafe { libc::preadv(fd, &iov, 1, 7) };
+        assert_eq!(res, 5);
+        assert_eq!(&z, b"World");
+    }
+
+    // Negative counts are rejected.
+    let res = unsafe { libc::readv(fd, iovs.as_ptr(), -1) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+#[cfg(target_os = "linux")]
+fn test_copy_file_range_sendfile() {
+    use std::fs::OpenOptions;
+    use std::io::Read;
+
+    let bytes = b"Hello, World!";
+    let src_path = utils::prepare_with_content("miri_test_libc_fs_copy_src.txt", bytes);
+    let dst_path = utils::prepare("miri_test_libc_fs_copy_dst.txt");
+    let src = File::open(&src_path).unwrap();
+    let mut dst = OpenOptions::new().read(true).write(true).create(true).open(&dst_path).unwrap();
+
+    // With an input offset, the file position of the input is not changed.
+    let mut off_in: libc::loff_t = 7;
+    let res = unsafe {
+        libc::copy_file_range(
+            src.as_raw_fd(),
+            &mut off_in,
+            dst.as_raw_fd(),
+            std::ptr::null_mut(),
+            5,
+            0,
+        )
+    };
+    assert_eq!(res, 5);
+    assert_eq!(off_in, 12);
+
+    // Without an offset, `sendfile` uses and advances the file position.
+    let res = unsafe { libc::sendfile(dst.as_raw_fd(), src.as_raw_fd(), std::ptr::null_mut(), 5) };
+    assert_eq!(res, 5);
+
+    // Flags are rejected.
+    let res = unsafe {
+        libc::copy_file_range(
+            src.as_raw_fd(),
+            std::ptr::null_mut(),
+            dst.as_raw_fd(),
+            std::ptr::null_mut(),
+            5,
+            1,
+        )
+    };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error(), Some(libc::EINVAL));
+
+    let mut contents = Vec::new();
+    unsafe { libc::lseek(dst.as_raw_fd(), 0, libc::SEEK_SET) };
+    dst.read_to_end(&mut contents).unwrap();
+    assert_eq!(&contents, b"WorldHello");
+
+    // After a short write, the file position only advances by the bytes that were written.
+    let mut fds = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds.as_mut_ptr()) }, 0);
+    // Leave room for just two more bytes in the pipe, which holds 212992 bytes in Miri.
+    let filler = vec![0u8; 212990];
+    let res = unsafe { libc::write(fds[1], filler.as_ptr().cast(), filler.len()) };
+    assert_eq!(res, 212990);
+    let res = unsafe { libc::sendfile(fds[1], src.as_raw_fd(), std::ptr::null_mut(), 5) };
+    assert_eq!(res, 2);
+    assert_eq!(unsafe { libc::lseek(src.as_raw_fd(), 0, libc::SEEK_CUR) }, 7);
+    unsafe {
+        libc::close(fds[0]);
+        libc::close(fds[1]);
+    }
+
+    drop((src, dst));
+    remove_file(&src_path).unwrap();
+    remove_file(&dst_path).unwrap();
+}
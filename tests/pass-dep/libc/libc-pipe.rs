@@ -15,6 +15,8 @@ fn main() {
     ))]
     // `pipe2` only exists in some specific os.
     test_pipe2();
+    #[cfg(target_os = "linux")]
+    test_splice();
 }
 
 fn test_pipe() {
@@ -131,3 +133,31 @@ fn test_pipe2() {
     let res = unsafe { libc::pipe2(fds.as_mut_ptr(), libc::O_NONBLOCK) };
     assert_eq!(res, 0);
 }
+
+#[cfg(target_os = "linux")]
+fn test_splice() {
+    let mut fds1 = [-1, -1];
+    let mut fds2 = [-1, -1];
+    assert_eq!(unsafe { libc::pipe(fds1.as_mut_ptr()) }, 0);
+    assert_eq!(unsafe { libc::pipe(fds2.as_mut_ptr()) }, 0);
+
+    // Move data from one pipe to the other.
+    let data = "abcde".as_bytes().as_ptr();
+    let res = unsafe { libc::write(fds1[1], data as *const libc::c_void, 5) };
+    assert_eq!(res, 5);
+    let res = unsafe {
+        libc::splice(
+            fds1[0],
+            std::ptr::null_mut(),
+            fds2[1],
+            std::ptr::null_mut(),
+            16,
+            libc::SPLICE_F_MOVE,
+        )
+    };
+    assert_eq!(res, 5);
+    let mut buf: [u8; 5] = [0; 5];
+    let res = unsafe { libc::read(fds2[0], buf.as_mut_ptr().cast(), buf.len() as libc::size_t) };
+    assert_eq!(res, 5);
+    assert_eq!(&buf, "abcde".as_bytes());
+}
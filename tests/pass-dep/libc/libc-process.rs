@@ -0,0 +1,174 @@
+//@ignore-target: windows # Supported only on unixes
+//@compile-flags: -Zmiri-hostname=miri-test -Zmiri-rlimit=nofile=256:512
+//@compile-flags: -Zmiri-rlimit=stack=4194304:unlimited
+
+use std::ffi::CStr;
+use std::io::Error;
+use std::mem::MaybeUninit;
+use std::ptr;
+
+fn errno() -> i32 {
+    Error::last_os_error().raw_os_error().unwrap()
+}
+
+fn test_ids() {
+    unsafe {
+        // Under isolation, we pretend to be started by `init`.
+        assert_eq!(libc::getppid(), 1);
+        assert_eq!(libc::geteuid(), libc::getuid());
+        assert_eq!(libc::getegid(), libc::getgid());
+
+        assert_eq!(libc::getgroups(0, ptr::null_mut()), 1);
+        let mut groups = [0; 4];
+        assert_eq!(libc::getgroups(4, groups.as_mut_ptr()), 1);
+        assert_eq!(groups[0], libc::getgid());
+        assert_eq!(libc::getgroups(-1, groups.as_mut_ptr()), -1);
+        assert_eq!(errno(), libc::EINVAL);
+    }
+}
+
+fn test_hostname() {
+    unsafe {
+        let mut buf = [0 as libc::c_char; 64];
+        assert_eq!(libc::gethostname(buf.as_mut_ptr(), buf.len()), 0);
+        assert_eq!(CStr::from_ptr(buf.as_ptr()).to_bytes(), b"miri-test");
+
+        assert_eq!(libc::gethostname(buf.as_mut_ptr(), 4), -1);
+        assert_eq!(errno(), libc::ENAMETOOLONG);
+
+        let mut uts = MaybeUninit::<libc::utsname>::uninit();
+        assert_eq!(libc::uname(uts.as_mut_ptr()), 0);
+        let uts = uts.assume_init();
+        let field = |field: &[libc::c_char]| CStr::from_ptr(field.as_ptr()).to_str().unwrap();
+        assert_eq!(field(&uts.nodename), "miri-test");
+        assert!(!field(&uts.release).is_empty());
+        assert!(!field(&uts.version).is_empty());
+        #[cfg(target_os = "linux")]
+        {
+            assert_eq!(field(&uts.sysname), "Linux");
+            assert_eq!(field(&uts.domainname), "(none)");
+        }
+        #[cfg(target_os = "macos")]
+        assert_eq!(field(&uts.sysname), "Darwin");
+        #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+        assert_eq!(field(&uts.machine), "x86_64");
+    }
+}
+
+fn getrlimit(resource: i32) -> libc::rlimit {
+    let mut rlim = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    assert_eq!(unsafe { libc::getrlimit(resource as _, &mut rlim) }, 0);
+    rlim
+}
+
+fn setrlimit(resource: i32, rlim_cur: libc::rlim_t, rlim_max: libc::rlim_t) -> i32 {
+    let rlim = libc::rlimit { rlim_cur, rlim_max };
+    unsafe { libc::setrlimit(resource as _, &rlim) }
+}
+
+#[cfg(target_os = "linux")]
+fn main_stack_size() -> usize {
+    unsafe {
+        let mut attr: libc::pthread_attr_t = std::mem::zeroed();
+        assert_eq!(libc::pthread_getattr_np(libc::pthread_self(), &mut attr), 0);
+        let mut addr = ptr::null_mut();
+        let mut size = 0;
+        assert_eq!(libc::pthread_attr_getstack(&attr, &mut addr, &mut size), 0);
+        assert_eq!(libc::pthread_attr_destroy(&mut attr), 0);
+        size
+    }
+}
+
+fn test_rlimit() {
+    let nofile = libc::RLIMIT_NOFILE as i32;
+
+    // The limits set on the command line.
+    let rlim = getrlimit(nofile);
+    assert_eq!((rlim.rlim_cur, rlim.rlim_max), (256, 512));
+    assert_eq!(unsafe { libc::sysconf(libc::_SC_OPEN_MAX) }, 256);
+    let rlim = getrlimit(libc::RLIMIT_STACK as i32);
+    assert_eq!((rlim.rlim_cur, rlim.rlim_max), (4 * 1024 * 1024, libc::RLIM_INFINITY));
+    // The main thread's stack has the size of the soft limit.
+    #[cfg(target_os = "linux")]
+    assert_eq!(main_stack_size() as libc::rlim_t, rlim.rlim_cur);
+
+    // The defaults.
+    let rlim = getrlimit(libc::RLIMIT_CORE as i32);
+    assert_eq!(rlim.rlim_cur, 0);
+
+    // Resources that do not exist.
+    let mut rlim = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+    assert_eq!(unsafe { libc::getrlimit(1000 as _, &mut rlim) }, -1);
+    assert_eq!(errno(), libc::EINVAL);
+    assert_eq!(setrlimit(1000, 0, 0), -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    // The soft limit can be raised up to the hard limit.
+    assert_eq!(setrlimit(nofile, 512, 512), 0);
+    assert_eq!(unsafe { libc::sysconf(libc::_SC_OPEN_MAX) }, 512);
+    assert_eq!(setrlimit(nofile, 1024, 512), -1);
+    assert_eq!(errno(), libc::EINVAL);
+
+    // The hard limit can be lowered, but not raised again.
+    assert_eq!(setrlimit(nofile, 300, 400), 0);
+    assert_eq!(setrlimit(nofile, 300, 512), -1);
+    assert_eq!(errno(), libc::EPERM);
+    let rlim = getrlimit(nofile);
+    assert_eq!((rlim.rlim_cur, rlim.rlim_max), (300, 400));
+}
+
+#[cfg(target_os = "linux")]
+fn test_prlimit() {
+    unsafe {
+        let mut old = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        let new = libc::rlimit { rlim_cur: 100, rlim_max: 400 };
+        assert_eq!(libc::prlimit(0, libc::RLIMIT_NOFILE, &new, &mut old), 0);
+        assert_eq!((old.rlim_cur, old.rlim_max), (300, 400));
+
+        let mut old = libc::rlimit64 { rlim_cur: 0, rlim_max: 0 };
+        assert_eq!(libc::prlimit64(libc::getpid(), libc::RLIMIT_NOFILE, ptr::null(), &mut old), 0);
+        assert_eq!((old.rlim_cur, old.rlim_max), (100, 400));
+
+        // There are no other processes.
+        let mut old = libc::rlimit { rlim_cur: 0, rlim_max: 0 };
+        let pid = libc::getpid() + 1;
+        assert_eq!(libc::prlimit(pid, libc::RLIMIT_NOFILE, ptr::null(), &mut old), -1);
+        assert_eq!(errno(), libc::ESRCH);
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn test_sysinfo() {
+    unsafe {
+        let mut info = MaybeUninit::<libc::sysinfo>::uninit();
+        assert_eq!(libc::sysinfo(info.as_mut_ptr()), 0);
+        let info = info.assume_init();
+        let unit = u64::from(info.mem_unit);
+        assert_eq!(info.totalram as u64 * unit, 8 * 1024 * 1024 * 1024);
+        assert!(info.freeram < info.totalram);
+        assert!(info.procs >= 1);
+        assert_eq!(info.loads, [0; 3]);
+    }
+}
+
+fn test_loadavg() {
+    unsafe {
+        let mut loads = [-1.0; 3];
+        assert_eq!(libc::getloadavg(loads.as_mut_ptr(), 2), 2);
+        assert_eq!(loads, [0.0, 0.0, -1.0]);
+        assert_eq!(libc::getloadavg(loads.as_mut_ptr(), 5), 3);
+        assert_eq!(loads, [0.0; 3]);
+        assert_eq!(libc::getloadavg(loads.as_mut_ptr(), -1), -1);
+    }
+}
+
+fn main() {
+    test_ids();
+    test_hostname();
+    test_rlimit();
+    #[cfg(target_os = "linux")]
+    test_prlimit();
+    #[cfg(target_os = "linux")]
+    test_sysinfo();
+    test_loadavg();
+}
@@ -0,0 +1,184 @@
+//@only-target: linux
+//@compile-flags: -Zmiri-num-cpus=4
+// The virtual procfs and sysfs are available with isolation enabled.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+use std::thread;
+
+fn main() {
+    test_cpus();
+    test_status();
+    test_meminfo();
+    test_maps();
+    test_comm();
+    test_file_operations();
+    test_dirs();
+    test_links();
+    test_missing();
+}
+
+fn test_cpus() {
+    assert_eq!(fs::read_to_string("/sys/devices/system/cpu/online").unwrap(), "0-3\n");
+    assert_eq!(fs::read_to_string("/sys/devices/system/cpu/possible").unwrap(), "0-3\n");
+
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").unwrap();
+    assert_eq!(cpuinfo.lines().filter(|line| line.starts_with("processor")).count(), 4);
+}
+
+fn status_field(status: &str, field: &str) -> String {
+    let line = status.lines().find(|line| line.starts_with(field)).unwrap();
+    line[field.len()..].trim().to_owned()
+}
+
+fn test_status() {
+    let status = fs::read_to_string("/proc/self/status").unwrap();
+    assert_eq!(status_field(&status, "Pid:"), std::process::id().to_string());
+    assert_eq!(status_field(&status, "Threads:"), "1");
+    assert_eq!(status_field(&status, "Cpus_allowed_list:"), "0-3");
+
+    // The thread count includes the threads that are currently running.
+    let handle = thread::spawn(|| {
+        let status = fs::read_to_string("/proc/self/status").unwrap();
+        assert_eq!(status_field(&status, "Threads:"), "2");
+    });
+    handle.join().unwrap();
+
+    // `/proc/<pid>` is the same as `/proc/self`.
+    let path = format!("/proc/{}/status", std::process::id());
+    assert!(fs::read_to_string(path).unwrap().starts_with("Name:"));
+}
+
+fn test_meminfo() {
+    let meminfo = fs::read_to_string("/proc/meminfo").unwrap();
+    let total = status_field(&meminfo, "MemTotal:");
+    let total: u64 = total.strip_suffix(" kB").unwrap().parse().unwrap();
+    let free = status_field(&meminfo, "MemFree:");
+    let free: u64 = free.strip_suffix(" kB").unwrap().parse().unwrap();
+    assert!(free < total);
+}
+
+fn test_maps() {
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+    let ptr = unsafe {
+        libc::mmap(
+            std::ptr::null_mut(),
+            2 * page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    assert_eq!(unsafe { libc::mprotect(ptr.byte_add(page_size), page_size, libc::PROT_READ) }, 0);
+
+    let start = ptr.addr();
+    let maps = fs::read_to_string("/proc/self/maps").unwrap();
+    let first = format!("{:08x}-{:08x} rw-p", start, start + page_size);
+    let second = format!("{:08x}-{:08x} r--p", start + page_size, start + 2 * page_size);
+    assert!(maps.lines().any(|line| line.starts_with(&first)), "{maps}");
+    assert!(maps.lines().any(|line| line.starts_with(&second)), "{maps}");
+
+    assert_eq!(unsafe { libc::munmap(ptr, 2 * page_size) }, 0);
+    let maps = fs::read_to_string("/proc/self/maps").unwrap();
+    assert!(!maps.lines().any(|line| line.starts_with(&first)), "{maps}");
+}
+
+fn test_comm() {
+    let handle = thread::Builder::new()
+        .name("worker".into())
+        .spawn(|| {
+            assert_eq!(fs::read_to_string("/proc/thread-self/comm").unwrap(), "worker\n");
+            let tid = unsafe { libc::gettid() };
+            let path = format!("/proc/self/task/{tid}/comm");
+            assert_eq!(fs::read_to_string(path).unwrap(), "worker\n");
+        })
+        .unwrap();
+    handle.join().unwrap();
+}
+
+fn test_file_operations() {
+    let mut file = File::open("/proc/self/status").unwrap();
+    assert_eq!(file.metadata().unwrap().len(), 0);
+    assert!(file.metadata().unwrap().permissions().readonly());
+
+    let mut start = [0; 5];
+    file.read_exact(&mut start).unwrap();
+    assert_eq!(&start, b"Name:");
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.read_exact(&mut start).unwrap();
+    assert_eq!(&start, b"Name:");
+
+    assert!(fs::metadata("/proc/cpuinfo").unwrap().is_file());
+
+    let err = OpenOptions::new().write(true).open("/proc/self/status").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::PermissionDenied);
+}
+
+fn list_dir(path: &str) -> Vec<String> {
+    let mut names: Vec<String> = fs::read_dir(path)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    names.sort();
+    names
+}
+
+fn test_dirs() {
+    assert_eq!(list_dir("/sys/devices/system/cpu"), ["online", "possible", "present"]);
+    assert!(list_dir("/proc/self").contains(&"status".to_owned()));
+    assert!(fs::metadata("/proc/self/task").unwrap().is_dir());
+
+    // `/proc/self/task` lists the live threads.
+    let main_tid = unsafe { libc::gettid() }.to_string();
+    assert_eq!(list_dir("/proc/self/task"), [main_tid.clone()]);
+    let handle = thread::spawn(move || {
+        let tid = unsafe { libc::gettid() }.to_string();
+        let mut tids = vec![main_tid, tid];
+        tids.sort();
+        assert_eq!(list_dir("/proc/self/task"), tids);
+    });
+    handle.join().unwrap();
+
+    let entry = fs::read_dir("/proc/self/task").unwrap().next().unwrap().unwrap();
+    assert!(entry.file_type().unwrap().is_dir());
+
+    // Files can be opened relative to a directory stream.
+    unsafe {
+        let dir = libc::opendir(c"/proc/thread-self".as_ptr());
+        assert!(!dir.is_null());
+        let fd = libc::openat(libc::dirfd(dir), c"comm".as_ptr(), libc::O_RDONLY);
+        assert!(fd >= 0);
+        let mut buf = [0u8; 32];
+        let len = libc::read(fd, buf.as_mut_ptr().cast(), buf.len());
+        let comm = fs::read_to_string("/proc/self/comm").unwrap();
+        assert_eq!(&buf[..len as usize], comm.as_bytes());
+        assert_eq!(libc::close(fd), 0);
+        assert_eq!(libc::closedir(dir), 0);
+    }
+
+    let err = fs::read_dir("/proc/cpuinfo").unwrap_err();
+    assert_eq!(err.raw_os_error(), Some(libc::ENOTDIR));
+}
+
+fn test_links() {
+    let pid = std::process::id();
+    let tid = unsafe { libc::gettid() };
+    assert_eq!(fs::read_link("/proc/self").unwrap().to_str().unwrap(), pid.to_string());
+    assert_eq!(
+        fs::read_link("/proc/thread-self").unwrap().to_str().unwrap(),
+        format!("{pid}/task/{tid}")
+    );
+
+    let err = fs::read_link("/proc/self/status").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+fn test_missing() {
+    assert_eq!(File::open("/proc/self/environ").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(fs::metadata("/sys/kernel/mm").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(File::open("/proc/self/task/1/comm").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(fs::read_dir("/proc/self/fd").unwrap_err().kind(), ErrorKind::NotFound);
+    assert_eq!(fs::read_link("/proc/self/cwd").unwrap_err().kind(), ErrorKind::NotFound);
+}
@@ -1,9 +1,14 @@
 //@ignore-target: windows # No mmap on Windows
 //@compile-flags: -Zmiri-disable-isolation -Zmiri-permissive-provenance
 
+use std::fs::{self, File};
 use std::io::Error;
+use std::os::unix::io::AsRawFd;
 use std::{ptr, slice};
 
+#[path = "../../utils/mod.rs"]
+mod utils;
+
 fn test_mmap<Offset: Default>(
     mmap: unsafe extern "C" fn(
         *mut libc::c_void,
@@ -141,10 +146,153 @@ fn test_mremap() {
     assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
 }
 
+fn test_file_mapping() {
+    let page_size = page_size::get();
+    let mut content = vec![1u8; page_size];
+    content.extend(std::iter::repeat(2u8).take(page_size / 2));
+    let path = utils::prepare_with_content("miri_test_libc_mmap_file.txt", &content);
+    let file = File::options().read(true).write(true).open(&path).unwrap();
+    let fd = file.as_raw_fd();
+
+    // A private mapping sees the contents of the file, but changes are not written back.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE,
+            fd,
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, page_size) };
+    assert!(slice.iter().all(|b| *b == 1));
+    slice[0] = 3;
+    assert_eq!(unsafe { libc::msync(ptr, page_size, libc::MS_SYNC) }, 0);
+    assert_eq!(unsafe { libc::munmap(ptr, page_size) }, 0);
+    assert_eq!(fs::read(&path).unwrap(), content);
+
+    // A shared mapping at an offset. The part past the end of the file is zero, and is not
+    // written back.
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            fd,
+            page_size.try_into().unwrap(),
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, page_size) };
+    assert!(slice[..page_size / 2].iter().all(|b| *b == 2));
+    assert!(slice[page_size / 2..].iter().all(|b| *b == 0));
+    slice.fill(4);
+    assert_eq!(unsafe { libc::msync(ptr, page_size, libc::MS_SYNC) }, 0);
+    content[page_size..].fill(4);
+    assert_eq!(fs::read(&path).unwrap(), content);
+    // `munmap` writes back as well.
+    slice[0] = 5;
+    assert_eq!(unsafe { libc::munmap(ptr, page_size) }, 0);
+    content[page_size] = 5;
+    assert_eq!(fs::read(&path).unwrap(), content);
+
+    // The offset must be a multiple of the page size.
+    let ptr =
+        unsafe { libc::mmap(ptr::null_mut(), page_size, libc::PROT_READ, libc::MAP_SHARED, fd, 1) };
+    assert_eq!(ptr, libc::MAP_FAILED);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
+
+    // The file descriptor must be valid.
+    let ptr =
+        unsafe { libc::mmap(ptr::null_mut(), page_size, libc::PROT_READ, libc::MAP_SHARED, -1, 0) };
+    assert_eq!(ptr, libc::MAP_FAILED);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EBADF);
+
+    // A writable shared mapping needs a file that was opened for writing.
+    let read_only = File::open(&path).unwrap();
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_SHARED,
+            read_only.as_raw_fd(),
+            0,
+        )
+    };
+    assert_eq!(ptr, libc::MAP_FAILED);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EACCES);
+    // Private and read-only mappings of it are fine.
+    for (prot, flags) in [
+        (libc::PROT_READ | libc::PROT_WRITE, libc::MAP_PRIVATE),
+        (libc::PROT_READ, libc::MAP_SHARED),
+    ] {
+        let ptr = unsafe {
+            libc::mmap(ptr::null_mut(), page_size, prot, flags, read_only.as_raw_fd(), 0)
+        };
+        assert_ne!(ptr, libc::MAP_FAILED);
+        assert_eq!(unsafe { libc::munmap(ptr, page_size) }, 0);
+    }
+
+    drop((file, read_only));
+    fs::remove_file(&path).unwrap();
+}
+
+fn test_mprotect_madvise() {
+    let page_size = page_size::get();
+    let ptr = unsafe {
+        libc::mmap(
+            ptr::null_mut(),
+            2 * page_size,
+            libc::PROT_READ | libc::PROT_WRITE,
+            libc::MAP_PRIVATE | libc::MAP_ANONYMOUS,
+            -1,
+            0,
+        )
+    };
+    assert_ne!(ptr, libc::MAP_FAILED);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, 2 * page_size) };
+    slice.fill(1);
+
+    // A read-only page can still be read.
+    let second = unsafe { ptr.byte_add(page_size) };
+    assert_eq!(unsafe { libc::mprotect(second, page_size, libc::PROT_READ) }, 0);
+    let slice = unsafe { slice::from_raw_parts(ptr as *const u8, 2 * page_size) };
+    assert!(slice.iter().all(|b| *b == 1));
+    // And made writable again.
+    assert_eq!(unsafe { libc::mprotect(second, page_size, libc::PROT_READ | libc::PROT_WRITE) }, 0);
+    let slice = unsafe { slice::from_raw_parts_mut(ptr as *mut u8, 2 * page_size) };
+    slice[page_size] = 2;
+
+    // MADV_DONTNEED zeroes private anonymous pages, even inaccessible ones.
+    assert_eq!(unsafe { libc::mprotect(second, page_size, libc::PROT_NONE) }, 0);
+    assert_eq!(unsafe { libc::madvise(ptr, 2 * page_size, libc::MADV_DONTNEED) }, 0);
+    assert_eq!(unsafe { libc::mprotect(second, page_size, libc::PROT_READ) }, 0);
+    let slice = unsafe { slice::from_raw_parts(ptr as *const u8, 2 * page_size) };
+    assert!(slice.iter().all(|b| *b == 0));
+    // Other advice is only a hint.
+    assert_eq!(unsafe { libc::madvise(ptr, page_size, libc::MADV_WILLNEED) }, 0);
+
+    // Test the error conditions.
+    let res = unsafe { libc::mprotect(ptr.byte_add(1), page_size, libc::PROT_READ) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::EINVAL);
+    let res = unsafe { libc::mprotect(ptr, 3 * page_size, libc::PROT_READ) };
+    assert_eq!(res, -1);
+    assert_eq!(Error::last_os_error().raw_os_error().unwrap(), libc::ENOMEM);
+
+    assert_eq!(unsafe { libc::munmap(ptr, 2 * page_size) }, 0);
+}
+
 fn main() {
     test_mmap(libc::mmap);
     #[cfg(target_os = "linux")]
     test_mmap(libc::mmap64);
     #[cfg(target_os = "linux")]
     test_mremap();
+    test_file_mapping();
+    test_mprotect_madvise();
 }
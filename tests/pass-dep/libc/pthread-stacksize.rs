@@ -0,0 +1,71 @@
+//@ignore-target: windows # No pthreads on Windows
+use std::{mem, ptr};
+
+const STACK_SIZE: usize = 256 * 1024;
+
+fn main() {
+    test_attr_stacksize();
+    test_create_with_stacksize();
+}
+
+fn test_attr_stacksize() {
+    unsafe {
+        let mut attr: libc::pthread_attr_t = mem::zeroed();
+        assert_eq!(libc::pthread_attr_init(&mut attr), 0);
+
+        let mut size = 0;
+        assert_eq!(libc::pthread_attr_getstacksize(&attr, &mut size), 0);
+        assert!(size > 0);
+
+        assert_eq!(libc::pthread_attr_setstacksize(&mut attr, STACK_SIZE), 0);
+        assert_eq!(libc::pthread_attr_getstacksize(&attr, &mut size), 0);
+        assert_eq!(size, STACK_SIZE);
+
+        assert_eq!(libc::pthread_attr_destroy(&mut attr), 0);
+    }
+}
+
+/// Returns the stack size of the current thread.
+fn current_stack_size() -> usize {
+    cfg_if::cfg_if! {
+        if #[cfg(target_os = "macos")] {
+            unsafe { libc::pthread_get_stacksize_np(libc::pthread_self()) }
+        } else if #[cfg(any(target_os = "linux", target_os = "android", target_os = "freebsd"))] {
+            unsafe {
+                let mut attr: libc::pthread_attr_t = mem::zeroed();
+                #[cfg(target_os = "freebsd")]
+                assert_eq!(libc::pthread_attr_init(&mut attr), 0);
+                #[cfg(target_os = "freebsd")]
+                assert_eq!(libc::pthread_attr_get_np(libc::pthread_self(), &mut attr), 0);
+                #[cfg(not(target_os = "freebsd"))]
+                assert_eq!(libc::pthread_getattr_np(libc::pthread_self(), &mut attr), 0);
+                let mut addr = ptr::null_mut();
+                let mut size = 0;
+                assert_eq!(libc::pthread_attr_getstack(&attr, &mut addr, &mut size), 0);
+                assert_eq!(libc::pthread_attr_destroy(&mut attr), 0);
+                size
+            }
+        } else {
+            STACK_SIZE
+        }
+    }
+}
+
+fn test_create_with_stacksize() {
+    extern "C" fn thread_start(_: *mut libc::c_void) -> *mut libc::c_void {
+        assert_eq!(current_stack_size(), STACK_SIZE);
+        ptr::null_mut()
+    }
+
+    unsafe {
+        let mut attr: libc::pthread_attr_t = mem::zeroed();
+        assert_eq!(libc::pthread_attr_init(&mut attr), 0);
+        assert_eq!(libc::pthread_attr_setstacksize(&mut attr, STACK_SIZE), 0);
+
+        let mut thread = mem::zeroed();
+        assert_eq!(libc::pthread_create(&mut thread, &attr, thread_start, ptr::null_mut()), 0);
+        assert_eq!(libc::pthread_join(thread, ptr::null_mut()), 0);
+
+        assert_eq!(libc::pthread_attr_destroy(&mut attr), 0);
+    }
+}
@@ -20,6 +20,12 @@ fn main() {
     check_rwlock_read_no_deadlock();
     check_cond();
     check_condattr();
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    check_spinlock();
+    #[cfg(any(target_os = "linux", target_os = "freebsd"))]
+    check_semaphore();
+    #[cfg(target_os = "linux")]
+    check_barrier();
 }
 
 // We want to only use pthread APIs here for easier testing.
@@ -337,3 +343,111 @@ fn test_rwlock_libc_static_initializer() {
         assert_eq!(libc::pthread_rwlock_destroy(rw.get()), 0);
     }
 }
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+fn check_spinlock() {
+    let bomb = AbortOnDrop;
+    // Specifically *not* using `Arc` to make sure there is no synchronization apart from the lock.
+    unsafe {
+        let data = SyncUnsafeCell::new((MaybeUninit::<libc::pthread_spinlock_t>::uninit(), 0));
+        let ptr = SendPtr { ptr: data.get() };
+        let lockptr = ptr::addr_of_mut!((*ptr.ptr).0).cast::<libc::pthread_spinlock_t>();
+        assert_eq!(libc::pthread_spin_init(lockptr, libc::PTHREAD_PROCESS_PRIVATE), 0);
+        let mut threads = Vec::new();
+
+        for _ in 0..3 {
+            let thread = thread::spawn(move || {
+                let ptr = ptr; // circumvent per-field closure capture
+                let lockptr = ptr::addr_of_mut!((*ptr.ptr).0).cast::<libc::pthread_spinlock_t>();
+                assert_eq!(libc::pthread_spin_lock(lockptr), 0);
+                thread::yield_now();
+                (*ptr.ptr).1 += 1;
+                assert_eq!(libc::pthread_spin_unlock(lockptr), 0);
+            });
+            threads.push(thread);
+        }
+
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        assert_eq!(libc::pthread_spin_trylock(lockptr), 0);
+        assert_eq!(libc::pthread_spin_trylock(lockptr), libc::EBUSY);
+        assert_eq!((*ptr.ptr).1, 3);
+        assert_eq!(libc::pthread_spin_unlock(lockptr), 0);
+        assert_eq!(libc::pthread_spin_destroy(lockptr), 0);
+    }
+    bomb.defuse();
+}
+
+#[cfg(any(target_os = "linux", target_os = "freebsd"))]
+fn check_semaphore() {
+    let bomb = AbortOnDrop;
+    unsafe {
+        let mut sem: MaybeUninit<libc::sem_t> = MaybeUninit::uninit();
+        assert_eq!(libc::sem_init(sem.as_mut_ptr(), 0, 0), 0);
+        let sem = SendPtr { ptr: sem.as_mut_ptr() };
+
+        let mut data = 0;
+        let data = SendPtr { ptr: &mut data };
+
+        // Nothing was posted yet.
+        assert_eq!(libc::sem_trywait(sem.ptr), -1);
+        assert_eq!(std::io::Error::last_os_error().raw_os_error(), Some(libc::EAGAIN));
+
+        let t = thread::spawn(move || {
+            let sem = sem; // circumvent per-field closure capture
+            let data = data;
+            // The post synchronizes this write with the read after the wait.
+            data.ptr.write(1);
+            assert_eq!(libc::sem_post(sem.ptr), 0);
+            assert_eq!(libc::sem_post(sem.ptr), 0);
+        });
+
+        // This blocks until the other thread posts.
+        assert_eq!(libc::sem_wait(sem.ptr), 0);
+        assert_eq!(data.ptr.read(), 1);
+        t.join().unwrap();
+
+        let mut value = 0;
+        assert_eq!(libc::sem_getvalue(sem.ptr, &mut value), 0);
+        assert_eq!(value, 1);
+        assert_eq!(libc::sem_trywait(sem.ptr), 0);
+        assert_eq!(libc::sem_destroy(sem.ptr), 0);
+    }
+    bomb.defuse();
+}
+
+#[cfg(target_os = "linux")]
+fn check_barrier() {
+    let bomb = AbortOnDrop;
+    unsafe {
+        let mut barrier: MaybeUninit<libc::pthread_barrier_t> = MaybeUninit::uninit();
+        assert_eq!(libc::pthread_barrier_init(barrier.as_mut_ptr(), ptr::null(), 3), 0);
+        let barrier = SendPtr { ptr: barrier.as_mut_ptr() };
+
+        let mut data = [0; 3];
+        let data = SendPtr { ptr: &mut data };
+
+        // Every thread writes its own slot, and reads all slots after the barrier.
+        let work = move |i: usize| {
+            let barrier = barrier; // circumvent per-field closure capture
+            let data = data;
+            (*data.ptr)[i] = i + 1;
+            let res = libc::pthread_barrier_wait(barrier.ptr);
+            assert_eq!(*data.ptr, [1, 2, 3]);
+            res
+        };
+        let threads = [thread::spawn(move || work(1)), thread::spawn(move || work(2))];
+        let mut results = vec![work(0)];
+        for thread in threads {
+            results.push(thread.join().unwrap());
+        }
+
+        // Exactly one thread is told that it completed the barrier.
+        results.sort();
+        assert_eq!(results, [-1, 0, 0]);
+        assert_eq!(libc::pthread_barrier_destroy(barrier.ptr), 0);
+    }
+    bomb.defuse();
+}
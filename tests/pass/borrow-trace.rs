@@ -0,0 +1,28 @@
+#![feature(start)]
+#![no_std]
+//@compile-flags: -Zmiri-borrow-trace=21,target/borrow-trace -Cpanic=abort
+//@only-target: linux # alloc IDs differ between OSes (due to extern static allocations)
+
+extern "Rust" {
+    fn miri_alloc(size: usize, align: usize) -> *mut u8;
+    fn miri_dealloc(ptr: *mut u8, size: usize, align: usize);
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    unsafe {
+        // Allocation, write, retag, read and write through the reference, and deallocation: the
+        // trace records 6 steps.
+        let ptr = miri_alloc(4, 4) as *mut u32;
+        *ptr = 42;
+        let r = &mut *ptr;
+        *r += 1;
+        miri_dealloc(ptr as *mut u8, 4, 4);
+    }
+    0
+}
+
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
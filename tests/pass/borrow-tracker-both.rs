@@ -0,0 +1,80 @@
+//@compile-flags: -Zmiri-borrow-tracker=both
+// Programs without aliasing violations must run cleanly when both models check them at once.
+// This covers pointers that Stacked Borrows retags but Tree Borrows does not (raw pointers,
+// interior mutability, `!Unpin` references), protectors, and boxes.
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::marker::PhantomPinned;
+
+fn protected(x: &mut i32, y: &i32) -> i32 {
+    *x += *y;
+    *x
+}
+
+fn raw_pointers() {
+    let mut x = 0u8;
+    let r = &mut x;
+    let p = r as *mut u8;
+    unsafe {
+        *p = 1;
+        *p.add(0) += 1;
+    }
+    *r += 1;
+    assert_eq!(x, 3);
+
+    let mut arr = [0i32; 4];
+    let base = arr.as_mut_ptr();
+    for i in 0..4 {
+        unsafe { base.add(i).write(i as i32) };
+    }
+    assert_eq!(arr, [0, 1, 2, 3]);
+}
+
+fn interior_mutability() {
+    let c = Cell::new(1);
+    let a = &c;
+    let b = &c;
+    a.set(b.get() + 1);
+    assert_eq!(c.get(), 2);
+
+    let r = RefCell::new(vec![1]);
+    r.borrow_mut().push(2);
+    assert_eq!(*r.borrow(), [1, 2]);
+}
+
+fn not_unpin() {
+    struct NotUnpin(i32, PhantomPinned);
+    let mut x = NotUnpin(1, PhantomPinned);
+    let r = &mut x;
+    let p = &raw mut r.0;
+    unsafe { *p = 2 };
+    r.0 += 1;
+    assert_eq!(x.0, 3);
+}
+
+fn boxes_and_collections() {
+    let mut b = Box::new(5);
+    *b += protected(&mut *b.clone(), &1);
+    assert_eq!(*b, 11);
+
+    let mut v = vec![1, 2];
+    v.push(v.len()); // two-phase borrow
+    let (a, rest) = v.split_at_mut(1);
+    a[0] += rest[1];
+    assert_eq!(v, [3, 2, 2]);
+
+    let mut map = HashMap::new();
+    map.insert("a", Box::new(1));
+    *map.get_mut("a").unwrap().as_mut() += 1;
+    assert_eq!(*map["a"], 2);
+}
+
+fn main() {
+    let mut x = 1;
+    let y = 2;
+    assert_eq!(protected(&mut x, &y), 3);
+    raw_pointers();
+    interior_mutability();
+    not_unpin();
+    boxes_and_collections();
+}
@@ -0,0 +1,13 @@
+//@compile-flags: -Zmiri-check-scope=local
+// Undefined behavior that only retagging or validation in the standard library would detect goes
+// unreported when checking is restricted to local crates.
+
+fn main() {
+    // `mem::swap` copies an invalid `bool` here. Only the validity check of that copy, which
+    // happens in a standard library frame, would detect this.
+    let mut byte = 2u8;
+    let invalid = unsafe { &mut *(&mut byte as *mut u8 as *mut bool) };
+    let mut valid = true;
+    std::mem::swap(invalid, &mut valid);
+    assert_eq!(byte, 1);
+}
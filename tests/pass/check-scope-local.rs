@@ -0,0 +1,37 @@
+//@compile-flags: -Zmiri-check-scope=local
+// Exercise a bunch of standard library code (which is not local, and hence skips retagging and
+// validation) mixed with local unsafe code (which is still fully checked).
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+fn fill(v: &mut Vec<u32>) {
+    let ptr = v.as_mut_ptr();
+    for i in 0..v.len() {
+        unsafe { *ptr.add(i) = i as u32 * 2 };
+    }
+}
+
+fn main() {
+    let mut v = vec![0u32; 16];
+    fill(&mut v);
+    assert_eq!(v.iter().sum::<u32>(), 240);
+
+    let mut map = HashMap::new();
+    for (i, x) in v.iter().enumerate() {
+        map.insert(i, *x);
+    }
+    assert_eq!(map[&3], 6);
+
+    let shared = Arc::new(Mutex::new(0));
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let shared = Arc::clone(&shared);
+            thread::spawn(move || *shared.lock().unwrap() += 1)
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(*shared.lock().unwrap(), 4);
+}
@@ -0,0 +1,16 @@
+//! Deep recursion that fits into the stack of the thread is not reported as a stack overflow.
+
+use std::thread;
+
+fn recurse(depth: u64) -> u64 {
+    let buf = [depth; 4];
+    if depth == 0 { 0 } else { recurse(depth - 1) + buf[3] }
+}
+
+fn main() {
+    assert_eq!(recurse(10_000), 50_005_000);
+
+    let handle =
+        thread::Builder::new().stack_size(2 * 1024 * 1024).spawn(|| recurse(10_000)).unwrap();
+    assert_eq!(handle.join().unwrap(), 50_005_000);
+}
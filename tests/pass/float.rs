@@ -28,6 +28,7 @@ fn main() {
     rounding();
     mul_add();
     libm();
+    libm_f16_f128();
     test_fast();
     test_algebraic();
     test_fmuladd();
@@ -1077,6 +1078,47 @@ pub fn libm() {
     assert_eq!(sign, -1);
 }
 
+fn libm_f16_f128() {
+    use std::intrinsics::{
+        cosf16, cosf128, exp2f16, exp2f128, expf16, expf128, log2f16, log2f128, log10f16,
+        log10f128, logf16, logf128, powf16, powf128, powif16, powif128, sinf16, sinf128, sqrtf16,
+        sqrtf128,
+    };
+
+    /// Miri's `f128` implementations are within a few units in the last place, see `src/math.rs`.
+    const F128_ULPS: u128 = 4;
+    #[track_caller]
+    fn assert_f128_ulps(result: f128, expected: f128) {
+        let diff = result.to_bits().abs_diff(expected.to_bits());
+        assert!(diff <= F128_ULPS, "{result:?} is not within {F128_ULPS} ulps of {expected:?}");
+    }
+
+    // These are computed in software, so the results are the same on every host.
+    unsafe {
+        assert_eq!(sqrtf16(6.25), 2.5);
+        assert_eq!(sinf16(0.5), 0.47949);
+        assert_eq!(cosf16(2.0), -0.41626);
+        assert_eq!(expf16(0.5), 1.6484);
+        assert_eq!(exp2f16(0.3), 1.2314);
+        assert_eq!(logf16(10.0), 2.3027);
+        assert_eq!(log10f16(5.0), 0.69873);
+        assert_eq!(log2f16(3.0), 1.585);
+        assert_eq!(powf16(2.5, 3.5), 24.703);
+        assert_eq!(powif16(3.0, -3), 0.037048);
+
+        assert_eq!(sqrtf128(6.25), 2.5);
+        assert_f128_ulps(sinf128(0.5), 0.479425538604203000273287935215571402);
+        assert_f128_ulps(cosf128(2.0), -0.416146836547142386997568229500762197);
+        assert_f128_ulps(expf128(0.5), 1.64872127070012814684865078781416358);
+        assert_f128_ulps(exp2f128(0.3), 1.23114441334491628449939306916774302);
+        assert_f128_ulps(logf128(10.0), 2.30258509299404568401799145468436418);
+        assert_f128_ulps(log10f128(5.0), 0.698970004336018804786261105275506932);
+        assert_f128_ulps(log2f128(3.0), 1.58496250072115618145373894394781657);
+        assert_f128_ulps(powf128(2.0, 10.0), 1024.0);
+        assert_f128_ulps(powif128(3.0, -3), 0.0370370370370370370370370370370370373);
+    }
+}
+
 fn test_fast() {
     use std::intrinsics::{fadd_fast, fdiv_fast, fmul_fast, frem_fast, fsub_fast};
 
@@ -0,0 +1,69 @@
+//@compile-flags: -Zmiri-float-nondet
+#![feature(core_intrinsics, portable_simd)]
+#![allow(internal_features)]
+
+use std::collections::HashSet;
+use std::hint::black_box;
+use std::intrinsics::simd::simd_reduce_add_unordered;
+use std::simd::f64x4;
+
+/// Evaluates `f` many times and returns the set of distinct results.
+fn results(f: impl Fn() -> f64) -> HashSet<u64> {
+    (0..64).map(|_| f().to_bits()).collect()
+}
+
+/// Checks that `f` is not deterministic, but always within a few ulp of `expected`.
+fn check_imprecise(f: impl Fn() -> f64, expected: f64) {
+    let results = results(f);
+    assert!(results.len() > 1, "result is always the same: {expected}");
+    for res in results {
+        let res = f64::from_bits(res);
+        let err = ((res - expected) / expected).abs();
+        assert!(err < 2.0f64.powi(-50), "{res} is too far from {expected}");
+    }
+}
+
+/// Checks that `f` always returns exactly `expected`.
+fn check_exact(f: impl Fn() -> f64, expected: f64) {
+    let results = results(f);
+    assert_eq!(results, HashSet::from([expected.to_bits()]));
+}
+
+fn main() {
+    check_imprecise(|| black_box(1.0f64).sin(), 0.8414709848078965);
+    check_imprecise(|| black_box(1.0f64).exp(), 2.718281828459045);
+    check_imprecise(|| black_box(2.0f64).ln(), 0.6931471805599453);
+    check_imprecise(|| black_box(1.5f64).powf(black_box(2.5)), 2.7556759606310752);
+    check_imprecise(|| black_box(1.1f64).powi(black_box(7)), 1.9487171000000012);
+    check_imprecise(|| black_box(0.5f64).atan(), 0.4636476090008061);
+    check_imprecise(|| black_box(4.5f64).gamma(), 11.631728396567448);
+
+    // Operations that are specified to be exact, or are exact at special points.
+    check_exact(|| black_box(2.0f64).sqrt(), std::f64::consts::SQRT_2);
+    check_exact(|| black_box(0.0f64).exp(), 1.0);
+    check_exact(|| black_box(0.0f64).cos(), 1.0);
+    check_exact(|| black_box(0.0f64).sin(), 0.0);
+    check_exact(|| black_box(1.0f64).ln(), 0.0);
+    check_exact(|| black_box(1.7f64).powf(black_box(0.0)), 1.0);
+    check_exact(|| black_box(1.0f64).powf(black_box(3.3)), 1.0);
+    check_exact(|| black_box(1.7f64).powi(black_box(0)), 1.0);
+
+    // Results stay within the range of the function.
+    for res in results(|| black_box(std::f64::consts::FRAC_PI_2).sin()) {
+        assert!(f64::from_bits(res) <= 1.0);
+    }
+    for res in results(|| black_box(-1.0f64).asin()) {
+        assert!(f64::from_bits(res) >= -std::f64::consts::FRAC_PI_2);
+    }
+    for res in results(|| black_box(-0.0f64).atan2(black_box(-1.0))) {
+        assert!(f64::from_bits(res) >= -std::f64::consts::PI);
+    }
+
+    // The order of unordered reductions is random.
+    let v = f64x4::from_array([1e16, 1.0, -1e16, 1.0]);
+    let sums = results(|| unsafe { simd_reduce_add_unordered(black_box(v)) });
+    assert!(sums.len() > 1);
+    for sum in sums {
+        assert!([0.0, 1.0, 2.0].contains(&f64::from_bits(sum)));
+    }
+}
@@ -0,0 +1,29 @@
+#![feature(start)]
+#![no_std]
+//@compile-flags: -Zmiri-heap-profile=- -Cpanic=abort
+// Writes the heap profile to stderr, so that it gets compared against the expected profile. Time
+// is measured in basic blocks, so every allocation and deallocation below advances the clock by 1.
+
+extern "Rust" {
+    fn miri_alloc(size: usize, align: usize) -> *mut u8;
+    fn miri_dealloc(ptr: *mut u8, size: usize, align: usize);
+}
+
+#[start]
+fn start(_: isize, _: *const *const u8) -> isize {
+    unsafe {
+        let a = miri_alloc(16, 8);
+        let b = miri_alloc(32, 8);
+        // This is the peak: 48 bytes in 2 blocks.
+        miri_dealloc(a, 16, 8);
+        let c = miri_alloc(16, 8);
+        miri_dealloc(b, 32, 8);
+        miri_dealloc(c, 16, 8);
+    }
+    0
+}
+
+#[panic_handler]
+fn panic_handler(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
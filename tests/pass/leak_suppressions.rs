@@ -0,0 +1,14 @@
+//@compile-flags: -Zmiri-leak-suppressions=tests/pass/leak_suppressions.supp
+
+// Leaks allocated by a function that matches a suppression are not reported.
+mod cache {
+    pub fn make_global() -> &'static mut Vec<i32> {
+        let mut v = Vec::with_capacity(4);
+        v.extend([1, 2, 3, 4]);
+        Box::leak(Box::new(v))
+    }
+}
+
+fn main() {
+    assert_eq!(cache::make_global().len(), 4);
+}
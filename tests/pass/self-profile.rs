@@ -0,0 +1,15 @@
+//@compile-flags: -Zmiri-self-profile=-
+// The measurements differ between runs, so only the format of the output is checked: the
+// functions with the most subsystem time and the table rows are erased, as are all numbers. The
+// exception is that Stacked Borrows must have been called, since the program uses references.
+//@normalize-stderr-test: "(?m)^(Stacked Borrows) +[1-9][0-9]* " -> "$1 NONZERO "
+//@normalize-stderr-test: "(?m)^ +[0-9]+\.[0-9]{3}s  .*\n" -> ""
+//@normalize-stderr-test: "(?m)^.*\t[0-9]+\t.*\n" -> ""
+//@normalize-stderr-test: " +[0-9]+\.[0-9]{3}s" -> " TIME"
+//@normalize-stderr-test: " +[0-9]+\.[0-9]%" -> " SHARE"
+//@normalize-stderr-test: " +[0-9]+ TIME" -> " N TIME"
+
+fn main() {
+    let v = vec![1, 2, 3];
+    assert_eq!(v.iter().sum::<i32>(), 6);
+}
@@ -35,6 +35,12 @@ fn main() {
     test_from_raw_os_error();
     #[cfg(unix)]
     test_pread_pwrite();
+    #[cfg(unix)]
+    test_set_permissions();
+    // Apple targets set timestamps with `fsetattrlist`, which Miri does not support.
+    #[cfg(not(target_vendor = "apple"))]
+    test_set_times();
+    test_hard_link();
 }
 
 fn test_path_conversion() {
@@ -347,3 +353,71 @@ fn test_pread_pwrite() {
     f.read_exact(&mut buf1).unwrap();
     assert_eq!(&buf1, b"  m");
 }
+
+#[cfg(unix)]
+fn test_set_permissions() {
+    use std::fs::{Permissions, set_permissions};
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = utils::prepare_with_content("miri_test_fs_set_permissions.txt", b"");
+
+    // Set the permissions through the path.
+    set_permissions(&path, Permissions::from_mode(0o444)).unwrap();
+    let permissions = path.metadata().unwrap().permissions();
+    assert!(permissions.readonly());
+    assert_eq!(permissions.mode() & 0o222, 0);
+
+    // Set the permissions through a file handle.
+    let file = File::open(&path).unwrap();
+    file.set_permissions(Permissions::from_mode(0o644)).unwrap();
+    let permissions = file.metadata().unwrap().permissions();
+    assert!(!permissions.readonly());
+    assert_ne!(permissions.mode() & 0o200, 0);
+    drop(file);
+
+    remove_file(&path).unwrap();
+}
+
+#[cfg(not(target_vendor = "apple"))]
+fn test_set_times() {
+    use std::fs::FileTimes;
+    use std::time::{Duration, SystemTime};
+
+    let path = utils::prepare_with_content("miri_test_fs_set_times.txt", b"");
+    let accessed = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000_000);
+    let modified = SystemTime::UNIX_EPOCH + Duration::from_secs(1_500_000_000);
+
+    let file = File::options().write(true).open(&path).unwrap();
+    file.set_times(FileTimes::new().set_accessed(accessed).set_modified(modified)).unwrap();
+    let metadata = path.metadata().unwrap();
+    assert_eq!(metadata.accessed().unwrap(), accessed);
+    assert_eq!(metadata.modified().unwrap(), modified);
+
+    // Timestamps that are not set are left alone.
+    let modified = modified + Duration::from_secs(1);
+    file.set_modified(modified).unwrap();
+    let metadata = file.metadata().unwrap();
+    assert_eq!(metadata.accessed().unwrap(), accessed);
+    assert_eq!(metadata.modified().unwrap(), modified);
+
+    drop(file);
+    remove_file(&path).unwrap();
+}
+
+fn test_hard_link() {
+    use std::fs::{hard_link, read_to_string, write};
+
+    let path = utils::prepare_with_content("miri_test_fs_hard_link.txt", b"before");
+    let link = utils::prepare("miri_test_fs_hard_link_2.txt");
+
+    hard_link(&path, &link).unwrap();
+    // Both paths refer to the same file.
+    write(&link, b"after").unwrap();
+    assert_eq!(read_to_string(&path).unwrap(), "after");
+    // Linking to an existing path fails.
+    assert_eq!(hard_link(&path, &link).unwrap_err().kind(), ErrorKind::AlreadyExists);
+
+    remove_file(&link).unwrap();
+    assert_eq!(read_to_string(&path).unwrap(), "after");
+    remove_file(&path).unwrap();
+}
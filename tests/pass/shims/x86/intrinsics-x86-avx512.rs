@@ -1,6 +1,6 @@
 // We're testing x86 target specific features
 //@only-target: x86_64 i686
-//@compile-flags: -C target-feature=+avx512f,+avx512vl,+avx512bitalg,+avx512vpopcntdq
+//@compile-flags: -C target-feature=+avx512f,+avx512vl,+avx512bw,+avx512bitalg,+avx512vpopcntdq
 
 #![feature(avx512_target_feature)]
 #![feature(stdarch_x86_avx512)]
@@ -14,15 +14,327 @@ use std::mem::transmute;
 fn main() {
     assert!(is_x86_feature_detected!("avx512f"));
     assert!(is_x86_feature_detected!("avx512vl"));
+    assert!(is_x86_feature_detected!("avx512bw"));
     assert!(is_x86_feature_detected!("avx512bitalg"));
     assert!(is_x86_feature_detected!("avx512vpopcntdq"));
 
     unsafe {
+        test_avx512f();
+        test_avx512bw();
         test_avx512bitalg();
         test_avx512vpopcntdq();
     }
 }
 
+#[target_feature(enable = "avx512f,avx512vl")]
+unsafe fn test_avx512f() {
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mask_loadu_epi32() {
+        let a: [i32; 16] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16];
+        let src = _mm512_set1_epi32(-1);
+        let r = _mm512_mask_loadu_epi32(src, 0b1010_1010_1010_1010, a.as_ptr());
+        let e = _mm512_setr_epi32(-1, 2, -1, 4, -1, 6, -1, 8, -1, 10, -1, 12, -1, 14, -1, 16);
+        assert_eq_m512i(r, e);
+
+        // Masked-off elements are not accessed.
+        let a: [i32; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+        let r = _mm512_maskz_loadu_epi32(0b0000_0000_1111_1111, a.as_ptr());
+        let e = _mm512_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, 0, 0, 0, 0);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_mask_loadu_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mask_load_epi32() {
+        #[repr(align(64))]
+        struct Align64([i32; 16]);
+        let a = Align64([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]);
+        let src = _mm512_set1_epi32(-1);
+        let r = _mm512_mask_load_epi32(src, 0b0000_0000_0000_0011, a.0.as_ptr());
+        let e = _mm512_setr_epi32(1, 2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1, -1);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_mask_load_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mask_storeu_epi32() {
+        // Masked-off elements are not accessed.
+        let mut r = [0i32; 8];
+        let a = _mm512_setr_epi32(1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16);
+        _mm512_mask_storeu_epi32(r.as_mut_ptr(), 0b0000_0000_1010_1111, a);
+        assert_eq!(r, [1, 2, 3, 4, 0, 6, 0, 8]);
+    }
+    test_mm512_mask_storeu_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mask_compress_epi32() {
+        let src = _mm512_set1_epi32(-1);
+        let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let r = _mm512_mask_compress_epi32(src, 0b0101_0101_0101_0101, a);
+        let e = _mm512_setr_epi32(0, 2, 4, 6, 8, 10, 12, 14, -1, -1, -1, -1, -1, -1, -1, -1);
+        assert_eq_m512i(r, e);
+
+        let r = _mm512_maskz_compress_epi32(0b1000_0000_0000_0110, a);
+        let e = _mm512_setr_epi32(1, 2, 15, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_mask_compress_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mask_expand_epi32() {
+        let src = _mm512_set1_epi32(-1);
+        let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let r = _mm512_mask_expand_epi32(src, 0b0101_0101_0101_0101, a);
+        let e = _mm512_setr_epi32(0, -1, 1, -1, 2, -1, 3, -1, 4, -1, 5, -1, 6, -1, 7, -1);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_mask_expand_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_mask_compressstoreu_epi32() {
+        // Only as many elements as are selected are written.
+        let mut r = [0i32; 3];
+        let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        _mm512_mask_compressstoreu_epi32(r.as_mut_ptr().cast(), 0b1000_0000_0000_0011, a);
+        assert_eq!(r, [0, 1, 15]);
+    }
+    test_mm512_mask_compressstoreu_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_permutex2var_epi32() {
+        let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let b = _mm512_setr_epi32(
+            100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115,
+        );
+        let idx = _mm512_setr_epi32(0, 16, 1, 17, 2, 18, 3, 19, 31, 15, 32, 48, -1, 5, 21, 37);
+        let r = _mm512_permutex2var_epi32(a, idx, b);
+        let e = _mm512_setr_epi32(0, 100, 1, 101, 2, 102, 3, 103, 115, 15, 0, 100, 115, 5, 105, 5);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_permutex2var_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_permutexvar_epi32() {
+        let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let idx = _mm512_setr_epi32(15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 16, 17, -1, 0);
+        let r = _mm512_permutexvar_epi32(idx, a);
+        let e = _mm512_setr_epi32(15, 14, 13, 12, 11, 10, 9, 8, 7, 6, 5, 4, 0, 1, 15, 0);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_permutexvar_epi32();
+
+    #[target_feature(enable = "avx512f,avx512vl")]
+    unsafe fn test_ternarylogic() {
+        let a = _mm512_set1_epi32(0b1100);
+        let b = _mm512_set1_epi32(0b1010);
+        let c = _mm512_set1_epi32(0b1001);
+        // a ^ b ^ c
+        assert_eq_m512i(_mm512_ternarylogic_epi32::<0x96>(a, b, c), _mm512_set1_epi32(0b1111));
+        // majority(a, b, c)
+        assert_eq_m512i(_mm512_ternarylogic_epi32::<0xe8>(a, b, c), _mm512_set1_epi32(0b1000));
+        // a
+        assert_eq_m512i(_mm512_ternarylogic_epi32::<0xf0>(a, b, c), a);
+        // !(a | b | c)
+        assert_eq_m512i(_mm512_ternarylogic_epi32::<0x01>(a, b, c), _mm512_set1_epi32(!0b1111));
+
+        let a = _mm_set1_epi64x(0b1100);
+        let b = _mm_set1_epi64x(0b1010);
+        let c = _mm_set1_epi64x(0b1001);
+        assert_eq_m128i(_mm_ternarylogic_epi64::<0x96>(a, b, c), _mm_set1_epi64x(0b1111));
+    }
+    test_ternarylogic();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_cmp_mask() {
+        let a = _mm512_setr_epi32(0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15);
+        let b = _mm512_set1_epi32(8);
+        assert_eq!(_mm512_cmp_epi32_mask::<_MM_CMPINT_LT>(a, b), 0b0000_0000_1111_1111);
+        assert_eq!(_mm512_cmp_epi32_mask::<_MM_CMPINT_NLE>(a, b), 0b1111_1110_0000_0000);
+        assert_eq!(_mm512_cmp_epi32_mask::<_MM_CMPINT_EQ>(a, b), 0b0000_0001_0000_0000);
+        assert_eq!(
+            _mm512_mask_cmp_epi32_mask::<_MM_CMPINT_NE>(0b0000_0011_0000_0011, a, b),
+            0b0000_0010_0000_0011
+        );
+
+        let a = _mm512_set1_epi32(-1);
+        let b = _mm512_set1_epi32(1);
+        assert_eq!(_mm512_cmplt_epi32_mask(a, b), 0xffff);
+        assert_eq!(_mm512_cmplt_epu32_mask(a, b), 0);
+
+        let a = _mm512_setr_ps(
+            0.,
+            1.,
+            2.,
+            3.,
+            4.,
+            5.,
+            6.,
+            7.,
+            8.,
+            9.,
+            10.,
+            11.,
+            12.,
+            13.,
+            14.,
+            f32::NAN,
+        );
+        let b = _mm512_set1_ps(8.);
+        assert_eq!(_mm512_cmp_ps_mask::<_CMP_LT_OQ>(a, b), 0b0000_0000_1111_1111);
+        assert_eq!(_mm512_cmp_ps_mask::<_CMP_NLT_UQ>(a, b), 0b1111_1111_0000_0000);
+        assert_eq!(_mm512_cmp_ps_mask::<_CMP_UNORD_Q>(a, b), 0b1000_0000_0000_0000);
+        assert_eq!(
+            _mm512_mask_cmp_ps_mask::<_CMP_GE_OS>(0b0100_0001_0000_0001, a, b),
+            0b0100_0001_0000_0000
+        );
+    }
+    test_mm512_cmp_mask();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_cvtps_epi32() {
+        let a = _mm512_setr_ps(
+            1.5,
+            -1.5,
+            2.5,
+            -2.5,
+            0.4,
+            -0.6,
+            1e10,
+            f32::NAN,
+            1.5,
+            -1.5,
+            2.5,
+            -2.5,
+            0.4,
+            -0.6,
+            -1e10,
+            f32::INFINITY,
+        );
+        let min = i32::MIN;
+        let r = _mm512_cvtps_epi32(a);
+        let e = _mm512_setr_epi32(2, -2, 2, -2, 0, -1, min, min, 2, -2, 2, -2, 0, -1, min, min);
+        assert_eq_m512i(r, e);
+        let r = _mm512_cvttps_epi32(a);
+        let e = _mm512_setr_epi32(1, -1, 2, -2, 0, 0, min, min, 1, -1, 2, -2, 0, 0, min, min);
+        assert_eq_m512i(r, e);
+        let r = _mm512_cvt_roundps_epi32::<{ _MM_FROUND_TO_NEG_INF | _MM_FROUND_NO_EXC }>(a);
+        let e = _mm512_setr_epi32(1, -2, 2, -3, 0, -1, min, min, 1, -2, 2, -3, 0, -1, min, min);
+        assert_eq_m512i(r, e);
+        let src = _mm512_set1_epi32(42);
+        let r = _mm512_mask_cvtps_epi32(src, 0b0000_0000_0000_0101, a);
+        let e = _mm512_setr_epi32(2, 42, 2, 42, 42, 42, 42, 42, 42, 42, 42, 42, 42, 42, 42, 42);
+        assert_eq_m512i(r, e);
+
+        let a = _mm512_setr_ps(
+            1.5,
+            2.5,
+            3e9,
+            -1.0,
+            5e9,
+            f32::NAN,
+            0.4,
+            7.0,
+            1.5,
+            2.5,
+            3e9,
+            -1.0,
+            5e9,
+            f32::NAN,
+            0.4,
+            7.0,
+        );
+        let max = u32::MAX as i32;
+        let big = 3_000_000_000u32 as i32;
+        let r = _mm512_cvtps_epu32(a);
+        let e = _mm512_setr_epi32(2, 2, big, max, max, max, 0, 7, 2, 2, big, max, max, max, 0, 7);
+        assert_eq_m512i(r, e);
+    }
+    test_mm512_cvtps_epi32();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_cvt_roundepi32_ps() {
+        let a = _mm512_set1_epi32(16777217);
+        let r = _mm512_cvt_roundepi32_ps::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(a);
+        assert_eq_m512(r, _mm512_set1_ps(16777216.));
+        let r = _mm512_cvt_roundepi32_ps::<{ _MM_FROUND_TO_POS_INF | _MM_FROUND_NO_EXC }>(a);
+        assert_eq_m512(r, _mm512_set1_ps(16777218.));
+
+        let a = _mm512_set1_epi32(-1);
+        let r = _mm512_cvt_roundepu32_ps::<{ _MM_FROUND_TO_ZERO | _MM_FROUND_NO_EXC }>(a);
+        assert_eq_m512(r, _mm512_set1_ps(4294967040.));
+        let r = _mm512_cvt_roundepu32_ps::<{ _MM_FROUND_TO_NEAREST_INT | _MM_FROUND_NO_EXC }>(a);
+        assert_eq_m512(r, _mm512_set1_ps(4294967296.));
+    }
+    test_mm512_cvt_roundepi32_ps();
+
+    #[target_feature(enable = "avx512f")]
+    unsafe fn test_mm512_cvtepi32_epi8() {
+        let a = _mm512_setr_epi32(
+            0,
+            1,
+            127,
+            128,
+            -1,
+            -128,
+            -129,
+            255,
+            256,
+            1000,
+            -1000,
+            i32::MAX,
+            i32::MIN,
+            42,
+            -42,
+            300,
+        );
+        let r = _mm512_cvtepi32_epi8(a);
+        let e = _mm_setr_epi8(0, 1, 127, -128, -1, -128, 127, -1, 0, -24, 24, -1, 0, 42, -42, 44);
+        assert_eq_m128i(r, e);
+        let r = _mm512_cvtsepi32_epi8(a);
+        let e = _mm_setr_epi8(
+            0, 1, 127, 127, -1, -128, -128, 127, 127, 127, -128, 127, -128, 42, -42, 127,
+        );
+        assert_eq_m128i(r, e);
+        let r = _mm512_cvtusepi32_epi8(a);
+        let e = _mm_setr_epi8(0, 1, 127, -128, -1, -1, -1, -1, -1, -1, -1, -1, -1, 42, -1, -1);
+        assert_eq_m128i(r, e);
+
+        // Only 8 elements are converted, the rest of the result is zero.
+        let a = _mm512_setr_epi64(1, 2, 3, 4, 5, 6, 7, 0x1_0000_0108);
+        let src = _mm_set1_epi8(-1);
+        let r = _mm512_mask_cvtepi64_epi8(src, 0b1000_0011, a);
+        let e = _mm_setr_epi8(1, 2, -1, -1, -1, -1, -1, 8, 0, 0, 0, 0, 0, 0, 0, 0);
+        assert_eq_m128i(r, e);
+    }
+    test_mm512_cvtepi32_epi8();
+}
+
+#[target_feature(enable = "avx512bw,avx512f")]
+unsafe fn test_avx512bw() {
+    #[target_feature(enable = "avx512bw,avx512f")]
+    unsafe fn test_mm512_maskz_loadu_epi8() {
+        // Masked-off elements are not accessed.
+        let a: [i8; 4] = [1, 2, 3, 4];
+        let r = _mm512_maskz_loadu_epi8(0b1011, a.as_ptr());
+        let mut e = [0i8; 64];
+        e[0] = 1;
+        e[1] = 2;
+        e[3] = 4;
+        assert_eq_m512i(r, transmute(e));
+    }
+    test_mm512_maskz_loadu_epi8();
+
+    #[target_feature(enable = "avx512bw,avx512f")]
+    unsafe fn test_mm512_permutexvar_epi16() {
+        let a: [i16; 32] = std::array::from_fn(|i| i as i16);
+        let idx: [i16; 32] = std::array::from_fn(|i| 31 - i as i16 + 32);
+        let r = _mm512_permutexvar_epi16(transmute(idx), transmute(a));
+        let e: [i16; 32] = std::array::from_fn(|i| 31 - i as i16);
+        assert_eq_m512i(r, transmute(e));
+    }
+    test_mm512_permutexvar_epi16();
+}
+
 // Some of the constants in the tests below are just bit patterns. They should not
 // be interpreted as integers; signedness does not make sense for them, but
 // __mXXXi happens to be defined in terms of signed integers.
@@ -194,6 +506,11 @@ unsafe fn test_avx512vpopcntdq() {
     test_mm_popcnt_epi64();
 }
 
+#[track_caller]
+unsafe fn assert_eq_m512(a: __m512, b: __m512) {
+    assert_eq!(transmute::<_, [u32; 16]>(a), transmute::<_, [u32; 16]>(b))
+}
+
 #[track_caller]
 unsafe fn assert_eq_m512i(a: __m512i, b: __m512i) {
     assert_eq!(transmute::<_, [i32; 16]>(a), transmute::<_, [i32; 16]>(b))
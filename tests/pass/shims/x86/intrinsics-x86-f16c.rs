@@ -0,0 +1,54 @@
+// We're testing x86 target specific features
+//@only-target: x86_64 i686
+//@compile-flags: -C target-feature=+f16c
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("f16c"));
+
+    unsafe {
+        test_cvtph_ps();
+        test_cvtps_ph();
+    }
+}
+
+#[target_feature(enable = "f16c")]
+unsafe fn test_cvtph_ps() {
+    let a = transmute::<[u16; 8], __m128i>([0x3c00, 0xc000, 0x3800, 0x7bff, 0x7c00, 1, 0x8000, 0]);
+    let r = transmute::<_, [f32; 4]>(_mm_cvtph_ps(a));
+    assert_eq!(r, [1., -2., 0.5, 65504.]);
+
+    let r = transmute::<_, [f32; 8]>(_mm256_cvtph_ps(a));
+    assert_eq!(r[..4], [1., -2., 0.5, 65504.]);
+    assert_eq!(r[4], f32::INFINITY);
+    // The smallest subnormal half-precision float.
+    assert_eq!(r[5], 2f32.powi(-24));
+    assert_eq!(r[6].to_bits(), (-0f32).to_bits());
+    assert_eq!(r[7].to_bits(), 0);
+
+    let a = transmute::<[u16; 8], __m128i>([0x7e00, 0xfc00, 0, 0, 0, 0, 0, 0]);
+    let r = transmute::<_, [f32; 4]>(_mm_cvtph_ps(a));
+    assert!(r[0].is_nan());
+    assert_eq!(r[1], f32::NEG_INFINITY);
+}
+
+#[target_feature(enable = "f16c")]
+unsafe fn test_cvtps_ph() {
+    // 1 + 2^-11 is exactly halfway between two half-precision floats.
+    let a = _mm_setr_ps(1., 1. + 2f32.powi(-11), 1e6, -1e6);
+    let r = transmute::<_, [u16; 8]>(_mm_cvtps_ph::<_MM_FROUND_TO_NEAREST_INT>(a));
+    assert_eq!(r, [0x3c00, 0x3c00, 0x7c00, 0xfc00, 0, 0, 0, 0]);
+    let r = transmute::<_, [u16; 8]>(_mm_cvtps_ph::<_MM_FROUND_TO_POS_INF>(a));
+    assert_eq!(r, [0x3c00, 0x3c01, 0x7c00, 0xfbff, 0, 0, 0, 0]);
+    let r = transmute::<_, [u16; 8]>(_mm_cvtps_ph::<_MM_FROUND_TO_ZERO>(a));
+    assert_eq!(r, [0x3c00, 0x3c00, 0x7bff, 0xfbff, 0, 0, 0, 0]);
+
+    let a = _mm256_setr_ps(1., 2., 0.5, 65504., f32::INFINITY, 2f32.powi(-24), -0., 0.);
+    let r = transmute::<_, [u16; 8]>(_mm256_cvtps_ph::<_MM_FROUND_CUR_DIRECTION>(a));
+    assert_eq!(r, [0x3c00, 0x4000, 0x3800, 0x7bff, 0x7c00, 1, 0x8000, 0]);
+}
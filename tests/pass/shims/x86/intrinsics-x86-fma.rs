@@ -0,0 +1,38 @@
+// We're testing x86 target specific features
+//@only-target: x86_64 i686
+//@compile-flags: -C target-feature=+fma
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::mem::transmute;
+
+fn main() {
+    assert!(is_x86_feature_detected!("fma"));
+
+    unsafe {
+        test_fma();
+    }
+}
+
+#[target_feature(enable = "fma")]
+unsafe fn test_fma() {
+    let a = _mm_setr_ps(1., 2., 3., 4.);
+    let b = _mm_set1_ps(10.);
+    let c = _mm_set1_ps(1.);
+    assert_eq!(transmute::<_, [f32; 4]>(_mm_fmaddsub_ps(a, b, c)), [9., 21., 29., 41.]);
+    assert_eq!(transmute::<_, [f32; 4]>(_mm_fmsubadd_ps(a, b, c)), [11., 19., 31., 39.]);
+
+    let a = _mm256_setr_pd(1., 2., 3., 4.);
+    let b = _mm256_set1_pd(10.);
+    let c = _mm256_set1_pd(1.);
+    assert_eq!(transmute::<_, [f64; 4]>(_mm256_fmaddsub_pd(a, b, c)), [9., 21., 29., 41.]);
+    assert_eq!(transmute::<_, [f64; 4]>(_mm256_fmsubadd_pd(a, b, c)), [11., 19., 31., 39.]);
+
+    // The result is only rounded once: `a * b` is `1 - 2^-60`, which would round to 1.
+    let a = _mm_set1_pd(1. + 2f64.powi(-30));
+    let b = _mm_set1_pd(1. - 2f64.powi(-30));
+    let c = _mm_set1_pd(1.);
+    assert_eq!(transmute::<_, [f64; 2]>(_mm_fmaddsub_pd(a, b, c)), [-(2f64.powi(-60)), 2.]);
+}
@@ -0,0 +1,66 @@
+// We're testing x86 target specific features
+//@only-target: x86_64 i686
+//@compile-flags: -C target-feature=+rdrand,+rdseed
+
+#[cfg(target_arch = "x86")]
+use std::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::*;
+use std::collections::HashSet;
+
+fn main() {
+    assert!(is_x86_feature_detected!("rdrand"));
+    assert!(is_x86_feature_detected!("rdseed"));
+
+    unsafe {
+        test_rdrand();
+        test_rdseed();
+        test_rdtsc();
+    }
+}
+
+#[target_feature(enable = "rdrand")]
+unsafe fn test_rdrand() {
+    let mut values = HashSet::new();
+    for _ in 0..16 {
+        let mut val = 0u32;
+        assert_eq!(_rdrand32_step(&mut val), 1);
+        values.insert(val);
+    }
+    assert!(values.len() > 1);
+
+    let mut val = 0u16;
+    assert_eq!(_rdrand16_step(&mut val), 1);
+    #[cfg(target_arch = "x86_64")]
+    {
+        let mut val = 0u64;
+        assert_eq!(_rdrand64_step(&mut val), 1);
+    }
+}
+
+#[target_feature(enable = "rdseed")]
+unsafe fn test_rdseed() {
+    let mut values = HashSet::new();
+    for _ in 0..16 {
+        let mut val = 0u32;
+        assert_eq!(_rdseed32_step(&mut val), 1);
+        values.insert(val);
+    }
+    assert!(values.len() > 1);
+
+    let mut val = 0u16;
+    assert_eq!(_rdseed16_step(&mut val), 1);
+    #[cfg(target_arch = "x86_64")]
+    {
+        let mut val = 0u64;
+        assert_eq!(_rdseed64_step(&mut val), 1);
+    }
+}
+
+unsafe fn test_rdtsc() {
+    let start = _rdtsc();
+    let mut aux = u32::MAX;
+    let end = __rdtscp(&mut aux);
+    assert!(end > start);
+    assert_eq!(aux, 0);
+}